@@ -1,20 +1,152 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use std::{collections::BTreeSet, fmt, marker::PhantomData, str::FromStr};
+
 use async_trait::async_trait;
-use auth_helper::jwt::{BuildValidation, JsonWebToken, Validation};
+use auth_helper::jwt::{
+    jsonwebtoken::{self, DecodingKey, EncodingKey, Header},
+    BuildValidation, Claims, JsonWebToken, Validation,
+};
 use axum::{
     extract::{FromRequest, OriginalUri},
     headers::{authorization::Bearer, Authorization},
+    http::Method,
     Extension, TypedHeader,
 };
+use chronicle::db::{mongodb::collections::ApiKeyDocument, ApiKeyStorage};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    config::{ApiConfigData, JwtArgonConfig, RouteGroup},
+    error::RequestError,
+    ApiError, AuthError,
+};
+
+/// A permission that is granted to a token.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, clap::ValueEnum)]
+pub enum Scope {
+    /// Read from the core API.
+    #[serde(rename = "core:read")]
+    #[value(name = "core:read")]
+    CoreRead,
+    /// Submit blocks via the core API.
+    #[serde(rename = "core:write")]
+    #[value(name = "core:write")]
+    CoreWrite,
+    /// Read from the explorer API.
+    #[serde(rename = "explorer:read")]
+    #[value(name = "explorer:read")]
+    ExplorerRead,
+    /// Read from the indexer API.
+    #[serde(rename = "indexer:read")]
+    #[value(name = "indexer:read")]
+    IndexerRead,
+    /// Read from the proof-of-inclusion API.
+    #[serde(rename = "poi:read")]
+    #[value(name = "poi:read")]
+    PoiRead,
+    /// Access every route, including the control routes.
+    #[serde(rename = "admin")]
+    #[value(name = "admin")]
+    Admin,
+}
 
-use super::{config::ApiConfigData, error::RequestError, ApiError, AuthError};
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::CoreRead => "core:read",
+            Self::CoreWrite => "core:write",
+            Self::ExplorerRead => "explorer:read",
+            Self::IndexerRead => "indexer:read",
+            Self::PoiRead => "poi:read",
+            Self::Admin => "admin",
+        }
+    }
 
-pub struct Auth;
+    /// Gets the scope that is required for a request to an authenticated route.
+    pub fn required_for(method: &Method, path: &str) -> Self {
+        if is_control_route(path) {
+            return Self::Admin;
+        }
+        match RouteGroup::from_path(path) {
+            Some(RouteGroup::Core) if method == Method::GET || method == Method::HEAD => Self::CoreRead,
+            Some(RouteGroup::Core) => Self::CoreWrite,
+            Some(RouteGroup::Explorer) => Self::ExplorerRead,
+            Some(RouteGroup::Indexer) => Self::IndexerRead,
+            Some(RouteGroup::Poi) => Self::PoiRead,
+            None => Self::Admin,
+        }
+    }
+}
+
+impl FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <Self as clap::ValueEnum>::from_str(s, false)
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The claims of the tokens that are issued by the API.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiClaims {
+    #[serde(flatten)]
+    pub claims: Claims,
+    /// The scopes that are granted to the token.
+    #[serde(default)]
+    pub scopes: BTreeSet<Scope>,
+    /// The name of the API key that the token was issued for. Tokens that were issued for the shared password have
+    /// none and cannot be revoked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+}
+
+impl ApiClaims {
+    /// Creates the claims of a token that expires after the configured duration.
+    pub fn new(
+        subject: String,
+        scopes: BTreeSet<Scope>,
+        key: Option<String>,
+        config: &ApiConfigData,
+    ) -> Result<Self, AuthError> {
+        Ok(Self {
+            claims: Claims::new(ApiConfigData::ISSUER, subject, ApiConfigData::AUDIENCE)
+                .and_then(|claims| claims.expires_after_duration(config.jwt_expiration))
+                .map_err(AuthError::InvalidJwt)?,
+            scopes,
+            key,
+        })
+    }
+
+    /// Whether the token grants the given scope.
+    pub fn grants(&self, scope: Scope) -> bool {
+        self.scopes.contains(&Scope::Admin) || self.scopes.contains(&scope)
+    }
+
+    /// Signs the claims with the secret key of the API.
+    pub fn encode(&self, config: &ApiConfigData) -> Result<JsonWebToken, AuthError> {
+        jsonwebtoken::encode(
+            &Header::default(),
+            self,
+            &EncodingKey::from_secret(config.jwt_secret_key.as_ref()),
+        )
+        .map(JsonWebToken)
+        .map_err(|e| AuthError::InvalidJwt(e.into()))
+    }
+}
+
+pub struct Auth<S>(PhantomData<S>);
 
 #[async_trait]
-impl<B: Send> FromRequest<B> for Auth {
+impl<B: Send, S: ApiKeyStorage> FromRequest<B> for Auth<S> {
     type Rejection = ApiError;
 
     async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
@@ -23,24 +155,99 @@ impl<B: Send> FromRequest<B> for Auth {
 
         let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
 
-        if config.public_routes.is_match(&uri.to_string()) {
-            return Ok(Auth);
+        // Control routes always require a token, even if they match a public route.
+        if !is_control_route(uri.path()) && config.public_routes.is_match(&uri.to_string()) {
+            return Ok(Auth(PhantomData));
         }
 
         let TypedHeader(Authorization(bearer)) = TypedHeader::<Authorization<Bearer>>::from_request(req)
             .await
             .map_err(RequestError::from)?;
-        let jwt = JsonWebToken(bearer.token().to_string());
-
-        jwt.validate(
-            Validation::default()
-                .with_issuer(ApiConfigData::ISSUER)
-                .with_audience(ApiConfigData::AUDIENCE)
-                .validate_nbf(true),
-            config.jwt_secret_key.as_ref(),
-        )
-        .map_err(AuthError::InvalidJwt)?;
+        let claims = validate_jwt(bearer.token(), &config)?;
+
+        let scope = Scope::required_for(req.method(), uri.path());
+        if !claims.grants(scope) {
+            return Err(AuthError::MissingScope(scope).into());
+        }
+
+        if let Some(name) = &claims.key {
+            let Extension(database) = Extension::<S>::from_request(req).await?;
+            let key = database.get_api_key(name).await?;
+            if !matches!(key, Some(key) if key.revoked_at.is_none()) {
+                return Err(AuthError::RevokedKey.into());
+            }
+        }
+
+        Ok(Auth(PhantomData))
+    }
+}
+
+/// Whether a route controls or reports on the application, e.g. by writing snapshot files.
+pub fn is_control_route(path: &str) -> bool {
+    path.starts_with("/api/core/v2/control/") || matches!(path, "/cache" | "/rate-limits")
+}
+
+/// Validates a JWT that was issued by the API and returns its claims.
+pub fn validate_jwt(token: &str, config: &ApiConfigData) -> Result<ApiClaims, AuthError> {
+    Ok(jsonwebtoken::decode::<ApiClaims>(
+        token,
+        &DecodingKey::from_secret(config.jwt_secret_key.as_ref()),
+        &Validation::default()
+            .with_issuer(ApiConfigData::ISSUER)
+            .with_audience(ApiConfigData::AUDIENCE)
+            .validate_nbf(true),
+    )
+    .map_err(|e| AuthError::InvalidJwt(e.into()))?
+    .claims)
+}
+
+/// Creates a named API key with a random secret. Returns the key to store together with the secret, which is only
+/// known to the caller from then on.
+pub fn create_api_key(name: String, scopes: &BTreeSet<Scope>) -> Result<(ApiKeyDocument, String), argon2::Error> {
+    let mut rng = rand::thread_rng();
+    let secret = hex::encode(rng.gen::<[u8; 32]>());
+    let salt = hex::encode(rng.gen::<[u8; 16]>());
+    let key_hash = argon2::hash_raw(secret.as_bytes(), salt.as_bytes(), &(&JwtArgonConfig::default()).into())?;
+    Ok((
+        ApiKeyDocument {
+            name,
+            key_hash,
+            salt,
+            scopes: scopes.iter().map(|scope| scope.to_string()).collect(),
+            created_at: time::OffsetDateTime::now_utc().unix_timestamp() as u64,
+            revoked_at: None,
+        },
+        secret,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
 
-        Ok(Auth)
+    #[test]
+    fn scopes_per_route() {
+        assert_eq!(
+            Scope::required_for(&Method::GET, "/api/core/v2/blocks/0x00"),
+            Scope::CoreRead
+        );
+        assert_eq!(
+            Scope::required_for(&Method::POST, "/api/core/v2/blocks"),
+            Scope::CoreWrite
+        );
+        assert_eq!(
+            Scope::required_for(&Method::POST, "/api/core/v2/control/snapshot/create"),
+            Scope::Admin
+        );
+        assert_eq!(
+            Scope::required_for(&Method::GET, "/api/explorer/v2/ledger/richest-addresses"),
+            Scope::ExplorerRead
+        );
+        assert!(is_control_route("/api/core/v2/control/database/prune"));
+        assert!(!is_control_route("/api/core/v2/info"));
+        assert_eq!(Scope::required_for(&Method::GET, "/cache"), Scope::Admin);
+        assert_eq!(Scope::required_for(&Method::GET, "/rate-limits"), Scope::Admin);
+        assert_eq!("poi:read".parse::<Scope>(), Ok(Scope::PoiRead));
+        assert_eq!(Scope::IndexerRead.to_string(), "indexer:read");
     }
 }
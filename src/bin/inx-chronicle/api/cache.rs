@@ -0,0 +1,330 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! A bounded in-memory cache for the lookups that are requested most often from the API.
+//!
+//! Blocks and milestones never change once they are stored, so they are only evicted when the cache is full. Outputs
+//! change when they are spent, so the INX worker invalidates the consumed outputs after it committed a milestone.
+//! Without an INX worker nothing would invalidate them, so outputs are not cached in that case.
+
+use std::{
+    future::Future,
+    hash::Hash,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use chronicle::{
+    db::mongodb::collections::OutputWithMetadataResult,
+    model::{
+        payload::{MilestoneId, MilestonePayload},
+        tangle::MilestoneIndex,
+        utxo::OutputId,
+        BlockId,
+    },
+};
+use lru_cache::LruCache;
+
+/// The hit and miss counts of a single kind of cached value.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub capacity: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// The share of lookups that were served from the cache, if there were any.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits as f64 / lookups as f64)
+    }
+}
+
+/// A snapshot of the statistics of all kinds of cached values.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReadCacheReport {
+    pub blocks: CacheStats,
+    pub milestones: CacheStats,
+    pub outputs: CacheStats,
+}
+
+/// A milestone payload together with the result of its signature verification.
+#[derive(Clone, Debug)]
+pub struct CachedMilestone {
+    pub payload: MilestonePayload,
+    pub signature_verified: Option<bool>,
+}
+
+/// Milestones can be looked up by either their index or their id.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MilestoneKey {
+    Index(MilestoneIndex),
+    Id(MilestoneId),
+}
+
+#[derive(Debug)]
+struct Entries<K: Hash + Eq, V> {
+    lru: LruCache<K, V>,
+    /// Incremented by every invalidation, so that values which were loaded before it are not inserted afterwards.
+    generation: u64,
+}
+
+#[derive(Debug)]
+struct Cache<K: Hash + Eq, V> {
+    capacity: usize,
+    entries: Mutex<Entries<K, V>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<K: Hash + Eq, V: Clone> Cache<K, V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(Entries {
+                lru: LruCache::new(capacity),
+                generation: 0,
+            }),
+            hits: Default::default(),
+            misses: Default::default(),
+        }
+    }
+
+    /// Returns the cached value for the key, or awaits `load` and caches its result. Missing values are not cached.
+    async fn get_or_load<E>(&self, key: K, load: impl Future<Output = Result<Option<V>, E>>) -> Result<Option<V>, E> {
+        if self.capacity == 0 {
+            return load.await;
+        }
+        let generation = {
+            let mut entries = self.entries.lock().unwrap();
+            if let Some(value) = entries.lru.get_mut(&key) {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(Some(value.clone()));
+            }
+            entries.generation
+        };
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let value = load.await?;
+        if let Some(value) = &value {
+            let mut entries = self.entries.lock().unwrap();
+            if entries.generation == generation {
+                entries.lru.insert(key, value.clone());
+            }
+        }
+        Ok(value)
+    }
+
+    // Outputs are only invalidated by the INX worker.
+    #[cfg_attr(not(feature = "inx"), allow(dead_code))]
+    fn invalidate<'a>(&self, keys: impl IntoIterator<Item = &'a K>)
+    where
+        K: 'a,
+    {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        entries.generation += 1;
+        for key in keys {
+            entries.lru.remove(key);
+        }
+    }
+
+    #[cfg_attr(not(feature = "inx"), allow(dead_code))]
+    fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.generation += 1;
+        entries.lru.clear();
+    }
+
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.entries.lock().unwrap().lru.len(),
+            capacity: self.capacity,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Caches {
+    blocks: Cache<BlockId, Arc<Vec<u8>>>,
+    milestones: Cache<MilestoneKey, Arc<CachedMilestone>>,
+    outputs: Cache<OutputId, Arc<OutputWithMetadataResult>>,
+}
+
+/// A shared handle to the read cache of the API.
+#[derive(Clone, Debug)]
+pub struct ReadCache(Arc<Caches>);
+
+impl ReadCache {
+    /// Creates a cache that holds up to `capacity` entries of each kind. A capacity of zero disables the cache.
+    /// Outputs are only cached if `cache_outputs` is set, because they must be invalidated when they are spent.
+    pub fn new(capacity: usize, cache_outputs: bool) -> Self {
+        Self(Arc::new(Caches {
+            blocks: Cache::new(capacity),
+            milestones: Cache::new(capacity),
+            outputs: Cache::new(if cache_outputs { capacity } else { 0 }),
+        }))
+    }
+
+    /// Gets the raw bytes of a block.
+    pub async fn block_raw<E>(
+        &self,
+        block_id: BlockId,
+        load: impl Future<Output = Result<Option<Vec<u8>>, E>>,
+    ) -> Result<Option<Arc<Vec<u8>>>, E> {
+        self.0
+            .blocks
+            .get_or_load(block_id, async { Ok(load.await?.map(Arc::new)) })
+            .await
+    }
+
+    /// Gets a milestone by its index or id.
+    pub async fn milestone<E>(
+        &self,
+        key: MilestoneKey,
+        load: impl Future<Output = Result<Option<CachedMilestone>, E>>,
+    ) -> Result<Option<Arc<CachedMilestone>>, E> {
+        self.0
+            .milestones
+            .get_or_load(key, async { Ok(load.await?.map(Arc::new)) })
+            .await
+    }
+
+    /// Gets an output together with its metadata.
+    pub async fn output<E>(
+        &self,
+        output_id: OutputId,
+        load: impl Future<Output = Result<Option<OutputWithMetadataResult>, E>>,
+    ) -> Result<Option<Arc<OutputWithMetadataResult>>, E> {
+        self.0
+            .outputs
+            .get_or_load(output_id, async { Ok(load.await?.map(Arc::new)) })
+            .await
+    }
+
+    /// Removes outputs whose metadata changed. Lookups that started before the invalidation do not cache their
+    /// results.
+    #[cfg_attr(not(feature = "inx"), allow(dead_code))]
+    pub fn invalidate_outputs<'a>(&self, output_ids: impl IntoIterator<Item = &'a OutputId>) {
+        self.0.outputs.invalidate(output_ids)
+    }
+
+    /// Removes all entries, e.g. because the database was cleared.
+    #[cfg_attr(not(feature = "inx"), allow(dead_code))]
+    pub fn clear(&self) {
+        self.0.blocks.clear();
+        self.0.milestones.clear();
+        self.0.outputs.clear();
+    }
+
+    /// Returns the current statistics.
+    pub fn report(&self) -> ReadCacheReport {
+        ReadCacheReport {
+            blocks: self.0.blocks.stats(),
+            milestones: self.0.milestones.stats(),
+            outputs: self.0.outputs.stats(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::Infallible;
+
+    use chronicle::model::payload::TransactionId;
+
+    use super::*;
+
+    fn output_id(index: u16) -> OutputId {
+        OutputId {
+            transaction_id: TransactionId([0; TransactionId::LENGTH]),
+            index,
+        }
+    }
+
+    #[tokio::test]
+    async fn cache_is_bounded() {
+        let cache = Cache::<OutputId, u16>::new(2);
+        for index in 0..3 {
+            cache
+                .get_or_load(output_id(index), async { Ok::<_, Infallible>(Some(index)) })
+                .await
+                .unwrap();
+        }
+        // The least recently used entry was evicted.
+        let value = cache
+            .get_or_load(output_id(0), async { Ok::<_, Infallible>(Some(10)) })
+            .await
+            .unwrap();
+        assert_eq!(value, Some(10));
+        let value = cache
+            .get_or_load(output_id(2), async { Ok::<_, Infallible>(Some(12)) })
+            .await
+            .unwrap();
+        assert_eq!(value, Some(2));
+        // Missing values are not cached.
+        cache
+            .get_or_load(output_id(3), async { Ok::<_, Infallible>(None) })
+            .await
+            .unwrap();
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                entries: 2,
+                capacity: 2,
+                hits: 1,
+                misses: 5,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn invalidation_discards_pending_loads() {
+        let cache = Cache::<OutputId, u16>::new(2);
+        cache
+            .get_or_load(output_id(0), async { Ok::<_, Infallible>(Some(0)) })
+            .await
+            .unwrap();
+        // A load that races with an invalidation returns its value without caching it.
+        let value = cache
+            .get_or_load(output_id(1), async {
+                cache.invalidate(&[output_id(0)]);
+                Ok::<_, Infallible>(Some(1))
+            })
+            .await
+            .unwrap();
+        assert_eq!(value, Some(1));
+        assert_eq!(cache.stats().entries, 0);
+        let value = cache
+            .get_or_load(output_id(0), async { Ok::<_, Infallible>(Some(10)) })
+            .await
+            .unwrap();
+        assert_eq!(value, Some(10));
+    }
+
+    #[tokio::test]
+    async fn outputs_are_only_cached_if_enabled() {
+        for cache_outputs in [false, true] {
+            let read_cache = ReadCache::new(2, cache_outputs);
+            read_cache
+                .block_raw(BlockId([0; BlockId::LENGTH]), async {
+                    Ok::<_, Infallible>(Some(vec![0]))
+                })
+                .await
+                .unwrap();
+            let report = read_cache.report();
+            assert_eq!(report.blocks.entries, 1);
+            assert_eq!(report.outputs.capacity, if cache_outputs { 2 } else { 0 });
+
+            read_cache.clear();
+            assert_eq!(read_cache.report().blocks.entries, 0);
+        }
+    }
+}
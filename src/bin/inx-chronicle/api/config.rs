@@ -1,7 +1,7 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use std::time::Duration;
+use std::{collections::BTreeMap, path::PathBuf, str::FromStr, time::Duration};
 
 use derive_more::From;
 use regex::RegexSet;
@@ -15,10 +15,155 @@ pub const DEFAULT_PORT: u16 = 8042;
 pub const DEFAULT_ALLOW_ORIGINS: &str = "0.0.0.0";
 pub const DEFAULT_PUBLIC_ROUTES: &str = "api/core/v2/*";
 pub const DEFAULT_MAX_PAGE_SIZE: usize = 1000;
+pub const DEFAULT_CACHE_CAPACITY: usize = 10_000;
+pub const DEFAULT_RATE_LIMIT_ENABLED: bool = false;
+pub const DEFAULT_RATE_LIMIT_CORE: &str = "50/100";
+pub const DEFAULT_RATE_LIMIT_EXPLORER: &str = "10/20";
+pub const DEFAULT_RATE_LIMIT_INDEXER: &str = "10/20";
+pub const DEFAULT_RATE_LIMIT_POI: &str = "5/10";
+pub const DEFAULT_RATE_LIMIT_TRUST_FORWARDED_FOR: bool = false;
+/// Routes that are more expensive to serve than others, together with the number of tokens that a request costs.
+pub const DEFAULT_ROUTE_COSTS: &[(&str, u32)] = &[
+    ("/api/explorer/v2/balance/*", 2),
+    ("/api/explorer/v2/blocks/*/graph", 5),
+    ("/api/explorer/v2/ledger/richest-addresses", 20),
+    ("/api/explorer/v2/ledger/token-distribution", 20),
+    ("/api/explorer/v2/ledger/unlock-calendar/*", 5),
+    ("/api/explorer/v2/ledger/updates/by-address/*", 5),
+    ("/api/explorer/v2/milestones/*/graph", 5),
+    ("/api/explorer/v2/receipts/by-address/*", 5),
+    ("/api/indexer/v1/outputs/*", 2),
+];
+#[cfg(feature = "inx")]
+pub const DEFAULT_NODE_FORWARDING_ENABLED: bool = true;
+#[cfg(feature = "inx")]
+pub const DEFAULT_NODE_FORWARDING_RATE_LIMIT: u32 = 10;
 pub const DEFAULT_JWT_PASSWORD: &str = "password";
 pub const DEFAULT_JWT_SALT: &str = "saltines";
 pub const DEFAULT_JWT_EXPIRATION: &str = "72h";
 
+/// An optional module of the API that can be disabled.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiModule {
+    /// The `explorer/v2` routes.
+    Explorer,
+    /// The `indexer/v1` routes.
+    Indexer,
+    /// The `poi/v1` routes.
+    Poi,
+}
+
+/// A group of routes that share a rate limit.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
+pub enum RouteGroup {
+    /// The `core/v2` routes.
+    Core,
+    /// The `explorer/v2` routes.
+    Explorer,
+    /// The `indexer/v1` routes.
+    Indexer,
+    /// The `poi/v1` routes.
+    Poi,
+}
+
+impl RouteGroup {
+    /// All route groups.
+    pub const ALL: &'static [Self] = &[Self::Core, Self::Explorer, Self::Indexer, Self::Poi];
+
+    /// Gets the group of a route below `/api`.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let path = path.strip_prefix("/api")?;
+        [
+            ("/core/", Self::Core),
+            ("/explorer/", Self::Explorer),
+            ("/indexer/", Self::Indexer),
+            ("/poi/", Self::Poi),
+        ]
+        .into_iter()
+        .find_map(|(prefix, group)| path.starts_with(prefix).then(|| group))
+    }
+}
+
+/// The limit of a token bucket.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimit {
+    /// The number of tokens that are refilled per second.
+    pub per_second: u32,
+    /// The maximum number of tokens that can be spent at once.
+    pub burst: u32,
+}
+
+impl FromStr for RateLimit {
+    type Err = String;
+
+    /// Parses a limit of the form `PER_SECOND[/BURST]`. The burst defaults to the rate.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |s: &str| {
+            s.trim()
+                .parse::<u32>()
+                .map_err(|e| format!("invalid rate limit `{s}`: {e}"))
+        };
+        let (per_second, burst) = match s.split_once('/') {
+            Some((per_second, burst)) => (parse(per_second)?, parse(burst)?),
+            None => (parse(s)?, parse(s)?),
+        };
+        if per_second == 0 || burst == 0 {
+            return Err(format!("rate limit `{s}` must be greater than zero"));
+        }
+        Ok(Self { per_second, burst })
+    }
+}
+
+/// Rate limiting configuration. Every client, identified by its JWT subject or else by its IP address, gets a token
+/// bucket per [`RouteGroup`].
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    pub core: RateLimit,
+    pub explorer: RateLimit,
+    pub indexer: RateLimit,
+    pub poi: RateLimit,
+    /// The number of tokens that a request to a route costs, if it is not 1. Routes may contain `*` wildcards and the
+    /// highest cost of all matching routes applies.
+    pub route_costs: BTreeMap<String, u32>,
+    /// Whether anonymous clients are identified by the last address in the `X-Forwarded-For` header instead of the
+    /// address of the connection. Only enable this behind a reverse proxy that sets the header.
+    pub trust_forwarded_for: bool,
+}
+
+impl RateLimitConfig {
+    /// Gets the limit of a route group.
+    pub fn limit(&self, group: RouteGroup) -> RateLimit {
+        match group {
+            RouteGroup::Core => self.core,
+            RouteGroup::Explorer => self.explorer,
+            RouteGroup::Indexer => self.indexer,
+            RouteGroup::Poi => self.poi,
+        }
+    }
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: DEFAULT_RATE_LIMIT_ENABLED,
+            // Panic: The default limits are valid.
+            core: DEFAULT_RATE_LIMIT_CORE.parse().unwrap(),
+            explorer: DEFAULT_RATE_LIMIT_EXPLORER.parse().unwrap(),
+            indexer: DEFAULT_RATE_LIMIT_INDEXER.parse().unwrap(),
+            poi: DEFAULT_RATE_LIMIT_POI.parse().unwrap(),
+            route_costs: DEFAULT_ROUTE_COSTS
+                .iter()
+                .map(|&(route, cost)| (route.to_string(), cost))
+                .collect(),
+            trust_forwarded_for: DEFAULT_RATE_LIMIT_TRUST_FORWARDED_FOR,
+        }
+    }
+}
+
 /// API configuration
 #[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 #[serde(default, deny_unknown_fields)]
@@ -33,6 +178,17 @@ pub struct ApiConfig {
     pub jwt_identity_file: Option<String>,
     #[serde(with = "humantime_serde")]
     pub jwt_expiration: Duration,
+    pub snapshot_dir: Option<String>,
+    pub disabled_modules: Vec<ApiModule>,
+    /// The number of blocks, milestones and outputs that are each kept in the read cache.
+    pub cache_capacity: usize,
+    pub rate_limit: RateLimitConfig,
+    /// Whether block submission and tip requests are forwarded to the node via INX.
+    #[cfg(feature = "inx")]
+    pub node_forwarding: bool,
+    /// The number of requests per second that are forwarded to the node, or 0 for no limit.
+    #[cfg(feature = "inx")]
+    pub node_forwarding_rate_limit: u32,
 }
 
 impl Default for ApiConfig {
@@ -47,10 +203,25 @@ impl Default for ApiConfig {
             jwt_password: DEFAULT_JWT_PASSWORD.to_string(),
             jwt_salt: DEFAULT_JWT_SALT.to_string(),
             jwt_expiration: DEFAULT_JWT_EXPIRATION.parse::<humantime::Duration>().unwrap().into(),
+            snapshot_dir: None,
+            disabled_modules: Vec::new(),
+            cache_capacity: DEFAULT_CACHE_CAPACITY,
+            rate_limit: Default::default(),
+            #[cfg(feature = "inx")]
+            node_forwarding: DEFAULT_NODE_FORWARDING_ENABLED,
+            #[cfg(feature = "inx")]
+            node_forwarding_rate_limit: DEFAULT_NODE_FORWARDING_RATE_LIMIT,
         }
     }
 }
 
+impl ApiConfig {
+    /// Whether the given module is served by the API.
+    pub fn is_module_enabled(&self, module: ApiModule) -> bool {
+        self.enabled && !self.disabled_modules.contains(&module)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ApiConfigData {
     pub port: u16,
@@ -62,11 +233,18 @@ pub struct ApiConfigData {
     pub jwt_secret_key: SecretKey,
     pub jwt_expiration: Duration,
     pub jwt_argon_config: JwtArgonConfig,
+    pub snapshot_dir: Option<PathBuf>,
+    pub disabled_modules: Vec<ApiModule>,
 }
 
 impl ApiConfigData {
     pub const ISSUER: &'static str = "chronicle";
     pub const AUDIENCE: &'static str = "api";
+
+    /// Whether the given module is served by the API.
+    pub fn is_module_enabled(&self, module: ApiModule) -> bool {
+        !self.disabled_modules.contains(&module)
+    }
 }
 
 impl TryFrom<ApiConfig> for ApiConfigData {
@@ -98,19 +276,21 @@ impl TryFrom<ApiConfig> for ApiConfigData {
             },
             jwt_expiration: config.jwt_expiration,
             jwt_argon_config: JwtArgonConfig::default(),
+            snapshot_dir: config.snapshot_dir.map(PathBuf::from),
+            disabled_modules: config.disabled_modules,
         })
     }
 }
 
-fn route_to_regex(route: &impl AsRef<str>) -> String {
+pub fn route_to_regex(route: &impl AsRef<str>) -> String {
     // Escape the string to make sure a regex can be built from it.
     // Existing wildcards `*` get escaped to `\\*`.
-    let mut escaped: String = regex::escape(route.as_ref());
+    let mut escaped: String = regex::escape(route.as_ref().trim_start_matches('/'));
     // Convert the escaped wildcard to a valid regex.
     escaped = escaped.replace("\\*", ".*");
-    // End the regex.
-    escaped.push('$');
-    escaped
+    // Anchor the regex at both ends, so that a route cannot match in the middle of a path. The leading slash is
+    // optional, as routes are configured with and without it.
+    format!("^/?{escaped}$")
 }
 
 /// Convenience type that allows specifying either a single value or a list of values
@@ -233,3 +413,23 @@ mod version {
         argon2::Version::from_u32(u32::from_le_bytes(decoded.try_into().unwrap())).map_err(serde::de::Error::custom)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use regex::Regex;
+
+    use super::*;
+
+    #[test]
+    fn routes_match_whole_paths() {
+        let public = Regex::new(&route_to_regex(&DEFAULT_PUBLIC_ROUTES)).unwrap();
+        assert!(public.is_match("/api/core/v2/milestones/by-index/10000"));
+        assert!(!public.is_match("/api/explorer/v2/api/core/v2/milestones"));
+
+        let route = Regex::new(&route_to_regex(&"/core/v2/milestones/by-index/*")).unwrap();
+        assert!(!route.is_match("/api/core/v2/milestones/by-index/10000"));
+
+        let route = Regex::new(&route_to_regex(&"*10000")).unwrap();
+        assert!(route.is_match("/api/core/v2/milestones/by-index/10000"));
+    }
+}
@@ -1,23 +1,151 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use iota_types::{api::core::response as iota, block::protocol::dto::ProtocolParametersDto};
+use chronicle::{model::tangle::MilestoneIndex, tangle::PayloadKind};
+use iota_types::{
+    api::core::response as iota,
+    block::{
+        output::dto::OutputMetadataDto, payload::dto::MilestonePayloadDto, protocol::dto::ProtocolParametersDto,
+        BlockDto,
+    },
+};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
-use crate::api::responses::impl_success_response;
+use crate::api::{
+    openapi::{api_schema, ApiSchema, DocResponse, Operation, SchemaGenerator},
+    responses::impl_success_response,
+    routes::BYTE_CONTENT_HEADER,
+};
 
-/// Response of `GET /api/info`.
+api_schema! {
+    /// Response of `GET /api/info`.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct InfoResponse {
+        pub name: String,
+        pub version: String,
+        pub status: iota::StatusResponse,
+        pub protocol: ProtocolParametersDto,
+        pub base_token: iota::BaseTokenResponse,
+        /// The filter that selects the stored blocks. If present, the block store is partial.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub ingestion_filter: Option<IngestionFilterDto>,
+    }
+}
+
+impl_success_response!(InfoResponse);
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct IngestionFilterDto {
+        pub tag_prefixes: Vec<String>,
+        pub addresses: Vec<String>,
+        pub payload_kinds: Vec<PayloadKind>,
+    }
+}
+
+/// Response of `GET /api/core/v2/milestones/:milestone_id` and `GET /api/core/v2/milestones/by-index/:index`.
+///
+/// The milestone payload is extended with the result of the signature verification, if it was performed.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct InfoResponse {
-    pub name: String,
-    pub version: String,
-    pub status: iota::StatusResponse,
-    pub protocol: ProtocolParametersDto,
-    pub base_token: iota::BaseTokenResponse,
+pub struct MilestoneResponse {
+    #[serde(flatten)]
+    pub payload: MilestonePayloadDto,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature_verified: Option<bool>,
 }
 
-impl_success_response!(InfoResponse);
+impl ApiSchema for MilestoneResponse {
+    fn schema(gen: &mut SchemaGenerator) -> Value {
+        gen.reference("MilestoneResponse", |gen| {
+            json!({
+                "allOf": [
+                    MilestonePayloadDto::schema(gen),
+                    {
+                        "type": "object",
+                        "properties": { "signatureVerified": bool::schema(gen) },
+                    },
+                ],
+            })
+        })
+    }
+}
+
+api_schema! {
+    /// Response of `POST /api/core/v2/control/snapshot/create`.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CreateSnapshotResponse {
+        pub index: MilestoneIndex,
+        pub file_path: String,
+    }
+}
+
+impl_success_response!(CreateSnapshotResponse);
+
+api_schema! {
+    /// Response of `POST /api/core/v2/blocks/batch`.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BlocksBatchResponse {
+        pub items: Vec<BlockBatchItem>,
+        /// The requested ids for which no block was found.
+        pub not_found: Vec<String>,
+    }
+}
+
+impl_success_response!(BlocksBatchResponse);
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BlockBatchItem {
+        pub block_id: String,
+        pub block: BlockDto,
+    }
+}
+
+api_schema! {
+    /// Response of `POST /api/core/v2/blocks/metadata/batch`.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BlockMetadataBatchResponse {
+        pub items: Vec<iota::BlockMetadataResponse>,
+        /// The requested ids for which no block was found.
+        pub not_found: Vec<String>,
+    }
+}
+
+impl_success_response!(BlockMetadataBatchResponse);
+
+api_schema! {
+    /// Response of `POST /api/core/v2/outputs/batch`.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct OutputsBatchResponse {
+        pub items: Vec<iota::OutputWithMetadataResponse>,
+        /// The requested ids for which no output was found at the current ledger index.
+        pub not_found: Vec<String>,
+    }
+}
+
+impl_success_response!(OutputsBatchResponse);
+
+api_schema! {
+    /// Response of `POST /api/core/v2/outputs/metadata/batch`.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct OutputMetadataBatchResponse {
+        pub items: Vec<OutputMetadataDto>,
+        /// The requested ids for which no output was found at the current ledger index.
+        pub not_found: Vec<String>,
+    }
+}
+
+impl_success_response!(OutputMetadataBatchResponse);
 
 /// A wrapper struct that allows us to implement [`IntoResponse`](axum::response::IntoResponse) for the foreign
 /// responses from [`iota_types`](iota_types::api::core::response).
@@ -30,6 +158,12 @@ impl<T: Serialize> axum::response::IntoResponse for IotaResponse<T> {
     }
 }
 
+impl<T: Serialize + ApiSchema> DocResponse for IotaResponse<T> {
+    fn document(operation: &mut Operation, gen: &mut SchemaGenerator) {
+        operation.json_response(T::schema(gen));
+    }
+}
+
 /// A wrapper struct that allows us to implement [`IntoResponse`](axum::response::IntoResponse) for the foreign
 /// raw responses from [`iota_types`](iota_types::api::core::response).
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -43,7 +177,25 @@ impl<T: Serialize> axum::response::IntoResponse for IotaRawResponse<T> {
     fn into_response(self) -> axum::response::Response {
         match self {
             Self::Json(res) => axum::Json(res).into_response(),
-            Self::Raw(bytes) => bytes.into_response(),
+            Self::Raw(bytes) => {
+                ([(axum::http::header::CONTENT_TYPE, BYTE_CONTENT_HEADER.clone())], bytes).into_response()
+            }
         }
     }
 }
+
+impl<T: Serialize + ApiSchema> DocResponse for IotaRawResponse<T> {
+    fn document(operation: &mut Operation, gen: &mut SchemaGenerator) {
+        operation.response(
+            "200",
+            json!({
+                "description": "Successful operation. The raw bytes are returned if they are accepted by the request.",
+                "content": {
+                    "application/json": { "schema": T::schema(gen) },
+                    // Panic: The header value is a valid string.
+                    BYTE_CONTENT_HEADER.to_str().unwrap(): { "schema": { "type": "string", "format": "binary" } },
+                },
+            }),
+        );
+    }
+}
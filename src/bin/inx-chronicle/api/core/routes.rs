@@ -1,109 +1,125 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use std::str::FromStr;
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    str::FromStr,
+};
 
+use axum::extract::{Extension, Json, Path};
+#[cfg(feature = "inx")]
 use axum::{
-    extract::{Extension, Path},
-    handler::Handler,
-    http::header::HeaderMap,
-    routing::get,
+    body::Bytes,
+    http::{header::HeaderMap, StatusCode},
 };
 use chronicle::{
     db::{
-        mongodb::collections::{
-            BlockCollection, ConfigurationUpdateCollection, MilestoneCollection, OutputCollection,
-            OutputMetadataResult, OutputWithMetadataResult, ProtocolUpdateCollection, TreasuryCollection,
-            UtxoChangesResult,
-        },
-        MongoDb,
+        mongodb::collections::{OutputMetadataResult, OutputWithMetadataResult, UtxoChangesResult},
+        Storage, StorageError,
     },
     model::{
         metadata::BlockMetadata,
-        payload::{MilestoneId, TransactionId},
+        payload::{milestone::MilestoneOption, MilestoneId, MilestonePayload, TransactionId},
         tangle::MilestoneIndex,
         utxo::OutputId,
-        BlockId, TryFromWithContext,
+        Block, BlockId, TryFromWithContext,
     },
+    snapshot::{write_full_snapshot, SnapshotError},
 };
-use futures::TryStreamExt;
 use iota_types::{
     api::core::{
         dto::ReceiptDto,
         response::{
-            self as iota, BaseTokenResponse, BlockMetadataResponse, ConfirmedMilestoneResponse,
-            LatestMilestoneResponse, OutputWithMetadataResponse, ReceiptsResponse, StatusResponse, TreasuryResponse,
-            UtxoChangesResponse,
+            self as iota, BlockMetadataResponse, ConfirmedMilestoneResponse, LatestMilestoneResponse,
+            OutputWithMetadataResponse, ReceiptsResponse, StatusResponse, TreasuryResponse, UtxoChangesResponse,
         },
     },
-    block::{
-        output::dto::{OutputMetadataDto, RentStructureDto},
-        payload::{dto::MilestonePayloadDto, milestone::option::dto::MilestoneOptionDto},
-        protocol::dto::ProtocolParametersDto,
-        BlockDto,
-    },
+    block::{output::dto::OutputMetadataDto, payload::milestone::option::dto::MilestoneOptionDto, BlockDto},
 };
 use packable::PackableExt;
+use serde::Deserialize;
 
-use super::responses::{InfoResponse, IotaRawResponse, IotaResponse};
+use super::responses::{
+    BlockBatchItem, BlockMetadataBatchResponse, BlocksBatchResponse, CreateSnapshotResponse, InfoResponse,
+    IngestionFilterDto, IotaRawResponse, IotaResponse, MilestoneResponse, OutputMetadataBatchResponse,
+    OutputsBatchResponse,
+};
+#[cfg(feature = "inx")]
+use crate::api::{node::NodeClient, routes::BYTE_CONTENT_HEADER};
 use crate::api::{
-    error::{ApiError, CorruptStateError, MissingError, RequestError},
-    router::Router,
-    routes::{is_healthy, not_implemented, BYTE_CONTENT_HEADER},
-    ApiResult,
+    cache::{CachedMilestone, MilestoneKey, ReadCache},
+    error::{ApiError, CorruptStateError, MissingError, RequestError, UnimplementedError},
+    extractors::{OptionalJson, ResponseFormat},
+    openapi::api_schema,
+    responses::{base_token_dto, protocol_parameters_dto},
+    router::{get, post, Router},
+    routes::{is_healthy, not_implemented},
+    ApiConfigData, ApiResult,
 };
 
-pub fn routes() -> Router {
+pub fn routes<S: Storage>() -> Router {
+    #[cfg(feature = "inx")]
+    let (tips_route, blocks_route) = (get(tips), post(submit_block::<S>));
+    #[cfg(not(feature = "inx"))]
+    let (tips_route, blocks_route) = (get(not_implemented), post(not_implemented));
+
     Router::new()
-        .route("/info", get(info))
-        .route("/tips", not_implemented.into_service())
+        .route("/info", get(info::<S>))
+        .route("/tips", tips_route)
         .nest(
             "/blocks",
             Router::new()
-                .route("/", not_implemented.into_service())
-                .route("/:block_id", get(block))
-                .route("/:block_id/metadata", get(block_metadata)),
+                .route("/", blocks_route)
+                .route("/batch", post(blocks_batch::<S>))
+                .route("/metadata/batch", post(blocks_metadata_batch::<S>))
+                .route("/:block_id", get(block::<S>))
+                .route("/:block_id/metadata", get(block_metadata::<S>)),
         )
         .nest(
             "/outputs",
             Router::new()
-                .route("/:output_id", get(output))
-                .route("/:output_id/metadata", get(output_metadata)),
+                .route("/batch", post(outputs_batch::<S>))
+                .route("/metadata/batch", post(outputs_metadata_batch::<S>))
+                .route("/:output_id", get(output::<S>))
+                .route("/:output_id/metadata", get(output_metadata::<S>)),
         )
         .nest(
             "/receipts",
             Router::new()
-                .route("/", get(receipts))
-                .route("/:migrated_at", get(receipts_migrated_at)),
+                .route("/", get(receipts::<S>))
+                .route("/:migrated_at", get(receipts_migrated_at::<S>)),
         )
-        .route("/treasury", get(treasury))
+        .route("/treasury", get(treasury::<S>))
         .nest(
             "/transactions",
             Router::new()
-                .route("/:transaction_id/included-block", get(included_block))
-                .route("/:transaction_id/included-block/metadata", get(included_block_metadata)),
+                .route("/:transaction_id/included-block", get(included_block::<S>))
+                .route(
+                    "/:transaction_id/included-block/metadata",
+                    get(included_block_metadata::<S>),
+                ),
         )
         .nest(
             "/milestones",
             Router::new()
-                .route("/:milestone_id", get(milestone))
-                .route("/:milestone_id/utxo-changes", get(utxo_changes))
-                .route("/by-index/:index", get(milestone_by_index))
-                .route("/by-index/:index/utxo-changes", get(utxo_changes_by_index)),
+                .route("/:milestone_id", get(milestone::<S>))
+                .route("/:milestone_id/utxo-changes", get(utxo_changes::<S>))
+                .route("/by-index/:index", get(milestone_by_index::<S>))
+                .route("/by-index/:index/utxo-changes", get(utxo_changes_by_index::<S>)),
         )
         .nest(
             "/peers",
             Router::new()
-                .route("/", not_implemented.into_service())
-                .route("/:peer_id", not_implemented.into_service()),
+                .route("/", get(not_implemented))
+                .route("/:peer_id", get(not_implemented)),
         )
-        .route("/control/database/prune", not_implemented.into_service())
-        .route("/control/snapshot/create", not_implemented.into_service())
+        .route("/control/database/prune", post(not_implemented))
+        .route("/control/snapshot/create", post(create_snapshot::<S>))
 }
 
-pub async fn info(database: Extension<MongoDb>) -> ApiResult<InfoResponse> {
+pub async fn info<S: Storage>(Extension(database): Extension<S>) -> ApiResult<InfoResponse> {
     let protocol = database
-        .collection::<ProtocolUpdateCollection>()
         .get_latest_protocol_parameters()
         .await?
         .ok_or(CorruptStateError::ProtocolParams)?
@@ -115,12 +131,10 @@ pub async fn info(database: Extension<MongoDb>) -> ApiResult<InfoResponse> {
     });
 
     let newest_milestone = database
-        .collection::<MilestoneCollection>()
         .get_newest_milestone()
         .await?
         .ok_or(CorruptStateError::Milestone)?;
     let oldest_milestone = database
-        .collection::<MilestoneCollection>()
         .get_oldest_milestone()
         .await?
         .ok_or(CorruptStateError::Milestone)?;
@@ -131,7 +145,6 @@ pub async fn info(database: Extension<MongoDb>) -> ApiResult<InfoResponse> {
         milestone_id: Some(
             iota_types::block::payload::milestone::MilestoneId::from(
                 database
-                    .collection::<MilestoneCollection>()
                     .get_milestone_id(newest_milestone.milestone_index)
                     .await?
                     .ok_or(CorruptStateError::Milestone)?,
@@ -148,13 +161,22 @@ pub async fn info(database: Extension<MongoDb>) -> ApiResult<InfoResponse> {
     };
 
     let base_token = database
-        .collection::<ConfigurationUpdateCollection>()
         .get_latest_node_configuration()
         .await?
         .ok_or(CorruptStateError::NodeConfig)?
         .config
         .base_token;
 
+    let ingestion_filter = database.get_ingestion_filter().await?.map(|filter| IngestionFilterDto {
+        tag_prefixes: filter.tag_prefixes.iter().map(|tag| tag.to_hex()).collect(),
+        addresses: filter
+            .addresses
+            .into_iter()
+            .map(|address| iota_types::block::address::Address::from(address).to_bech32(&protocol.bech32_hrp))
+            .collect(),
+        payload_kinds: filter.payload_kinds,
+    });
+
     Ok(InfoResponse {
         name: chronicle::CHRONICLE_APP_NAME.into(),
         version: std::env!("CARGO_PKG_VERSION").to_string(),
@@ -164,56 +186,103 @@ pub async fn info(database: Extension<MongoDb>) -> ApiResult<InfoResponse> {
             confirmed_milestone,
             pruning_index: oldest_milestone.milestone_index.0 - 1,
         },
-        protocol: ProtocolParametersDto {
-            protocol_version: protocol.version,
-            network_name: protocol.network_name,
-            below_max_depth: protocol.below_max_depth,
-            bech32_hrp: protocol.bech32_hrp,
-            min_pow_score: protocol.min_pow_score,
-            rent_structure: RentStructureDto {
-                v_byte_cost: protocol.rent_structure.v_byte_cost,
-                v_byte_factor_data: protocol.rent_structure.v_byte_factor_data,
-                v_byte_factor_key: protocol.rent_structure.v_byte_factor_key,
-            },
-            token_supply: protocol.token_supply.to_string(),
-        },
-        base_token: BaseTokenResponse {
-            name: base_token.name,
-            ticker_symbol: base_token.ticker_symbol,
-            decimals: base_token.decimals as u8,
-            unit: base_token.unit,
-            subunit: Some(base_token.subunit),
-            use_metric_prefix: base_token.use_metric_prefix,
-        },
+        protocol: protocol_parameters_dto(protocol),
+        base_token: base_token_dto(base_token),
+        ingestion_filter,
     })
 }
 
-async fn block(
-    database: Extension<MongoDb>,
+async fn block<S: Storage>(
+    Extension(database): Extension<S>,
+    Extension(cache): Extension<ReadCache>,
     Path(block_id): Path<String>,
-    headers: HeaderMap,
+    format: ResponseFormat,
 ) -> ApiResult<IotaRawResponse<BlockDto>> {
     let block_id = BlockId::from_str(&block_id).map_err(RequestError::from)?;
 
-    if matches!(headers.get(axum::http::header::ACCEPT), Some(header) if header == BYTE_CONTENT_HEADER) {
-        return Ok(IotaRawResponse::Raw(
-            database
-                .collection::<BlockCollection>()
-                .get_block_raw(&block_id)
-                .await?
-                .ok_or(MissingError::NoResults)?,
-        ));
-    }
-
-    let block = database
-        .collection::<BlockCollection>()
-        .get_block(&block_id)
+    let raw = cache
+        .block_raw(block_id, database.get_block_raw(&block_id))
         .await?
         .ok_or(MissingError::NoResults)?;
 
+    if format == ResponseFormat::Raw {
+        return Ok(IotaRawResponse::Raw(raw.to_vec()));
+    }
+
+    // Panic: The stored raw bytes are guaranteed to be a valid block.
+    let block = Block::from(iota_types::block::Block::unpack_unverified(raw.as_slice()).unwrap());
+
     Ok(IotaRawResponse::Json(block.into()))
 }
 
+/// The number of tips that are requested from the node.
+#[cfg(feature = "inx")]
+const TIPS_COUNT: u32 = 8;
+
+#[cfg(feature = "inx")]
+async fn tips(Extension(node): Extension<Option<NodeClient>>) -> ApiResult<IotaResponse<iota::TipsResponse>> {
+    let node = node.ok_or(UnimplementedError)?;
+    let tips = node.request_tips(TIPS_COUNT).await?;
+
+    Ok(iota::TipsResponse {
+        tips: tips.iter().map(BlockId::to_hex).collect(),
+    }
+    .into())
+}
+
+/// A submitted block, which is either already serialized or sent as JSON, depending on the content type.
+#[cfg(feature = "inx")]
+#[derive(Debug, PartialEq)]
+enum SubmittedBlock {
+    Raw(Vec<u8>),
+    Json(Box<BlockDto>),
+}
+
+#[cfg(feature = "inx")]
+impl SubmittedBlock {
+    fn parse(headers: &HeaderMap, body: &[u8]) -> Result<Self, RequestError> {
+        Ok(match headers.get(axum::http::header::CONTENT_TYPE) {
+            Some(header) if header == BYTE_CONTENT_HEADER => Self::Raw(body.to_vec()),
+            _ => Self::Json(Box::new(serde_json::from_slice(body)?)),
+        })
+    }
+}
+
+#[cfg(feature = "inx")]
+async fn submit_block<S: Storage>(
+    Extension(database): Extension<S>,
+    Extension(node): Extension<Option<NodeClient>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> ApiResult<(StatusCode, IotaResponse<iota::SubmitBlockResponse>)> {
+    let node = node.ok_or(UnimplementedError)?;
+
+    let raw_block = match SubmittedBlock::parse(&headers, &body)? {
+        SubmittedBlock::Raw(raw_block) => raw_block,
+        SubmittedBlock::Json(block) => {
+            let protocol_params = database
+                .get_latest_protocol_parameters()
+                .await?
+                .ok_or(CorruptStateError::ProtocolParams)?
+                .parameters
+                .try_into()?;
+            iota_types::block::Block::try_from_dto(&block, &protocol_params)
+                .map_err(RequestError::from)?
+                .pack_to_vec()
+        }
+    };
+
+    let block_id = node.submit_block(raw_block).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        iota::SubmitBlockResponse {
+            block_id: block_id.to_hex(),
+        }
+        .into(),
+    ))
+}
+
 fn create_block_metadata_response(block_id: BlockId, metadata: BlockMetadata) -> iota::BlockMetadataResponse {
     iota::BlockMetadataResponse {
         block_id: block_id.to_hex(),
@@ -229,13 +298,12 @@ fn create_block_metadata_response(block_id: BlockId, metadata: BlockMetadata) ->
     }
 }
 
-async fn block_metadata(
-    database: Extension<MongoDb>,
+async fn block_metadata<S: Storage>(
+    Extension(database): Extension<S>,
     Path(block_id_str): Path<String>,
 ) -> ApiResult<IotaResponse<BlockMetadataResponse>> {
     let block_id = BlockId::from_str(&block_id_str).map_err(RequestError::from)?;
     let metadata = database
-        .collection::<BlockCollection>()
         .get_block_metadata(&block_id)
         .await?
         .ok_or(MissingError::NoResults)?;
@@ -267,27 +335,24 @@ fn create_output_metadata_response(metadata: OutputMetadataResult, ledger_index:
     }
 }
 
-async fn output(
-    database: Extension<MongoDb>,
+async fn output<S: Storage>(
+    Extension(database): Extension<S>,
+    Extension(cache): Extension<ReadCache>,
     Path(output_id): Path<String>,
-    headers: HeaderMap,
+    format: ResponseFormat,
 ) -> ApiResult<IotaRawResponse<OutputWithMetadataResponse>> {
-    let ledger_index = database
-        .collection::<MilestoneCollection>()
-        .get_ledger_index()
-        .await?
-        .ok_or(MissingError::NoResults)?;
+    let ledger_index = database.get_ledger_index().await?.ok_or(MissingError::NoResults)?;
     let output_id = OutputId::from_str(&output_id).map_err(RequestError::from)?;
 
-    let OutputWithMetadataResult { output, metadata } = database
-        .collection::<OutputCollection>()
-        .get_output_with_metadata(&output_id, ledger_index)
+    let OutputWithMetadataResult { output, metadata } = cache
+        .output(output_id, database.get_output_with_metadata(&output_id, ledger_index))
         .await?
-        .ok_or(MissingError::NoResults)?;
+        .ok_or(MissingError::NoResults)?
+        .as_ref()
+        .clone();
 
-    if matches!(headers.get(axum::http::header::ACCEPT), Some(header) if header == BYTE_CONTENT_HEADER) {
+    if format == ResponseFormat::Raw {
         let ctx = database
-            .collection::<ProtocolUpdateCollection>()
             .get_protocol_parameters_for_ledger_index(metadata.booked.milestone_index)
             .await?
             .ok_or(MissingError::NoResults)?
@@ -304,36 +369,178 @@ async fn output(
     }))
 }
 
-async fn output_metadata(
-    database: Extension<MongoDb>,
+async fn output_metadata<S: Storage>(
+    Extension(database): Extension<S>,
+    Extension(cache): Extension<ReadCache>,
     Path(output_id): Path<String>,
 ) -> ApiResult<IotaResponse<OutputMetadataDto>> {
-    let ledger_index = database
-        .collection::<MilestoneCollection>()
-        .get_ledger_index()
-        .await?
-        .ok_or(MissingError::NoResults)?;
+    let ledger_index = database.get_ledger_index().await?.ok_or(MissingError::NoResults)?;
     let output_id = OutputId::from_str(&output_id).map_err(RequestError::from)?;
-    let metadata = database
-        .collection::<OutputCollection>()
-        .get_output_metadata(&output_id, ledger_index)
+    let metadata = cache
+        .output(output_id, database.get_output_with_metadata(&output_id, ledger_index))
         .await?
-        .ok_or(MissingError::NoResults)?;
+        .ok_or(MissingError::NoResults)?
+        .metadata
+        .clone();
 
     Ok(create_output_metadata_response(metadata, ledger_index).into())
 }
 
-async fn included_block(
-    database: Extension<MongoDb>,
+api_schema! {
+    #[derive(Deserialize)]
+    struct BatchRequest {
+        ids: Vec<String>,
+    }
+}
+
+/// Parses the ids of a batch request. Duplicates are dropped while the order of the request is kept.
+fn parse_batch_ids<T>(ids: Vec<String>, max_page_size: usize) -> Result<Vec<T>, ApiError>
+where
+    T: FromStr + Copy + Eq + Hash,
+    RequestError: From<T::Err>,
+{
+    if ids.len() > max_page_size {
+        return Err(RequestError::BatchTooLarge {
+            provided: ids.len(),
+            max: max_page_size,
+        }
+        .into());
+    }
+    let mut seen = HashSet::new();
+    let mut res = Vec::with_capacity(ids.len());
+    for id in ids {
+        let id = T::from_str(&id).map_err(RequestError::from)?;
+        if seen.insert(id) {
+            res.push(id);
+        }
+    }
+    Ok(res)
+}
+
+/// Orders the found results by the requested ids and collects the hex representations of the ids without a result.
+fn split_batch_results<I: Eq + Hash, T>(
+    ids: &[I],
+    mut found: HashMap<I, T>,
+    to_hex: impl Fn(&I) -> String,
+) -> (Vec<T>, Vec<String>) {
+    let mut items = Vec::with_capacity(found.len());
+    let mut not_found = Vec::new();
+    for id in ids {
+        match found.remove(id) {
+            Some(item) => items.push(item),
+            None => not_found.push(to_hex(id)),
+        }
+    }
+    (items, not_found)
+}
+
+async fn blocks_batch<S: Storage>(
+    Extension(database): Extension<S>,
+    Extension(config): Extension<ApiConfigData>,
+    Json(BatchRequest { ids }): Json<BatchRequest>,
+) -> ApiResult<BlocksBatchResponse> {
+    let block_ids = parse_batch_ids::<BlockId>(ids, config.max_page_size)?;
+
+    let found = database
+        .get_blocks_raw(&block_ids)
+        .await?
+        .into_iter()
+        .map(|res| {
+            // Panic: The stored raw bytes are guaranteed to be a valid block.
+            let block = Block::from(iota_types::block::Block::unpack_unverified(res.raw).unwrap());
+            (
+                res.block_id,
+                BlockBatchItem {
+                    block_id: res.block_id.to_hex(),
+                    block: block.into(),
+                },
+            )
+        })
+        .collect();
+    let (items, not_found) = split_batch_results(&block_ids, found, BlockId::to_hex);
+
+    Ok(BlocksBatchResponse { items, not_found })
+}
+
+async fn blocks_metadata_batch<S: Storage>(
+    Extension(database): Extension<S>,
+    Extension(config): Extension<ApiConfigData>,
+    Json(BatchRequest { ids }): Json<BatchRequest>,
+) -> ApiResult<BlockMetadataBatchResponse> {
+    let block_ids = parse_batch_ids::<BlockId>(ids, config.max_page_size)?;
+
+    let found = database
+        .get_blocks_metadata(&block_ids)
+        .await?
+        .into_iter()
+        .map(|res| (res.block_id, create_block_metadata_response(res.block_id, res.metadata)))
+        .collect();
+    let (items, not_found) = split_batch_results(&block_ids, found, BlockId::to_hex);
+
+    Ok(BlockMetadataBatchResponse { items, not_found })
+}
+
+async fn outputs_batch<S: Storage>(
+    Extension(database): Extension<S>,
+    Extension(config): Extension<ApiConfigData>,
+    Json(BatchRequest { ids }): Json<BatchRequest>,
+) -> ApiResult<OutputsBatchResponse> {
+    let output_ids = parse_batch_ids::<OutputId>(ids, config.max_page_size)?;
+    let ledger_index = database.get_ledger_index().await?.ok_or(MissingError::NoResults)?;
+
+    let found = database
+        .get_outputs_with_metadata(&output_ids, ledger_index)
+        .await?
+        .into_iter()
+        .map(|OutputWithMetadataResult { output, metadata }| {
+            (
+                metadata.output_id,
+                OutputWithMetadataResponse {
+                    metadata: create_output_metadata_response(metadata, ledger_index),
+                    output: output.into(),
+                },
+            )
+        })
+        .collect();
+    let (items, not_found) = split_batch_results(&output_ids, found, OutputId::to_hex);
+
+    Ok(OutputsBatchResponse { items, not_found })
+}
+
+async fn outputs_metadata_batch<S: Storage>(
+    Extension(database): Extension<S>,
+    Extension(config): Extension<ApiConfigData>,
+    Json(BatchRequest { ids }): Json<BatchRequest>,
+) -> ApiResult<OutputMetadataBatchResponse> {
+    let output_ids = parse_batch_ids::<OutputId>(ids, config.max_page_size)?;
+    let ledger_index = database.get_ledger_index().await?.ok_or(MissingError::NoResults)?;
+
+    let found = database
+        .get_outputs_metadata(&output_ids, ledger_index)
+        .await?
+        .into_iter()
+        .map(|metadata| {
+            (
+                metadata.output_id,
+                create_output_metadata_response(metadata, ledger_index),
+            )
+        })
+        .collect();
+    let (items, not_found) = split_batch_results(&output_ids, found, OutputId::to_hex);
+
+    Ok(OutputMetadataBatchResponse { items, not_found })
+}
+
+async fn included_block<S: Storage>(
+    Extension(database): Extension<S>,
     Path(transaction_id): Path<String>,
-    headers: HeaderMap,
+    format: ResponseFormat,
 ) -> ApiResult<IotaRawResponse<BlockDto>> {
     let transaction_id = TransactionId::from_str(&transaction_id).map_err(RequestError::from)?;
 
-    if matches!(headers.get(axum::http::header::ACCEPT), Some(header) if header == BYTE_CONTENT_HEADER) {
+    if format == ResponseFormat::Raw {
         return Ok(IotaRawResponse::Raw(
             database
-                .collection::<BlockCollection>()
                 .get_block_raw_for_transaction(&transaction_id)
                 .await?
                 .ok_or(MissingError::NoResults)?,
@@ -341,7 +548,6 @@ async fn included_block(
     }
 
     let block = database
-        .collection::<BlockCollection>()
         .get_block_for_transaction(&transaction_id)
         .await?
         .ok_or(MissingError::NoResults)?
@@ -350,14 +556,13 @@ async fn included_block(
     Ok(IotaRawResponse::Json(block.into()))
 }
 
-async fn included_block_metadata(
-    database: Extension<MongoDb>,
+async fn included_block_metadata<S: Storage>(
+    Extension(database): Extension<S>,
     Path(transaction_id): Path<String>,
 ) -> ApiResult<IotaResponse<BlockMetadataResponse>> {
     let transaction_id = TransactionId::from_str(&transaction_id).map_err(RequestError::from)?;
 
     let res = database
-        .collection::<BlockCollection>()
         .get_block_metadata_for_transaction(&transaction_id)
         .await?
         .ok_or(MissingError::NoResults)?;
@@ -367,32 +572,23 @@ async fn included_block_metadata(
     Ok(create_block_metadata_response(block_id, metadata).into())
 }
 
-async fn receipts(database: Extension<MongoDb>) -> ApiResult<IotaResponse<ReceiptsResponse>> {
-    let mut receipts_at = database.collection::<MilestoneCollection>().get_all_receipts().await?;
-    let mut receipts = Vec::new();
-    while let Some((receipt, at)) = receipts_at.try_next().await? {
-        if let MilestoneOptionDto::Receipt(receipt) = receipt.into() {
-            receipts.push(ReceiptDto {
-                receipt,
-                milestone_index: *at,
-            });
-        } else {
-            unreachable!("the query only returns receipt milestone options");
-        }
-    }
-    Ok(iota::ReceiptsResponse { receipts }.into())
+async fn receipts<S: Storage>(Extension(database): Extension<S>) -> ApiResult<IotaResponse<ReceiptsResponse>> {
+    let receipts_at = database.get_all_receipts().await?;
+    Ok(create_receipts_response(receipts_at).into())
 }
 
-async fn receipts_migrated_at(
-    database: Extension<MongoDb>,
+async fn receipts_migrated_at<S: Storage>(
+    Extension(database): Extension<S>,
     Path(index): Path<u32>,
 ) -> ApiResult<IotaResponse<ReceiptsResponse>> {
-    let mut receipts_at = database
-        .collection::<MilestoneCollection>()
-        .get_receipts_migrated_at(index.into())
-        .await?;
+    let receipts_at = database.get_receipts_migrated_at(index.into()).await?;
+    Ok(create_receipts_response(receipts_at).into())
+}
+
+/// Creates the response for a list of receipts, which is only served as JSON because it has no packed form.
+fn create_receipts_response(receipts_at: Vec<(MilestoneOption, MilestoneIndex)>) -> ReceiptsResponse {
     let mut receipts = Vec::new();
-    while let Some((receipt, at)) = receipts_at.try_next().await? {
+    for (receipt, at) in receipts_at {
         if let MilestoneOptionDto::Receipt(receipt) = receipt.into() {
             receipts.push(ReceiptDto {
                 receipt,
@@ -402,12 +598,11 @@ async fn receipts_migrated_at(
             unreachable!("the query only returns receipt milestone options");
         }
     }
-    Ok(iota::ReceiptsResponse { receipts }.into())
+    iota::ReceiptsResponse { receipts }
 }
 
-async fn treasury(database: Extension<MongoDb>) -> ApiResult<IotaResponse<TreasuryResponse>> {
+async fn treasury<S: Storage>(Extension(database): Extension<S>) -> ApiResult<IotaResponse<TreasuryResponse>> {
     Ok(database
-        .collection::<TreasuryCollection>()
         .get_latest_treasury()
         .await?
         .ok_or(MissingError::NoResults)
@@ -420,72 +615,85 @@ async fn treasury(database: Extension<MongoDb>) -> ApiResult<IotaResponse<Treasu
         })?)
 }
 
-async fn milestone(
-    database: Extension<MongoDb>,
+async fn milestone<S: Storage>(
+    Extension(database): Extension<S>,
+    Extension(cache): Extension<ReadCache>,
     Path(milestone_id): Path<String>,
-    headers: HeaderMap,
-) -> ApiResult<IotaRawResponse<MilestonePayloadDto>> {
+    format: ResponseFormat,
+) -> ApiResult<IotaRawResponse<MilestoneResponse>> {
     let milestone_id = MilestoneId::from_str(&milestone_id).map_err(RequestError::from)?;
-    let milestone_payload = database
-        .collection::<MilestoneCollection>()
-        .get_milestone_payload_by_id(&milestone_id)
+    let milestone = cache
+        .milestone(MilestoneKey::Id(milestone_id), async {
+            load_milestone(&database, database.get_milestone_payload_by_id(&milestone_id).await?).await
+        })
         .await?
         .ok_or(MissingError::NoResults)?;
 
-    if matches!(headers.get(axum::http::header::ACCEPT), Some(header) if header == BYTE_CONTENT_HEADER) {
-        let protocol_params = database
-            .collection::<ProtocolUpdateCollection>()
-            .get_protocol_parameters_for_ledger_index(milestone_payload.essence.index)
-            .await?
-            .ok_or(MissingError::NoResults)?
-            .parameters
-            .try_into()?;
-
-        let milestone_payload =
-            iota_types::block::payload::MilestonePayload::try_from_with_context(&protocol_params, milestone_payload)?;
-
-        return Ok(IotaRawResponse::Raw(milestone_payload.pack_to_vec()));
-    }
-
-    Ok(IotaRawResponse::Json(milestone_payload.into()))
+    create_milestone_response(&database, &milestone, format).await
 }
 
-async fn milestone_by_index(
-    database: Extension<MongoDb>,
+async fn milestone_by_index<S: Storage>(
+    Extension(database): Extension<S>,
+    Extension(cache): Extension<ReadCache>,
     Path(index): Path<MilestoneIndex>,
-    headers: HeaderMap,
-) -> ApiResult<IotaRawResponse<MilestonePayloadDto>> {
-    let milestone_payload = database
-        .collection::<MilestoneCollection>()
-        .get_milestone_payload(index)
+    format: ResponseFormat,
+) -> ApiResult<IotaRawResponse<MilestoneResponse>> {
+    let milestone = cache
+        .milestone(MilestoneKey::Index(index), async {
+            load_milestone(&database, database.get_milestone_payload(index).await?).await
+        })
         .await?
         .ok_or(MissingError::NoResults)?;
 
-    if matches!(headers.get(axum::http::header::ACCEPT), Some(header) if header == BYTE_CONTENT_HEADER) {
+    create_milestone_response(&database, &milestone, format).await
+}
+
+async fn load_milestone(
+    database: &impl Storage,
+    payload: Option<MilestonePayload>,
+) -> Result<Option<CachedMilestone>, StorageError> {
+    Ok(match payload {
+        Some(payload) => Some(CachedMilestone {
+            signature_verified: database.get_milestone_signature_verified(payload.essence.index).await?,
+            payload,
+        }),
+        None => None,
+    })
+}
+
+async fn create_milestone_response(
+    database: &impl Storage,
+    milestone: &CachedMilestone,
+    format: ResponseFormat,
+) -> ApiResult<IotaRawResponse<MilestoneResponse>> {
+    if format == ResponseFormat::Raw {
         let protocol_params = database
-            .collection::<ProtocolUpdateCollection>()
-            .get_protocol_parameters_for_ledger_index(milestone_payload.essence.index)
+            .get_protocol_parameters_for_ledger_index(milestone.payload.essence.index)
             .await?
             .ok_or(MissingError::NoResults)?
             .parameters
             .try_into()?;
 
-        let milestone_payload =
-            iota_types::block::payload::MilestonePayload::try_from_with_context(&protocol_params, milestone_payload)?;
+        let milestone_payload = iota_types::block::payload::MilestonePayload::try_from_with_context(
+            &protocol_params,
+            milestone.payload.clone(),
+        )?;
 
         return Ok(IotaRawResponse::Raw(milestone_payload.pack_to_vec()));
     }
 
-    Ok(IotaRawResponse::Json(milestone_payload.into()))
+    Ok(IotaRawResponse::Json(MilestoneResponse {
+        payload: milestone.payload.clone().into(),
+        signature_verified: milestone.signature_verified,
+    }))
 }
 
-async fn utxo_changes(
-    database: Extension<MongoDb>,
+async fn utxo_changes<S: Storage>(
+    Extension(database): Extension<S>,
     Path(milestone_id): Path<String>,
 ) -> ApiResult<IotaResponse<UtxoChangesResponse>> {
     let milestone_id = MilestoneId::from_str(&milestone_id).map_err(RequestError::from)?;
     let milestone_index = database
-        .collection::<MilestoneCollection>()
         .get_milestone_payload_by_id(&milestone_id)
         .await?
         .ok_or(MissingError::NoResults)?
@@ -494,24 +702,22 @@ async fn utxo_changes(
     collect_utxo_changes(&database, milestone_index).await.map(Into::into)
 }
 
-async fn utxo_changes_by_index(
-    database: Extension<MongoDb>,
+async fn utxo_changes_by_index<S: Storage>(
+    Extension(database): Extension<S>,
     Path(milestone_index): Path<MilestoneIndex>,
 ) -> ApiResult<IotaResponse<UtxoChangesResponse>> {
     collect_utxo_changes(&database, milestone_index).await.map(Into::into)
 }
 
-async fn collect_utxo_changes(database: &MongoDb, milestone_index: MilestoneIndex) -> ApiResult<UtxoChangesResponse> {
-    let ledger_index = database
-        .collection::<MilestoneCollection>()
-        .get_ledger_index()
-        .await?
-        .ok_or(MissingError::NoResults)?;
+async fn collect_utxo_changes(
+    database: &impl Storage,
+    milestone_index: MilestoneIndex,
+) -> ApiResult<UtxoChangesResponse> {
+    let ledger_index = database.get_ledger_index().await?.ok_or(MissingError::NoResults)?;
     let UtxoChangesResult {
         created_outputs,
         consumed_outputs,
     } = database
-        .collection::<OutputCollection>()
         .get_utxo_changes(milestone_index, ledger_index)
         .await?
         .ok_or(MissingError::NoResults)?;
@@ -525,3 +731,65 @@ async fn collect_utxo_changes(database: &MongoDb, milestone_index: MilestoneInde
         consumed_outputs,
     })
 }
+
+api_schema! {
+    #[derive(Default, Deserialize)]
+    struct CreateSnapshotRequest {
+        index: Option<MilestoneIndex>,
+    }
+}
+
+async fn create_snapshot<S: Storage>(
+    OptionalJson(CreateSnapshotRequest { index }): OptionalJson<CreateSnapshotRequest>,
+    Extension(database): Extension<S>,
+    Extension(config): Extension<ApiConfigData>,
+) -> ApiResult<CreateSnapshotResponse> {
+    let snapshot_dir = config.snapshot_dir.ok_or(UnimplementedError)?;
+    let index = match index {
+        Some(index) => index,
+        None => database.get_ledger_index().await?.ok_or(MissingError::NoResults)?,
+    };
+    let file_path = snapshot_dir.join(format!("full_snapshot_{index}.bin"));
+    tokio::task::spawn_blocking(move || std::fs::create_dir_all(snapshot_dir)).await??;
+    write_full_snapshot(&database, index, &file_path)
+        .await
+        .map_err(|e| match e {
+            SnapshotError::MissingMilestone(_) => ApiError::from(MissingError::NoResults),
+            e => ApiError::from(e),
+        })?;
+
+    Ok(CreateSnapshotResponse {
+        index,
+        file_path: file_path.display().to_string(),
+    })
+}
+
+#[cfg(all(test, feature = "inx"))]
+mod test {
+    use axum::{http::header::CONTENT_TYPE, response::IntoResponse};
+    use iota_types::block::rand::block::rand_block;
+
+    use super::*;
+
+    #[test]
+    fn submitted_block_content_type() {
+        let block = rand_block();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, BYTE_CONTENT_HEADER.clone());
+        assert_eq!(
+            SubmittedBlock::parse(&headers, &block.pack_to_vec()).unwrap(),
+            SubmittedBlock::Raw(block.pack_to_vec())
+        );
+
+        let json = serde_json::to_vec(&BlockDto::from(&block)).unwrap();
+        let expected = SubmittedBlock::Json(Box::new(BlockDto::from(&block)));
+        assert_eq!(SubmittedBlock::parse(&HeaderMap::new(), &json).unwrap(), expected);
+        headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
+        assert_eq!(SubmittedBlock::parse(&headers, &json).unwrap(), expected);
+
+        let error = SubmittedBlock::parse(&headers, b"{\"protocolVersion\":").unwrap_err();
+        assert!(matches!(error, RequestError::InvalidJson(_)));
+        assert_eq!(ApiError::from(error).into_response().status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -4,7 +4,7 @@
 use std::{num::ParseIntError, str::ParseBoolError};
 
 use axum::{
-    extract::rejection::{QueryRejection, TypedHeaderRejection},
+    extract::rejection::{BytesRejection, QueryRejection, TypedHeaderRejection},
     response::IntoResponse,
 };
 use chronicle::db::mongodb::collections::ParseSortError;
@@ -62,10 +62,14 @@ macro_rules! impl_internal_error {
 
 impl_internal_error!(
     mongodb::error::Error,
+    chronicle::db::StorageError,
     axum::extract::rejection::ExtensionRejection,
     auth_helper::jwt::Error,
     argon2::Error,
-    iota_types::block::Error
+    iota_types::block::Error,
+    std::io::Error,
+    tokio::task::JoinError,
+    chronicle::snapshot::SnapshotError
 );
 
 impl IntoResponse for ApiError {
@@ -77,12 +81,22 @@ impl IntoResponse for ApiError {
         } else {
             self.error.to_string()
         };
-        ErrorBody {
+        let retry_after = self
+            .error
+            .downcast_ref::<RateLimitedError>()
+            .map(|error| retry_after_secs(&error.retry_after));
+        let mut response = ErrorBody {
             status: self.code,
             code: self.code.as_u16(),
             message,
         }
-        .into_response()
+        .into_response();
+        if let Some(retry_after) = retry_after {
+            response
+                .headers_mut()
+                .insert(hyper::header::RETRY_AFTER, retry_after.into());
+        }
+        response
     }
 }
 
@@ -98,6 +112,8 @@ pub enum CorruptStateError {
     NodeConfig,
     #[error("no protocol parameters in the database")]
     ProtocolParams,
+    #[error("unknown payload kind `{0}` in the database")]
+    PayloadKind(String),
 }
 
 impl ErrorStatus for CorruptStateError {
@@ -113,11 +129,18 @@ pub enum AuthError {
     IncorrectPassword,
     #[error("invalid JWT provided: {0}")]
     InvalidJwt(auth_helper::jwt::Error),
+    #[error("the token lacks the `{0}` scope")]
+    MissingScope(super::auth::Scope),
+    #[error("the API key of the token was revoked")]
+    RevokedKey,
 }
 
 impl ErrorStatus for AuthError {
     fn status(&self) -> StatusCode {
-        StatusCode::UNAUTHORIZED
+        match self {
+            Self::MissingScope(_) => StatusCode::FORBIDDEN,
+            Self::IncorrectPassword | Self::InvalidJwt(_) | Self::RevokedKey => StatusCode::UNAUTHORIZED,
+        }
     }
 }
 
@@ -161,13 +184,19 @@ impl IntoResponse for MissingError {
 
 #[derive(Error, Debug)]
 pub enum RequestError {
+    #[error("only one of `index` and `timestamp` can be provided")]
+    AmbiguousMilestone,
     #[error("invalid cursor")]
     BadPagingState,
     #[error("invalid time range")]
     BadTimeRange,
+    #[error("too many ids provided: {provided} exceeds the maximum of {max}")]
+    BatchTooLarge { provided: usize, max: usize },
 
     #[error("invalid IOTA Stardust data: {0}")]
     IotaStardust(#[from] iota_types::block::Error),
+    #[error("invalid block provided: {0}")]
+    Block(#[from] iota_types::block::DtoError),
     #[error("invalid bool value provided: {0}")]
     Bool(#[from] ParseBoolError),
     #[error("invalid U256 value provided: {0}")]
@@ -176,13 +205,19 @@ pub enum RequestError {
     Hex(#[from] prefix_hex::Error),
     #[error("invalid integer value provided: {0}")]
     Int(#[from] ParseIntError),
+    #[error("invalid request body provided: {0}")]
+    InvalidBody(#[from] BytesRejection),
     #[error("invalid authorization header provided: {0}")]
     InvalidAuthHeader(#[from] TypedHeaderRejection),
+    #[error("invalid JSON body provided: {0}")]
+    InvalidJson(#[from] serde_json::Error),
     #[error("invalid query parameters provided: {0}")]
     InvalidQueryParams(#[from] QueryRejection),
     #[cfg(feature = "poi")]
     #[error(transparent)]
     PoI(#[from] crate::api::poi::RequestError),
+    #[error("invalid packed output provided: {0}")]
+    RawOutput(String),
     #[error("invalid sort order provided: {0}")]
     SortOrder(#[from] ParseSortError),
 }
@@ -193,6 +228,50 @@ impl ErrorStatus for RequestError {
     }
 }
 
+#[derive(Error, Debug)]
+#[allow(missing_docs)]
+#[error("too many requests; retry in {} s", retry_after_secs(.retry_after))]
+pub struct RateLimitedError {
+    pub retry_after: std::time::Duration,
+}
+
+/// Rounds up to whole seconds, as required by the `Retry-After` header.
+fn retry_after_secs(retry_after: &std::time::Duration) -> u64 {
+    retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0)
+}
+
+impl ErrorStatus for RateLimitedError {
+    fn status(&self) -> StatusCode {
+        StatusCode::TOO_MANY_REQUESTS
+    }
+}
+
+impl IntoResponse for RateLimitedError {
+    fn into_response(self) -> axum::response::Response {
+        ApiError::from(self).into_response()
+    }
+}
+
+#[cfg(feature = "inx")]
+#[derive(Error, Debug)]
+#[allow(missing_docs)]
+pub enum NodeError {
+    #[error("the node rejected the request: {0}")]
+    Rejected(String),
+    #[error("the node is unavailable: {0}")]
+    Unavailable(Box<chronicle::inx::InxError>),
+}
+
+#[cfg(feature = "inx")]
+impl ErrorStatus for NodeError {
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Rejected(_) => StatusCode::BAD_REQUEST,
+            Self::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum ConfigError {
     #[error("invalid allow-origin header in config: {0}")]
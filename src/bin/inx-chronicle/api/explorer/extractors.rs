@@ -9,15 +9,21 @@ use axum::{
     Extension,
 };
 use chronicle::{
-    db::mongodb::collections::SortOrder,
+    db::mongodb::collections::{SortOrder, UnlockEventKind},
     model::{
+        payload::milestone::MigratedFundsEntry,
         tangle::{MilestoneIndex, MilestoneTimestamp},
         utxo::OutputId,
     },
 };
 use serde::Deserialize;
 
-use crate::api::{config::ApiConfigData, error::RequestError, ApiError, DEFAULT_PAGE_SIZE};
+use crate::api::{
+    config::ApiConfigData,
+    error::RequestError,
+    openapi::{api_schema, impl_query_extractor},
+    ApiError, DEFAULT_PAGE_SIZE,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LedgerUpdatesByAddressPagination {
@@ -26,13 +32,15 @@ pub struct LedgerUpdatesByAddressPagination {
     pub cursor: Option<(MilestoneIndex, Option<(OutputId, bool)>)>,
 }
 
-#[derive(Clone, Deserialize, Default)]
-#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
-pub struct LedgerUpdatesByAddressPaginationQuery {
-    pub page_size: Option<usize>,
-    pub sort: Option<String>,
-    pub start_milestone_index: Option<MilestoneIndex>,
-    pub cursor: Option<String>,
+api_schema! {
+    #[derive(Clone, Deserialize, Default)]
+    #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+    pub struct LedgerUpdatesByAddressPaginationQuery {
+        pub page_size: Option<usize>,
+        pub sort: Option<String>,
+        pub start_milestone_index: Option<MilestoneIndex>,
+        pub cursor: Option<String>,
+    }
 }
 
 #[derive(Clone)]
@@ -116,11 +124,13 @@ pub struct LedgerUpdatesByMilestonePagination {
     pub cursor: Option<(OutputId, bool)>,
 }
 
-#[derive(Clone, Deserialize, Default)]
-#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
-pub struct LedgerUpdatesByMilestonePaginationQuery {
-    pub page_size: Option<usize>,
-    pub cursor: Option<String>,
+api_schema! {
+    #[derive(Clone, Deserialize, Default)]
+    #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+    pub struct LedgerUpdatesByMilestonePaginationQuery {
+        pub page_size: Option<usize>,
+        pub cursor: Option<String>,
+    }
 }
 
 #[derive(Clone)]
@@ -184,14 +194,16 @@ pub struct MilestonesPagination {
     pub cursor: Option<MilestoneIndex>,
 }
 
-#[derive(Clone, Deserialize, Default)]
-#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
-pub struct MilestonesPaginationQuery {
-    pub start_timestamp: Option<u32>,
-    pub end_timestamp: Option<u32>,
-    pub sort: Option<String>,
-    pub page_size: Option<usize>,
-    pub cursor: Option<String>,
+api_schema! {
+    #[derive(Clone, Deserialize, Default)]
+    #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+    pub struct MilestonesPaginationQuery {
+        pub start_timestamp: Option<u32>,
+        pub end_timestamp: Option<u32>,
+        pub sort: Option<String>,
+        pub page_size: Option<usize>,
+        pub cursor: Option<String>,
+    }
 }
 
 #[derive(Clone)]
@@ -258,13 +270,139 @@ impl<B: Send> FromRequest<B> for MilestonesPagination {
     }
 }
 
+pub struct TreasuryHistoryPagination {
+    pub sort: SortOrder,
+    pub page_size: usize,
+    pub cursor: Option<MilestoneIndex>,
+}
+
+api_schema! {
+    #[derive(Clone, Deserialize, Default)]
+    #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+    pub struct TreasuryHistoryPaginationQuery {
+        pub sort: Option<String>,
+        pub page_size: Option<usize>,
+        pub cursor: Option<String>,
+    }
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for TreasuryHistoryPagination {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<TreasuryHistoryPaginationQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
+
+        let sort = query
+            .sort
+            .as_deref()
+            .map_or(Ok(Default::default()), str::parse)
+            .map_err(RequestError::SortOrder)?;
+
+        // The treasury is keyed by milestone index, so the milestones cursor is reused.
+        let (page_size, cursor) = if let Some(cursor) = query.cursor {
+            let cursor: MilestonesCursor = cursor.parse()?;
+            (cursor.page_size, Some(cursor.milestone_index))
+        } else {
+            (query.page_size.unwrap_or(DEFAULT_PAGE_SIZE), None)
+        };
+
+        Ok(TreasuryHistoryPagination {
+            sort,
+            page_size: page_size.min(config.max_page_size),
+            cursor,
+        })
+    }
+}
+
+pub struct MigratedFundsPagination {
+    pub page_size: usize,
+    pub cursor: Option<(MilestoneIndex, [u8; MigratedFundsEntry::TAIL_TRANSACTION_HASH_LENGTH])>,
+}
+
+api_schema! {
+    #[derive(Clone, Deserialize, Default)]
+    #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+    pub struct MigratedFundsPaginationQuery {
+        pub page_size: Option<usize>,
+        pub cursor: Option<String>,
+    }
+}
+
+#[derive(Clone)]
+pub struct MigratedFundsCursor {
+    pub milestone_index: MilestoneIndex,
+    pub tail_transaction_hash: [u8; MigratedFundsEntry::TAIL_TRANSACTION_HASH_LENGTH],
+    pub page_size: usize,
+}
+
+impl FromStr for MigratedFundsCursor {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.split('.').collect();
+        Ok(match parts[..] {
+            [ms, h, ps] => MigratedFundsCursor {
+                milestone_index: ms.parse().map_err(RequestError::from)?,
+                tail_transaction_hash: prefix_hex::decode(h).map_err(RequestError::from)?,
+                page_size: ps.parse().map_err(RequestError::from)?,
+            },
+            _ => return Err(ApiError::from(RequestError::BadPagingState)),
+        })
+    }
+}
+
+impl Display for MigratedFundsCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}",
+            self.milestone_index,
+            prefix_hex::encode(self.tail_transaction_hash),
+            self.page_size
+        )
+    }
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for MigratedFundsPagination {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<MigratedFundsPaginationQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
+
+        let (page_size, cursor) = if let Some(cursor) = query.cursor {
+            let cursor: MigratedFundsCursor = cursor.parse()?;
+            (
+                cursor.page_size,
+                Some((cursor.milestone_index, cursor.tail_transaction_hash)),
+            )
+        } else {
+            (query.page_size.unwrap_or(DEFAULT_PAGE_SIZE), None)
+        };
+
+        Ok(MigratedFundsPagination {
+            page_size: page_size.min(config.max_page_size),
+            cursor,
+        })
+    }
+}
+
 const DEFAULT_TOP_RICHLIST: usize = 100;
 
-#[derive(Clone, Deserialize)]
-#[serde(default, deny_unknown_fields)]
-pub struct RichestAddressesQuery {
-    pub top: usize,
-    pub ledger_index: Option<MilestoneIndex>,
+api_schema! {
+    #[derive(Clone, Deserialize)]
+    #[serde(default, deny_unknown_fields)]
+    pub struct RichestAddressesQuery {
+        pub top: usize,
+        pub ledger_index: Option<MilestoneIndex>,
+    }
 }
 
 impl Default for RichestAddressesQuery {
@@ -290,10 +428,12 @@ impl<B: Send> FromRequest<B> for RichestAddressesQuery {
     }
 }
 
-#[derive(Copy, Clone, Deserialize, Default)]
-#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
-pub struct LedgerIndex {
-    pub ledger_index: Option<MilestoneIndex>,
+api_schema! {
+    #[derive(Copy, Clone, Deserialize, Default)]
+    #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+    pub struct LedgerIndex {
+        pub ledger_index: Option<MilestoneIndex>,
+    }
 }
 
 #[async_trait]
@@ -308,6 +448,235 @@ impl<B: Send> FromRequest<B> for LedgerIndex {
     }
 }
 
+const DEFAULT_GRAPH_DEPTH: u8 = 2;
+const MAX_GRAPH_DEPTH: u8 = 10;
+const DEFAULT_GRAPH_MAX_NODES: usize = 100;
+
+api_schema! {
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+    #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+    pub struct BlockGraphQuery {
+        /// The number of hops to follow from the block.
+        pub depth: u8,
+        pub max_nodes: usize,
+    }
+}
+
+impl Default for BlockGraphQuery {
+    fn default() -> Self {
+        Self {
+            depth: DEFAULT_GRAPH_DEPTH,
+            max_nodes: DEFAULT_GRAPH_MAX_NODES,
+        }
+    }
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for BlockGraphQuery {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(mut query) = Query::<BlockGraphQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
+        query.depth = query.depth.min(MAX_GRAPH_DEPTH);
+        query.max_nodes = query.max_nodes.min(config.max_page_size).max(1);
+        Ok(query)
+    }
+}
+
+api_schema! {
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+    #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+    pub struct MilestoneGraphQuery {
+        pub max_nodes: usize,
+    }
+}
+
+impl Default for MilestoneGraphQuery {
+    fn default() -> Self {
+        Self {
+            max_nodes: DEFAULT_GRAPH_MAX_NODES,
+        }
+    }
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for MilestoneGraphQuery {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(mut query) = Query::<MilestoneGraphQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
+        query.max_nodes = query.max_nodes.min(config.max_page_size).max(1);
+        Ok(query)
+    }
+}
+
+api_schema! {
+    #[derive(Copy, Clone, Deserialize, Default)]
+    #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+    pub struct BalanceAtQuery {
+        /// The timestamp at which the unlock conditions are evaluated. Defaults to the timestamp of the ledger index.
+        pub timestamp: Option<u32>,
+    }
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for BalanceAtQuery {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<BalanceAtQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        Ok(query)
+    }
+}
+
+/// Selects the milestone at which versioned data such as the protocol parameters are looked up.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EffectiveAt {
+    LedgerIndex,
+    Index(MilestoneIndex),
+    /// The newest milestone that is not newer than the timestamp.
+    Timestamp(MilestoneTimestamp),
+}
+
+api_schema! {
+    #[derive(Copy, Clone, Deserialize, Default)]
+    #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+    pub struct EffectiveAtQuery {
+        pub index: Option<MilestoneIndex>,
+        pub timestamp: Option<u32>,
+    }
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for EffectiveAt {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<EffectiveAtQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        Ok(match (query.index, query.timestamp) {
+            (None, None) => EffectiveAt::LedgerIndex,
+            (Some(index), None) => EffectiveAt::Index(index),
+            (None, Some(timestamp)) => EffectiveAt::Timestamp(timestamp.into()),
+            (Some(_), Some(_)) => return Err(ApiError::from(RequestError::AmbiguousMilestone)),
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct UnlockCalendarPagination {
+    pub start_timestamp: Option<MilestoneTimestamp>,
+    pub end_timestamp: Option<MilestoneTimestamp>,
+    pub page_size: usize,
+    pub cursor: Option<(OutputId, UnlockEventKind)>,
+}
+
+api_schema! {
+    #[derive(Clone, Deserialize, Default)]
+    #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+    pub struct UnlockCalendarPaginationQuery {
+        /// Defaults to the timestamp of the ledger index.
+        pub start_timestamp: Option<u32>,
+        /// Defaults to 30 days after the start.
+        pub end_timestamp: Option<u32>,
+        pub page_size: Option<usize>,
+        pub cursor: Option<String>,
+    }
+}
+
+#[derive(Clone)]
+pub struct UnlockCalendarCursor {
+    pub timestamp: MilestoneTimestamp,
+    pub output_id: OutputId,
+    pub kind: UnlockEventKind,
+    pub end_timestamp: MilestoneTimestamp,
+    pub page_size: usize,
+}
+
+impl FromStr for UnlockCalendarCursor {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.split('.').collect();
+        Ok(match parts[..] {
+            [t, o, k, e, ps] => UnlockCalendarCursor {
+                timestamp: t.parse::<u32>().map_err(RequestError::from)?.into(),
+                output_id: o.parse().map_err(RequestError::from)?,
+                kind: *UnlockEventKind::ALL
+                    .iter()
+                    .find(|kind| kind.as_str() == k)
+                    .ok_or(RequestError::BadPagingState)?,
+                end_timestamp: e.parse::<u32>().map_err(RequestError::from)?.into(),
+                page_size: ps.parse().map_err(RequestError::from)?,
+            },
+            _ => return Err(ApiError::from(RequestError::BadPagingState)),
+        })
+    }
+}
+
+impl Display for UnlockCalendarCursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}.{}.{}",
+            self.timestamp.0,
+            self.output_id.to_hex(),
+            self.kind.as_str(),
+            self.end_timestamp.0,
+            self.page_size
+        )
+    }
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for UnlockCalendarPagination {
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<UnlockCalendarPaginationQuery>::from_request(req)
+            .await
+            .map_err(RequestError::from)?;
+        let Extension(config) = Extension::<ApiConfigData>::from_request(req).await?;
+
+        if matches!((query.start_timestamp, query.end_timestamp), (Some(start), Some(end)) if end < start) {
+            return Err(ApiError::from(RequestError::BadTimeRange));
+        }
+
+        let (start_timestamp, end_timestamp, page_size, cursor) = if let Some(cursor) = query.cursor {
+            let cursor: UnlockCalendarCursor = cursor.parse()?;
+            (
+                Some(cursor.timestamp),
+                Some(cursor.end_timestamp),
+                cursor.page_size,
+                Some((cursor.output_id, cursor.kind)),
+            )
+        } else {
+            (
+                query.start_timestamp.map(Into::into),
+                query.end_timestamp.map(Into::into),
+                query.page_size.unwrap_or(DEFAULT_PAGE_SIZE),
+                None,
+            )
+        };
+
+        Ok(UnlockCalendarPagination {
+            start_timestamp,
+            end_timestamp,
+            page_size: page_size.min(config.max_page_size),
+            cursor,
+        })
+    }
+}
+
 #[derive(Copy, Clone, Deserialize, Default)]
 #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
 pub struct MilestoneRange {
@@ -336,12 +705,14 @@ pub struct BlocksByMilestoneIndexPagination {
     pub cursor: Option<u32>,
 }
 
-#[derive(Clone, Deserialize, Default)]
-#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
-pub struct BlocksByMilestoneIndexPaginationQuery {
-    pub sort: Option<String>,
-    pub page_size: Option<usize>,
-    pub cursor: Option<String>,
+api_schema! {
+    #[derive(Clone, Deserialize, Default)]
+    #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+    pub struct BlocksByMilestoneIndexPaginationQuery {
+        pub sort: Option<String>,
+        pub page_size: Option<usize>,
+        pub cursor: Option<String>,
+    }
 }
 
 #[derive(Clone)]
@@ -408,12 +779,14 @@ pub struct BlocksByMilestoneIdPagination {
     pub cursor: Option<u32>,
 }
 
-#[derive(Clone, Deserialize, Default)]
-#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
-pub struct BlocksByMilestoneIdPaginationQuery {
-    pub sort: Option<String>,
-    pub page_size: Option<usize>,
-    pub cursor: Option<String>,
+api_schema! {
+    #[derive(Clone, Deserialize, Default)]
+    #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+    pub struct BlocksByMilestoneIdPaginationQuery {
+        pub sort: Option<String>,
+        pub page_size: Option<usize>,
+        pub cursor: Option<String>,
+    }
 }
 
 #[async_trait]
@@ -447,6 +820,23 @@ impl<B: Send> FromRequest<B> for BlocksByMilestoneIdPagination {
     }
 }
 
+impl_query_extractor!(
+    LedgerUpdatesByAddressPagination => LedgerUpdatesByAddressPaginationQuery,
+    LedgerUpdatesByMilestonePagination => LedgerUpdatesByMilestonePaginationQuery,
+    MilestonesPagination => MilestonesPaginationQuery,
+    RichestAddressesQuery => RichestAddressesQuery,
+    LedgerIndex => LedgerIndex,
+    BlocksByMilestoneIndexPagination => BlocksByMilestoneIndexPaginationQuery,
+    BlocksByMilestoneIdPagination => BlocksByMilestoneIdPaginationQuery,
+    BlockGraphQuery => BlockGraphQuery,
+    MilestoneGraphQuery => MilestoneGraphQuery,
+    BalanceAtQuery => BalanceAtQuery,
+    UnlockCalendarPagination => UnlockCalendarPaginationQuery,
+    TreasuryHistoryPagination => TreasuryHistoryPaginationQuery,
+    MigratedFundsPagination => MigratedFundsPaginationQuery,
+    EffectiveAt => EffectiveAtQuery,
+);
+
 #[cfg(test)]
 mod test {
     use axum::{extract::RequestParts, http::Request};
@@ -477,6 +867,29 @@ mod test {
         assert_eq!(parsed.to_string(), cursor);
     }
 
+    #[test]
+    fn migrated_funds_cursor_from_to_str() {
+        let milestone_index = 164338324u32;
+        let tail_transaction_hash_str = prefix_hex::encode([7u8; MigratedFundsEntry::TAIL_TRANSACTION_HASH_LENGTH]);
+        let page_size_str = "1337";
+
+        let cursor = format!("{milestone_index}.{tail_transaction_hash_str}.{page_size_str}");
+        let parsed: MigratedFundsCursor = cursor.parse().unwrap();
+        assert_eq!(parsed.to_string(), cursor);
+
+        assert!("1.0x07.1337".parse::<MigratedFundsCursor>().is_err());
+    }
+
+    #[test]
+    fn unlock_calendar_cursor_from_to_str() {
+        let output_id_str = "0xfa0de75d225cca2799395e5fc340702fc7eac821d2bdd79911126f131ae097a20100";
+
+        let cursor = format!("20.{output_id_str}.expires_from_address.30.1337");
+        let parsed: UnlockCalendarCursor = cursor.parse().unwrap();
+        assert_eq!(parsed.kind, UnlockEventKind::ExpiresFromAddress);
+        assert_eq!(parsed.to_string(), cursor);
+    }
+
     #[tokio::test]
     async fn page_size_clamped() {
         let mut req = RequestParts::new(
@@ -514,4 +927,122 @@ mod test {
             }
         );
     }
+
+    #[tokio::test]
+    async fn graph_query_clamped() {
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method("GET")
+                .uri("/blocks/0x00/graph?depth=200&maxNodes=9999999")
+                .extension(ApiConfigData::try_from(ApiConfig::default()).unwrap())
+                .body(())
+                .unwrap(),
+        );
+        assert_eq!(
+            BlockGraphQuery::from_request(&mut req).await.unwrap(),
+            BlockGraphQuery {
+                depth: MAX_GRAPH_DEPTH,
+                max_nodes: 1000,
+            }
+        );
+
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method("GET")
+                .uri("/blocks/0x00/graph")
+                .extension(ApiConfigData::try_from(ApiConfig::default()).unwrap())
+                .body(())
+                .unwrap(),
+        );
+        assert_eq!(
+            BlockGraphQuery::from_request(&mut req).await.unwrap(),
+            BlockGraphQuery::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn unlock_calendar_pagination() {
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method("GET")
+                .uri("/ledger/unlock-calendar/0x00?startTimestamp=10&pageSize=9999999")
+                .extension(ApiConfigData::try_from(ApiConfig::default()).unwrap())
+                .body(())
+                .unwrap(),
+        );
+        assert_eq!(
+            UnlockCalendarPagination::from_request(&mut req).await.unwrap(),
+            UnlockCalendarPagination {
+                start_timestamp: Some(10.into()),
+                end_timestamp: None,
+                page_size: 1000,
+                cursor: None,
+            }
+        );
+
+        let output_id_str = "0xfa0de75d225cca2799395e5fc340702fc7eac821d2bdd79911126f131ae097a20100";
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method("GET")
+                .uri(format!(
+                    "/ledger/unlock-calendar/0x00?startTimestamp=1&cursor=20.{output_id_str}.expires_to_address.30.5"
+                ))
+                .extension(ApiConfigData::try_from(ApiConfig::default()).unwrap())
+                .body(())
+                .unwrap(),
+        );
+        assert_eq!(
+            UnlockCalendarPagination::from_request(&mut req).await.unwrap(),
+            UnlockCalendarPagination {
+                start_timestamp: Some(20.into()),
+                end_timestamp: Some(30.into()),
+                page_size: 5,
+                cursor: Some((output_id_str.parse().unwrap(), UnlockEventKind::ExpiresToAddress)),
+            }
+        );
+
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method("GET")
+                .uri(format!(
+                    "/ledger/unlock-calendar/0x00?cursor=20.{output_id_str}.unknown.30.5"
+                ))
+                .extension(ApiConfigData::try_from(ApiConfig::default()).unwrap())
+                .body(())
+                .unwrap(),
+        );
+        assert!(UnlockCalendarPagination::from_request(&mut req).await.is_err());
+
+        let mut req = RequestParts::new(
+            Request::builder()
+                .method("GET")
+                .uri("/ledger/unlock-calendar/0x00?startTimestamp=10&endTimestamp=5")
+                .extension(ApiConfigData::try_from(ApiConfig::default()).unwrap())
+                .body(())
+                .unwrap(),
+        );
+        assert!(UnlockCalendarPagination::from_request(&mut req).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn effective_at() {
+        async fn effective_at(query: &str) -> Result<EffectiveAt, ApiError> {
+            let mut req = RequestParts::new(
+                Request::builder()
+                    .method("GET")
+                    .uri(format!("/protocol-parameters/at{}", query))
+                    .body(())
+                    .unwrap(),
+            );
+            EffectiveAt::from_request(&mut req).await
+        }
+
+        assert_eq!(effective_at("").await.unwrap(), EffectiveAt::LedgerIndex);
+        assert_eq!(effective_at("?index=7").await.unwrap(), EffectiveAt::Index(7.into()));
+        assert_eq!(
+            effective_at("?timestamp=12345").await.unwrap(),
+            EffectiveAt::Timestamp(12345.into())
+        );
+        assert!(effective_at("?index=7&timestamp=12345").await.is_err());
+    }
 }
@@ -6,33 +6,49 @@ use std::ops::Range;
 use chronicle::{
     db::mongodb::collections::{
         DistributionStat, LedgerUpdateByAddressRecord, LedgerUpdateByMilestoneRecord, MilestoneResult,
+        TreasuryHistoryResult, UnlockEventKind, UnlockEventResult,
     },
     model::{
+        metadata::LedgerInclusionState,
+        node::NodeConfiguration,
         tangle::{MilestoneIndex, MilestoneTimestamp},
         utxo::Address,
+        RentStructure,
     },
+    poi::MilestoneKeyRangeDto,
+    tangle::MilestoneStats,
 };
+use iota_types::{api::core::response::BaseTokenResponse, block::protocol::dto::ProtocolParametersDto};
 use serde::{Deserialize, Serialize};
 
-use crate::api::responses::impl_success_response;
+use serde_json::Value;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct LedgerUpdatesByAddressResponse {
-    pub address: String,
-    pub items: Vec<LedgerUpdateByAddressDto>,
-    pub cursor: Option<String>,
+use crate::api::{
+    openapi::{api_schema, string_enum_schema, ApiSchema, SchemaGenerator},
+    responses::{base_token_dto, impl_success_response},
+};
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct LedgerUpdatesByAddressResponse {
+        pub address: String,
+        pub items: Vec<LedgerUpdateByAddressDto>,
+        pub cursor: Option<String>,
+    }
 }
 
 impl_success_response!(LedgerUpdatesByAddressResponse);
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct LedgerUpdateByAddressDto {
-    pub output_id: String,
-    pub is_spent: bool,
-    pub milestone_index: MilestoneIndex,
-    pub milestone_timestamp: MilestoneTimestamp,
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct LedgerUpdateByAddressDto {
+        pub output_id: String,
+        pub is_spent: bool,
+        pub milestone_index: MilestoneIndex,
+        pub milestone_timestamp: MilestoneTimestamp,
+    }
 }
 
 impl From<LedgerUpdateByAddressRecord> for LedgerUpdateByAddressDto {
@@ -46,22 +62,26 @@ impl From<LedgerUpdateByAddressRecord> for LedgerUpdateByAddressDto {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct LedgerUpdatesByMilestoneResponse {
-    pub milestone_index: MilestoneIndex,
-    pub items: Vec<LedgerUpdateByMilestoneDto>,
-    pub cursor: Option<String>,
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct LedgerUpdatesByMilestoneResponse {
+        pub milestone_index: MilestoneIndex,
+        pub items: Vec<LedgerUpdateByMilestoneDto>,
+        pub cursor: Option<String>,
+    }
 }
 
 impl_success_response!(LedgerUpdatesByMilestoneResponse);
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct LedgerUpdateByMilestoneDto {
-    pub address: Address,
-    pub output_id: String,
-    pub is_spent: bool,
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct LedgerUpdateByMilestoneDto {
+        pub address: Address,
+        pub output_id: String,
+        pub is_spent: bool,
+    }
 }
 
 impl From<LedgerUpdateByMilestoneRecord> for LedgerUpdateByMilestoneDto {
@@ -74,99 +94,314 @@ impl From<LedgerUpdateByMilestoneRecord> for LedgerUpdateByMilestoneDto {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct BalanceResponse {
-    pub total_balance: String,
-    pub sig_locked_balance: String,
-    pub ledger_index: MilestoneIndex,
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BalanceResponse {
+        pub total_balance: String,
+        pub sig_locked_balance: String,
+        pub ledger_index: MilestoneIndex,
+    }
 }
 
 impl_success_response!(BalanceResponse);
 
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct BlockChildrenResponse {
-    pub block_id: String,
-    pub max_results: usize,
-    pub count: usize,
-    pub children: Vec<String>,
+api_schema! {
+    /// Response of `GET /api/explorer/v2/balance/:address/at`.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct TimedBalanceResponse {
+        pub total_balance: String,
+        /// The part of the balance that is still timelocked at the timestamp.
+        pub timelocked_balance: String,
+        pub spendable_balance: String,
+        pub timestamp: MilestoneTimestamp,
+        pub ledger_index: MilestoneIndex,
+    }
+}
+
+impl_success_response!(TimedBalanceResponse);
+
+api_schema! {
+    /// Response of `GET /api/explorer/v2/ledger/unlock-calendar/:address`.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct UnlockCalendarResponse {
+        pub address: String,
+        pub start_timestamp: MilestoneTimestamp,
+        pub end_timestamp: MilestoneTimestamp,
+        pub items: Vec<UnlockEventDto>,
+        pub cursor: Option<String>,
+        pub ledger_index: MilestoneIndex,
+    }
+}
+
+impl_success_response!(UnlockCalendarResponse);
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct UnlockEventDto {
+        pub output_id: String,
+        pub amount: String,
+        pub kind: UnlockEventKind,
+        pub timestamp: MilestoneTimestamp,
+    }
+}
+
+impl From<UnlockEventResult> for UnlockEventDto {
+    fn from(res: UnlockEventResult) -> Self {
+        Self {
+            output_id: res.output_id.to_hex(),
+            amount: res.amount.0.to_string(),
+            kind: res.kind,
+            timestamp: res.timestamp,
+        }
+    }
+}
+
+api_schema! {
+    /// Response of `GET /api/explorer/v2/balance/:address/storage-deposit`.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct AddressStorageDepositResponse {
+        pub total_balance: String,
+        /// The part of the balance that is locked as storage deposit of the owned outputs.
+        pub storage_deposit: String,
+        /// The part of the balance that is not locked as storage deposit, owed to other addresses by storage deposit
+        /// return unlock conditions or timelocked.
+        pub spendable_balance: String,
+        pub output_count: usize,
+        pub rent_structure: RentStructureDto,
+        pub ledger_index: MilestoneIndex,
+    }
+}
+
+impl_success_response!(AddressStorageDepositResponse);
+
+api_schema! {
+    /// Response of `POST /api/explorer/v2/storage-deposit`.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StorageDepositResponse {
+        /// The minimum amount that the output must hold.
+        pub storage_deposit: String,
+        pub num_key_bytes: u64,
+        pub num_data_bytes: u64,
+        pub rent_structure: RentStructureDto,
+        pub ledger_index: MilestoneIndex,
+    }
+}
+
+impl_success_response!(StorageDepositResponse);
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RentStructureDto {
+        pub v_byte_cost: u32,
+        pub v_byte_factor_data: u8,
+        pub v_byte_factor_key: u8,
+    }
+}
+
+impl From<RentStructure> for RentStructureDto {
+    fn from(value: RentStructure) -> Self {
+        Self {
+            v_byte_cost: value.v_byte_cost,
+            v_byte_factor_data: value.v_byte_factor_data,
+            v_byte_factor_key: value.v_byte_factor_key,
+        }
+    }
+}
+
+api_schema! {
+    #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BlockChildrenResponse {
+        pub block_id: String,
+        pub max_results: usize,
+        pub count: usize,
+        pub children: Vec<String>,
+    }
 }
 
 impl_success_response!(BlockChildrenResponse);
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct MilestonesResponse {
-    pub items: Vec<MilestoneDto>,
-    pub cursor: Option<String>,
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct MilestonesResponse {
+        pub items: Vec<MilestoneDto>,
+        pub cursor: Option<String>,
+    }
 }
 
 impl_success_response!(MilestonesResponse);
 
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct BlockPayloadTypeDto {
-    pub block_id: String,
-    #[serde(rename = "payloadType")]
-    pub payload_kind: Option<u32>,
+api_schema! {
+    #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BlockPayloadTypeDto {
+        pub block_id: String,
+        #[serde(rename = "payloadType")]
+        pub payload_kind: Option<u32>,
+    }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct BlocksByMilestoneResponse {
-    pub blocks: Vec<BlockPayloadTypeDto>,
-    pub cursor: Option<String>,
+api_schema! {
+    #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BlocksByMilestoneResponse {
+        pub blocks: Vec<BlockPayloadTypeDto>,
+        pub cursor: Option<String>,
+    }
 }
 
 impl_success_response!(BlocksByMilestoneResponse);
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct MilestoneDto {
-    milestone_id: String,
-    index: MilestoneIndex,
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct MilestoneDto {
+        milestone_id: String,
+        index: MilestoneIndex,
+        timestamp: MilestoneTimestamp,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        signature_verified: Option<bool>,
+        /// The statistics of the milestone. Missing for milestones that were stored before they were computed.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        stats: Option<MilestoneStatsDto>,
+    }
 }
 
+impl_success_response!(MilestoneDto);
+
 impl From<MilestoneResult> for MilestoneDto {
     fn from(res: MilestoneResult) -> Self {
         Self {
             milestone_id: res.milestone_id.to_hex(),
             index: res.index,
+            timestamp: res.timestamp,
+            signature_verified: res.signature_verified,
+            stats: res.stats.map(Into::into),
+        }
+    }
+}
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct MilestoneStatsDto {
+        pub block_count: u32,
+        pub no_payload_count: u32,
+        pub tagged_data_count: u32,
+        pub transaction_count: u32,
+        pub milestone_count: u32,
+        pub treasury_transaction_count: u32,
+        pub included_count: u32,
+        pub conflicting_count: u32,
+        pub no_transaction_count: u32,
+        pub created_output_count: u32,
+        pub consumed_output_count: u32,
+        /// The amount of tokens in the created outputs.
+        pub created_amount: String,
+    }
+}
+
+impl From<MilestoneStats> for MilestoneStatsDto {
+    fn from(stats: MilestoneStats) -> Self {
+        Self {
+            block_count: stats.block_count,
+            no_payload_count: stats.no_payload_count,
+            tagged_data_count: stats.tagged_data_count,
+            transaction_count: stats.transaction_count,
+            milestone_count: stats.milestone_count,
+            treasury_transaction_count: stats.treasury_transaction_count,
+            included_count: stats.included_count,
+            conflicting_count: stats.conflicting_count,
+            no_transaction_count: stats.no_transaction_count,
+            created_output_count: stats.created_output_count,
+            consumed_output_count: stats.consumed_output_count,
+            created_amount: stats.created_amount.0.to_string(),
         }
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct RichestAddressesResponse {
-    pub top: Vec<AddressStatDto>,
-    pub ledger_index: MilestoneIndex,
+api_schema! {
+    /// Response of `GET /api/explorer/v2/blocks/:block_id/graph` and the graph routes of milestones.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BlockGraphResponse {
+        pub nodes: Vec<BlockGraphNodeDto>,
+        pub edges: Vec<BlockGraphEdgeDto>,
+        /// Whether blocks were left out because the maximum number of nodes was reached.
+        pub truncated: bool,
+    }
+}
+
+impl_success_response!(BlockGraphResponse);
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct BlockGraphNodeDto {
+        pub id: String,
+        #[serde(rename = "payloadType")]
+        pub payload_kind: Option<u32>,
+        pub inclusion_state: LedgerInclusionState,
+        pub white_flag_index: u32,
+        pub referenced_by_milestone_index: MilestoneIndex,
+    }
+}
+
+api_schema! {
+    /// An edge that points from a block to one of its parents.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct BlockGraphEdgeDto {
+        pub source: String,
+        pub target: String,
+    }
+}
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RichestAddressesResponse {
+        pub top: Vec<AddressStatDto>,
+        pub ledger_index: MilestoneIndex,
+    }
 }
 
 impl_success_response!(RichestAddressesResponse);
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct AddressStatDto {
-    pub address: String,
-    pub balance: String,
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct AddressStatDto {
+        pub address: String,
+        pub balance: String,
+    }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct TokenDistributionResponse {
-    pub distribution: Vec<DistributionStatDto>,
-    pub ledger_index: MilestoneIndex,
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct TokenDistributionResponse {
+        pub distribution: Vec<DistributionStatDto>,
+        pub ledger_index: MilestoneIndex,
+    }
 }
 
 impl_success_response!(TokenDistributionResponse);
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct DistributionStatDto {
-    pub range: Range<u64>,
-    pub address_count: String,
-    pub total_balance: String,
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DistributionStatDto {
+        pub range: Range<u64>,
+        pub address_count: String,
+        pub total_balance: String,
+    }
 }
 
 impl From<DistributionStat> for DistributionStatDto {
@@ -178,3 +413,215 @@ impl From<DistributionStat> for DistributionStatDto {
         }
     }
 }
+
+api_schema! {
+    /// Response of `GET /api/explorer/v2/search/:query`.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SearchResponse {
+        pub query: String,
+        pub results: Vec<SearchResultDto>,
+    }
+}
+
+impl_success_response!(SearchResponse);
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SearchResultDto {
+        pub kind: SearchResultKind,
+        /// The canonical id of the resource.
+        pub id: String,
+        /// The API route that serves the resource.
+        pub link: String,
+    }
+}
+
+/// The kind of resource that matched a search query.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchResultKind {
+    Block,
+    Transaction,
+    Milestone,
+    Output,
+    Address,
+    Alias,
+    Nft,
+    Foundry,
+    /// The newest block with a tagged data payload that carries the tag.
+    TaggedData,
+    /// A tag of basic outputs.
+    Tag,
+}
+
+impl ApiSchema for SearchResultKind {
+    fn schema(gen: &mut SchemaGenerator) -> Value {
+        gen.reference("SearchResultKind", |_| string_enum_schema::<Self>())
+    }
+}
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct TreasuryHistoryResponse {
+        pub items: Vec<TreasuryDto>,
+        pub cursor: Option<String>,
+    }
+}
+
+impl_success_response!(TreasuryHistoryResponse);
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct TreasuryDto {
+        pub milestone_index: MilestoneIndex,
+        /// The id of the milestone that created the treasury output.
+        pub milestone_id: String,
+        pub amount: String,
+    }
+}
+
+impl From<TreasuryHistoryResult> for TreasuryDto {
+    fn from(res: TreasuryHistoryResult) -> Self {
+        Self {
+            milestone_index: res.milestone_index,
+            milestone_id: res.milestone_id.to_hex(),
+            amount: res.amount.to_string(),
+        }
+    }
+}
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct MigratedFundsByAddressResponse {
+        pub address: String,
+        pub items: Vec<MigratedFundsDto>,
+        pub cursor: Option<String>,
+    }
+}
+
+impl_success_response!(MigratedFundsByAddressResponse);
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct MigratedFundsDto {
+        pub tail_transaction_hash: String,
+        pub address: String,
+        pub amount: String,
+        /// The index of the legacy milestone in which the funds were migrated.
+        pub migrated_at: MilestoneIndex,
+        /// Whether the receipt is the last one for `migrated_at`.
+        pub last: bool,
+        pub milestone_id: String,
+        pub milestone_index: MilestoneIndex,
+        pub milestone_timestamp: MilestoneTimestamp,
+    }
+}
+
+impl_success_response!(MigratedFundsDto);
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ProtocolParametersHistoryResponse {
+        pub items: Vec<ProtocolParametersVersionDto>,
+    }
+}
+
+impl_success_response!(ProtocolParametersHistoryResponse);
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ProtocolParametersVersionDto {
+        /// The first milestone index at which the parameters apply.
+        pub start_index: MilestoneIndex,
+        /// The last milestone index at which the parameters apply. Missing for the current parameters.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub end_index: Option<MilestoneIndex>,
+        pub parameters: ProtocolParametersDto,
+    }
+}
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ProtocolParametersAtResponse {
+        /// The milestone index that was looked up.
+        pub milestone_index: MilestoneIndex,
+        pub start_index: MilestoneIndex,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub end_index: Option<MilestoneIndex>,
+        pub parameters: ProtocolParametersDto,
+    }
+}
+
+impl_success_response!(ProtocolParametersAtResponse);
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct NodeConfigurationHistoryResponse {
+        pub items: Vec<NodeConfigurationVersionDto>,
+    }
+}
+
+impl_success_response!(NodeConfigurationHistoryResponse);
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct NodeConfigurationVersionDto {
+        /// The first milestone index at which the configuration applies.
+        pub start_index: MilestoneIndex,
+        /// The last milestone index at which the configuration applies. Missing for the current configuration.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub end_index: Option<MilestoneIndex>,
+        pub config: NodeConfigurationDto,
+    }
+}
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct NodeConfigurationAtResponse {
+        /// The milestone index that was looked up.
+        pub milestone_index: MilestoneIndex,
+        pub start_index: MilestoneIndex,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub end_index: Option<MilestoneIndex>,
+        pub config: NodeConfigurationDto,
+    }
+}
+
+impl_success_response!(NodeConfigurationAtResponse);
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct NodeConfigurationDto {
+        pub milestone_public_key_count: u32,
+        pub milestone_key_ranges: Vec<MilestoneKeyRangeDto>,
+        pub base_token: BaseTokenResponse,
+    }
+}
+
+impl From<NodeConfiguration> for NodeConfigurationDto {
+    fn from(config: NodeConfiguration) -> Self {
+        Self {
+            milestone_public_key_count: config.milestone_public_key_count,
+            milestone_key_ranges: config
+                .milestone_key_ranges
+                .into_vec()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            base_token: base_token_dto(config.base_token),
+        }
+    }
+}
@@ -1,60 +1,95 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use std::str::FromStr;
+use std::{collections::HashSet, str::FromStr};
 
-use axum::{extract::Path, routing::get, Extension};
+use axum::{
+    body::Bytes,
+    extract::Path,
+    http::{header::CONTENT_TYPE, HeaderMap},
+    Extension,
+};
 use chronicle::{
     db::{
         mongodb::collections::{
-            BlockCollection, LedgerUpdateCollection, MilestoneCollection, OutputCollection, ProtocolUpdateCollection,
+            BasicOutputsQuery, BlockCollection, BlockGraphNodeResult, ConfigurationUpdateCollection,
+            LedgerUpdateCollection, MigratedFundsQuery, MigratedFundsResult, MilestoneCollection, OutputCollection,
+            ProtocolUpdateCollection, SortOrder, TreasuryCollection,
         },
         MongoDb,
     },
     model::{
-        payload::{MilestoneId, MilestonePayload, TaggedDataPayload, TransactionPayload, TreasuryTransactionPayload},
+        ledger::RentStructureBytes,
+        node::NodeConfiguration,
+        payload::{
+            milestone::MigratedFundsEntry, transaction::output::Tag, MilestoneId, MilestonePayload, TaggedDataPayload,
+            TransactionId, TransactionPayload, TreasuryTransactionPayload,
+        },
         tangle::MilestoneIndex,
-        utxo::Address,
-        BlockId,
+        utxo::{Address, AliasId, FoundryId, NftId, OutputId},
+        BlockId, ProtocolParameters, RentStructure,
     },
 };
 use futures::{StreamExt, TryStreamExt};
+use iota_types::block::output::{dto::OutputDto, Rent};
+use packable::PackableExt;
 
 use super::{
     extractors::{
-        BlocksByMilestoneCursor, BlocksByMilestoneIdPagination, BlocksByMilestoneIndexPagination, LedgerIndex,
-        LedgerUpdatesByAddressCursor, LedgerUpdatesByAddressPagination, LedgerUpdatesByMilestoneCursor,
-        LedgerUpdatesByMilestonePagination, MilestonesCursor, MilestonesPagination, RichestAddressesQuery,
+        BalanceAtQuery, BlockGraphQuery, BlocksByMilestoneCursor, BlocksByMilestoneIdPagination,
+        BlocksByMilestoneIndexPagination, EffectiveAt, LedgerIndex, LedgerUpdatesByAddressCursor,
+        LedgerUpdatesByAddressPagination, LedgerUpdatesByMilestoneCursor, LedgerUpdatesByMilestonePagination,
+        MigratedFundsCursor, MigratedFundsPagination, MilestoneGraphQuery, MilestonesCursor, MilestonesPagination,
+        RichestAddressesQuery, TreasuryHistoryPagination, UnlockCalendarCursor, UnlockCalendarPagination,
     },
     responses::{
-        AddressStatDto, BalanceResponse, BlockChildrenResponse, BlockPayloadTypeDto, BlocksByMilestoneResponse,
-        LedgerUpdatesByAddressResponse, LedgerUpdatesByMilestoneResponse, MilestonesResponse, RichestAddressesResponse,
-        TokenDistributionResponse,
+        AddressStatDto, AddressStorageDepositResponse, BalanceResponse, BlockChildrenResponse, BlockGraphEdgeDto,
+        BlockGraphNodeDto, BlockGraphResponse, BlockPayloadTypeDto, BlocksByMilestoneResponse,
+        LedgerUpdatesByAddressResponse, LedgerUpdatesByMilestoneResponse, MigratedFundsByAddressResponse,
+        MigratedFundsDto, MilestoneDto, MilestonesResponse, NodeConfigurationAtResponse,
+        NodeConfigurationHistoryResponse, NodeConfigurationVersionDto, ProtocolParametersAtResponse,
+        ProtocolParametersHistoryResponse, ProtocolParametersVersionDto, RichestAddressesResponse, SearchResponse,
+        SearchResultDto, SearchResultKind, StorageDepositResponse, TimedBalanceResponse, TokenDistributionResponse,
+        TreasuryHistoryResponse, UnlockCalendarResponse,
     },
 };
 use crate::api::{
+    config::ApiModule,
     error::{CorruptStateError, MissingError, RequestError},
     extractors::Pagination,
-    router::Router,
-    ApiResult,
+    responses::protocol_parameters_dto,
+    router::{get, post, Router},
+    routes::BYTE_CONTENT_HEADER,
+    ApiConfigData, ApiResult,
 };
 
+/// The time window of the unlock calendar if no end is given.
+const DEFAULT_UNLOCK_CALENDAR_WINDOW_SECS: u32 = 30 * 24 * 60 * 60;
+
 pub fn routes() -> Router {
     Router::new()
         .route("/balance/:address", get(balance))
+        .route("/balance/:address/at", get(balance_at))
+        .route("/balance/:address/storage-deposit", get(address_storage_deposit))
         .route("/blocks/:block_id/children", get(block_children))
+        .route("/blocks/:block_id/graph", get(block_graph))
         .nest(
             "/milestones",
             Router::new()
                 .route("/", get(milestones))
+                .route("/:milestone_id", get(milestone_by_id))
                 .route("/:milestone_id/blocks", get(blocks_by_milestone_id))
-                .route("/by-index/:milestone_index/blocks", get(blocks_by_milestone_index)),
+                .route("/:milestone_id/graph", get(milestone_graph_by_id))
+                .route("/by-index/:milestone_index", get(milestone_by_index))
+                .route("/by-index/:milestone_index/blocks", get(blocks_by_milestone_index))
+                .route("/by-index/:milestone_index/graph", get(milestone_graph_by_index)),
         )
         .nest(
             "/ledger",
             Router::new()
                 .route("/richest-addresses", get(richest_addresses_ledger_analytics))
                 .route("/token-distribution", get(token_distribution_ledger_analytics))
+                .route("/unlock-calendar/:address", get(unlock_calendar))
                 .nest(
                     "/updates",
                     Router::new()
@@ -62,6 +97,30 @@ pub fn routes() -> Router {
                         .route("/by-milestone/:milestone_id", get(ledger_updates_by_milestone)),
                 ),
         )
+        .nest(
+            "/node-configuration",
+            Router::new()
+                .route("/", get(node_configuration_history))
+                .route("/at", get(node_configuration_at)),
+        )
+        .nest(
+            "/protocol-parameters",
+            Router::new()
+                .route("/", get(protocol_parameters_history))
+                .route("/at", get(protocol_parameters_at)),
+        )
+        .nest(
+            "/receipts",
+            Router::new()
+                .route("/by-address/:address", get(migrated_funds_by_address))
+                .route(
+                    "/by-tail-transaction-hash/:tail_transaction_hash",
+                    get(migrated_funds_by_tail_transaction_hash),
+                ),
+        )
+        .route("/search/:query", get(search))
+        .route("/storage-deposit", post(storage_deposit))
+        .route("/treasury/history", get(treasury_history))
 }
 
 async fn ledger_updates_by_address(
@@ -173,6 +232,171 @@ async fn balance(database: Extension<MongoDb>, Path(address): Path<String>) -> A
     })
 }
 
+async fn balance_at(
+    database: Extension<MongoDb>,
+    Path(address): Path<String>,
+    BalanceAtQuery { timestamp }: BalanceAtQuery,
+) -> ApiResult<TimedBalanceResponse> {
+    let ledger_index = database
+        .collection::<MilestoneCollection>()
+        .get_newest_milestone()
+        .await?
+        .ok_or(MissingError::NoResults)?;
+    let timestamp = timestamp.map_or(ledger_index.milestone_timestamp, Into::into);
+    let address = Address::from_str(&address).map_err(RequestError::from)?;
+    let res = database
+        .collection::<OutputCollection>()
+        .get_address_balance_at(address, ledger_index.milestone_index, timestamp)
+        .await?;
+
+    Ok(TimedBalanceResponse {
+        total_balance: res.total_balance,
+        timelocked_balance: res.timelocked_balance,
+        spendable_balance: res.spendable_balance,
+        timestamp,
+        ledger_index: ledger_index.milestone_index,
+    })
+}
+
+async fn unlock_calendar(
+    database: Extension<MongoDb>,
+    Path(address): Path<String>,
+    UnlockCalendarPagination {
+        start_timestamp,
+        end_timestamp,
+        page_size,
+        cursor,
+    }: UnlockCalendarPagination,
+) -> ApiResult<UnlockCalendarResponse> {
+    let ledger_index = database
+        .collection::<MilestoneCollection>()
+        .get_newest_milestone()
+        .await?
+        .ok_or(MissingError::NoResults)?;
+    let start_timestamp = start_timestamp.unwrap_or(ledger_index.milestone_timestamp);
+    let end_timestamp = end_timestamp.unwrap_or_else(|| {
+        start_timestamp
+            .0
+            .saturating_add(DEFAULT_UNLOCK_CALENDAR_WINDOW_SECS)
+            .into()
+    });
+    if end_timestamp < start_timestamp {
+        return Err(RequestError::BadTimeRange.into());
+    }
+    let address_dto = Address::from_str(&address).map_err(RequestError::from)?;
+    let mut record_stream = database
+        .collection::<OutputCollection>()
+        .get_address_unlock_events(
+            address_dto,
+            ledger_index.milestone_index,
+            start_timestamp..=end_timestamp,
+            // Get one extra record so that we can create the cursor.
+            page_size + 1,
+            cursor,
+        )
+        .await?;
+
+    // Take all of the requested records first
+    let items = record_stream
+        .by_ref()
+        .take(page_size)
+        .map_ok(Into::into)
+        .try_collect()
+        .await?;
+
+    // If any record is left, use it to make the cursor
+    let cursor = record_stream.try_next().await?.map(|rec| {
+        UnlockCalendarCursor {
+            timestamp: rec.timestamp,
+            output_id: rec.output_id,
+            kind: rec.kind,
+            end_timestamp,
+            page_size,
+        }
+        .to_string()
+    });
+
+    Ok(UnlockCalendarResponse {
+        address,
+        start_timestamp,
+        end_timestamp,
+        items,
+        cursor,
+        ledger_index: ledger_index.milestone_index,
+    })
+}
+
+async fn address_storage_deposit(
+    database: Extension<MongoDb>,
+    Path(address): Path<String>,
+    LedgerIndex { ledger_index }: LedgerIndex,
+) -> ApiResult<AddressStorageDepositResponse> {
+    let ledger_index = resolve_ledger_index(&database, ledger_index).await?;
+    let address = Address::from_str(&address).map_err(RequestError::from)?;
+    let rent_structure = get_rent_structure(&database, ledger_index).await?;
+    let timestamp = database
+        .collection::<MilestoneCollection>()
+        .get_milestone_timestamp(ledger_index)
+        .await?
+        .ok_or(MissingError::NoResults)?;
+    let res = database
+        .collection::<OutputCollection>()
+        .get_address_storage_deposit(address, ledger_index, timestamp, rent_structure)
+        .await?;
+
+    Ok(AddressStorageDepositResponse {
+        total_balance: res.total_balance,
+        storage_deposit: res.storage_deposit,
+        spendable_balance: res.spendable_balance,
+        output_count: res.output_count,
+        rent_structure: rent_structure.into(),
+        ledger_index,
+    })
+}
+
+async fn storage_deposit(
+    database: Extension<MongoDb>,
+    LedgerIndex { ledger_index }: LedgerIndex,
+    headers: HeaderMap,
+    body: Bytes,
+) -> ApiResult<StorageDepositResponse> {
+    let ledger_index = resolve_ledger_index(&database, ledger_index).await?;
+    let rent_structure = get_rent_structure(&database, ledger_index).await?;
+    let output = parse_output(&headers, &body)?;
+    let rent_bytes = RentStructureBytes::compute(&output);
+
+    Ok(StorageDepositResponse {
+        storage_deposit: output.rent_cost(&rent_structure.into()).to_string(),
+        num_key_bytes: rent_bytes.num_key_bytes,
+        num_data_bytes: rent_bytes.num_data_bytes,
+        rent_structure: rent_structure.into(),
+        ledger_index,
+    })
+}
+
+/// Parses an output in its packed binary form or as JSON, depending on the content type. The output is not verified,
+/// so that the deposit of an output can be computed before its amount is known.
+fn parse_output(headers: &HeaderMap, body: &[u8]) -> ApiResult<iota_types::block::output::Output> {
+    Ok(match headers.get(CONTENT_TYPE) {
+        Some(header) if header == BYTE_CONTENT_HEADER => iota_types::block::output::Output::unpack_unverified(body)
+            .map_err(|e| RequestError::RawOutput(format!("{:?}", e)))?,
+        _ => {
+            let output = serde_json::from_slice::<OutputDto>(body).map_err(RequestError::from)?;
+            iota_types::block::output::Output::try_from_dto_unverified(&output).map_err(RequestError::from)?
+        }
+    })
+}
+
+async fn get_rent_structure(database: &MongoDb, ledger_index: MilestoneIndex) -> ApiResult<RentStructure> {
+    Ok(database
+        .collection::<ProtocolUpdateCollection>()
+        .get_protocol_parameters_for_ledger_index(ledger_index)
+        .await?
+        .ok_or(MissingError::NoResults)?
+        .parameters
+        .rent_structure)
+}
+
 async fn block_children(
     database: Extension<MongoDb>,
     Path(block_id): Path<String>,
@@ -264,17 +488,14 @@ async fn blocks_by_milestone_index(
     let blocks = record_stream
         .by_ref()
         .take(page_size)
-        .map_ok(|rec| BlockPayloadTypeDto {
-            block_id: rec.block_id.to_hex(),
-            payload_kind: rec.payload_kind.map(|kind| match kind.as_str() {
-                TransactionPayload::KIND => iota_types::block::payload::TransactionPayload::KIND,
-                MilestonePayload::KIND => iota_types::block::payload::MilestonePayload::KIND,
-                TreasuryTransactionPayload::KIND => iota_types::block::payload::TreasuryTransactionPayload::KIND,
-                TaggedDataPayload::KIND => iota_types::block::payload::TaggedDataPayload::KIND,
-                _ => panic!("Unknown payload type."),
-            }),
+        .map(|rec| -> ApiResult<_> {
+            let rec = rec?;
+            Ok(BlockPayloadTypeDto {
+                block_id: rec.block_id.to_hex(),
+                payload_kind: rec.payload_kind.as_deref().map(payload_type).transpose()?,
+            })
         })
-        .try_collect()
+        .try_collect::<Vec<_>>()
         .await?;
 
     // If any record is left, use it to make the paging state
@@ -318,6 +539,181 @@ async fn blocks_by_milestone_id(
     .await
 }
 
+/// Converts a stored payload kind to the payload type of the protocol.
+fn payload_type(kind: &str) -> ApiResult<u32> {
+    Ok(match kind {
+        TransactionPayload::KIND => iota_types::block::payload::TransactionPayload::KIND,
+        MilestonePayload::KIND => iota_types::block::payload::MilestonePayload::KIND,
+        TreasuryTransactionPayload::KIND => iota_types::block::payload::TreasuryTransactionPayload::KIND,
+        TaggedDataPayload::KIND => iota_types::block::payload::TaggedDataPayload::KIND,
+        _ => return Err(CorruptStateError::PayloadKind(kind.to_string()).into()),
+    })
+}
+
+/// Creates the graph response from a set of nodes. Only edges between the nodes are included.
+fn create_graph_response(nodes: Vec<BlockGraphNodeResult>, truncated: bool) -> ApiResult<BlockGraphResponse> {
+    let block_ids = nodes.iter().map(|node| node.block_id).collect::<HashSet<_>>();
+    let edges = nodes
+        .iter()
+        .flat_map(|node| {
+            node.metadata
+                .parents
+                .iter()
+                .filter(|parent| block_ids.contains(parent))
+                .map(|parent| BlockGraphEdgeDto {
+                    source: node.block_id.to_hex(),
+                    target: parent.to_hex(),
+                })
+        })
+        .collect();
+    let nodes = nodes
+        .into_iter()
+        .map(|node| {
+            Ok(BlockGraphNodeDto {
+                id: node.block_id.to_hex(),
+                payload_kind: node.payload_kind.as_deref().map(payload_type).transpose()?,
+                inclusion_state: node.metadata.inclusion_state,
+                white_flag_index: node.metadata.white_flag_index,
+                referenced_by_milestone_index: node.metadata.referenced_by_milestone_index,
+            })
+        })
+        .collect::<ApiResult<_>>()?;
+
+    Ok(BlockGraphResponse {
+        nodes,
+        edges,
+        truncated,
+    })
+}
+
+async fn block_graph(
+    database: Extension<MongoDb>,
+    Path(block_id): Path<String>,
+    BlockGraphQuery { depth, max_nodes }: BlockGraphQuery,
+) -> ApiResult<BlockGraphResponse> {
+    let block_id = BlockId::from_str(&block_id).map_err(RequestError::from)?;
+    let blocks = database.collection::<BlockCollection>();
+    let root = blocks
+        .get_graph_nodes(&[block_id])
+        .await?
+        .pop()
+        .ok_or(MissingError::NoResults)?;
+    let below_max_depth = database
+        .collection::<ProtocolUpdateCollection>()
+        .get_protocol_parameters_for_ledger_index(root.metadata.referenced_by_milestone_index)
+        .await?
+        .ok_or(MissingError::NoResults)?
+        .parameters
+        .below_max_depth;
+
+    let mut known = HashSet::from([block_id]);
+    let mut nodes = vec![root];
+    let mut frontier = 0..nodes.len();
+    let mut truncated = false;
+    // Every hop adds the parents and children of the blocks that were added by the previous hop.
+    for _ in 0..depth {
+        if frontier.is_empty() || truncated {
+            break;
+        }
+        let frontier_ids = nodes[frontier.clone()]
+            .iter()
+            .map(|node| node.block_id)
+            .collect::<Vec<_>>();
+        let parent_ids = nodes[frontier.clone()]
+            .iter()
+            .flat_map(|node| node.metadata.parents.iter().copied())
+            .filter(|parent| !known.contains(parent))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        // Children are referenced by the same milestone as their parent or by one of the next `below_max_depth`
+        // milestones. Blocks that are not referenced yet carry no index, so they do not restrict the window.
+        let referenced_by = nodes[frontier.clone()]
+            .iter()
+            .map(|node| node.metadata.referenced_by_milestone_index)
+            .filter(|&index| index != 0);
+        let window = referenced_by
+            .clone()
+            .min()
+            .zip(referenced_by.max())
+            .map(|(min, max)| min..=max + below_max_depth as u32);
+
+        let mut found = blocks.get_graph_nodes(&parent_ids).await?;
+        // Known blocks can be among the children, so as many children are fetched as the graph can hold.
+        found.extend(blocks.get_graph_children(&frontier_ids, window, max_nodes).await?);
+
+        let start = nodes.len();
+        for node in found {
+            if known.insert(node.block_id) {
+                if nodes.len() == max_nodes {
+                    truncated = true;
+                    break;
+                }
+                nodes.push(node);
+            }
+        }
+        frontier = start..nodes.len();
+    }
+
+    create_graph_response(nodes, truncated)
+}
+
+async fn milestone_by_id(database: Extension<MongoDb>, Path(milestone_id): Path<String>) -> ApiResult<MilestoneDto> {
+    let milestone_id = MilestoneId::from_str(&milestone_id).map_err(RequestError::from)?;
+    Ok(database
+        .collection::<MilestoneCollection>()
+        .get_milestone_result_by_id(&milestone_id)
+        .await?
+        .ok_or(MissingError::NoResults)?
+        .into())
+}
+
+async fn milestone_by_index(
+    database: Extension<MongoDb>,
+    Path(milestone_index): Path<MilestoneIndex>,
+) -> ApiResult<MilestoneDto> {
+    Ok(database
+        .collection::<MilestoneCollection>()
+        .get_milestone_result(milestone_index)
+        .await?
+        .ok_or(MissingError::NoResults)?
+        .into())
+}
+
+async fn milestone_graph_by_index(
+    database: Extension<MongoDb>,
+    Path(milestone_index): Path<MilestoneIndex>,
+    MilestoneGraphQuery { max_nodes }: MilestoneGraphQuery,
+) -> ApiResult<BlockGraphResponse> {
+    let mut nodes = database
+        .collection::<BlockCollection>()
+        .get_milestone_cone_graph_nodes(milestone_index, max_nodes + 1)
+        .await?;
+    if nodes.is_empty() {
+        return Err(MissingError::NoResults.into());
+    }
+    let truncated = nodes.len() > max_nodes;
+    nodes.truncate(max_nodes);
+
+    create_graph_response(nodes, truncated)
+}
+
+async fn milestone_graph_by_id(
+    database: Extension<MongoDb>,
+    Path(milestone_id): Path<String>,
+    query: MilestoneGraphQuery,
+) -> ApiResult<BlockGraphResponse> {
+    let milestone_id = MilestoneId::from_str(&milestone_id).map_err(RequestError::from)?;
+    let milestone_index = database
+        .collection::<MilestoneCollection>()
+        .get_milestone_payload_by_id(&milestone_id)
+        .await?
+        .ok_or(MissingError::NoResults)?
+        .essence
+        .index;
+    milestone_graph_by_index(database, Path(milestone_index), query).await
+}
+
 async fn richest_addresses_ledger_analytics(
     database: Extension<MongoDb>,
     RichestAddressesQuery { top, ledger_index }: RichestAddressesQuery,
@@ -365,6 +761,492 @@ async fn token_distribution_ledger_analytics(
     })
 }
 
+/// An interpretation of a search query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum SearchQuery {
+    MilestoneIndex(MilestoneIndex),
+    /// A bech32 address together with its HRP.
+    Address(String, iota_types::block::address::Address),
+    /// A 32 byte identifier, which may be a block, transaction, milestone, alias or NFT id.
+    Id(BlockId),
+    OutputId(OutputId),
+    FoundryId(FoundryId),
+    Tag(Tag),
+}
+
+/// The maximum length of a tag in bytes.
+const TAG_LENGTH_MAX: usize = 64;
+
+/// Classifies a search query into every kind of identifier that it could be.
+fn parse_search_query(query: &str) -> Vec<SearchQuery> {
+    let mut queries = Vec::new();
+    let tag = if let Ok(index) = query.parse::<u32>() {
+        queries.push(SearchQuery::MilestoneIndex(MilestoneIndex(index)));
+        None
+    } else if let Ok((hrp, address)) = iota_types::block::address::Address::try_from_bech32(query) {
+        queries.push(SearchQuery::Address(hrp, address));
+        None
+    } else if query.starts_with("0x") {
+        if let Ok(block_id) = BlockId::from_str(query) {
+            queries.push(SearchQuery::Id(block_id));
+        } else if let Ok(output_id) = OutputId::from_str(query) {
+            queries.push(SearchQuery::OutputId(output_id));
+        } else if let Ok(foundry_id) = FoundryId::from_str(query) {
+            queries.push(SearchQuery::FoundryId(foundry_id));
+        }
+        Tag::from_hex(query).ok()
+    } else {
+        Some(Tag::from(query))
+    };
+    if let Some(tag) = tag.filter(|tag| !tag.as_ref().is_empty() && tag.as_ref().len() <= TAG_LENGTH_MAX) {
+        queries.push(SearchQuery::Tag(tag));
+    }
+    queries
+}
+
+async fn search(
+    database: Extension<MongoDb>,
+    Extension(config): Extension<ApiConfigData>,
+    Path(query): Path<String>,
+) -> ApiResult<SearchResponse> {
+    let ledger_index = database
+        .collection::<MilestoneCollection>()
+        .get_ledger_index()
+        .await?
+        .ok_or(MissingError::NoResults)?;
+    let hrp = database
+        .collection::<ProtocolUpdateCollection>()
+        .get_protocol_parameters_for_ledger_index(ledger_index)
+        .await?
+        .ok_or(CorruptStateError::ProtocolParams)?
+        .parameters
+        .bech32_hrp;
+    // The lookups by alias, NFT and foundry id and of outputs by tag rely on the indexes of the indexer.
+    let indexer_enabled = config.is_module_enabled(ApiModule::Indexer);
+
+    let query = query.trim();
+    let mut results = Vec::new();
+    let mut push = |kind, id: String, link: String| results.push(SearchResultDto { kind, id, link });
+
+    for search_query in parse_search_query(query) {
+        match search_query {
+            SearchQuery::MilestoneIndex(index) => {
+                if let Some(milestone_id) = database
+                    .collection::<MilestoneCollection>()
+                    .get_milestone_id(index)
+                    .await?
+                {
+                    push(
+                        SearchResultKind::Milestone,
+                        milestone_id.to_hex(),
+                        format!("/api/core/v2/milestones/by-index/{}", index),
+                    );
+                }
+            }
+            SearchQuery::Address(query_hrp, address) => {
+                if query_hrp == hrp
+                    && database
+                        .collection::<LedgerUpdateCollection>()
+                        .get_ledger_updates_by_address(&address.into(), 1, None, SortOrder::Newest)
+                        .await?
+                        .try_next()
+                        .await?
+                        .is_some()
+                {
+                    let bech32 = address.to_bech32(hrp.clone());
+                    push(
+                        SearchResultKind::Address,
+                        bech32.clone(),
+                        format!("/api/explorer/v2/ledger/updates/by-address/{}", bech32),
+                    );
+                }
+            }
+            SearchQuery::Id(block_id) => {
+                // All identifiers of this length share the canonical hex representation.
+                let id = block_id.to_hex();
+                if database
+                    .collection::<BlockCollection>()
+                    .get_block_metadata(&block_id)
+                    .await?
+                    .is_some()
+                {
+                    push(
+                        SearchResultKind::Block,
+                        id.clone(),
+                        format!("/api/core/v2/blocks/{}", id),
+                    );
+                }
+                if database
+                    .collection::<BlockCollection>()
+                    .get_block_metadata_for_transaction(&TransactionId(block_id.0))
+                    .await?
+                    .is_some()
+                {
+                    push(
+                        SearchResultKind::Transaction,
+                        id.clone(),
+                        format!("/api/core/v2/transactions/{}/included-block", id),
+                    );
+                }
+                if database
+                    .collection::<MilestoneCollection>()
+                    .get_milestone_payload_by_id(&MilestoneId(block_id.0))
+                    .await?
+                    .is_some()
+                {
+                    push(
+                        SearchResultKind::Milestone,
+                        id.clone(),
+                        format!("/api/core/v2/milestones/{}", id),
+                    );
+                }
+                if indexer_enabled {
+                    if database
+                        .collection::<OutputCollection>()
+                        .get_indexed_output_by_id(AliasId(block_id.0), ledger_index)
+                        .await?
+                        .is_some()
+                    {
+                        push(
+                            SearchResultKind::Alias,
+                            id.clone(),
+                            format!("/api/indexer/v1/outputs/alias/{}", id),
+                        );
+                    }
+                    if database
+                        .collection::<OutputCollection>()
+                        .get_indexed_output_by_id(NftId(block_id.0), ledger_index)
+                        .await?
+                        .is_some()
+                    {
+                        push(
+                            SearchResultKind::Nft,
+                            id.clone(),
+                            format!("/api/indexer/v1/outputs/nft/{}", id),
+                        );
+                    }
+                }
+            }
+            SearchQuery::OutputId(output_id) => {
+                if database
+                    .collection::<OutputCollection>()
+                    .get_output_metadata(&output_id, ledger_index)
+                    .await?
+                    .is_some()
+                {
+                    push(
+                        SearchResultKind::Output,
+                        output_id.to_hex(),
+                        format!("/api/core/v2/outputs/{}", output_id.to_hex()),
+                    );
+                }
+            }
+            SearchQuery::FoundryId(foundry_id) => {
+                if indexer_enabled
+                    && database
+                        .collection::<OutputCollection>()
+                        .get_indexed_output_by_id(foundry_id, ledger_index)
+                        .await?
+                        .is_some()
+                {
+                    let id = prefix_hex::encode(foundry_id.0.as_ref());
+                    push(
+                        SearchResultKind::Foundry,
+                        id.clone(),
+                        format!("/api/indexer/v1/outputs/foundry/{}", id),
+                    );
+                }
+            }
+            SearchQuery::Tag(tag) => {
+                // Tagged data payloads are indexed by the explorer itself.
+                if let Some(block_id) = database
+                    .collection::<BlockCollection>()
+                    .get_block_id_by_tag(tag.as_ref())
+                    .await?
+                {
+                    let id = block_id.to_hex();
+                    push(
+                        SearchResultKind::TaggedData,
+                        id.clone(),
+                        format!("/api/core/v2/blocks/{}", id),
+                    );
+                }
+                if indexer_enabled && search_output_tag(&database, tag.clone(), ledger_index).await? {
+                    let tag_hex = tag.to_hex();
+                    push(
+                        SearchResultKind::Tag,
+                        tag_hex.clone(),
+                        format!("/api/indexer/v1/outputs/basic?tag={}", tag_hex),
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(SearchResponse {
+        query: query.to_string(),
+        results,
+    })
+}
+
+/// Looks up whether any basic output carries the tag.
+async fn search_output_tag(database: &MongoDb, tag: Tag, ledger_index: MilestoneIndex) -> ApiResult<bool> {
+    Ok(!database
+        .collection::<OutputCollection>()
+        .get_indexed_outputs(
+            BasicOutputsQuery {
+                tag: Some(tag),
+                ..Default::default()
+            },
+            1,
+            None,
+            SortOrder::Newest,
+            true,
+            ledger_index,
+        )
+        .await?
+        .outputs
+        .is_empty())
+}
+
+async fn treasury_history(
+    database: Extension<MongoDb>,
+    TreasuryHistoryPagination {
+        sort,
+        page_size,
+        cursor,
+    }: TreasuryHistoryPagination,
+) -> ApiResult<TreasuryHistoryResponse> {
+    let mut record_stream = database
+        .collection::<TreasuryCollection>()
+        .get_treasury_history(sort, page_size + 1, cursor)
+        .await?;
+
+    // Take all of the requested records first
+    let items = record_stream
+        .by_ref()
+        .take(page_size)
+        .map_ok(Into::into)
+        .try_collect()
+        .await?;
+
+    // If any record is left, use it to make the paging state
+    let cursor = record_stream.try_next().await?.map(|rec| {
+        MilestonesCursor {
+            milestone_index: rec.milestone_index,
+            page_size,
+        }
+        .to_string()
+    });
+
+    Ok(TreasuryHistoryResponse { items, cursor })
+}
+
+async fn migrated_funds_by_tail_transaction_hash(
+    database: Extension<MongoDb>,
+    Path(tail_transaction_hash): Path<String>,
+) -> ApiResult<MigratedFundsDto> {
+    let tail_transaction_hash =
+        prefix_hex::decode::<[u8; MigratedFundsEntry::TAIL_TRANSACTION_HASH_LENGTH]>(&tail_transaction_hash)
+            .map_err(RequestError::from)?;
+    let res = database
+        .collection::<MilestoneCollection>()
+        .get_migrated_funds(MigratedFundsQuery::TailTransactionHash(tail_transaction_hash), 1, None)
+        .await?
+        .try_next()
+        .await?
+        .ok_or(MissingError::NoResults)?;
+    let hrp = get_bech32_hrp(&database, res.at.milestone_index).await?;
+
+    Ok(migrated_funds_dto(res, &hrp))
+}
+
+async fn migrated_funds_by_address(
+    database: Extension<MongoDb>,
+    Path(address): Path<String>,
+    MigratedFundsPagination { page_size, cursor }: MigratedFundsPagination,
+) -> ApiResult<MigratedFundsByAddressResponse> {
+    let address_dto = Address::from_str(&address).map_err(RequestError::from)?;
+    let mut record_stream = database
+        .collection::<MilestoneCollection>()
+        // Get one extra record so that we can create the cursor.
+        .get_migrated_funds(MigratedFundsQuery::Address(address_dto), page_size + 1, cursor)
+        .await?;
+
+    // Take all of the requested records first
+    let records = record_stream.by_ref().take(page_size).try_collect::<Vec<_>>().await?;
+
+    // If any record is left, use it to make the cursor
+    let cursor = record_stream.try_next().await?.map(|rec| {
+        MigratedFundsCursor {
+            milestone_index: rec.at.milestone_index,
+            tail_transaction_hash: *rec.entry.tail_transaction_hash(),
+            page_size,
+        }
+        .to_string()
+    });
+
+    let items = match records.first() {
+        Some(first) => {
+            let hrp = get_bech32_hrp(&database, first.at.milestone_index).await?;
+            records.into_iter().map(|res| migrated_funds_dto(res, &hrp)).collect()
+        }
+        None => Vec::new(),
+    };
+
+    Ok(MigratedFundsByAddressResponse { address, items, cursor })
+}
+
+async fn protocol_parameters_history(database: Extension<MongoDb>) -> ApiResult<ProtocolParametersHistoryResponse> {
+    let versions = get_protocol_parameters_versions(&database).await?;
+
+    Ok(ProtocolParametersHistoryResponse {
+        items: versions
+            .into_iter()
+            .map(|(start_index, end_index, parameters)| ProtocolParametersVersionDto {
+                start_index,
+                end_index,
+                parameters: protocol_parameters_dto(parameters),
+            })
+            .collect(),
+    })
+}
+
+async fn protocol_parameters_at(
+    database: Extension<MongoDb>,
+    at: EffectiveAt,
+) -> ApiResult<ProtocolParametersAtResponse> {
+    let milestone_index = resolve_effective_at(&database, at).await?;
+    let collection = database.collection::<ProtocolUpdateCollection>();
+    let update = collection
+        .get_protocol_parameters_for_ledger_index(milestone_index)
+        .await?
+        .ok_or(MissingError::NoResults)?;
+    let next_update = collection.get_next_protocol_parameters(milestone_index).await?;
+
+    Ok(ProtocolParametersAtResponse {
+        milestone_index,
+        start_index: update.tangle_index,
+        end_index: next_update.map(|next| next.tangle_index - 1),
+        parameters: protocol_parameters_dto(update.parameters),
+    })
+}
+
+async fn node_configuration_history(database: Extension<MongoDb>) -> ApiResult<NodeConfigurationHistoryResponse> {
+    let versions = get_node_configuration_versions(&database).await?;
+
+    Ok(NodeConfigurationHistoryResponse {
+        items: versions
+            .into_iter()
+            .map(|(start_index, end_index, config)| NodeConfigurationVersionDto {
+                start_index,
+                end_index,
+                config: config.into(),
+            })
+            .collect(),
+    })
+}
+
+async fn node_configuration_at(
+    database: Extension<MongoDb>,
+    at: EffectiveAt,
+) -> ApiResult<NodeConfigurationAtResponse> {
+    let milestone_index = resolve_effective_at(&database, at).await?;
+    let collection = database.collection::<ConfigurationUpdateCollection>();
+    let update = collection
+        .get_node_configuration_for_ledger_index(milestone_index)
+        .await?
+        .ok_or(MissingError::NoResults)?;
+    let next_update = collection.get_next_node_configuration(milestone_index).await?;
+
+    Ok(NodeConfigurationAtResponse {
+        milestone_index,
+        start_index: update.ledger_index,
+        end_index: next_update.map(|next| next.ledger_index - 1),
+        config: update.config.into(),
+    })
+}
+
+/// A version of data that applies from a milestone index until an optional (inclusive) end.
+type Version<T> = (MilestoneIndex, Option<MilestoneIndex>, T);
+
+async fn get_protocol_parameters_versions(database: &MongoDb) -> ApiResult<Vec<Version<ProtocolParameters>>> {
+    let updates = database
+        .collection::<ProtocolUpdateCollection>()
+        .get_protocol_parameters_history()
+        .await?
+        .map_ok(|doc| (doc.tangle_index, doc.parameters))
+        .try_collect()
+        .await?;
+    Ok(into_versions(updates))
+}
+
+async fn get_node_configuration_versions(database: &MongoDb) -> ApiResult<Vec<Version<NodeConfiguration>>> {
+    let updates = database
+        .collection::<ConfigurationUpdateCollection>()
+        .get_node_configuration_history()
+        .await?
+        .map_ok(|doc| (doc.ledger_index, doc.config))
+        .try_collect()
+        .await?;
+    Ok(into_versions(updates))
+}
+
+/// Turns updates, ordered by the index from which they apply, into versions that end before the next update.
+fn into_versions<T>(updates: Vec<(MilestoneIndex, T)>) -> Vec<Version<T>> {
+    let next_starts = updates.iter().skip(1).map(|(start, _)| *start).collect::<Vec<_>>();
+    updates
+        .into_iter()
+        .enumerate()
+        .map(|(i, (start, value))| (start, next_starts.get(i).map(|next| *next - 1), value))
+        .collect()
+}
+
+/// Resolves the milestone index at which versioned data is looked up. Indexes beyond the ledger index are not known
+/// yet.
+async fn resolve_effective_at(database: &MongoDb, at: EffectiveAt) -> ApiResult<MilestoneIndex> {
+    Ok(match at {
+        EffectiveAt::LedgerIndex => resolve_ledger_index(database, None).await?,
+        EffectiveAt::Index(index) => {
+            if index > resolve_ledger_index(database, None).await? {
+                return Err(MissingError::NoResults.into());
+            }
+            index
+        }
+        EffectiveAt::Timestamp(timestamp) => {
+            database
+                .collection::<MilestoneCollection>()
+                .find_last_milestone(timestamp)
+                .await?
+                .ok_or(MissingError::NoResults)?
+                .milestone_index
+        }
+    })
+}
+
+fn migrated_funds_dto(res: MigratedFundsResult, hrp: &str) -> MigratedFundsDto {
+    MigratedFundsDto {
+        tail_transaction_hash: prefix_hex::encode(res.entry.tail_transaction_hash()),
+        address: iota_types::block::address::Address::from(*res.entry.address()).to_bech32(hrp),
+        amount: res.entry.amount().to_string(),
+        migrated_at: res.migrated_at,
+        last: res.last,
+        milestone_id: res.milestone_id.to_hex(),
+        milestone_index: res.at.milestone_index,
+        milestone_timestamp: res.at.milestone_timestamp,
+    }
+}
+
+async fn get_bech32_hrp(database: &MongoDb, ledger_index: MilestoneIndex) -> ApiResult<String> {
+    Ok(database
+        .collection::<ProtocolUpdateCollection>()
+        .get_protocol_parameters_for_ledger_index(ledger_index)
+        .await?
+        .ok_or(CorruptStateError::ProtocolParams)?
+        .parameters
+        .bech32_hrp)
+}
+
 /// This is just a helper fn to either unwrap an optional ledger index param or fetch the latest
 /// index from the database.
 async fn resolve_ledger_index(database: &MongoDb, ledger_index: Option<MilestoneIndex>) -> ApiResult<MilestoneIndex> {
@@ -378,3 +1260,101 @@ async fn resolve_ledger_index(database: &MongoDb, ledger_index: Option<Milestone
             .ok_or(MissingError::NoResults)?
     })
 }
+
+#[cfg(test)]
+mod test {
+    use iota_types::block::{
+        address::{Address as IotaAddress, Ed25519Address},
+        output::{unlock_condition::AddressUnlockCondition, BasicOutputBuilder},
+    };
+
+    use super::*;
+
+    #[test]
+    fn storage_deposit_of_parsed_output() {
+        let protocol_params = iota_types::block::protocol::protocol_parameters();
+        let address = IotaAddress::Ed25519(Ed25519Address::new([1; Ed25519Address::LENGTH]));
+        // The amount is a placeholder that does not cover the deposit.
+        let output = BasicOutputBuilder::new_with_amount(1)
+            .unwrap()
+            .add_unlock_condition(AddressUnlockCondition::new(address).into())
+            .finish_output(protocol_params.token_supply())
+            .unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, BYTE_CONTENT_HEADER.clone());
+        assert_eq!(parse_output(&headers, &output.pack_to_vec()).unwrap(), output);
+        assert!(parse_output(&headers, &[0xff]).is_err());
+
+        let json = serde_json::to_vec(&OutputDto::from(&output)).unwrap();
+        let parsed = parse_output(&HeaderMap::new(), &json).unwrap();
+        assert_eq!(parsed, output);
+        assert_eq!(
+            parsed.rent_cost(protocol_params.rent_structure()),
+            output.rent_cost(protocol_params.rent_structure())
+        );
+        assert!(parsed.rent_cost(protocol_params.rent_structure()) > 1);
+    }
+
+    #[test]
+    fn updates_into_versions() {
+        assert!(into_versions::<()>(Vec::new()).is_empty());
+        assert_eq!(into_versions(vec![(5.into(), 'a')]), vec![(5.into(), None, 'a')]);
+        assert_eq!(
+            into_versions(vec![(0.into(), 'a'), (10.into(), 'b'), (11.into(), 'c')]),
+            vec![
+                (0.into(), Some(9.into()), 'a'),
+                (10.into(), Some(10.into()), 'b'),
+                (11.into(), None, 'c'),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_payload_kind_is_an_error() {
+        assert_eq!(
+            payload_type(TaggedDataPayload::KIND).unwrap(),
+            iota_types::block::payload::TaggedDataPayload::KIND
+        );
+        assert!(payload_type("unknown").is_err());
+    }
+
+    #[test]
+    fn search_query_classification() {
+        assert_eq!(
+            parse_search_query("42"),
+            vec![SearchQuery::MilestoneIndex(MilestoneIndex(42))]
+        );
+
+        let address = IotaAddress::Ed25519(Ed25519Address::new([1; Ed25519Address::LENGTH]));
+        assert_eq!(
+            parse_search_query(&address.to_bech32("rms")),
+            vec![SearchQuery::Address("rms".to_string(), address)]
+        );
+
+        // A 32 byte identifier can also be a tag.
+        let id = prefix_hex::encode([2u8; BlockId::LENGTH]);
+        assert_eq!(
+            parse_search_query(&id),
+            vec![
+                SearchQuery::Id(BlockId([2; BlockId::LENGTH])),
+                SearchQuery::Tag(Tag::from_hex(&id).unwrap()),
+            ]
+        );
+        let output_id = format!("{}0100", prefix_hex::encode([3u8; TransactionId::LENGTH]));
+        assert!(matches!(
+            parse_search_query(&output_id)[..],
+            [SearchQuery::OutputId(_), SearchQuery::Tag(_)]
+        ));
+        let foundry_id = prefix_hex::encode([4u8; iota_types::block::output::FoundryId::LENGTH]);
+        assert!(matches!(
+            parse_search_query(&foundry_id)[..],
+            [SearchQuery::FoundryId(_), SearchQuery::Tag(_)]
+        ));
+
+        // Anything else is a tag, unless it is too long or invalid hex.
+        assert_eq!(parse_search_query("hello"), vec![SearchQuery::Tag(Tag::from("hello"))]);
+        assert_eq!(parse_search_query(&"a".repeat(TAG_LENGTH_MAX + 1)), Vec::new());
+        assert_eq!(parse_search_query("0xzz"), Vec::new());
+    }
+}
@@ -1,25 +1,33 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use std::convert::Infallible;
+
 use async_trait::async_trait;
 use axum::{
+    body::{Bytes, HttpBody},
     extract::{FromRequest, Query},
-    Extension,
+    http::header::{HeaderMap, ACCEPT},
+    BoxError, Extension,
 };
 use chronicle::model::tangle::MilestoneTimestamp;
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize};
 
 use super::{
     config::ApiConfigData,
     error::{ApiError, RequestError},
+    openapi::{api_schema, impl_query_extractor, DocExtractor},
+    routes::BYTE_CONTENT_HEADER,
     DEFAULT_PAGE_SIZE,
 };
 
-#[derive(Debug, Copy, Clone, Deserialize, PartialEq, Eq)]
-#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
-pub struct Pagination {
-    pub page_size: usize,
-    pub page: usize,
+api_schema! {
+    #[derive(Debug, Copy, Clone, Deserialize, PartialEq, Eq)]
+    #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+    pub struct Pagination {
+        pub page_size: usize,
+        pub page: usize,
+    }
 }
 
 impl Default for Pagination {
@@ -45,10 +53,12 @@ impl<B: Send> FromRequest<B> for Pagination {
     }
 }
 
-#[derive(Copy, Clone, Default, Deserialize)]
-#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
-pub struct ListRoutesQuery {
-    pub depth: Option<usize>,
+api_schema! {
+    #[derive(Copy, Clone, Default, Deserialize)]
+    #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+    pub struct ListRoutesQuery {
+        pub depth: Option<usize>,
+    }
 }
 
 #[async_trait]
@@ -98,6 +108,81 @@ impl<B: Send> FromRequest<B> for TimeRange {
     }
 }
 
+impl_query_extractor!(Pagination => Pagination, ListRoutesQuery => ListRoutesQuery);
+
+/// The representation of a resource that is preferred by the `Accept` header of a request.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ResponseFormat {
+    Json,
+    /// The packed bytes of the resource.
+    Raw,
+}
+
+impl ResponseFormat {
+    /// Negotiates the format from the media ranges of the `Accept` headers. The raw format has to be listed
+    /// explicitly and is chosen if its quality is at least that of JSON.
+    pub fn negotiate(headers: &HeaderMap) -> Self {
+        let (mut raw_quality, mut json_quality) = (0.0f32, 0.0f32);
+        let media_ranges = headers
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','));
+        for media_range in media_ranges {
+            let mut parts = media_range.split(';').map(str::trim);
+            let media_type = parts.next().unwrap_or_default().to_ascii_lowercase();
+            let quality = parts
+                .find_map(|param| param.strip_prefix("q="))
+                .and_then(|quality| quality.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if media_type == BYTE_CONTENT_HEADER {
+                raw_quality = raw_quality.max(quality);
+            } else if matches!(media_type.as_str(), "application/json" | "application/*" | "*/*") {
+                json_quality = json_quality.max(quality);
+            }
+        }
+        if raw_quality > 0.0 && raw_quality >= json_quality {
+            Self::Raw
+        } else {
+            Self::Json
+        }
+    }
+}
+
+#[async_trait]
+impl<B: Send> FromRequest<B> for ResponseFormat {
+    type Rejection = Infallible;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        Ok(Self::negotiate(req.headers()))
+    }
+}
+
+// Both formats are documented by the response.
+impl DocExtractor for ResponseFormat {}
+
+/// A JSON request body that can be omitted, in which case the default value is used.
+pub struct OptionalJson<T>(pub T);
+
+#[async_trait]
+impl<B, T> FromRequest<B> for OptionalJson<T>
+where
+    B: HttpBody + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    T: DeserializeOwned + Default,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut axum::extract::RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let body = Bytes::from_request(req).await.map_err(RequestError::from)?;
+        if body.iter().all(u8::is_ascii_whitespace) {
+            return Ok(Self(T::default()));
+        }
+        Ok(Self(serde_json::from_slice(&body).map_err(RequestError::from)?))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use axum::{
@@ -108,6 +193,27 @@ mod test {
     use super::*;
     use crate::api::ApiConfig;
 
+    #[test]
+    fn response_format_negotiated() {
+        let format = |accept: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(ACCEPT, accept.parse().unwrap());
+            ResponseFormat::negotiate(&headers)
+        };
+        assert_eq!(ResponseFormat::negotiate(&HeaderMap::new()), ResponseFormat::Json);
+        assert_eq!(format("application/vnd.iota.serializer-v1"), ResponseFormat::Raw);
+        assert_eq!(
+            format("application/json;q=0.5, application/vnd.iota.serializer-v1"),
+            ResponseFormat::Raw
+        );
+        assert_eq!(
+            format("application/vnd.iota.serializer-v1;q=0.5, */*"),
+            ResponseFormat::Json
+        );
+        assert_eq!(format("application/vnd.iota.serializer-v1;q=0"), ResponseFormat::Json);
+        assert_eq!(format("*/*"), ResponseFormat::Json);
+    }
+
     #[tokio::test]
     async fn page_size_clamped() {
         let mut req = RequestParts::new(
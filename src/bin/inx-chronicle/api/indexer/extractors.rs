@@ -19,7 +19,12 @@ use mongodb::bson;
 use primitive_types::U256;
 use serde::Deserialize;
 
-use crate::api::{config::ApiConfigData, error::RequestError, ApiError, DEFAULT_PAGE_SIZE};
+use crate::api::{
+    config::ApiConfigData,
+    error::RequestError,
+    openapi::{api_schema, impl_query_extractor},
+    ApiError, DEFAULT_PAGE_SIZE,
+};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct IndexedOutputsPagination<Q>
@@ -68,30 +73,32 @@ impl Display for IndexedOutputsCursor {
     }
 }
 
-#[derive(Clone, Deserialize, Default)]
-#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
-pub struct BasicOutputsPaginationQuery {
-    pub address: Option<String>,
-    pub has_native_tokens: Option<bool>,
-    pub min_native_token_count: Option<String>,
-    pub max_native_token_count: Option<String>,
-    pub has_storage_deposit_return: Option<bool>,
-    pub storage_deposit_return_address: Option<String>,
-    pub has_timelock: Option<bool>,
-    pub timelocked_before: Option<u32>,
-    pub timelocked_after: Option<u32>,
-    pub has_expiration: Option<bool>,
-    pub expires_before: Option<u32>,
-    pub expires_after: Option<u32>,
-    pub expiration_return_address: Option<String>,
-    pub sender: Option<String>,
-    pub tag: Option<String>,
-    pub created_before: Option<u32>,
-    pub created_after: Option<u32>,
-    pub page_size: Option<usize>,
-    pub cursor: Option<String>,
-    pub sort: Option<String>,
-    pub include_spent: Option<bool>,
+api_schema! {
+    #[derive(Clone, Deserialize, Default)]
+    #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+    pub struct BasicOutputsPaginationQuery {
+        pub address: Option<String>,
+        pub has_native_tokens: Option<bool>,
+        pub min_native_token_count: Option<String>,
+        pub max_native_token_count: Option<String>,
+        pub has_storage_deposit_return: Option<bool>,
+        pub storage_deposit_return_address: Option<String>,
+        pub has_timelock: Option<bool>,
+        pub timelocked_before: Option<u32>,
+        pub timelocked_after: Option<u32>,
+        pub has_expiration: Option<bool>,
+        pub expires_before: Option<u32>,
+        pub expires_after: Option<u32>,
+        pub expiration_return_address: Option<String>,
+        pub sender: Option<String>,
+        pub tag: Option<String>,
+        pub created_before: Option<u32>,
+        pub created_after: Option<u32>,
+        pub page_size: Option<usize>,
+        pub cursor: Option<String>,
+        pub sort: Option<String>,
+        pub include_spent: Option<bool>,
+    }
 }
 
 #[async_trait]
@@ -173,22 +180,24 @@ impl<B: Send> FromRequest<B> for IndexedOutputsPagination<BasicOutputsQuery> {
     }
 }
 
-#[derive(Clone, Deserialize, Default)]
-#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
-pub struct AliasOutputsPaginationQuery {
-    pub state_controller: Option<String>,
-    pub governor: Option<String>,
-    pub issuer: Option<String>,
-    pub sender: Option<String>,
-    pub has_native_tokens: Option<bool>,
-    pub min_native_token_count: Option<String>,
-    pub max_native_token_count: Option<String>,
-    pub created_before: Option<u32>,
-    pub created_after: Option<u32>,
-    pub page_size: Option<usize>,
-    pub cursor: Option<String>,
-    pub sort: Option<String>,
-    pub include_spent: Option<bool>,
+api_schema! {
+    #[derive(Clone, Deserialize, Default)]
+    #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+    pub struct AliasOutputsPaginationQuery {
+        pub state_controller: Option<String>,
+        pub governor: Option<String>,
+        pub issuer: Option<String>,
+        pub sender: Option<String>,
+        pub has_native_tokens: Option<bool>,
+        pub min_native_token_count: Option<String>,
+        pub max_native_token_count: Option<String>,
+        pub created_before: Option<u32>,
+        pub created_after: Option<u32>,
+        pub page_size: Option<usize>,
+        pub cursor: Option<String>,
+        pub sort: Option<String>,
+        pub include_spent: Option<bool>,
+    }
 }
 
 #[async_trait]
@@ -258,19 +267,21 @@ impl<B: Send> FromRequest<B> for IndexedOutputsPagination<AliasOutputsQuery> {
     }
 }
 
-#[derive(Clone, Deserialize, Default)]
-#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
-pub struct FoundryOutputsPaginationQuery {
-    pub alias_address: Option<String>,
-    pub has_native_tokens: Option<bool>,
-    pub min_native_token_count: Option<String>,
-    pub max_native_token_count: Option<String>,
-    pub created_before: Option<u32>,
-    pub created_after: Option<u32>,
-    pub page_size: Option<usize>,
-    pub cursor: Option<String>,
-    pub sort: Option<String>,
-    pub include_spent: Option<bool>,
+api_schema! {
+    #[derive(Clone, Deserialize, Default)]
+    #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+    pub struct FoundryOutputsPaginationQuery {
+        pub alias_address: Option<String>,
+        pub has_native_tokens: Option<bool>,
+        pub min_native_token_count: Option<String>,
+        pub max_native_token_count: Option<String>,
+        pub created_before: Option<u32>,
+        pub created_after: Option<u32>,
+        pub page_size: Option<usize>,
+        pub cursor: Option<String>,
+        pub sort: Option<String>,
+        pub include_spent: Option<bool>,
+    }
 }
 
 #[async_trait]
@@ -325,31 +336,33 @@ impl<B: Send> FromRequest<B> for IndexedOutputsPagination<FoundryOutputsQuery> {
     }
 }
 
-#[derive(Clone, Deserialize, Default)]
-#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
-pub struct NftOutputsPaginationQuery {
-    pub address: Option<String>,
-    pub issuer: Option<String>,
-    pub sender: Option<String>,
-    pub has_native_tokens: Option<bool>,
-    pub min_native_token_count: Option<String>,
-    pub max_native_token_count: Option<String>,
-    pub has_storage_deposit_return: Option<bool>,
-    pub storage_deposit_return_address: Option<String>,
-    pub has_timelock: Option<bool>,
-    pub timelocked_before: Option<u32>,
-    pub timelocked_after: Option<u32>,
-    pub has_expiration: Option<bool>,
-    pub expires_before: Option<u32>,
-    pub expires_after: Option<u32>,
-    pub expiration_return_address: Option<String>,
-    pub tag: Option<String>,
-    pub created_before: Option<u32>,
-    pub created_after: Option<u32>,
-    pub page_size: Option<usize>,
-    pub cursor: Option<String>,
-    pub sort: Option<String>,
-    pub include_spent: Option<bool>,
+api_schema! {
+    #[derive(Clone, Deserialize, Default)]
+    #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+    pub struct NftOutputsPaginationQuery {
+        pub address: Option<String>,
+        pub issuer: Option<String>,
+        pub sender: Option<String>,
+        pub has_native_tokens: Option<bool>,
+        pub min_native_token_count: Option<String>,
+        pub max_native_token_count: Option<String>,
+        pub has_storage_deposit_return: Option<bool>,
+        pub storage_deposit_return_address: Option<String>,
+        pub has_timelock: Option<bool>,
+        pub timelocked_before: Option<u32>,
+        pub timelocked_after: Option<u32>,
+        pub has_expiration: Option<bool>,
+        pub expires_before: Option<u32>,
+        pub expires_after: Option<u32>,
+        pub expiration_return_address: Option<String>,
+        pub tag: Option<String>,
+        pub created_before: Option<u32>,
+        pub created_after: Option<u32>,
+        pub page_size: Option<usize>,
+        pub cursor: Option<String>,
+        pub sort: Option<String>,
+        pub include_spent: Option<bool>,
+    }
 }
 
 #[async_trait]
@@ -436,6 +449,13 @@ impl<B: Send> FromRequest<B> for IndexedOutputsPagination<NftOutputsQuery> {
     }
 }
 
+impl_query_extractor!(
+    IndexedOutputsPagination<BasicOutputsQuery> => BasicOutputsPaginationQuery,
+    IndexedOutputsPagination<AliasOutputsQuery> => AliasOutputsPaginationQuery,
+    IndexedOutputsPagination<FoundryOutputsQuery> => FoundryOutputsPaginationQuery,
+    IndexedOutputsPagination<NftOutputsQuery> => NftOutputsPaginationQuery,
+);
+
 #[cfg(test)]
 mod test {
     use axum::{extract::RequestParts, http::Request};
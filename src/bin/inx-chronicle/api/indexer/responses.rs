@@ -4,15 +4,17 @@
 use chronicle::model::tangle::MilestoneIndex;
 use serde::{Deserialize, Serialize};
 
-use crate::api::responses::impl_success_response;
+use crate::api::{openapi::api_schema, responses::impl_success_response};
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct IndexerOutputsResponse {
-    pub ledger_index: MilestoneIndex,
-    pub items: Vec<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub cursor: Option<String>,
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct IndexerOutputsResponse {
+        pub ledger_index: MilestoneIndex,
+        pub items: Vec<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub cursor: Option<String>,
+    }
 }
 
 impl_success_response!(IndexerOutputsResponse);
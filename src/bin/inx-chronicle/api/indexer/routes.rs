@@ -3,7 +3,7 @@
 
 use std::str::FromStr;
 
-use axum::{extract::Path, routing::get, Extension};
+use axum::{extract::Path, Extension};
 use chronicle::{
     db::{
         mongodb::collections::{
@@ -20,7 +20,7 @@ use super::{extractors::IndexedOutputsPagination, responses::IndexerOutputsRespo
 use crate::api::{
     error::{MissingError, RequestError},
     indexer::extractors::IndexedOutputsCursor,
-    router::Router,
+    router::{get, Router},
     ApiResult,
 };
 
@@ -10,17 +10,24 @@ mod secret_key;
 #[macro_use]
 mod responses;
 mod auth;
+mod cache;
 pub mod config;
 mod core;
 mod explorer;
 mod indexer;
+#[cfg(feature = "inx")]
+mod node;
+mod openapi;
 #[cfg(feature = "poi")]
 mod poi;
+mod rate_limit;
 mod router;
 mod routes;
+mod storage;
+
+use std::net::SocketAddr;
 
 use axum::{Extension, Server};
-use chronicle::db::MongoDb;
 use futures::Future;
 use hyper::Method;
 use tower_http::{
@@ -30,49 +37,86 @@ use tower_http::{
 };
 use tracing::info;
 
+use self::rate_limit::RateLimiter;
+use crate::progress::SyncProgress;
+
+#[cfg(feature = "inx")]
+pub use self::node::NodeClient;
 pub use self::{
+    auth::{create_api_key, ApiClaims, Scope},
+    cache::ReadCache,
     config::{ApiConfig, ApiConfigData},
     error::{ApiError, ApiResult, AuthError, ConfigError},
     secret_key::SecretKey,
+    storage::ApiStorage,
 };
 
 pub const DEFAULT_PAGE_SIZE: usize = 100;
 
 /// The Chronicle API actor
 #[derive(Debug)]
-pub struct ApiWorker {
-    db: MongoDb,
+pub struct ApiWorker<S> {
+    db: S,
     api_data: ApiConfigData,
+    sync_progress: SyncProgress,
+    read_cache: ReadCache,
+    rate_limiter: RateLimiter,
+    #[cfg(feature = "inx")]
+    node: Option<NodeClient>,
 }
 
-impl ApiWorker {
-    /// Create a new Chronicle API actor from a mongo connection.
-    pub fn new(db: MongoDb, config: ApiConfig) -> Result<Self, ConfigError> {
+impl<S: ApiStorage> ApiWorker<S> {
+    /// Create a new Chronicle API actor from a storage backend.
+    pub fn new(db: S, config: ApiConfig) -> Result<Self, ConfigError> {
         Ok(Self {
             db,
+            read_cache: ReadCache::new(config.cache_capacity, false),
+            rate_limiter: RateLimiter::new(config.rate_limit.clone())?,
             api_data: config.try_into()?,
+            sync_progress: Default::default(),
+            #[cfg(feature = "inx")]
+            node: None,
         })
     }
 
+    pub fn set_sync_progress(&mut self, sync_progress: &SyncProgress) {
+        self.sync_progress = sync_progress.clone();
+    }
+
+    pub fn set_read_cache(&mut self, read_cache: &ReadCache) {
+        self.read_cache = read_cache.clone();
+    }
+
+    #[cfg(feature = "inx")]
+    pub fn set_node(&mut self, node: &NodeClient) {
+        self.node.replace(node.clone());
+    }
+
     pub async fn run(&self, shutdown_handle: impl Future<Output = ()>) -> eyre::Result<()> {
         info!("Starting API server on port `{}`", self.api_data.port);
 
         let port = self.api_data.port;
-        let routes = routes::routes()
+        let routes = routes::routes::<S>(&self.api_data)
             .layer(Extension(self.db.clone()))
             .layer(Extension(self.api_data.clone()))
+            .layer(Extension(self.sync_progress.clone()))
+            .layer(Extension(self.read_cache.clone()))
+            .layer(Extension(self.rate_limiter.clone()));
+        #[cfg(feature = "inx")]
+        let routes = routes.layer(Extension(self.node.clone()));
+        let routes = routes
             .layer(CatchPanicLayer::new())
             .layer(TraceLayer::new_for_http())
             .layer(
                 CorsLayer::new()
                     .allow_origin(self.api_data.allow_origins.clone())
-                    .allow_methods(vec![Method::GET, Method::OPTIONS])
+                    .allow_methods(vec![Method::GET, Method::POST, Method::OPTIONS])
                     .allow_headers(Any)
                     .allow_credentials(false),
             );
 
         Server::bind(&([0, 0, 0, 0], port).into())
-            .serve(routes.into_make_service())
+            .serve(routes.into_make_service_with_connect_info::<SocketAddr>())
             .with_graceful_shutdown(shutdown_handle)
             .await?;
 
@@ -0,0 +1,112 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Forwards the requests of the core API that need a node, like block submission and tip selection, via INX.
+
+use std::sync::Arc;
+
+use chronicle::{
+    inx::{Inx, InxError},
+    model::BlockId,
+};
+
+use super::{
+    error::{NodeError, RateLimitedError},
+    rate_limit::TokenBucket,
+    ApiResult,
+};
+
+/// A handle to the node that requests are forwarded to.
+#[derive(Clone, Debug)]
+pub struct NodeClient {
+    inx: Inx,
+    rate_limit: Option<Arc<TokenBucket>>,
+}
+
+impl NodeClient {
+    /// Creates a client that forwards up to `rate_limit` requests per second. A limit of zero disables rate limiting.
+    pub fn new(inx: Inx, rate_limit: u32) -> Self {
+        Self {
+            inx,
+            rate_limit: (rate_limit > 0).then(|| Arc::new(TokenBucket::new(rate_limit as f64, rate_limit as f64))),
+        }
+    }
+
+    /// Submits a serialized block to the node.
+    pub async fn submit_block(&self, raw_block: Vec<u8>) -> ApiResult<BlockId> {
+        self.acquire()?;
+        Ok(self.inx.clone().submit_block(raw_block).await.map_err(NodeError::from)?)
+    }
+
+    /// Requests up to `count` tips from the node.
+    pub async fn request_tips(&self, count: u32) -> ApiResult<Vec<BlockId>> {
+        self.acquire()?;
+        Ok(self.inx.clone().request_tips(count, false).await.map_err(NodeError::from)?)
+    }
+
+    fn acquire(&self) -> Result<(), RateLimitedError> {
+        match &self.rate_limit {
+            Some(bucket) => bucket
+                .try_acquire(1.0)
+                .map_err(|retry_after| RateLimitedError { retry_after }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl From<InxError> for NodeError {
+    fn from(error: InxError) -> Self {
+        match error {
+            InxError::StatusCode(status)
+                if matches!(
+                    status.code(),
+                    tonic::Code::InvalidArgument | tonic::Code::FailedPrecondition | tonic::Code::AlreadyExists
+                ) =>
+            {
+                Self::Rejected(status.message().to_string())
+            }
+            error => Self::Unavailable(Box::new(error)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use hyper::StatusCode;
+
+    use super::*;
+    use crate::api::{error::ErrorStatus, ApiError};
+
+    fn status(code: tonic::Code) -> NodeError {
+        InxError::StatusCode(tonic::Status::new(code, "message")).into()
+    }
+
+    #[test]
+    fn inx_error_mapping() {
+        for code in [
+            tonic::Code::InvalidArgument,
+            tonic::Code::FailedPrecondition,
+            tonic::Code::AlreadyExists,
+        ] {
+            let error = status(code);
+            assert!(matches!(&error, NodeError::Rejected(message) if message == "message"));
+            assert_eq!(error.status(), StatusCode::BAD_REQUEST);
+        }
+        for code in [tonic::Code::Unavailable, tonic::Code::Internal, tonic::Code::Unknown] {
+            let error = status(code);
+            assert!(matches!(error, NodeError::Unavailable(_)));
+            assert_eq!(error.status(), StatusCode::SERVICE_UNAVAILABLE);
+        }
+        let error = NodeError::from(InxError::MissingField("block_id"));
+        assert_eq!(error.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn rate_limited_requests_have_retry_after() {
+        let node = NodeClient::new(Inx::connect_lazy("http://localhost:9029".to_string()).unwrap(), 1);
+        node.acquire().unwrap();
+        let response = axum::response::IntoResponse::into_response(ApiError::from(node.acquire().unwrap_err()));
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[hyper::header::RETRY_AFTER], "1");
+    }
+}
@@ -0,0 +1,728 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generates the OpenAPI specification of the API from the routes themselves. Every handler that is added via
+//! [`get`](super::router::get) or [`post`](super::router::post) is documented by the [`DocExtractor`]s of its
+//! arguments and the [`DocResponse`] of its return type, so a route cannot be added without a schema. The schemas
+//! of the response structs are derived from their definitions by wrapping them in [`api_schema!`], while the field
+//! names are taken from their [`Deserialize`] implementations to respect any `serde` renames.
+
+use std::{collections::BTreeMap, future::Future, ops::Range};
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query},
+    http::{header::HeaderMap, StatusCode},
+    Extension, Json, TypedHeader,
+};
+use chronicle::{
+    db::mongodb::collections::UnlockEventKind,
+    model::{
+        metadata::LedgerInclusionState,
+        tangle::{MilestoneIndex, MilestoneTimestamp},
+        utxo::Address,
+    },
+    poi::MilestoneKeyRangeDto,
+    tangle::PayloadKind,
+};
+use iota_types::{
+    api::core::response as iota,
+    block::{
+        output::dto::OutputMetadataDto, payload::dto::MilestonePayloadDto, protocol::dto::ProtocolParametersDto,
+        BlockDto,
+    },
+};
+use serde::{
+    de::{self, DeserializeOwned, Visitor},
+    forward_to_deserialize_any,
+};
+use serde_json::{json, Map, Value};
+
+use super::{
+    auth::is_control_route,
+    config::RouteGroup,
+    error::{ApiError, MissingError, UnimplementedError},
+    extractors::OptionalJson,
+    router::RouteNode,
+};
+use crate::progress::SyncPhase;
+
+/// The specification of the core REST API of the node, which defines the shared data types.
+const CORE_API_SPEC_URL: &str = "https://github.com/iotaledger/tips/blob/main/tips/TIP-0025/core-rest-api.yaml";
+
+/// A function that documents a single operation of a route.
+pub type OperationFn = fn(&mut SchemaGenerator) -> Operation;
+
+/// Collects the named schemas that are referenced by the operations.
+#[derive(Debug, Default)]
+pub struct SchemaGenerator {
+    schemas: BTreeMap<String, Value>,
+}
+
+impl SchemaGenerator {
+    /// Returns a reference to a named schema, which is generated when it is first used.
+    pub fn reference(&mut self, name: &str, schema: impl FnOnce(&mut Self) -> Value) -> Value {
+        if !self.schemas.contains_key(name) {
+            // A placeholder ends the recursion of self-referencing schemas.
+            self.schemas.insert(name.to_string(), Value::Null);
+            let schema = schema(self);
+            self.schemas.insert(name.to_string(), schema);
+        }
+        json!({ "$ref": format!("#/components/schemas/{}", name) })
+    }
+
+    fn error_response(&mut self, status: StatusCode) -> Value {
+        let schema = self.reference("ErrorBody", |_| {
+            json!({
+                "type": "object",
+                "properties": {
+                    "code": { "type": "integer", "minimum": 0 },
+                    "message": { "type": "string" },
+                },
+                "required": ["code", "message"],
+            })
+        });
+        json!({
+            "description": status.canonical_reason().unwrap_or_default(),
+            "content": { "application/json": { "schema": schema } },
+        })
+    }
+}
+
+/// The documentation of a single operation.
+#[derive(Debug, Default)]
+pub struct Operation {
+    path_parameter: Option<Value>,
+    query_parameters: Vec<Value>,
+    request_body: Option<Value>,
+    responses: BTreeMap<String, Value>,
+}
+
+impl Operation {
+    pub fn response(&mut self, status: &str, response: Value) {
+        self.responses.insert(status.to_string(), response);
+    }
+
+    pub fn json_response(&mut self, schema: Value) {
+        self.response(
+            "200",
+            json!({
+                "description": "Successful operation.",
+                "content": { "application/json": { "schema": schema } },
+            }),
+        );
+    }
+
+    fn error_response(&mut self, gen: &mut SchemaGenerator, status: StatusCode) {
+        let response = gen.error_response(status);
+        self.response(status.as_str(), response);
+    }
+
+    /// Documents the fields of a query struct as query parameters.
+    pub fn query_parameters<T: ApiObject>(&mut self, gen: &mut SchemaGenerator) {
+        for Property { name, schema, .. } in T::properties(gen) {
+            // All query structs default their missing fields.
+            self.query_parameters
+                .push(json!({ "name": name, "in": "query", "required": false, "schema": schema }));
+        }
+    }
+
+    fn into_json(self, path_parameters: &[&str], tag: Option<&str>, secured: bool) -> Value {
+        let mut operation = Map::new();
+        if let Some(tag) = tag {
+            operation.insert("tags".into(), json!([tag]));
+        }
+        let path_schema = self.path_parameter.unwrap_or_else(|| json!({ "type": "string" }));
+        let parameters = path_parameters
+            .iter()
+            .map(|name| json!({ "name": name, "in": "path", "required": true, "schema": path_schema }))
+            .chain(self.query_parameters)
+            .collect::<Vec<_>>();
+        if !parameters.is_empty() {
+            operation.insert("parameters".into(), parameters.into());
+        }
+        if let Some(body) = self.request_body {
+            operation.insert("requestBody".into(), body);
+        }
+        operation.insert("responses".into(), json!(self.responses));
+        if secured {
+            operation.insert("security".into(), json!([{ "bearerAuth": [] }]));
+        }
+        operation.into()
+    }
+}
+
+/// Builds the OpenAPI specification of all routes below the given node.
+pub fn spec(root: &RouteNode) -> Value {
+    let mut gen = SchemaGenerator::default();
+    let mut paths = Map::new();
+    for (route, operations) in root.operations() {
+        let path_parameters = route
+            .split('/')
+            .filter_map(|segment| segment.strip_prefix(':'))
+            .collect::<Vec<_>>();
+        let path = route
+            .split('/')
+            .map(|segment| match segment.strip_prefix(':') {
+                Some(name) => format!("{{{}}}", name),
+                None => segment.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        let tag = RouteGroup::from_path(&route).map(|group| match group {
+            RouteGroup::Core => "core",
+            RouteGroup::Explorer => "explorer",
+            RouteGroup::Indexer => "indexer",
+            RouteGroup::Poi => "poi",
+        });
+        // Only the grouped routes below `/api` and the control routes require authentication.
+        let secured = tag.is_some() || is_control_route(&route);
+        let mut item = Map::new();
+        for (method, operation) in operations {
+            let mut operation = operation(&mut gen);
+            if secured {
+                for status in [StatusCode::UNAUTHORIZED, StatusCode::FORBIDDEN] {
+                    operation.error_response(&mut gen, status);
+                }
+            }
+            // Only the routes below `/api` are throttled.
+            if tag.is_some() {
+                operation.error_response(&mut gen, StatusCode::TOO_MANY_REQUESTS);
+            }
+            item.insert(method.to_string(), operation.into_json(&path_parameters, tag, secured));
+        }
+        paths.insert(path, item.into());
+    }
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Chronicle API",
+            "version": std::env!("CARGO_PKG_VERSION"),
+            "license": { "name": "Apache 2.0", "url": "https://www.apache.org/licenses/LICENSE-2.0.html" },
+        },
+        "paths": paths,
+        "components": {
+            "schemas": gen.schemas,
+            "securitySchemes": {
+                "bearerAuth": { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" },
+            },
+        },
+    })
+}
+
+/// A type that can be described by a JSON schema.
+pub trait ApiSchema {
+    /// Whether a field of this type is always present.
+    const REQUIRED: bool = true;
+
+    fn schema(gen: &mut SchemaGenerator) -> Value;
+}
+
+/// A named field of an [`ApiObject`].
+#[derive(Debug)]
+pub struct Property {
+    pub name: &'static str,
+    pub schema: Value,
+    pub required: bool,
+}
+
+/// A type that is serialized as a JSON object with a fixed set of fields.
+pub trait ApiObject {
+    fn properties(gen: &mut SchemaGenerator) -> Vec<Property>;
+}
+
+/// Pairs the serialized field names of a struct with the schemas of its fields.
+pub fn properties<T: DeserializeOwned>(schemas: Vec<(Value, bool)>) -> Vec<Property> {
+    let names = serde_names::<T>();
+    assert_eq!(
+        names.len(),
+        schemas.len(),
+        "the fields of `{}` do not match its serialized form",
+        std::any::type_name::<T>()
+    );
+    names
+        .iter()
+        .zip(schemas)
+        .map(|(&name, (schema, required))| Property { name, schema, required })
+        .collect()
+}
+
+/// Creates the schema of a JSON object.
+pub fn object_schema(properties: Vec<Property>) -> Value {
+    let required = properties
+        .iter()
+        .filter(|property| property.required)
+        .map(|property| property.name)
+        .collect::<Vec<_>>();
+    let mut schema = json!({
+        "type": "object",
+        "properties": properties
+            .into_iter()
+            .map(|property| (property.name.to_string(), property.schema))
+            .collect::<Map<_, _>>(),
+    });
+    if !required.is_empty() {
+        schema["required"] = required.into();
+    }
+    schema
+}
+
+/// Creates the schema of an enum whose variants are serialized as plain strings.
+pub fn string_enum_schema<T: DeserializeOwned>() -> Value {
+    json!({ "type": "string", "enum": serde_names::<T>() })
+}
+
+/// Gets the field names of a struct, or the variant names of an enum, as they are expected by its [`Deserialize`]
+/// implementation.
+fn serde_names<T: DeserializeOwned>() -> &'static [&'static str] {
+    let mut names = None;
+    // The introspection always fails after capturing the names.
+    T::deserialize(Introspector(&mut names)).ok();
+    names.unwrap_or_default()
+}
+
+/// A deserializer that captures the names that a type asks for instead of providing any data.
+struct Introspector<'a>(&'a mut Option<&'static [&'static str]>);
+
+impl<'de, 'a> de::Deserializer<'de> for Introspector<'a> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("only structs and enums can be introspected"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = Some(fields);
+        Err(de::Error::custom("introspected"))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = Some(variants);
+        Err(de::Error::custom("introspected"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit unit_struct
+        newtype_struct seq tuple tuple_struct map identifier ignored_any
+    }
+}
+
+/// Defines a struct and derives its [`ApiSchema`] from the types of its fields. The struct must implement
+/// [`Deserialize`](serde::Deserialize), which provides the names of its fields.
+macro_rules! api_schema {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$field_attr:meta])*
+                $field_vis:vis $field:ident: $ty:ty
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        $vis struct $name {
+            $(
+                $(#[$field_attr])*
+                $field_vis $field: $ty,
+            )*
+        }
+
+        impl $crate::api::openapi::ApiObject for $name {
+            fn properties(
+                gen: &mut $crate::api::openapi::SchemaGenerator,
+            ) -> Vec<$crate::api::openapi::Property> {
+                $crate::api::openapi::properties::<Self>(vec![$((
+                    <$ty as $crate::api::openapi::ApiSchema>::schema(gen),
+                    <$ty as $crate::api::openapi::ApiSchema>::REQUIRED,
+                )),*])
+            }
+        }
+
+        impl $crate::api::openapi::ApiSchema for $name {
+            fn schema(gen: &mut $crate::api::openapi::SchemaGenerator) -> serde_json::Value {
+                gen.reference(stringify!($name), |gen| {
+                    $crate::api::openapi::object_schema(<Self as $crate::api::openapi::ApiObject>::properties(gen))
+                })
+            }
+        }
+    };
+}
+
+pub(crate) use api_schema;
+
+/// Documents extractors that parse their query from the given [`ApiObject`].
+macro_rules! impl_query_extractor {
+    ($($extractor:ty => $query:ty),* $(,)?) => {
+        $(
+            impl $crate::api::openapi::DocExtractor for $extractor {
+                fn document(
+                    operation: &mut $crate::api::openapi::Operation,
+                    gen: &mut $crate::api::openapi::SchemaGenerator,
+                ) {
+                    operation.query_parameters::<$query>(gen);
+                }
+            }
+        )*
+    };
+}
+
+pub(crate) use impl_query_extractor;
+
+macro_rules! impl_primitive_schema {
+    ($($type:ty => $schema:tt),* $(,)?) => {
+        $(
+            impl ApiSchema for $type {
+                fn schema(_gen: &mut SchemaGenerator) -> Value {
+                    json!($schema)
+                }
+            }
+        )*
+    };
+}
+
+impl_primitive_schema!(
+    bool => { "type": "boolean" },
+    u8 => { "type": "integer", "minimum": 0 },
+    u32 => { "type": "integer", "format": "int32", "minimum": 0 },
+    u64 => { "type": "integer", "format": "int64", "minimum": 0 },
+    usize => { "type": "integer", "minimum": 0 },
+    f64 => { "type": "number", "format": "double" },
+    String => { "type": "string" },
+    Value => { "type": "object" },
+    MilestoneIndex => { "type": "integer", "format": "int32", "minimum": 0 },
+    MilestoneTimestamp => {
+        "type": "integer",
+        "format": "int32",
+        "minimum": 0,
+        "description": "A unix timestamp in seconds."
+    },
+);
+
+/// Documents the types that are defined by the core REST API of the node by linking to its specification.
+macro_rules! impl_external_schema {
+    ($($type:ty => $name:literal),* $(,)?) => {
+        $(
+            impl ApiSchema for $type {
+                fn schema(gen: &mut SchemaGenerator) -> Value {
+                    gen.reference($name, |_| {
+                        json!({
+                            "type": "object",
+                            "description": format!("The `{}` as defined by the core REST API of the node.", $name),
+                            "externalDocs": { "url": CORE_API_SPEC_URL },
+                        })
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_external_schema!(
+    BlockDto => "Block",
+    MilestonePayloadDto => "MilestonePayload",
+    OutputMetadataDto => "OutputMetadata",
+    ProtocolParametersDto => "ProtocolParameters",
+    iota::BaseTokenResponse => "BaseTokenResponse",
+    iota::BlockMetadataResponse => "BlockMetadataResponse",
+    iota::OutputWithMetadataResponse => "OutputWithMetadataResponse",
+    iota::ReceiptsResponse => "ReceiptsResponse",
+    iota::StatusResponse => "StatusResponse",
+    iota::SubmitBlockResponse => "SubmitBlockResponse",
+    iota::TipsResponse => "TipsResponse",
+    iota::TreasuryResponse => "TreasuryResponse",
+    iota::UtxoChangesResponse => "UtxoChangesResponse",
+);
+
+impl<T: ApiSchema> ApiSchema for Option<T> {
+    const REQUIRED: bool = false;
+
+    fn schema(gen: &mut SchemaGenerator) -> Value {
+        T::schema(gen)
+    }
+}
+
+impl<T: ApiSchema> ApiSchema for Vec<T> {
+    fn schema(gen: &mut SchemaGenerator) -> Value {
+        json!({ "type": "array", "items": T::schema(gen) })
+    }
+}
+
+impl<T: ApiSchema> ApiSchema for Range<T> {
+    fn schema(gen: &mut SchemaGenerator) -> Value {
+        let bound = T::schema(gen);
+        json!({
+            "type": "object",
+            "properties": { "start": bound, "end": bound },
+            "required": ["start", "end"],
+        })
+    }
+}
+
+impl ApiSchema for Address {
+    fn schema(gen: &mut SchemaGenerator) -> Value {
+        gen.reference("Address", |_| {
+            json!({
+                "type": "object",
+                "description": "An address that is keyed by its kind, which is one of `ed25519`, `alias` or `nft`.",
+            })
+        })
+    }
+}
+
+impl ApiSchema for MilestoneKeyRangeDto {
+    fn schema(gen: &mut SchemaGenerator) -> Value {
+        gen.reference("MilestoneKeyRange", |gen| {
+            object_schema(properties::<Self>(vec![
+                (String::schema(gen), true),
+                (MilestoneIndex::schema(gen), true),
+                (MilestoneIndex::schema(gen), true),
+            ]))
+        })
+    }
+}
+
+impl ApiSchema for LedgerInclusionState {
+    fn schema(gen: &mut SchemaGenerator) -> Value {
+        gen.reference("LedgerInclusionState", |_| string_enum_schema::<Self>())
+    }
+}
+
+impl ApiSchema for PayloadKind {
+    fn schema(gen: &mut SchemaGenerator) -> Value {
+        gen.reference("PayloadKind", |_| string_enum_schema::<Self>())
+    }
+}
+
+impl ApiSchema for UnlockEventKind {
+    fn schema(gen: &mut SchemaGenerator) -> Value {
+        gen.reference("UnlockEventKind", |_| string_enum_schema::<Self>())
+    }
+}
+
+impl ApiSchema for SyncPhase {
+    fn schema(gen: &mut SchemaGenerator) -> Value {
+        gen.reference("SyncPhase", |_| string_enum_schema::<Self>())
+    }
+}
+
+/// An argument of a handler that contributes to the documentation of its operation.
+pub trait DocExtractor {
+    fn document(_operation: &mut Operation, _gen: &mut SchemaGenerator) {}
+}
+
+impl<T> DocExtractor for Extension<T> {}
+
+impl<T> DocExtractor for TypedHeader<T> {}
+
+impl DocExtractor for HeaderMap {}
+
+impl<T: DocExtractor> DocExtractor for Option<T> {
+    fn document(operation: &mut Operation, gen: &mut SchemaGenerator) {
+        T::document(operation, gen)
+    }
+}
+
+impl<T: ApiSchema> DocExtractor for Path<T> {
+    fn document(operation: &mut Operation, gen: &mut SchemaGenerator) {
+        operation.path_parameter = Some(T::schema(gen));
+    }
+}
+
+impl<T: ApiObject> DocExtractor for Query<T> {
+    fn document(operation: &mut Operation, gen: &mut SchemaGenerator) {
+        operation.query_parameters::<T>(gen);
+    }
+}
+
+impl<T: ApiSchema> DocExtractor for Json<T> {
+    fn document(operation: &mut Operation, gen: &mut SchemaGenerator) {
+        operation.request_body = Some(json!({
+            "required": true,
+            "content": { "application/json": { "schema": T::schema(gen) } },
+        }));
+    }
+}
+
+impl<T: ApiSchema> DocExtractor for OptionalJson<T> {
+    fn document(operation: &mut Operation, gen: &mut SchemaGenerator) {
+        operation.request_body = Some(json!({
+            "required": false,
+            "content": { "application/json": { "schema": T::schema(gen) } },
+        }));
+    }
+}
+
+impl DocExtractor for Bytes {
+    fn document(operation: &mut Operation, _gen: &mut SchemaGenerator) {
+        operation.request_body = Some(json!({
+            "required": true,
+            "content": { "application/octet-stream": { "schema": { "type": "string", "format": "binary" } } },
+        }));
+    }
+}
+
+/// The return type of a handler that documents the responses of its operation.
+pub trait DocResponse {
+    fn document(operation: &mut Operation, gen: &mut SchemaGenerator);
+}
+
+impl<T: ApiSchema> DocResponse for Json<T> {
+    fn document(operation: &mut Operation, gen: &mut SchemaGenerator) {
+        operation.json_response(T::schema(gen));
+    }
+}
+
+impl DocResponse for String {
+    fn document(operation: &mut Operation, _gen: &mut SchemaGenerator) {
+        operation.response(
+            "200",
+            json!({
+                "description": "Successful operation.",
+                "content": { "text/plain": { "schema": { "type": "string" } } },
+            }),
+        );
+    }
+}
+
+impl DocResponse for StatusCode {
+    fn document(operation: &mut Operation, _gen: &mut SchemaGenerator) {
+        operation.response("200", json!({ "description": "Successful operation." }));
+        operation.response(
+            "default",
+            json!({ "description": "The result is reported by the status code only." }),
+        );
+    }
+}
+
+impl<T: DocResponse> DocResponse for (StatusCode, T) {
+    fn document(operation: &mut Operation, gen: &mut SchemaGenerator) {
+        T::document(operation, gen);
+        // The exact status code is only known at runtime.
+        if let Some(response) = operation.responses.remove("200") {
+            operation.response("2XX", response);
+        }
+    }
+}
+
+impl<T: DocResponse> DocResponse for Result<T, ApiError> {
+    fn document(operation: &mut Operation, gen: &mut SchemaGenerator) {
+        T::document(operation, gen);
+        for status in [
+            StatusCode::BAD_REQUEST,
+            StatusCode::NOT_FOUND,
+            StatusCode::INTERNAL_SERVER_ERROR,
+        ] {
+            operation.error_response(gen, status);
+        }
+    }
+}
+
+impl DocResponse for MissingError {
+    fn document(operation: &mut Operation, gen: &mut SchemaGenerator) {
+        operation.error_response(gen, StatusCode::NOT_FOUND);
+    }
+}
+
+impl DocResponse for UnimplementedError {
+    fn document(operation: &mut Operation, gen: &mut SchemaGenerator) {
+        operation.error_response(gen, StatusCode::NOT_IMPLEMENTED);
+    }
+}
+
+/// A handler whose operation can be documented from its signature.
+pub trait DocHandler<T> {
+    fn operation(gen: &mut SchemaGenerator) -> Operation;
+}
+
+macro_rules! impl_doc_handler {
+    ($($ty:ident),*) => {
+        #[allow(non_snake_case)]
+        impl<F, Fut, Res, $($ty,)*> DocHandler<($($ty,)*)> for F
+        where
+            F: FnOnce($($ty,)*) -> Fut,
+            Fut: Future<Output = Res>,
+            Res: DocResponse,
+            $($ty: DocExtractor,)*
+        {
+            fn operation(gen: &mut SchemaGenerator) -> Operation {
+                let mut operation = Operation::default();
+                $($ty::document(&mut operation, gen);)*
+                Res::document(&mut operation, gen);
+                operation
+            }
+        }
+    };
+}
+
+impl_doc_handler!();
+impl_doc_handler!(T1);
+impl_doc_handler!(T1, T2);
+impl_doc_handler!(T1, T2, T3);
+impl_doc_handler!(T1, T2, T3, T4);
+impl_doc_handler!(T1, T2, T3, T4, T5);
+impl_doc_handler!(T1, T2, T3, T4, T5, T6);
+
+#[cfg(test)]
+mod test {
+    use chronicle::db::MongoDb;
+
+    use super::*;
+    use crate::api::{config::ApiConfig, routes::routes, ApiConfigData};
+
+    fn collect_references<'a>(value: &'a Value, references: &mut Vec<&'a str>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(reference)) = map.get("$ref") {
+                    references.push(reference);
+                }
+                map.values().for_each(|value| collect_references(value, references));
+            }
+            Value::Array(values) => values.iter().for_each(|value| collect_references(value, references)),
+            _ => (),
+        }
+    }
+
+    #[test]
+    fn every_route_is_documented() {
+        let config = ApiConfigData::try_from(ApiConfig::default()).unwrap();
+        let router = routes::<MongoDb>(&config);
+        let spec = spec(router.root());
+
+        for (route, operations) in router.root().operations() {
+            assert!(!operations.is_empty(), "route `{}` is not documented", route);
+        }
+        for (path, item) in spec["paths"].as_object().unwrap() {
+            for (method, operation) in item.as_object().unwrap() {
+                assert!(
+                    !operation["responses"].as_object().unwrap().is_empty(),
+                    "`{} {}` has no responses",
+                    method,
+                    path
+                );
+            }
+        }
+        assert!(spec["paths"]["/api/core/v2/blocks/{block_id}"]["get"].is_object());
+        assert!(spec["paths"]["/api/openapi.json"]["get"].is_object());
+
+        let mut references = Vec::new();
+        collect_references(&spec, &mut references);
+        for reference in references {
+            let name = reference.strip_prefix("#/components/schemas/").unwrap();
+            assert!(
+                spec["components"]["schemas"][name].is_object(),
+                "schema `{}` is missing",
+                name
+            );
+        }
+    }
+}
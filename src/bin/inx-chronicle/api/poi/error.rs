@@ -1,6 +1,7 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use chronicle::poi::CreateProofError;
 use iota_types::block::payload::milestone::MilestoneValidationError;
 use thiserror::Error;
 
@@ -33,17 +34,3 @@ pub enum CorruptStateError {
     #[error("Error decoding public key")]
     DecodePublicKey,
 }
-
-#[derive(Error, Debug)]
-#[allow(missing_docs)]
-pub enum CreateProofError {
-    #[error("Block '{0}' is not included in the given ordered list of blocks")]
-    BlockNotIncluded(String),
-    #[error(
-        "The calculated merkle root '{calculated_merkle_root}' does not match the expected: '{expected_merkle_root}'"
-    )]
-    MerkleRootMismatch {
-        calculated_merkle_root: String,
-        expected_merkle_root: String,
-    },
-}
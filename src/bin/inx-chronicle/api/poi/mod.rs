@@ -2,8 +2,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod error;
-mod merkle_hasher;
-mod merkle_proof;
 mod responses;
 mod routes;
 
@@ -1,27 +1,82 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use chronicle::poi::{MerkleAuditPathDto, MilestoneKeyRangeDto, ProofBundle, ProofKind};
 use iota_types::block::{payload::dto::MilestonePayloadDto, BlockDto};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
-use super::merkle_proof::MerkleAuditPathDto;
-use crate::api::responses::impl_success_response;
+use crate::api::{
+    openapi::{api_schema, object_schema, properties, string_enum_schema, ApiSchema, SchemaGenerator},
+    responses::impl_success_response,
+};
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct CreateProofResponse {
-    pub milestone: MilestonePayloadDto,
-    pub block: BlockDto,
-    #[serde(rename = "proof")]
-    pub audit_path: MerkleAuditPathDto,
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CreateProofResponse {
+        pub milestone: MilestonePayloadDto,
+        pub block: BlockDto,
+        #[serde(rename = "proof")]
+        pub audit_path: MerkleAuditPathDto,
+    }
 }
 
 impl_success_response!(CreateProofResponse);
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ValidateProofResponse {
-    pub valid: bool,
+api_schema! {
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ValidateProofResponse {
+        pub valid: bool,
+    }
 }
 
 impl_success_response!(ValidateProofResponse);
+
+impl ApiSchema for MerkleAuditPathDto {
+    fn schema(gen: &mut SchemaGenerator) -> Value {
+        gen.reference("MerkleAuditPath", |gen| {
+            let hashable = json!({
+                "oneOf": [
+                    { "type": "object", "properties": { "h": { "type": "string" } }, "required": ["h"] },
+                    Self::schema(gen),
+                    { "type": "object", "properties": { "value": { "type": "string" } }, "required": ["value"] },
+                ],
+            });
+            json!({
+                "type": "object",
+                "properties": { "l": hashable, "r": hashable },
+                "required": ["l"],
+            })
+        })
+    }
+}
+
+/// A [`ProofBundle`], which is serialized as is so that it can be verified offline.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ProofBundleResponse(pub ProofBundle);
+
+impl_success_response!(ProofBundleResponse);
+
+impl ApiSchema for ProofBundleResponse {
+    fn schema(gen: &mut SchemaGenerator) -> Value {
+        gen.reference("ProofBundle", |gen| {
+            object_schema(properties::<ProofBundle>(vec![
+                (ProofKind::schema(gen), true),
+                (MilestonePayloadDto::schema(gen), true),
+                (BlockDto::schema(gen), true),
+                (MerkleAuditPathDto::schema(gen), true),
+                (u32::schema(gen), true),
+                (Vec::<MilestoneKeyRangeDto>::schema(gen), true),
+            ]))
+        })
+    }
+}
+
+impl ApiSchema for ProofKind {
+    fn schema(gen: &mut SchemaGenerator) -> Value {
+        gen.reference("ProofKind", |_| string_enum_schema::<Self>())
+    }
+}
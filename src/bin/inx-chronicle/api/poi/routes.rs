@@ -1,11 +1,10 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{collections::HashSet, str::FromStr};
+use std::str::FromStr;
 
 use axum::{
     extract::{Json, Path},
-    routing::{get, post},
     Extension,
 };
 use chronicle::{
@@ -13,17 +12,17 @@ use chronicle::{
         mongodb::collections::{BlockCollection, ConfigurationUpdateCollection, MilestoneCollection},
         MongoDb,
     },
-    model::{metadata::LedgerInclusionState, node::MilestoneKeyRange, tangle::MilestoneIndex, BlockId},
+    model::{metadata::LedgerInclusionState, BlockId},
+    poi::{CreateProofError, MerkleAuditPath, MerkleProof, ProofBundle, ProofKind},
 };
 
 use super::{
     error as poi,
-    merkle_proof::{MerkleAuditPath, MerkleProof},
-    responses::{CreateProofResponse, ValidateProofResponse},
+    responses::{CreateProofResponse, ProofBundleResponse, ValidateProofResponse},
 };
 use crate::api::{
     error::{CorruptStateError, MissingError, RequestError},
-    router::Router,
+    router::{get, post, Router},
     ApiResult,
 };
 
@@ -34,8 +33,13 @@ pub fn routes() -> Router {
             get(create_proof_for_referenced_blocks),
         )
         .route("/referenced-block/validate", post(validate_proof_for_referenced_blocks))
+        .route(
+            "/referenced-block/bundle/:block_id",
+            get(create_bundle_for_referenced_blocks),
+        )
         .route("/applied-block/create/:block_id", get(create_proof_for_applied_blocks))
         .route("/applied-block/validate", post(validate_proof_for_applied_blocks))
+        .route("/applied-block/bundle/:block_id", get(create_bundle_for_applied_blocks))
 }
 
 async fn create_proof_for_referenced_blocks(
@@ -89,7 +93,7 @@ async fn create_proof_for_referenced_blocks(
     let expected_merkle_root = milestone_payload.essence.inclusion_merkle_root;
     if calculated_merkle_root.as_slice() != expected_merkle_root {
         return Err(CorruptStateError::PoI(poi::CorruptStateError::CreateProof(
-            poi::CreateProofError::MerkleRootMismatch {
+            CreateProofError::MerkleRootMismatch {
                 calculated_merkle_root: prefix_hex::encode(calculated_merkle_root.as_slice()),
                 expected_merkle_root: prefix_hex::encode(expected_merkle_root),
             },
@@ -130,8 +134,9 @@ async fn validate_proof_for_referenced_blocks(
         .ok_or(MissingError::NoResults)?
         .config;
     let public_key_count = node_configuration.milestone_public_key_count as usize;
-    let key_ranges = node_configuration.milestone_key_ranges;
-    let applicable_public_keys = get_valid_public_keys_for_index(key_ranges, milestone_index.into())?;
+    let applicable_public_keys = node_configuration
+        .applicable_public_keys(milestone_index.into())
+        .map_err(|_| CorruptStateError::PoI(poi::CorruptStateError::DecodePublicKey))?;
 
     // Validate the given milestone.
     if let Err(e) = milestone.validate(&applicable_public_keys, public_key_count) {
@@ -194,7 +199,7 @@ async fn create_proof_for_applied_blocks(
     let expected_merkle_root = milestone.essence.applied_merkle_root;
     if calculated_merkle_root.as_slice() != expected_merkle_root {
         return Err(CorruptStateError::PoI(poi::CorruptStateError::CreateProof(
-            poi::CreateProofError::MerkleRootMismatch {
+            CreateProofError::MerkleRootMismatch {
                 calculated_merkle_root: prefix_hex::encode(calculated_merkle_root.as_slice()),
                 expected_merkle_root: prefix_hex::encode(expected_merkle_root),
             },
@@ -235,8 +240,9 @@ async fn validate_proof_for_applied_blocks(
         .ok_or(MissingError::NoResults)?
         .config;
     let public_key_count = node_configuration.milestone_public_key_count as usize;
-    let key_ranges = node_configuration.milestone_key_ranges;
-    let applicable_public_keys = get_valid_public_keys_for_index(key_ranges, milestone_index.into())?;
+    let applicable_public_keys = node_configuration
+        .applicable_public_keys(milestone_index.into())
+        .map_err(|_| CorruptStateError::PoI(poi::CorruptStateError::DecodePublicKey))?;
 
     // Validate the given milestone.
     if let Err(e) = milestone.validate(&applicable_public_keys, public_key_count) {
@@ -249,25 +255,50 @@ async fn validate_proof_for_applied_blocks(
     }
 }
 
-// The returned public keys must be hex strings without the `0x` prefix for the milestone validation to work.
-#[allow(clippy::boxed_local)]
-fn get_valid_public_keys_for_index(
-    mut key_ranges: Box<[MilestoneKeyRange]>,
-    index: MilestoneIndex,
-) -> Result<Vec<String>, CorruptStateError> {
-    key_ranges.sort();
-    let mut public_keys = HashSet::with_capacity(key_ranges.len());
-    for key_range in key_ranges.iter() {
-        match (key_range.start, key_range.end) {
-            (start, _) if start > index => break,
-            (start, end) if index <= end || start == end => {
-                let public_key_raw = prefix_hex::decode::<Vec<_>>(&key_range.public_key)
-                    .map_err(|_| CorruptStateError::PoI(poi::CorruptStateError::DecodePublicKey))?;
-                let public_key_hex = hex::encode(public_key_raw);
-                public_keys.insert(public_key_hex);
-            }
-            (_, _) => continue,
-        }
-    }
-    Ok(public_keys.into_iter().collect::<Vec<_>>())
+async fn create_bundle_for_referenced_blocks(
+    database: Extension<MongoDb>,
+    block_id: Path<String>,
+) -> ApiResult<ProofBundleResponse> {
+    let proof = create_proof_for_referenced_blocks(database.clone(), block_id).await?;
+    create_bundle(&database, ProofKind::Referenced, proof).await
+}
+
+async fn create_bundle_for_applied_blocks(
+    database: Extension<MongoDb>,
+    block_id: Path<String>,
+) -> ApiResult<ProofBundleResponse> {
+    let proof = create_proof_for_applied_blocks(database.clone(), block_id).await?;
+    create_bundle(&database, ProofKind::Applied, proof).await
+}
+
+/// Extends a proof with the milestone public keys that are needed to verify it offline.
+async fn create_bundle(
+    database: &MongoDb,
+    kind: ProofKind,
+    CreateProofResponse {
+        milestone,
+        block,
+        audit_path,
+    }: CreateProofResponse,
+) -> ApiResult<ProofBundleResponse> {
+    let node_configuration = database
+        .collection::<ConfigurationUpdateCollection>()
+        .get_node_configuration_for_ledger_index(milestone.index.into())
+        .await?
+        .ok_or(MissingError::NoResults)?
+        .config;
+
+    Ok(ProofBundleResponse(ProofBundle {
+        kind,
+        milestone,
+        block,
+        audit_path,
+        milestone_public_key_count: node_configuration.milestone_public_key_count,
+        milestone_key_ranges: node_configuration
+            .milestone_key_ranges
+            .into_vec()
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+    }))
 }
@@ -0,0 +1,339 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{ConnectInfo, OriginalUri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use hyper::{header::AUTHORIZATION, Request};
+use regex::Regex;
+
+use super::{
+    auth::validate_jwt,
+    config::{route_to_regex, ApiConfigData, RateLimit, RateLimitConfig, RouteGroup},
+    error::{ConfigError, RateLimitedError},
+};
+
+/// The number of tracked buckets above which full buckets are removed.
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// The minimum interval between two removals of full buckets.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// A token bucket that holds up to `capacity` tokens and is refilled at a constant rate.
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_second: f64,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// Creates a full bucket.
+    pub fn new(capacity: f64, refill_per_second: f64) -> Self {
+        Self {
+            capacity,
+            refill_per_second,
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes `cost` tokens from the bucket. If there are not enough tokens, returns the time after which the request
+    /// can be retried.
+    pub fn try_acquire(&self, cost: f64) -> Result<(), Duration> {
+        self.try_acquire_at(cost, Instant::now())
+    }
+
+    fn try_acquire_at(&self, cost: f64, now: Instant) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap();
+        self.refill(&mut state, now);
+        if state.tokens >= cost {
+            state.tokens -= cost;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((cost - state.tokens) / self.refill_per_second))
+        }
+    }
+
+    /// Whether the bucket is full, in which case it is indistinguishable from a new one.
+    fn is_full_at(&self, now: Instant) -> bool {
+        let mut state = self.state.lock().unwrap();
+        self.refill(&mut state, now);
+        state.tokens >= self.capacity
+    }
+
+    fn refill(&self, state: &mut BucketState, now: Instant) {
+        let elapsed = now.saturating_duration_since(state.updated).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        state.updated = now;
+    }
+}
+
+/// Identifies the client that a request is accounted to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum ClientKey {
+    /// The subject of a valid JWT.
+    Subject(String),
+    /// The IP address of an anonymous client.
+    Ip(IpAddr),
+}
+
+#[derive(Debug)]
+struct Buckets {
+    buckets: HashMap<(RouteGroup, ClientKey), TokenBucket>,
+    last_pruned: Instant,
+}
+
+#[derive(Debug)]
+struct Limiter {
+    config: RateLimitConfig,
+    route_costs: Vec<(Regex, u32)>,
+    buckets: Mutex<Buckets>,
+    throttled: BTreeMap<RouteGroup, AtomicU64>,
+}
+
+/// The statistics of the rate limit of a route group.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RateLimitStats {
+    pub limit: RateLimit,
+    /// The number of requests that were rejected.
+    pub throttled: u64,
+    /// The number of clients whose bucket is not full.
+    pub clients: usize,
+}
+
+/// A snapshot of the statistics of the rate limiter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RateLimitReport {
+    pub enabled: bool,
+    pub groups: BTreeMap<RouteGroup, RateLimitStats>,
+}
+
+/// A shared handle to the rate limiter of the API.
+#[derive(Clone, Debug)]
+pub struct RateLimiter(Arc<Limiter>);
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Result<Self, ConfigError> {
+        let route_costs = config
+            .route_costs
+            .iter()
+            .map(|(route, &cost)| Ok((Regex::new(&route_to_regex(route))?, cost)))
+            .collect::<Result<_, ConfigError>>()?;
+        Ok(Self(Arc::new(Limiter {
+            config,
+            route_costs,
+            buckets: Mutex::new(Buckets {
+                buckets: HashMap::new(),
+                last_pruned: Instant::now(),
+            }),
+            throttled: RouteGroup::ALL
+                .iter()
+                .map(|&group| (group, AtomicU64::new(0)))
+                .collect(),
+        })))
+    }
+
+    /// Gets the number of tokens that a request to the given path costs.
+    fn cost(&self, path: &str) -> u32 {
+        self.0
+            .route_costs
+            .iter()
+            .filter(|(route, _)| route.is_match(path))
+            .map(|&(_, cost)| cost)
+            .max()
+            .unwrap_or(1)
+    }
+
+    /// Takes the cost of a request from the bucket of the client. If the bucket does not hold enough tokens, returns
+    /// the time after which the request can be retried.
+    fn check_at(&self, group: RouteGroup, client: ClientKey, cost: u32, now: Instant) -> Result<(), Duration> {
+        let limit = self.0.config.limit(group);
+        let mut buckets = self.0.buckets.lock().unwrap();
+        if buckets.buckets.len() >= PRUNE_THRESHOLD
+            && now.saturating_duration_since(buckets.last_pruned) >= PRUNE_INTERVAL
+        {
+            buckets.buckets.retain(|_, bucket| !bucket.is_full_at(now));
+            buckets.last_pruned = now;
+        }
+        let res = buckets
+            .buckets
+            .entry((group, client))
+            .or_insert_with(|| TokenBucket::new(limit.burst as f64, limit.per_second as f64))
+            // A request that costs more than the burst could never pass otherwise.
+            .try_acquire_at(cost.min(limit.burst) as f64, now);
+        if res.is_err() {
+            self.0.throttled[&group].fetch_add(1, Ordering::Relaxed);
+        }
+        res
+    }
+
+    /// Returns the current statistics.
+    pub fn report(&self) -> RateLimitReport {
+        let now = Instant::now();
+        let buckets = self.0.buckets.lock().unwrap();
+        RateLimitReport {
+            enabled: self.0.config.enabled,
+            groups: RouteGroup::ALL
+                .iter()
+                .map(|&group| {
+                    (
+                        group,
+                        RateLimitStats {
+                            limit: self.0.config.limit(group),
+                            throttled: self.0.throttled[&group].load(Ordering::Relaxed),
+                            clients: buckets
+                                .buckets
+                                .iter()
+                                .filter(|((g, _), bucket)| *g == group && !bucket.is_full_at(now))
+                                .count(),
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Throttles the requests to the route groups of the API.
+pub async fn rate_limit<B>(req: Request<B>, next: Next<B>) -> Response {
+    if let Some(limiter) = req
+        .extensions()
+        .get::<RateLimiter>()
+        .filter(|limiter| limiter.0.config.enabled)
+    {
+        let path = match req.extensions().get::<OriginalUri>() {
+            Some(OriginalUri(uri)) => uri.path(),
+            None => req.uri().path(),
+        };
+        if let Some(group) = RouteGroup::from_path(path) {
+            let client = client_key(&req, limiter.0.config.trust_forwarded_for);
+            if let Err(retry_after) = limiter.check_at(group, client.clone(), limiter.cost(path), Instant::now()) {
+                tracing::debug!("Throttled request to `{}` by {:?}", path, client);
+                return RateLimitedError { retry_after }.into_response();
+            }
+        }
+    }
+    next.run(req).await
+}
+
+/// Accounts requests with a valid JWT to its subject and all others to the IP address of the client.
+///
+/// The IP address is the peer address of the connection, so behind a reverse proxy all anonymous clients share one
+/// bucket unless `trust_forwarded_for` is set. In that case the last address in the `X-Forwarded-For` header is used,
+/// which is the one that the proxy appended.
+fn client_key<B>(req: &Request<B>, trust_forwarded_for: bool) -> ClientKey {
+    let token = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header| header.strip_prefix("Bearer "));
+    if let (Some(token), Some(config)) = (token, req.extensions().get::<ApiConfigData>()) {
+        if let Ok(claims) = validate_jwt(token, config) {
+            return ClientKey::Subject(claims.claims.sub);
+        }
+    }
+    let forwarded_for = trust_forwarded_for
+        .then(|| req.headers().get(X_FORWARDED_FOR))
+        .flatten()
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header| header.rsplit(',').next())
+        .and_then(|addr| addr.trim().parse().ok());
+    ClientKey::Ip(
+        forwarded_for
+            .or_else(|| {
+                req.extensions()
+                    .get::<ConnectInfo<SocketAddr>>()
+                    .map(|ConnectInfo(addr)| addr.ip())
+            })
+            .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn token_bucket_refills() {
+        let bucket = TokenBucket::new(2.0, 4.0);
+        let start = Instant::now();
+        assert!(bucket.try_acquire_at(1.0, start).is_ok());
+        assert!(bucket.try_acquire_at(1.0, start).is_ok());
+        assert_eq!(bucket.try_acquire_at(1.0, start), Err(Duration::from_millis(250)));
+        assert!(bucket.try_acquire_at(1.0, start + Duration::from_millis(250)).is_ok());
+        // The bucket never holds more than its capacity.
+        let later = start + Duration::from_secs(10);
+        assert!(bucket.try_acquire_at(2.0, later).is_ok());
+        assert!(bucket.try_acquire_at(1.0, later).is_err());
+    }
+
+    #[test]
+    fn rate_limiter_weighs_routes() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            enabled: true,
+            explorer: "1/10".parse().unwrap(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(limiter.cost("/api/explorer/v2/ledger/richest-addresses"), 20);
+        assert_eq!(limiter.cost("/api/explorer/v2/balance/iota1abc"), 2);
+        assert_eq!(limiter.cost("/api/core/v2/info"), 1);
+
+        let now = Instant::now();
+        let alice = ClientKey::Subject("alice".to_string());
+        let bob = ClientKey::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        // An expensive request takes the whole burst.
+        assert!(limiter.check_at(RouteGroup::Explorer, alice.clone(), 20, now).is_ok());
+        assert_eq!(
+            limiter.check_at(RouteGroup::Explorer, alice.clone(), 1, now),
+            Err(Duration::from_secs(1))
+        );
+        // Clients and route groups have separate buckets.
+        assert!(limiter.check_at(RouteGroup::Explorer, bob, 1, now).is_ok());
+        assert!(limiter.check_at(RouteGroup::Core, alice, 1, now).is_ok());
+
+        let report = limiter.report();
+        assert_eq!(report.groups[&RouteGroup::Explorer].throttled, 1);
+        assert_eq!(report.groups[&RouteGroup::Core].throttled, 0);
+    }
+
+    #[test]
+    fn clients_are_identified_by_address() {
+        let mut req = Request::new(());
+        req.extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 8080))));
+        req.headers_mut()
+            .insert(X_FORWARDED_FOR, "203.0.113.1, 198.51.100.2".parse().unwrap());
+        assert_eq!(client_key(&req, false), ClientKey::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+        assert_eq!(
+            client_key(&req, true),
+            ClientKey::Ip(IpAddr::V4(Ipv4Addr::new(198, 51, 100, 2)))
+        );
+        // An invalid header falls back to the address of the connection.
+        req.headers_mut().insert(X_FORWARDED_FOR, "unknown".parse().unwrap());
+        assert_eq!(client_key(&req, true), ClientKey::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+    }
+}
@@ -1,8 +1,21 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use chronicle::model::{node::BaseToken, tangle::MilestoneIndex, ProtocolParameters};
+use iota_types::{
+    api::core::response::BaseTokenResponse,
+    block::{output::dto::RentStructureDto, protocol::dto::ProtocolParametersDto},
+};
 use serde::{Deserialize, Serialize};
 
+use super::{
+    cache::{CacheStats, ReadCacheReport},
+    config::RouteGroup,
+    openapi::api_schema,
+    rate_limit::{RateLimitReport, RateLimitStats},
+};
+use crate::progress::{SyncPhase, SyncReport};
+
 macro_rules! impl_success_response {
     ($($type:ty),*) => {
         $(
@@ -11,16 +24,196 @@ macro_rules! impl_success_response {
                     axum::Json(self).into_response()
                 }
             }
+
+            impl $crate::api::openapi::DocResponse for $type {
+                fn document(
+                    operation: &mut $crate::api::openapi::Operation,
+                    gen: &mut $crate::api::openapi::SchemaGenerator,
+                ) {
+                    <axum::Json<Self> as $crate::api::openapi::DocResponse>::document(operation, gen)
+                }
+            }
         )*
     };
 }
 
 pub(crate) use impl_success_response;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct RoutesResponse {
-    pub routes: Vec<String>,
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RoutesResponse {
+        pub routes: Vec<String>,
+    }
 }
 
 impl_success_response!(RoutesResponse);
+
+api_schema! {
+    /// Response of `GET /sync`.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SyncResponse {
+        pub phase: SyncPhase,
+        pub node_confirmed_index: Option<MilestoneIndex>,
+        pub synced_index: Option<MilestoneIndex>,
+        pub behind_by: Option<u32>,
+        pub milestones_per_second: SyncRatesDto,
+        pub eta_seconds: Option<u64>,
+    }
+}
+
+impl_success_response!(SyncResponse);
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SyncRatesDto {
+        pub one_minute: Option<f64>,
+        pub five_minutes: Option<f64>,
+        pub fifteen_minutes: Option<f64>,
+    }
+}
+
+impl From<SyncReport> for SyncResponse {
+    fn from(report: SyncReport) -> Self {
+        let [one_minute, five_minutes, fifteen_minutes] = report.milestones_per_second;
+        Self {
+            phase: report.phase,
+            node_confirmed_index: report.node_confirmed_index,
+            synced_index: report.synced_index,
+            behind_by: report.behind_by,
+            milestones_per_second: SyncRatesDto {
+                one_minute,
+                five_minutes,
+                fifteen_minutes,
+            },
+            eta_seconds: report.eta.map(|eta| eta.as_secs()),
+        }
+    }
+}
+
+api_schema! {
+    /// Response of `GET /cache`.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CacheResponse {
+        pub blocks: CacheStatsDto,
+        pub milestones: CacheStatsDto,
+        pub outputs: CacheStatsDto,
+    }
+}
+
+impl_success_response!(CacheResponse);
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CacheStatsDto {
+        pub entries: usize,
+        pub capacity: usize,
+        pub hits: u64,
+        pub misses: u64,
+        pub hit_rate: Option<f64>,
+    }
+}
+
+impl From<CacheStats> for CacheStatsDto {
+    fn from(stats: CacheStats) -> Self {
+        Self {
+            entries: stats.entries,
+            capacity: stats.capacity,
+            hits: stats.hits,
+            misses: stats.misses,
+            hit_rate: stats.hit_rate(),
+        }
+    }
+}
+
+impl From<ReadCacheReport> for CacheResponse {
+    fn from(report: ReadCacheReport) -> Self {
+        Self {
+            blocks: report.blocks.into(),
+            milestones: report.milestones.into(),
+            outputs: report.outputs.into(),
+        }
+    }
+}
+
+api_schema! {
+    /// Response of `GET /rate-limits`.
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RateLimitsResponse {
+        pub enabled: bool,
+        pub core: RateLimitStatsDto,
+        pub explorer: RateLimitStatsDto,
+        pub indexer: RateLimitStatsDto,
+        pub poi: RateLimitStatsDto,
+    }
+}
+
+impl_success_response!(RateLimitsResponse);
+
+api_schema! {
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct RateLimitStatsDto {
+        pub per_second: u32,
+        pub burst: u32,
+        pub throttled: u64,
+        pub clients: usize,
+    }
+}
+
+impl From<RateLimitStats> for RateLimitStatsDto {
+    fn from(stats: RateLimitStats) -> Self {
+        Self {
+            per_second: stats.limit.per_second,
+            burst: stats.limit.burst,
+            throttled: stats.throttled,
+            clients: stats.clients,
+        }
+    }
+}
+
+impl From<RateLimitReport> for RateLimitsResponse {
+    fn from(report: RateLimitReport) -> Self {
+        Self {
+            enabled: report.enabled,
+            core: report.groups[&RouteGroup::Core].into(),
+            explorer: report.groups[&RouteGroup::Explorer].into(),
+            indexer: report.groups[&RouteGroup::Indexer].into(),
+            poi: report.groups[&RouteGroup::Poi].into(),
+        }
+    }
+}
+
+/// Converts the stored protocol parameters into the representation of the core REST API of the node.
+pub(crate) fn protocol_parameters_dto(protocol: ProtocolParameters) -> ProtocolParametersDto {
+    ProtocolParametersDto {
+        protocol_version: protocol.version,
+        network_name: protocol.network_name,
+        below_max_depth: protocol.below_max_depth,
+        bech32_hrp: protocol.bech32_hrp,
+        min_pow_score: protocol.min_pow_score,
+        rent_structure: RentStructureDto {
+            v_byte_cost: protocol.rent_structure.v_byte_cost,
+            v_byte_factor_data: protocol.rent_structure.v_byte_factor_data,
+            v_byte_factor_key: protocol.rent_structure.v_byte_factor_key,
+        },
+        token_supply: protocol.token_supply.to_string(),
+    }
+}
+
+/// Converts the stored base token into the representation of the core REST API of the node.
+pub(crate) fn base_token_dto(base_token: BaseToken) -> BaseTokenResponse {
+    BaseTokenResponse {
+        name: base_token.name,
+        ticker_symbol: base_token.ticker_symbol,
+        decimals: base_token.decimals as u8,
+        unit: base_token.unit,
+        subunit: Some(base_token.subunit),
+        use_metric_prefix: base_token.use_metric_prefix,
+    }
+}
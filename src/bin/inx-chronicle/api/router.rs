@@ -4,9 +4,10 @@
 //! This `Router` wraps the functionality we use from [`axum::Router`] and tracks the string routes
 //! as they are added in a tree node structure. The reason for this ugliness is to provide a routes
 //! endpoint which can output a list of unique routes at any depth level. The most critical part of
-//! this is the [`Router::into_make_service()`] function, which adds an [`Extension`] containing the
-//! root [`RouteNode`]. These routes can also be filtered using a [`RegexSet`] to allow the exclusion
-//! of unauthorized routes.
+//! this is the [`Router::into_make_service_with_connect_info()`] function, which adds an [`Extension`]
+//! containing the root [`RouteNode`]. These routes can also be filtered using a [`RegexSet`] to allow
+//! the exclusion of unauthorized routes. Every route also carries the documentation of its operations, from which
+//! the OpenAPI specification is generated.
 
 use std::{
     collections::{BTreeMap, BTreeSet},
@@ -15,22 +16,28 @@ use std::{
 
 use axum::{
     body::{Bytes, HttpBody},
+    extract::connect_info::IntoMakeServiceWithConnectInfo,
+    handler::Handler,
     response::Response,
-    routing::{future::RouteFuture, IntoMakeService, Route},
+    routing::{future::RouteFuture, Route},
     BoxError, Extension,
 };
 use hyper::{Body, Request};
 use regex::RegexSet;
 use tower::{Layer, Service};
 
+use super::openapi::{DocHandler, OperationFn};
+
 #[derive(Clone, Debug, Default)]
 pub struct RouteNode {
     children: BTreeMap<String, RouteNode>,
+    operations: BTreeMap<&'static str, OperationFn>,
 }
 
 impl RouteNode {
     fn merge(&mut self, other: RouteNode) {
         use std::collections::btree_map::Entry::*;
+        self.operations.extend(other.operations);
         for (name, child) in other.children {
             match self.children.entry(name) {
                 Occupied(mut o) => o.get_mut().merge(child),
@@ -75,6 +82,66 @@ impl RouteNode {
             parents.pop();
         }
     }
+
+    /// Lists the operations of every route by their method.
+    pub fn operations(&self) -> Vec<(String, &BTreeMap<&'static str, OperationFn>)> {
+        let mut operations = Vec::new();
+        self.operations_recursive(&mut Vec::new(), &mut operations);
+        operations
+    }
+
+    fn operations_recursive<'a>(
+        &'a self,
+        parents: &mut Vec<&'a str>,
+        operations: &mut Vec<(String, &'a BTreeMap<&'static str, OperationFn>)>,
+    ) {
+        if self.children.is_empty() {
+            let route = parents.join("");
+            // Nested routes at `/` are served without the trailing slash.
+            let route = match route.strip_suffix('/') {
+                Some(route) if !route.is_empty() => route.to_string(),
+                _ => route,
+            };
+            operations.push((route, &self.operations));
+        }
+        for (name, child) in self.children.iter() {
+            parents.push(name);
+            child.operations_recursive(parents, operations);
+            parents.pop();
+        }
+    }
+}
+
+/// A [`MethodRouter`](axum::routing::MethodRouter) that documents the operations of its handlers.
+pub struct MethodRouter<B = Body> {
+    inner: axum::routing::MethodRouter<B>,
+    operations: BTreeMap<&'static str, OperationFn>,
+}
+
+/// Routes `GET` requests to the given handler.
+pub fn get<H, T, B>(handler: H) -> MethodRouter<B>
+where
+    H: Handler<T, B> + DocHandler<T>,
+    T: 'static,
+    B: Send + 'static,
+{
+    MethodRouter {
+        inner: axum::routing::get(handler),
+        operations: [("get", H::operation as OperationFn)].into_iter().collect(),
+    }
+}
+
+/// Routes `POST` requests to the given handler.
+pub fn post<H, T, B>(handler: H) -> MethodRouter<B>
+where
+    H: Handler<T, B> + DocHandler<T>,
+    T: 'static,
+    B: Send + 'static,
+{
+    MethodRouter {
+        inner: axum::routing::post(handler),
+        operations: [("post", H::operation as OperationFn)].into_iter().collect(),
+    }
 }
 
 #[derive(Debug)]
@@ -112,14 +179,15 @@ where
         }
     }
 
-    pub fn route<T>(mut self, path: &str, service: T) -> Self
-    where
-        T: Service<Request<B>, Response = Response, Error = Infallible> + Clone + Send + 'static,
-        T::Future: Send + 'static,
-    {
-        self.root.children.entry(path.to_string()).or_default();
+    pub fn route(mut self, path: &str, method_router: MethodRouter<B>) -> Self {
+        self.root
+            .children
+            .entry(path.to_string())
+            .or_default()
+            .operations
+            .extend(method_router.operations);
         Self {
-            inner: self.inner.route(path, service),
+            inner: self.inner.route(path, method_router.inner),
             root: self.root,
         }
     }
@@ -189,8 +257,15 @@ where
         }
     }
 
-    pub fn into_make_service(self) -> IntoMakeService<axum::Router<B>> {
-        self.inner.layer(Extension(self.root)).into_make_service()
+    #[cfg(test)]
+    pub fn root(&self) -> &RouteNode {
+        &self.root
+    }
+
+    pub fn into_make_service_with_connect_info<C>(self) -> IntoMakeServiceWithConnectInfo<axum::Router<B>, C> {
+        self.inner
+            .layer(Extension(self.root))
+            .into_make_service_with_connect_info()
     }
 }
 
@@ -1,91 +1,121 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use auth_helper::jwt::{BuildValidation, Claims, JsonWebToken, Validation};
 use axum::{
     handler::Handler,
     headers::{authorization::Bearer, Authorization},
     http::HeaderValue,
-    middleware::from_extractor,
-    routing::{get, post},
+    middleware::{from_extractor, from_fn},
     Extension, Json, TypedHeader,
 };
 use chronicle::{
-    db::{mongodb::collections::MilestoneCollection, MongoDb},
+    db::{ApiKeyStorage, Storage},
     model::tangle::MilestoneTimestamp,
 };
 use hyper::StatusCode;
 use regex::RegexSet;
 use serde::Deserialize;
+use serde_json::Value;
 use time::{Duration, OffsetDateTime};
 
 use super::{
-    auth::Auth,
+    auth::{validate_jwt, ApiClaims, Auth, Scope},
+    cache::ReadCache,
     config::ApiConfigData,
     error::{ApiError, MissingError, UnimplementedError},
     extractors::ListRoutesQuery,
-    responses::RoutesResponse,
-    router::{RouteNode, Router},
-    ApiResult, AuthError,
+    openapi::{self, api_schema},
+    rate_limit::{rate_limit, RateLimiter},
+    responses::{CacheResponse, RateLimitsResponse, RoutesResponse, SyncResponse},
+    router::{get, post, RouteNode, Router},
+    ApiResult, ApiStorage, AuthError,
 };
+use crate::progress::SyncProgress;
 
 pub(crate) static BYTE_CONTENT_HEADER: HeaderValue = HeaderValue::from_static("application/vnd.iota.serializer-v1");
 
-const ALWAYS_AVAILABLE_ROUTES: &[&str] = &["/health", "/login", "/routes"];
+const ALWAYS_AVAILABLE_ROUTES: &[&str] = &["/api/openapi.json", "/health", "/login", "/routes", "/sync"];
 
 // Similar to Hornet, we enforce that the latest known milestone is newer than 5 minutes. This should give Chronicle
 // sufficient time to catch up with the node that it is connected too. The current milestone interval is 5 seconds.
 const STALE_MILESTONE_DURATION: Duration = Duration::minutes(5);
 
-pub fn routes() -> Router {
-    #[allow(unused_mut)]
-    let mut router = Router::new()
-        .nest("/core/v2", super::core::routes())
-        .nest("/explorer/v2", super::explorer::routes())
-        .nest("/indexer/v1", super::indexer::routes());
-
-    #[cfg(feature = "poi")]
-    {
-        router = router.nest("/poi/v1", super::poi::routes());
-    }
+pub fn routes<S: ApiStorage>(config: &ApiConfigData) -> Router {
+    let router = S::extend_routes(Router::new().nest("/core/v2", super::core::routes::<S>()), config);
 
     Router::new()
-        .route("/health", get(health))
-        .route("/login", post(login))
+        // The statistics of the application require a token, and the layer only applies to the routes above it.
+        .route("/cache", get(cache))
+        .route("/rate-limits", get(rate_limits))
+        .route_layer(from_extractor::<Auth<S>>())
+        .route("/health", get(health::<S>))
+        .route("/login", post(login::<S>))
         .route("/routes", get(list_routes))
-        .nest("/api", router.route_layer(from_extractor::<Auth>()))
+        .route("/sync", get(sync))
+        .route("/api/openapi.json", get(openapi_spec))
+        // Throttling happens before authentication, so that rejected requests are cheap.
+        .nest(
+            "/api",
+            router
+                .route_layer(from_extractor::<Auth<S>>())
+                .route_layer(from_fn(rate_limit)),
+        )
         .fallback(not_found.into_service())
 }
 
-#[derive(Deserialize)]
-struct LoginInfo {
-    password: String,
+api_schema! {
+    #[derive(Deserialize)]
+    struct LoginInfo {
+        /// The name of an API key. The password is checked against the shared password if it is missing.
+        name: Option<String>,
+        password: String,
+    }
 }
 
-async fn login(
-    Json(LoginInfo { password }): Json<LoginInfo>,
+async fn login<S: ApiKeyStorage>(
+    Json(LoginInfo { name, password }): Json<LoginInfo>,
     Extension(config): Extension<ApiConfigData>,
+    Extension(database): Extension<S>,
 ) -> ApiResult<String> {
-    if password_verify(
-        password.as_bytes(),
-        config.jwt_password_salt.as_bytes(),
-        &config.jwt_password_hash,
-        Into::into(&config.jwt_argon_config),
-    )? {
-        let jwt = JsonWebToken::new(
-            Claims::new(
-                ApiConfigData::ISSUER,
+    let claims = match name {
+        // The shared password grants full access.
+        None => {
+            if !password_verify(
+                password.as_bytes(),
+                config.jwt_password_salt.as_bytes(),
+                &config.jwt_password_hash,
+                Into::into(&config.jwt_argon_config),
+            )? {
+                return Err(ApiError::from(AuthError::IncorrectPassword));
+            }
+            ApiClaims::new(
                 uuid::Uuid::new_v4().to_string(),
-                ApiConfigData::AUDIENCE,
+                [Scope::Admin].into_iter().collect(),
+                None,
+                &config,
             )?
-            .expires_after_duration(config.jwt_expiration)?,
-            config.jwt_secret_key.as_ref(),
-        )?;
+        }
+        Some(name) => {
+            let key = database
+                .get_api_key(&name)
+                .await?
+                .filter(|key| key.revoked_at.is_none());
+            // The password is hashed even if the key does not exist, so that the response time does not reveal which
+            // key names exist.
+            let (salt, hash) = match &key {
+                Some(key) => (key.salt.as_bytes(), key.key_hash.as_slice()),
+                None => (config.jwt_password_salt.as_bytes(), config.jwt_password_hash.as_slice()),
+            };
+            let verified = password_verify(password.as_bytes(), salt, hash, Into::into(&config.jwt_argon_config))?;
+            let key = key.filter(|_| verified).ok_or(AuthError::IncorrectPassword)?;
+            // Scopes that are unknown to this version are not granted.
+            let scopes = key.scopes.iter().filter_map(|scope| scope.parse().ok()).collect();
+            ApiClaims::new(name.clone(), scopes, Some(name), &config)?
+        }
+    };
 
-        Ok(format!("Bearer {jwt}"))
-    } else {
-        Err(ApiError::from(AuthError::IncorrectPassword))
-    }
+    let jwt = claims.encode(&config)?;
+    Ok(format!("Bearer {jwt}"))
 }
 
 /// Verifies if a password/salt pair matches a password hash.
@@ -112,16 +142,7 @@ async fn list_routes(
 ) -> ApiResult<RoutesResponse> {
     let depth = depth.or(Some(3));
     let routes = if let Some(TypedHeader(Authorization(bearer))) = bearer_header {
-        let jwt = JsonWebToken(bearer.token().to_string());
-
-        jwt.validate(
-            Validation::default()
-                .with_issuer(ApiConfigData::ISSUER)
-                .with_audience(ApiConfigData::AUDIENCE)
-                .validate_nbf(true),
-            config.jwt_secret_key.as_ref(),
-        )
-        .map_err(AuthError::InvalidJwt)?;
+        validate_jwt(bearer.token(), &config)?;
 
         root.list_routes(None, depth)
     } else {
@@ -137,13 +158,9 @@ async fn list_routes(
     Ok(RoutesResponse { routes })
 }
 
-pub async fn is_healthy(database: &MongoDb) -> ApiResult<bool> {
+pub async fn is_healthy(database: &impl Storage) -> ApiResult<bool> {
     {
-        let newest = match database
-            .collection::<MilestoneCollection>()
-            .get_newest_milestone()
-            .await?
-        {
+        let newest = match database.get_newest_milestone().await? {
             Some(last) => last,
             None => return Ok(false),
         };
@@ -156,7 +173,7 @@ pub async fn is_healthy(database: &MongoDb) -> ApiResult<bool> {
     Ok(true)
 }
 
-pub async fn health(database: Extension<MongoDb>) -> StatusCode {
+pub async fn health<S: Storage>(Extension(database): Extension<S>) -> StatusCode {
     let handle_error = |ApiError { error, .. }| {
         tracing::error!("An error occured during health check: {error}");
         false
@@ -169,6 +186,23 @@ pub async fn health(database: Extension<MongoDb>) -> StatusCode {
     }
 }
 
+pub async fn sync(Extension(sync_progress): Extension<SyncProgress>) -> SyncResponse {
+    sync_progress.report().into()
+}
+
+pub async fn cache(Extension(read_cache): Extension<ReadCache>) -> CacheResponse {
+    read_cache.report().into()
+}
+
+pub async fn rate_limits(Extension(rate_limiter): Extension<RateLimiter>) -> RateLimitsResponse {
+    rate_limiter.report().into()
+}
+
+/// Serves the OpenAPI specification of all routes.
+pub async fn openapi_spec(Extension(root): Extension<RouteNode>) -> Json<Value> {
+    Json(openapi::spec(&root))
+}
+
 pub async fn not_found() -> MissingError {
     MissingError::NotFound
 }
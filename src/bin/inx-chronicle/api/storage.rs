@@ -0,0 +1,43 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#[cfg(feature = "kv")]
+use chronicle::db::KvDb;
+use chronicle::db::{MongoDb, Storage};
+
+use super::{
+    config::{ApiConfigData, ApiModule},
+    router::Router,
+};
+
+/// A storage backend that can be served by the API.
+///
+/// The core API is available for every backend. The explorer, indexer and proof-of-inclusion APIs rely on the
+/// aggregation capabilities of MongoDB and are therefore only served by that backend.
+pub trait ApiStorage: Storage {
+    /// Adds the routes that are specific to this backend.
+    fn extend_routes(router: Router, _config: &ApiConfigData) -> Router {
+        router
+    }
+}
+
+impl ApiStorage for MongoDb {
+    fn extend_routes(mut router: Router, config: &ApiConfigData) -> Router {
+        if config.is_module_enabled(ApiModule::Explorer) {
+            router = router.nest("/explorer/v2", super::explorer::routes());
+        }
+        if config.is_module_enabled(ApiModule::Indexer) {
+            router = router.nest("/indexer/v1", super::indexer::routes());
+        }
+
+        #[cfg(feature = "poi")]
+        if config.is_module_enabled(ApiModule::Poi) {
+            router = router.nest("/poi/v1", super::poi::routes());
+        }
+
+        router
+    }
+}
+
+#[cfg(feature = "kv")]
+impl ApiStorage for KvDb {}
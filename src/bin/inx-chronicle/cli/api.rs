@@ -2,9 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use api::ApiConfig;
-use clap::{Args, Parser};
+use chronicle::db::{ApiKeyStorage, MongoDb};
+use clap::{Args, Parser, Subcommand};
 
-use crate::api::config as api;
+use crate::{
+    api::{config as api, Scope},
+    config::{ChronicleConfig, StorageKind},
+};
 
 #[derive(Args, Debug)]
 pub struct ApiArgs {
@@ -23,9 +27,29 @@ pub struct ApiArgs {
     /// JWT arguments.
     #[command(flatten)]
     pub jwt: JwtArgs,
+    /// The directory that snapshots created via the API are written to. Snapshot creation is disabled if unset.
+    #[arg(long, value_name = "DIR", env = "SNAPSHOT_DIR", default_value = None)]
+    pub snapshot_dir: Option<String>,
     /// Disable REST API.
     #[arg(long, default_value_t = !api::DEFAULT_ENABLED)]
     pub disable_api: bool,
+    /// Disable a module of the REST API. The indexes that are only used by disabled modules are not built.
+    #[arg(long = "disable-api-module", value_name = "MODULE", value_enum)]
+    pub disabled_modules: Vec<api::ApiModule>,
+    /// The number of blocks, milestones and outputs that are each kept in the read cache. Set to 0 to disable it.
+    #[arg(long, value_name = "ENTRIES", default_value_t = api::DEFAULT_CACHE_CAPACITY)]
+    pub api_cache_capacity: usize,
+    /// Rate limiting arguments.
+    #[command(flatten)]
+    pub rate_limit: RateLimitArgs,
+    /// Disable forwarding block submission and tip requests to the node via INX.
+    #[cfg(feature = "inx")]
+    #[arg(long, default_value_t = !api::DEFAULT_NODE_FORWARDING_ENABLED)]
+    pub disable_node_forwarding: bool,
+    /// The number of requests per second that are forwarded to the node. Set to 0 to disable the limit.
+    #[cfg(feature = "inx")]
+    #[arg(long, value_name = "REQUESTS", default_value_t = api::DEFAULT_NODE_FORWARDING_RATE_LIMIT)]
+    pub node_forwarding_rate_limit: u32,
 }
 
 impl From<&ApiArgs> for api::ApiConfig {
@@ -40,10 +64,67 @@ impl From<&ApiArgs> for api::ApiConfig {
             jwt_expiration: value.jwt.jwt_expiration,
             max_page_size: value.max_page_size,
             public_routes: value.public_routes.clone(),
+            snapshot_dir: value.snapshot_dir.clone(),
+            disabled_modules: value.disabled_modules.clone(),
+            cache_capacity: value.api_cache_capacity,
+            rate_limit: (&value.rate_limit).into(),
+            #[cfg(feature = "inx")]
+            node_forwarding: !value.disable_node_forwarding,
+            #[cfg(feature = "inx")]
+            node_forwarding_rate_limit: value.node_forwarding_rate_limit,
         }
     }
 }
 
+#[derive(Args, Debug)]
+pub struct RateLimitArgs {
+    /// Enable per-client rate limiting of the API route groups.
+    #[arg(long = "rate-limit", default_value_t = api::DEFAULT_RATE_LIMIT_ENABLED)]
+    pub enabled: bool,
+    /// The rate limit of the core API.
+    #[arg(long, value_name = "PER_SECOND[/BURST]", default_value = api::DEFAULT_RATE_LIMIT_CORE)]
+    pub rate_limit_core: api::RateLimit,
+    /// The rate limit of the explorer API.
+    #[arg(long, value_name = "PER_SECOND[/BURST]", default_value = api::DEFAULT_RATE_LIMIT_EXPLORER)]
+    pub rate_limit_explorer: api::RateLimit,
+    /// The rate limit of the indexer API.
+    #[arg(long, value_name = "PER_SECOND[/BURST]", default_value = api::DEFAULT_RATE_LIMIT_INDEXER)]
+    pub rate_limit_indexer: api::RateLimit,
+    /// The rate limit of the PoI API.
+    #[arg(long, value_name = "PER_SECOND[/BURST]", default_value = api::DEFAULT_RATE_LIMIT_POI)]
+    pub rate_limit_poi: api::RateLimit,
+    /// The number of tokens that a request to a route costs. Overrides or extends the default costs.
+    #[arg(long = "route-cost", value_name = "ROUTE=COST", value_parser = parse_route_cost)]
+    pub route_costs: Vec<(String, u32)>,
+    /// Identify anonymous clients by the `X-Forwarded-For` header. Only enable this behind a reverse proxy.
+    #[arg(long, default_value_t = api::DEFAULT_RATE_LIMIT_TRUST_FORWARDED_FOR)]
+    pub rate_limit_trust_forwarded_for: bool,
+}
+
+impl From<&RateLimitArgs> for api::RateLimitConfig {
+    fn from(value: &RateLimitArgs) -> Self {
+        let mut route_costs = Self::default().route_costs;
+        route_costs.extend(value.route_costs.iter().cloned());
+        Self {
+            enabled: value.enabled,
+            core: value.rate_limit_core,
+            explorer: value.rate_limit_explorer,
+            indexer: value.rate_limit_indexer,
+            poi: value.rate_limit_poi,
+            route_costs,
+            trust_forwarded_for: value.rate_limit_trust_forwarded_for,
+        }
+    }
+}
+
+fn parse_route_cost(arg: &str) -> Result<(String, u32), String> {
+    let (route, cost) = arg
+        .rsplit_once('=')
+        .ok_or_else(|| format!("expected `ROUTE=COST`, found `{}`", arg))?;
+    let cost = cost.parse().map_err(|e| format!("invalid cost `{}`: {}", cost, e))?;
+    Ok((route.to_string(), cost))
+}
+
 #[derive(Args, Debug)]
 pub struct JwtArgs {
     /// The location of the identity file for JWT auth.
@@ -66,23 +147,24 @@ fn parse_duration(arg: &str) -> Result<std::time::Duration, humantime::DurationE
 
 /// Generate a JWT token using the available config.
 #[derive(Clone, Debug, PartialEq, Eq, Parser)]
-pub struct GenerateJWTCommand;
+pub struct GenerateJWTCommand {
+    /// The scopes that are granted to the token.
+    #[arg(long = "scope", value_name = "SCOPE", value_enum, default_value = "admin")]
+    scopes: Vec<Scope>,
+}
 
 impl GenerateJWTCommand {
     pub fn handle(&self, config: &ApiConfig) -> eyre::Result<()> {
-        use crate::api::ApiConfigData;
+        use crate::api::{ApiClaims, ApiConfigData};
         let api_data = ApiConfigData::try_from(config.clone()).expect("invalid API config");
-        let claims = auth_helper::jwt::Claims::new(
-            ApiConfigData::ISSUER,
+        let claims = ApiClaims::new(
             uuid::Uuid::new_v4().to_string(),
-            ApiConfigData::AUDIENCE,
-        )
-        .unwrap() // Panic: Cannot fail.
-        .expires_after_duration(api_data.jwt_expiration)
-        .map_err(crate::api::AuthError::InvalidJwt)?;
-        let exp_ts = time::OffsetDateTime::from_unix_timestamp(claims.exp.unwrap() as _).unwrap();
-        let jwt = auth_helper::jwt::JsonWebToken::new(claims, api_data.jwt_secret_key.as_ref())
-            .map_err(crate::api::AuthError::InvalidJwt)?;
+            self.scopes.iter().copied().collect(),
+            None,
+            &api_data,
+        )?;
+        let exp_ts = time::OffsetDateTime::from_unix_timestamp(claims.claims.exp.unwrap() as _).unwrap();
+        let jwt = claims.encode(&api_data)?;
         tracing::info!("Bearer {}", jwt);
         tracing::info!(
             "Expires: {} ({})",
@@ -92,3 +174,84 @@ impl GenerateJWTCommand {
         Ok(())
     }
 }
+
+/// Create, list or revoke the named keys that can log in to the API.
+#[derive(Clone, Debug, PartialEq, Eq, Args)]
+pub struct ApiKeyCommand {
+    #[command(subcommand)]
+    action: ApiKeyAction,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Subcommand)]
+enum ApiKeyAction {
+    /// Create a key and print its secret. The secret cannot be retrieved later.
+    Create {
+        /// The unique name of the key.
+        name: String,
+        /// The scopes that are granted to the tokens of the key.
+        #[arg(long = "scope", value_name = "SCOPE", value_enum, required = true)]
+        scopes: Vec<Scope>,
+    },
+    /// List all keys.
+    List,
+    /// Revoke a key. The tokens that were issued for it are rejected from then on.
+    Revoke {
+        /// The name of the key.
+        name: String,
+    },
+}
+
+impl ApiKeyCommand {
+    pub async fn handle(&self, config: &ChronicleConfig) -> eyre::Result<()> {
+        match config.storage {
+            StorageKind::MongoDb => {
+                tracing::info!("Connecting to database using hosts: `{}`.", config.mongodb.hosts_str()?);
+                self.run(&MongoDb::connect(&config.mongodb).await?).await
+            }
+            #[cfg(feature = "kv")]
+            StorageKind::Kv => self.run(&chronicle::db::KvDb::open(&config.kv)?).await,
+        }
+    }
+
+    async fn run(&self, db: &impl ApiKeyStorage) -> eyre::Result<()> {
+        match &self.action {
+            ApiKeyAction::Create { name, scopes } => {
+                let (key, secret) = crate::api::create_api_key(name.clone(), &scopes.iter().copied().collect())?;
+                if !db.insert_api_key(&key).await? {
+                    eyre::bail!("An API key named `{}` already exists.", name);
+                }
+                tracing::info!("Created API key `{}` with scopes {}.", name, key.scopes.join(", "));
+                tracing::info!("Secret: {}", secret);
+            }
+            ApiKeyAction::List => {
+                for key in db.get_api_keys().await? {
+                    let status = match key.revoked_at {
+                        Some(revoked_at) => format!("revoked at {}", format_timestamp(revoked_at)),
+                        None => "active".to_string(),
+                    };
+                    tracing::info!(
+                        "`{}`: {} (created at {}, {})",
+                        key.name,
+                        key.scopes.join(", "),
+                        format_timestamp(key.created_at),
+                        status
+                    );
+                }
+            }
+            ApiKeyAction::Revoke { name } => {
+                let now = time::OffsetDateTime::now_utc().unix_timestamp() as u64;
+                if !db.revoke_api_key(name, now).await? {
+                    eyre::bail!("There is no active API key named `{}`.", name);
+                }
+                tracing::info!("Revoked API key `{}`.", name);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn format_timestamp(timestamp: u64) -> String {
+    time::OffsetDateTime::from_unix_timestamp(timestamp as i64)
+        .map(|ts| ts.to_string())
+        .unwrap_or_else(|_| timestamp.to_string())
+}
@@ -0,0 +1,95 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use bytesize::ByteSize;
+use chronicle::db::{
+    mongodb::{
+        collections::{BlockCollection, LedgerUpdateCollection, MilestoneCollection, OutputCollection},
+        IndexProfile, IndexStatus,
+    },
+    MongoDb,
+};
+use clap::{Args, Subcommand};
+
+use crate::config::ChronicleConfig;
+
+#[derive(Clone, Debug, PartialEq, Eq, Args)]
+pub struct IndexesCommand {
+    #[command(subcommand)]
+    action: Option<IndexesAction>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Subcommand)]
+enum IndexesAction {
+    /// Report which indexes are present, missing, unused or unknown per collection (default).
+    Status,
+    /// Create the indexes that are required by the profile.
+    Create,
+    /// Drop the indexes that Chronicle defines but the profile does not require. Unknown indexes are kept.
+    Drop,
+}
+
+impl IndexesCommand {
+    pub async fn handle(&self, config: &ChronicleConfig) -> eyre::Result<()> {
+        tracing::info!("Connecting to database using hosts: `{}`.", config.mongodb.hosts_str()?);
+        let db = MongoDb::connect(&config.mongodb).await?;
+        let profile = config.index_profile();
+        tracing::info!("Using index profile {:?}.", profile.groups());
+
+        match self.action.as_ref().unwrap_or(&IndexesAction::Status) {
+            IndexesAction::Status => {
+                for status in index_status(&db, &profile).await? {
+                    report(&status);
+                }
+            }
+            IndexesAction::Create => {
+                crate::build_indexes(&db, &profile).await?;
+                tracing::info!("Indexes created successfully.");
+            }
+            IndexesAction::Drop => {
+                let mut dropped = db.drop_unused_indexes::<OutputCollection>(&profile).await?;
+                dropped.extend(db.drop_unused_indexes::<BlockCollection>(&profile).await?);
+                dropped.extend(db.drop_unused_indexes::<LedgerUpdateCollection>(&profile).await?);
+                dropped.extend(db.drop_unused_indexes::<MilestoneCollection>(&profile).await?);
+                for name in &dropped {
+                    tracing::info!("Dropped index `{}`.", name);
+                }
+                tracing::info!("Dropped {} unused indexes.", dropped.len());
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn index_status(db: &MongoDb, profile: &IndexProfile) -> eyre::Result<Vec<IndexStatus>> {
+    Ok(vec![
+        db.index_status::<OutputCollection>(profile).await?,
+        db.index_status::<BlockCollection>(profile).await?,
+        db.index_status::<LedgerUpdateCollection>(profile).await?,
+        db.index_status::<MilestoneCollection>(profile).await?,
+    ])
+}
+
+fn report(status: &IndexStatus) {
+    tracing::info!(
+        "{}: {} present, {} missing, {} unused, {} unknown ({} in total)",
+        status.collection,
+        status.present.len(),
+        status.missing.len(),
+        status.unused.len(),
+        status.unknown.len(),
+        ByteSize::b(status.total_size()),
+    );
+    for info in &status.present {
+        tracing::info!("  present  {} ({})", info.name, ByteSize::b(info.size));
+    }
+    for name in &status.missing {
+        tracing::info!("  missing  {}", name);
+    }
+    for info in &status.unused {
+        tracing::info!("  unused   {} ({})", info.name, ByteSize::b(info.size));
+    }
+    for info in &status.unknown {
+        tracing::info!("  unknown  {} ({})", info.name, ByteSize::b(info.size));
+    }
+}
@@ -1,6 +1,10 @@
 // Copyright 2023 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use chronicle::{
+    model::utxo::{Address, Tag},
+    tangle::{IngestionFilter, PayloadKind},
+};
 use clap::Args;
 
 use crate::inx::config as inx;
@@ -14,6 +18,25 @@ pub struct InxArgs {
     /// genesis block. If set to `0` Chronicle will start syncing from the most recent milestone it received.
     #[arg(long, value_name = "START", default_value_t = inx::DEFAULT_SYNC_START)]
     pub inx_sync_start: u32,
+    /// The full snapshot file that is used to bootstrap an empty database. Synchronization continues via INX from the
+    /// ledger index of the snapshot.
+    #[arg(long, value_name = "FILEPATH", env = "INX_BOOTSTRAP_SNAPSHOT", default_value = None)]
+    pub inx_bootstrap_snapshot: Option<String>,
+    /// Verify the signatures of milestones against the node's milestone key ranges before storing them.
+    #[arg(long, value_name = "MODE", value_enum, default_value_t = inx::DEFAULT_MILESTONE_VERIFICATION)]
+    pub inx_milestone_verification: inx::MilestoneVerification,
+    /// Only store blocks with a tagged data payload whose tag starts with this `0x`-prefixed hex prefix. Can be given
+    /// multiple times. If any ingestion filter is set, only blocks that match at least one of them are stored, while
+    /// the ledger and milestones are always stored in full.
+    #[arg(long = "inx-filter-tag-prefix", value_name = "HEX")]
+    pub inx_filter_tag_prefixes: Vec<Tag>,
+    /// Only store blocks with a transaction that consumes or creates an output owned by this Bech32 address. Can be
+    /// given multiple times.
+    #[arg(long = "inx-filter-address", value_name = "BECH32")]
+    pub inx_filter_addresses: Vec<Address>,
+    /// Only store blocks with this kind of payload. Can be given multiple times.
+    #[arg(long = "inx-filter-payload-kind", value_name = "KIND", value_enum)]
+    pub inx_filter_payload_kinds: Vec<PayloadKindChoice>,
     /// Disable the INX synchronization workflow.
     #[arg(long, default_value_t = !inx::DEFAULT_ENABLED)]
     pub disable_inx: bool,
@@ -25,6 +48,44 @@ impl From<&InxArgs> for inx::InxConfig {
             enabled: !value.disable_inx,
             url: value.inx_url.clone(),
             sync_start_milestone: value.inx_sync_start.into(),
+            bootstrap_snapshot: value.inx_bootstrap_snapshot.clone(),
+            milestone_verification: value.inx_milestone_verification,
+            ingestion_filter: IngestionFilter {
+                tag_prefixes: value.inx_filter_tag_prefixes.clone(),
+                addresses: value.inx_filter_addresses.clone(),
+                payload_kinds: value
+                    .inx_filter_payload_kinds
+                    .iter()
+                    .copied()
+                    .map(PayloadKind::from)
+                    .collect(),
+            },
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum PayloadKindChoice {
+    /// A block without a payload.
+    NoPayload,
+    /// A transaction payload.
+    Transaction,
+    /// A milestone payload.
+    Milestone,
+    /// A treasury transaction payload.
+    TreasuryTransaction,
+    /// A tagged data payload.
+    TaggedData,
+}
+
+impl From<PayloadKindChoice> for PayloadKind {
+    fn from(value: PayloadKindChoice) -> Self {
+        match value {
+            PayloadKindChoice::NoPayload => Self::NoPayload,
+            PayloadKindChoice::Transaction => Self::Transaction,
+            PayloadKindChoice::Milestone => Self::Milestone,
+            PayloadKindChoice::TreasuryTransaction => Self::TreasuryTransaction,
+            PayloadKindChoice::TaggedData => Self::TaggedData,
         }
     }
 }
@@ -0,0 +1,82 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use chronicle::{
+    db::{mongodb::collections::MilestoneCollection, MongoDb},
+    model::tangle::MilestoneIndex,
+    tangle::{MilestoneStats, Tangle},
+};
+use clap::Parser;
+use futures::TryStreamExt;
+
+use crate::config::ChronicleConfig;
+
+#[derive(Clone, Debug, PartialEq, Eq, Parser)]
+pub struct FillMilestoneStatsCommand {
+    /// The inclusive starting milestone index. Defaults to the oldest milestone.
+    #[arg(short, long)]
+    start_milestone: Option<MilestoneIndex>,
+    /// The inclusive ending milestone index. Defaults to the newest milestone.
+    #[arg(short, long)]
+    end_milestone: Option<MilestoneIndex>,
+    /// Recompute the statistics of milestones that already have them.
+    #[arg(long)]
+    overwrite: bool,
+}
+
+impl FillMilestoneStatsCommand {
+    pub async fn handle(&self, config: &ChronicleConfig) -> eyre::Result<()> {
+        tracing::info!("Connecting to database using hosts: `{}`.", config.mongodb.hosts_str()?);
+        let db = MongoDb::connect(&config.mongodb).await?;
+        let collection = db.collection::<MilestoneCollection>();
+        let start_milestone = match self.start_milestone {
+            Some(index) => index,
+            None => {
+                collection
+                    .get_oldest_milestone()
+                    .await?
+                    .ok_or_else(|| eyre::eyre!("No milestones in database."))?
+                    .milestone_index
+            }
+        };
+        let end_milestone = match self.end_milestone {
+            Some(index) => index,
+            None => {
+                collection
+                    .get_newest_milestone()
+                    .await?
+                    .ok_or_else(|| eyre::eyre!("No milestones in database."))?
+                    .milestone_index
+            }
+        };
+        if end_milestone < start_milestone {
+            eyre::bail!("No milestones in range: {start_milestone}..={end_milestone}.");
+        }
+
+        let tangle = Tangle::from(db.clone());
+        let mut milestone_stream = tangle.milestone_stream(start_milestone..=end_milestone).await?;
+        let mut count = 0;
+        while let Some(milestone) = milestone_stream.try_next().await? {
+            let index = milestone.at.milestone_index;
+            if !self.overwrite && collection.get_milestone_stats(index).await?.is_some() {
+                continue;
+            }
+            let mut stats = MilestoneStats::default();
+            let mut cone_stream = milestone.cone_stream().await?;
+            while let Some(block) = cone_stream.try_next().await? {
+                stats.handle_block(&block);
+            }
+            stats.handle_ledger_updates(milestone.ledger_updates());
+            collection.set_milestone_stats(index, stats).await?;
+            count += 1;
+            tracing::debug!("Computed statistics for milestone {}.", index);
+        }
+        tracing::info!(
+            "Computed statistics for {} milestones in range {}..={}.",
+            count,
+            start_milestone,
+            end_milestone
+        );
+        Ok(())
+    }
+}
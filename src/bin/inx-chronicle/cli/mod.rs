@@ -4,7 +4,7 @@
 use chronicle::db::mongodb::config as mongodb;
 use clap::{Args, Parser, Subcommand};
 
-use crate::config::ChronicleConfig;
+use crate::config::{ChronicleConfig, StorageKind};
 
 #[cfg(feature = "analytics")]
 pub mod analytics;
@@ -12,14 +12,20 @@ pub mod analytics;
 mod api;
 #[cfg(feature = "influx")]
 mod influx;
+mod indexes;
 #[cfg(feature = "inx")]
 mod inx;
+mod milestone_stats;
+mod snapshot;
 
 /// Chronicle permanode storage as an INX plugin
 #[derive(Parser, Debug)]
 // #[command(author, version, about, next_display_order = None)]
 #[command(author, version, about)]
 pub struct ClArgs {
+    /// Storage arguments.
+    #[command(flatten, next_help_heading = "Storage")]
+    pub storage: StorageArgs,
     /// MongoDb arguments.
     #[command(flatten, next_help_heading = "MongoDb")]
     pub mongodb: MongoDbArgs,
@@ -40,6 +46,26 @@ pub struct ClArgs {
     pub subcommand: Option<Subcommands>,
 }
 
+#[derive(Args, Debug)]
+pub struct StorageArgs {
+    /// The storage backend that is used to persist the data.
+    #[arg(long, value_name = "KIND", env = "CHRONICLE_STORAGE", value_enum, default_value_t = StorageKind::MongoDb)]
+    pub storage: StorageKind,
+    /// The path of the embedded key-value store.
+    #[cfg(feature = "kv")]
+    #[arg(long, value_name = "PATH", env = "KV_PATH", default_value = chronicle::db::kv::config::DEFAULT_PATH)]
+    pub kv_path: std::path::PathBuf,
+}
+
+#[cfg(feature = "kv")]
+impl From<&StorageArgs> for chronicle::db::KvDbConfig {
+    fn from(value: &StorageArgs) -> Self {
+        Self {
+            path: value.kv_path.clone(),
+        }
+    }
+}
+
 #[derive(Args, Debug)]
 pub struct MongoDbArgs {
     /// The MongoDb connection string.
@@ -53,6 +79,33 @@ pub struct MongoDbArgs {
     /// The MongoDb database name.
     #[arg(long, value_name = "NAME", env = "MONGODB_DB_NAME", default_value = mongodb::DEFAULT_DATABASE_NAME)]
     pub mongodb_database_name: String,
+    /// How blocks are stored. Existing blocks are converted to this mode on startup or with the `migrate` subcommand.
+    #[arg(
+        long,
+        value_name = "MODE",
+        env = "MONGODB_BLOCK_STORAGE",
+        value_enum,
+        default_value_t = BlockStorageModeChoice::Full
+    )]
+    pub mongodb_block_storage: BlockStorageModeChoice,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum BlockStorageModeChoice {
+    /// The decoded block is stored together with its raw bytes.
+    Full,
+    /// Only the raw bytes are stored, together with the fields of the block that are needed by the indexes. The block
+    /// is decoded from the raw bytes when it is read.
+    RawOnly,
+}
+
+impl From<BlockStorageModeChoice> for mongodb::BlockStorageMode {
+    fn from(value: BlockStorageModeChoice) -> Self {
+        match value {
+            BlockStorageModeChoice::Full => Self::Full,
+            BlockStorageModeChoice::RawOnly => Self::RawOnly,
+        }
+    }
 }
 
 impl From<&MongoDbArgs> for chronicle::db::MongoDbConfig {
@@ -60,6 +113,7 @@ impl From<&MongoDbArgs> for chronicle::db::MongoDbConfig {
         Self {
             conn_str: value.mongodb_conn_str.clone(),
             database_name: value.mongodb_database_name.clone(),
+            block_storage_mode: value.mongodb_block_storage.into(),
         }
     }
 }
@@ -68,7 +122,10 @@ impl ClArgs {
     /// Creates a [`ChronicleConfig`] from the given command-line arguments, environment variables, and defaults.
     pub fn get_config(&self) -> ChronicleConfig {
         ChronicleConfig {
+            storage: self.storage.storage,
             mongodb: (&self.mongodb).into(),
+            #[cfg(feature = "kv")]
+            kv: (&self.storage).into(),
             #[cfg(feature = "influx")]
             influxdb: (&self.influxdb).into(),
             #[cfg(feature = "inx")]
@@ -88,12 +145,18 @@ impl ClArgs {
                 Subcommands::GenerateJWT(cmd) => {
                     cmd.handle(&config.api)?;
                 }
+                #[cfg(feature = "api")]
+                Subcommands::ApiKey(cmd) => {
+                    cmd.handle(config).await?;
+                }
                 #[cfg(feature = "analytics")]
                 Subcommands::FillAnalytics(cmd) => {
+                    ensure_mongodb(config, "fill-analytics")?;
                     cmd.handle(config).await?;
                 }
                 #[cfg(debug_assertions)]
                 Subcommands::ClearDatabase { run } => {
+                    ensure_mongodb(config, "clear-database")?;
                     tracing::info!("Connecting to database using hosts: `{}`.", config.mongodb.hosts_str()?);
                     let db = chronicle::db::MongoDb::connect(&config.mongodb).await?;
                     db.clear().await?;
@@ -103,16 +166,38 @@ impl ClArgs {
                     }
                 }
                 Subcommands::BuildIndexes => {
+                    ensure_mongodb(config, "build-indexes")?;
                     tracing::info!("Connecting to database using hosts: `{}`.", config.mongodb.hosts_str()?);
                     let db = chronicle::db::MongoDb::connect(&config.mongodb).await?;
-                    super::build_indexes(&db).await?;
+                    super::build_indexes(&db, &config.index_profile()).await?;
                     tracing::info!("Indexes built successfully.");
                 }
-                Subcommands::Migrate => {
+                Subcommands::Indexes(cmd) => {
+                    ensure_mongodb(config, "indexes")?;
+                    cmd.handle(config).await?;
+                }
+                Subcommands::Migrate { dry_run, down } => {
+                    ensure_mongodb(config, "migrate")?;
                     tracing::info!("Connecting to database using hosts: `{}`.", config.mongodb.hosts_str()?);
                     let db = chronicle::db::MongoDb::connect(&config.mongodb).await?;
-                    crate::migrations::migrate(&db).await?;
-                    tracing::info!("Migration completed successfully.");
+                    if *down {
+                        crate::migrations::revert(&db, *dry_run).await?;
+                    } else {
+                        crate::migrations::migrate(&db, *dry_run).await?;
+                        crate::migrations::convert_block_storage(&db, *dry_run).await?;
+                    }
+                    if *dry_run {
+                        tracing::info!("Dry run completed successfully; the database was not modified.");
+                    } else {
+                        tracing::info!("Migration completed successfully.");
+                    }
+                }
+                Subcommands::CreateSnapshot(cmd) => {
+                    cmd.handle(config).await?;
+                }
+                Subcommands::FillMilestoneStats(cmd) => {
+                    ensure_mongodb(config, "fill-milestone-stats")?;
+                    cmd.handle(config).await?;
                 }
                 _ => (),
             }
@@ -123,10 +208,24 @@ impl ClArgs {
     }
 }
 
+/// Fails if the subcommand is run against a storage backend other than MongoDB, which is the only one it supports.
+#[cfg_attr(not(feature = "kv"), allow(unused_variables))]
+fn ensure_mongodb(config: &ChronicleConfig, subcommand: &str) -> eyre::Result<()> {
+    match config.storage {
+        StorageKind::MongoDb => Ok(()),
+        #[cfg(feature = "kv")]
+        StorageKind::Kv => {
+            eyre::bail!("The `{subcommand}` subcommand is only supported by the `mongodb` storage backend.")
+        }
+    }
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Subcommands {
     #[cfg(feature = "api")]
     GenerateJWT(api::GenerateJWTCommand),
+    #[cfg(feature = "api")]
+    ApiKey(api::ApiKeyCommand),
     #[cfg(feature = "analytics")]
     FillAnalytics(analytics::FillAnalyticsCommand),
     /// Clear the Chronicle database.
@@ -138,8 +237,25 @@ pub enum Subcommands {
     },
     /// Manually build indexes.
     BuildIndexes,
-    /// Migrate to a new version.
-    Migrate,
+    /// Report on or manage the indexes of the database. The profile of required indexes is derived from the enabled API
+    /// modules (see `--disable-api` and `--disable-api-module`).
+    Indexes(indexes::IndexesCommand),
+    /// Migrate to a new version. Interrupted migrations are resumed where they stopped.
+    Migrate {
+        /// Report the steps and the number of affected documents without modifying the database.
+        #[arg(long)]
+        dry_run: bool,
+        /// Revert the latest migration, or the migration that was interrupted, instead.
+        #[arg(long)]
+        down: bool,
+    },
+    /// Create a full ledger snapshot from the database.
+    CreateSnapshot(snapshot::CreateSnapshotCommand),
+    /// Compute the statistics of milestones that were stored without them.
+    ///
+    /// The statistics are computed from the blocks in the database, so milestones that were synced with an ingestion
+    /// filter only count the blocks that matched the filter.
+    FillMilestoneStats(milestone_stats::FillMilestoneStatsCommand),
 }
 
 #[derive(Copy, Clone, PartialEq, Eq)]
@@ -0,0 +1,54 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::PathBuf;
+
+use chronicle::{
+    db::{MongoDb, Storage},
+    model::tangle::MilestoneIndex,
+    snapshot::write_full_snapshot,
+};
+use clap::Parser;
+
+use crate::config::{ChronicleConfig, StorageKind};
+
+#[derive(Clone, Debug, PartialEq, Eq, Parser)]
+pub struct CreateSnapshotCommand {
+    /// The ledger index of the snapshot. Defaults to the current ledger index.
+    #[arg(short, long)]
+    ledger_index: Option<MilestoneIndex>,
+    /// The file that the snapshot is written to.
+    #[arg(short, long, value_name = "FILEPATH", default_value = "full_snapshot.bin")]
+    output: PathBuf,
+}
+
+impl CreateSnapshotCommand {
+    pub async fn handle(&self, config: &ChronicleConfig) -> eyre::Result<()> {
+        match config.storage {
+            StorageKind::MongoDb => {
+                tracing::info!("Connecting to database using hosts: `{}`.", config.mongodb.hosts_str()?);
+                self.run(&MongoDb::connect(&config.mongodb).await?).await
+            }
+            #[cfg(feature = "kv")]
+            StorageKind::Kv => self.run(&chronicle::db::KvDb::open(&config.kv)?).await,
+        }
+    }
+
+    async fn run(&self, db: &impl Storage) -> eyre::Result<()> {
+        let ledger_index = match self.ledger_index {
+            Some(index) => index,
+            None => db
+                .get_ledger_index()
+                .await?
+                .ok_or_else(|| eyre::eyre!("No milestones in database."))?,
+        };
+        tracing::info!(
+            "Creating full snapshot at ledger index {} in `{}`.",
+            ledger_index,
+            self.output.display()
+        );
+        let output_count = write_full_snapshot(db, ledger_index, &self.output).await?;
+        tracing::info!("Snapshot created successfully with {} outputs.", output_count);
+        Ok(())
+    }
+}
@@ -1,12 +1,32 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use chronicle::db::MongoDbConfig;
+use chronicle::db::{mongodb::IndexProfile, MongoDbConfig};
+
+/// The storage backend that Chronicle writes to and serves from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum StorageKind {
+    /// An external MongoDB deployment.
+    #[value(name = "mongodb")]
+    MongoDb,
+    /// An embedded key-value store on the local disk.
+    #[cfg(feature = "kv")]
+    Kv,
+}
+
+impl Default for StorageKind {
+    fn default() -> Self {
+        Self::MongoDb
+    }
+}
 
 /// Configuration of Chronicle.
 #[derive(Clone, Default, Debug)]
 pub struct ChronicleConfig {
+    pub storage: StorageKind,
     pub mongodb: MongoDbConfig,
+    #[cfg(feature = "kv")]
+    pub kv: chronicle::db::KvDbConfig,
     #[cfg(feature = "influx")]
     pub influxdb: chronicle::db::influxdb::InfluxDbConfig,
     #[cfg(feature = "api")]
@@ -14,3 +34,24 @@ pub struct ChronicleConfig {
     #[cfg(feature = "inx")]
     pub inx: super::inx::InxConfig,
 }
+
+impl ChronicleConfig {
+    /// Gets the profile of the indexes that are required by the enabled components.
+    pub fn index_profile(&self) -> IndexProfile {
+        #[allow(unused_mut)]
+        let mut groups = Vec::new();
+        #[cfg(feature = "api")]
+        {
+            use chronicle::db::mongodb::IndexGroup;
+
+            use crate::api::config::ApiModule;
+            if self.api.is_module_enabled(ApiModule::Explorer) {
+                groups.push(IndexGroup::Explorer);
+            }
+            if self.api.is_module_enabled(ApiModule::Indexer) {
+                groups.push(IndexGroup::Indexer);
+            }
+        }
+        IndexProfile::new(groups)
+    }
+}
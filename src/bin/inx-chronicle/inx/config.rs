@@ -1,11 +1,12 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use chronicle::model::tangle::MilestoneIndex;
+use chronicle::{model::tangle::MilestoneIndex, tangle::IngestionFilter};
 
 pub const DEFAULT_ENABLED: bool = true;
 pub const DEFAULT_URL: &str = "http://localhost:9029";
 pub const DEFAULT_SYNC_START: u32 = 0;
+pub const DEFAULT_MILESTONE_VERIFICATION: MilestoneVerification = MilestoneVerification::Disabled;
 
 /// Configuration for an INX connection.
 #[derive(Clone, Debug)]
@@ -15,6 +16,12 @@ pub struct InxConfig {
     pub url: String,
     /// The milestone at which synchronization should begin.
     pub sync_start_milestone: MilestoneIndex,
+    /// The full snapshot that is used to bootstrap an empty database instead of the node's ledger state.
+    pub bootstrap_snapshot: Option<String>,
+    /// Whether and how milestone signatures are verified before a milestone is stored.
+    pub milestone_verification: MilestoneVerification,
+    /// Selects the blocks that are stored. The ledger and the milestones are always stored in full.
+    pub ingestion_filter: IngestionFilter,
 }
 
 impl Default for InxConfig {
@@ -23,6 +30,20 @@ impl Default for InxConfig {
             enabled: DEFAULT_ENABLED,
             url: DEFAULT_URL.to_string(),
             sync_start_milestone: DEFAULT_SYNC_START.into(),
+            bootstrap_snapshot: None,
+            milestone_verification: DEFAULT_MILESTONE_VERIFICATION,
+            ingestion_filter: Default::default(),
         }
     }
 }
+
+/// The action that is taken when verifying the signatures of incoming milestones.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum MilestoneVerification {
+    /// Milestone signatures are not verified.
+    Disabled,
+    /// A milestone with invalid signatures halts the synchronization.
+    Halt,
+    /// A milestone with invalid signatures is stored and flagged.
+    Flag,
+}
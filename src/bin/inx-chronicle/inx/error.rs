@@ -16,8 +16,16 @@ pub enum InxWorkerError {
     #[cfg(feature = "analytics")]
     #[error("missing application state")]
     MissingAppState,
+    #[error("invalid signatures for milestone {index}: {reason}")]
+    InvalidMilestoneSignature { index: MilestoneIndex, reason: String },
+    #[error("milestone {0} is missing from the node")]
+    MissingMilestone(MilestoneIndex),
     #[error("network changed from previous run. old network name: `{old}`, new network name: `{new}`")]
     NetworkChanged { old: String, new: String },
+    #[error("snapshot network `{snapshot}` does not match node network `{node}`")]
+    SnapshotNetworkMismatch { snapshot: String, node: String },
+    #[error("node confirmed milestone index `{node}` is less than snapshot ledger index `{snapshot}`")]
+    SnapshotIndexMismatch { node: MilestoneIndex, snapshot: MilestoneIndex },
     #[error("node pruned required milestones between `{start}` and `{end}`")]
     SyncMilestoneGap { start: MilestoneIndex, end: MilestoneIndex },
     #[error("node confirmed milestone index `{node}` is less than index in database `{db}`")]
@@ -7,8 +7,7 @@ use chronicle::{
     analytics::Analytic,
     db::{
         influxdb::{AnalyticsChoice, InfluxDb},
-        mongodb::collections::{ApplicationStateCollection, OutputCollection},
-        MongoDb,
+        Storage,
     },
     inx::Inx,
     model::tangle::MilestoneIndex,
@@ -26,7 +25,7 @@ pub struct AnalyticsInfo {
 }
 
 impl AnalyticsInfo {
-    pub async fn init(db: &MongoDb, influx_db: Option<&InfluxDb>) -> eyre::Result<Option<Self>> {
+    pub async fn init(db: &impl Storage, influx_db: Option<&InfluxDb>) -> eyre::Result<Option<Self>> {
         Ok(if let Some(influx_db) = influx_db {
             Some(Self {
                 analytics_choices: if influx_db.config().analytics.is_empty() {
@@ -36,7 +35,6 @@ impl AnalyticsInfo {
                 },
                 state: None,
                 synced_index: db
-                    .collection::<ApplicationStateCollection>()
                     .get_starting_index()
                     .await?
                     .ok_or(InxWorkerError::MissingAppState)?
@@ -48,7 +46,7 @@ impl AnalyticsInfo {
     }
 }
 
-impl InxWorker {
+impl<S: Storage> InxWorker<S> {
     pub async fn update_analytics<'a>(
         &self,
         milestone: &Milestone<'a, Inx>,
@@ -64,8 +62,7 @@ impl InxWorker {
                 if !matches!(&state, Some(state) if state.prev_protocol_params == milestone.protocol_params) {
                     let ledger_state = self
                         .db
-                        .collection::<OutputCollection>()
-                        .get_unspent_output_stream(milestone.at.milestone_index - 1)
+                        .get_unspent_outputs(milestone.at.milestone_index - 1)
                         .await?
                         .try_collect::<Vec<_>>()
                         .await?;
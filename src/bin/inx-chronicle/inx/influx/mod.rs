@@ -4,11 +4,11 @@
 #[cfg(feature = "analytics")]
 pub mod analytics;
 
-use chronicle::{inx::Inx, tangle::Milestone};
+use chronicle::{db::Storage, inx::Inx, tangle::Milestone};
 
 use super::{InxWorker, InxWorkerError};
 
-impl InxWorker {
+impl<S: Storage> InxWorker<S> {
     pub async fn update_influx<'a>(
         &self,
         milestone: &Milestone<'a, Inx>,
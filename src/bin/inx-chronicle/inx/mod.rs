@@ -6,59 +6,80 @@ mod error;
 #[cfg(feature = "influx")]
 mod influx;
 
-use std::time::Duration;
+use std::{fs::File, io::BufReader, time::Duration};
 
 use chronicle::{
-    db::{
-        mongodb::collections::{
-            ApplicationStateCollection, BlockCollection, ConfigurationUpdateCollection, LedgerUpdateCollection,
-            MilestoneCollection, OutputCollection, ProtocolUpdateCollection, TreasuryCollection,
-        },
-        MongoDb,
-    },
-    inx::{Inx, InxError},
+    db::Storage,
+    inx::{Inx, InxError, NodeConfigurationMessage},
     model::{
         ledger::{LedgerOutput, LedgerSpent},
         metadata::LedgerInclusionState,
-        payload::Payload,
+        node::NodeConfiguration,
+        payload::{milestone::MilestoneSignatureError, MilestoneId, MilestonePayload, Payload},
         tangle::{MilestoneIndex, MilestoneIndexTimestamp},
+        ProtocolParameters,
     },
-    tangle::{Milestone, Tangle},
+    snapshot::FullSnapshotReader,
+    tangle::{Milestone, MilestoneStats, Tangle},
 };
 use eyre::{bail, Result};
 use futures::{StreamExt, TryStreamExt};
 use tokio::{task::JoinSet, try_join};
-use tracing::{debug, info, instrument, trace_span, Instrument};
+use tracing::{debug, info, instrument, trace_span, warn, Instrument};
 
-pub use self::{config::InxConfig, error::InxWorkerError};
-use crate::migrations::{LatestMigration, Migration};
+pub use self::{
+    config::{InxConfig, MilestoneVerification},
+    error::InxWorkerError,
+};
+use crate::{
+    migrations::{LatestMigration, Migration},
+    progress::{SyncPhase, SyncProgress},
+};
 
 /// Batch size for insert operations.
 pub const INSERT_BATCH_SIZE: usize = 1000;
 
-pub struct InxWorker {
-    db: MongoDb,
+/// The interval at which the sync progress is logged.
+const SYNC_PROGRESS_LOG_INTERVAL: Duration = Duration::from_secs(30);
+
+pub struct InxWorker<S> {
+    db: S,
     config: InxConfig,
+    sync_progress: SyncProgress,
     #[cfg(feature = "influx")]
     influx_db: Option<chronicle::db::influxdb::InfluxDb>,
+    #[cfg(feature = "api")]
+    read_cache: Option<crate::api::ReadCache>,
 }
 
-impl InxWorker {
+impl<S: Storage> InxWorker<S> {
     /// Creates an [`Inx`] client by connecting to the endpoint specified in `inx_config`.
-    pub fn new(db: MongoDb, inx_config: InxConfig) -> Self {
+    pub fn new(db: S, inx_config: InxConfig) -> Self {
         Self {
             db,
             config: inx_config,
+            sync_progress: Default::default(),
             #[cfg(feature = "influx")]
             influx_db: None,
+            #[cfg(feature = "api")]
+            read_cache: None,
         }
     }
 
+    pub fn set_sync_progress(&mut self, sync_progress: &SyncProgress) {
+        self.sync_progress = sync_progress.clone();
+    }
+
     #[cfg(feature = "influx")]
     pub fn set_influx_db(&mut self, influx_db: &chronicle::db::influxdb::InfluxDb) {
         self.influx_db.replace(influx_db.clone());
     }
 
+    #[cfg(feature = "api")]
+    pub fn set_read_cache(&mut self, read_cache: &crate::api::ReadCache) {
+        self.read_cache.replace(read_cache.clone());
+    }
+
     async fn connect(&self) -> Result<Inx> {
         let url = url::Url::parse(&self.config.url)?;
 
@@ -70,9 +91,10 @@ impl InxWorker {
     }
 
     pub async fn run(&mut self) -> Result<()> {
-        let (start_index, inx) = self.init().await?;
+        let (start_index, mut inx) = self.init().await?;
+        self.sync_progress.record_milestone(start_index - 1);
 
-        let tangle = Tangle::from(inx);
+        let tangle = Tangle::from(inx.clone());
 
         let mut stream = tangle.milestone_stream(start_index..).await?;
 
@@ -81,13 +103,34 @@ impl InxWorker {
 
         debug!("Started listening to ledger updates via INX.");
 
+        let mut last_progress_log = std::time::Instant::now();
         while let Some(milestone) = stream.try_next().await? {
+            let milestone_index = milestone.at.milestone_index;
             self.handle_ledger_update(
                 milestone,
                 #[cfg(feature = "analytics")]
                 analytics_info.as_mut(),
             )
             .await?;
+            self.sync_progress.record_milestone(milestone_index);
+
+            if last_progress_log.elapsed() >= SYNC_PROGRESS_LOG_INTERVAL {
+                // The report keeps the previous confirmed index if the status cannot be read, so that progress
+                // reporting never stops the sync.
+                match inx.read_node_status().await {
+                    Ok(node_status) => self
+                        .sync_progress
+                        .set_node_confirmed_index(node_status.confirmed_milestone.milestone_info.milestone_index),
+                    Err(e) => warn!("Failed to read the node status for the sync progress: {e}"),
+                }
+                let report = self.sync_progress.report();
+                if report.phase == SyncPhase::Synced {
+                    debug!("{report}");
+                } else {
+                    info!("{report}");
+                }
+                last_progress_log = std::time::Instant::now();
+            }
         }
 
         tracing::debug!("INX stream closed unexpectedly.");
@@ -97,6 +140,7 @@ impl InxWorker {
 
     #[instrument(skip_all, err, level = "trace")]
     async fn init(&mut self) -> Result<(MilestoneIndex, Inx)> {
+        self.sync_progress.set_phase(SyncPhase::Connecting);
         info!("Connecting to INX at bind address `{}`.", &self.config.url);
         let mut inx = self.connect().await?;
         info!("Connected to INX.");
@@ -116,16 +160,30 @@ impl InxWorker {
             "The node has a pruning index of `{}` and a latest confirmed milestone index of `{}`.",
             node_status.tangle_pruning_index, node_status.confirmed_milestone.milestone_info.milestone_index,
         );
+        self.sync_progress
+            .set_node_confirmed_index(node_status.confirmed_milestone.milestone_info.milestone_index);
+
+        let newest_milestone = self.db.get_newest_milestone().await?;
+
+        // A snapshot is only used to bootstrap an empty database.
+        let snapshot = match &self.config.bootstrap_snapshot {
+            Some(path) if newest_milestone.is_none() && self.db.get_latest_protocol_parameters().await?.is_none() => {
+                info!("Reading snapshot from `{}`.", path);
+                let path = path.clone();
+                let reader = tokio::task::spawn_blocking(move || -> Result<_> {
+                    Ok(FullSnapshotReader::new(BufReader::new(File::open(path)?))?)
+                })
+                .await??;
+                Some(reader)
+            }
+            _ => None,
+        };
 
         // Check if there is an unfixable gap in our node data.
         let start_index = if let Some(MilestoneIndexTimestamp {
             milestone_index: latest_milestone,
             ..
-        }) = self
-            .db
-            .collection::<MilestoneCollection>()
-            .get_newest_milestone()
-            .await?
+        }) = newest_milestone
         {
             if node_status.tangle_pruning_index.0 > latest_milestone.0 {
                 bail!(InxWorkerError::SyncMilestoneGap {
@@ -140,6 +198,21 @@ impl InxWorker {
             } else {
                 latest_milestone + 1
             }
+        } else if let Some(snapshot) = &snapshot {
+            let ledger_index = snapshot.header().ledger_milestone_index;
+            if node_status.tangle_pruning_index.0 > ledger_index.0 {
+                bail!(InxWorkerError::SyncMilestoneGap {
+                    start: ledger_index + 1,
+                    end: node_status.tangle_pruning_index,
+                });
+            } else if node_status.confirmed_milestone.milestone_info.milestone_index.0 < ledger_index.0 {
+                bail!(InxWorkerError::SnapshotIndexMismatch {
+                    node: node_status.confirmed_milestone.milestone_info.milestone_index,
+                    snapshot: ledger_index,
+                });
+            } else {
+                ledger_index + 1
+            }
         } else {
             self.config
                 .sync_start_milestone
@@ -161,12 +234,7 @@ impl InxWorker {
             node_configuration.base_token.ticker_symbol
         );
 
-        if let Some(latest) = self
-            .db
-            .collection::<ProtocolUpdateCollection>()
-            .get_latest_protocol_parameters()
-            .await?
-        {
+        if let Some(latest) = self.db.get_latest_protocol_parameters().await? {
             let protocol_parameters = chronicle::model::ProtocolParameters::from(protocol_parameters);
             if latest.parameters.network_name != protocol_parameters.network_name {
                 bail!(InxWorkerError::NetworkChanged {
@@ -178,100 +246,235 @@ impl InxWorker {
             if latest.parameters != protocol_parameters {
                 debug!("Updating protocol parameters.");
                 self.db
-                    .collection::<ProtocolUpdateCollection>()
                     .upsert_protocol_parameters(start_index, protocol_parameters)
                     .await?;
             }
         } else {
+            // The snapshot is checked before the database is cleared, so that a wrong snapshot does not wipe it.
+            if let Some(snapshot) = &snapshot {
+                let network_name = &snapshot.header().protocol_parameters.network_name;
+                if network_name != protocol_parameters.network_name() {
+                    bail!(InxWorkerError::SnapshotNetworkMismatch {
+                        snapshot: network_name.clone(),
+                        node: protocol_parameters.network_name().to_owned(),
+                    });
+                }
+            }
+
             self.db.clear().await?;
+            #[cfg(feature = "api")]
+            if let Some(read_cache) = &self.read_cache {
+                read_cache.clear();
+            }
 
             let latest_version = LatestMigration::version();
             info!("Setting migration version to {}", latest_version);
+            self.db.set_last_migration(latest_version).await?;
+            let starting_index = match snapshot {
+                Some(snapshot) => {
+                    self.bootstrap_from_snapshot(&mut inx, snapshot, &protocol_parameters, &node_configuration)
+                        .await?
+                }
+                None => self.bootstrap_from_inx(&mut inx).await?,
+            };
+
+            self.db.set_starting_index(starting_index).await?;
+
+            info!(
+                "Linking {} storage to network `{}`.",
+                self.db.backend_name(),
+                protocol_parameters.network_name()
+            );
+
             self.db
-                .collection::<ApplicationStateCollection>()
-                .set_last_migration(latest_version)
-                .await?;
-            info!("Reading unspent outputs.");
-            let unspent_output_stream = inx
-                .read_unspent_outputs()
-                .instrument(trace_span!("inx_read_unspent_outputs"))
+                .upsert_protocol_parameters(start_index, protocol_parameters.into())
                 .await?;
+        }
 
-            let mut starting_index = None;
-
-            let mut count = 0;
-            let mut tasks = unspent_output_stream
-                .inspect_ok(|_| count += 1)
-                .map(|msg| {
-                    let msg = msg?;
-                    let ledger_index = &msg.ledger_index;
-                    if let Some(index) = starting_index.as_ref() {
-                        if index != ledger_index {
-                            bail!(InxWorkerError::InvalidUnspentOutputIndex {
-                                found: *ledger_index,
-                                expected: *index,
-                            })
-                        }
-                    } else {
-                        starting_index = Some(*ledger_index);
-                    }
-                    Ok(msg)
-                })
-                .map(|res| Ok(res?.output))
-                .try_chunks(INSERT_BATCH_SIZE)
-                // We only care if we had an error, so discard the other data
-                .map_err(|e| e.1)
-                // Convert batches to tasks
-                .try_fold(JoinSet::new(), |mut tasks, batch| async {
-                    let db = self.db.clone();
-                    tasks.spawn(async move { insert_unspent_outputs(&db, &batch).await });
-                    Result::<_>::Ok(tasks)
-                })
-                .await?;
+        self.record_ingestion_filter().await?;
+
+        Ok((start_index, inx))
+    }
 
-            while let Some(res) = tasks.join_next().await {
-                res??;
+    /// Records the configured ingestion filter so that the API can report that the block store is partial.
+    async fn record_ingestion_filter(&self) -> Result<()> {
+        let filter = if self.config.ingestion_filter.is_empty() {
+            None
+        } else {
+            info!("Applying ingestion filter {:?}.", self.config.ingestion_filter);
+            Some(&self.config.ingestion_filter)
+        };
+        let previous = self.db.get_ingestion_filter().await?;
+        if previous.as_ref() != filter {
+            if let Some(previous) = previous {
+                warn!(
+                    "The ingestion filter changed. Blocks that were stored before were filtered by {:?}.",
+                    previous
+                );
             }
+            self.db.set_ingestion_filter(filter).await?;
+        }
+        Ok(())
+    }
+
+    /// Inserts the unspent outputs of the node's current ledger state and returns the starting index.
+    async fn bootstrap_from_inx(&self, inx: &mut Inx) -> Result<MilestoneIndexTimestamp> {
+        self.sync_progress.set_phase(SyncPhase::ReadingUnspentOutputs);
+        info!("Reading unspent outputs.");
+        let unspent_output_stream = inx
+            .read_unspent_outputs()
+            .instrument(trace_span!("inx_read_unspent_outputs"))
+            .await?;
+
+        let mut starting_index = None;
+
+        let mut count = 0;
+        let mut tasks = unspent_output_stream
+            .inspect_ok(|_| count += 1)
+            .map(|msg| {
+                let msg = msg?;
+                let ledger_index = &msg.ledger_index;
+                if let Some(index) = starting_index.as_ref() {
+                    if index != ledger_index {
+                        bail!(InxWorkerError::InvalidUnspentOutputIndex {
+                            found: *ledger_index,
+                            expected: *index,
+                        })
+                    }
+                } else {
+                    starting_index = Some(*ledger_index);
+                }
+                Ok(msg)
+            })
+            .map(|res| Ok(res?.output))
+            .try_chunks(INSERT_BATCH_SIZE)
+            // We only care if we had an error, so discard the other data
+            .map_err(|e| e.1)
+            // Convert batches to tasks
+            .try_fold(JoinSet::new(), |mut tasks, batch| async {
+                let db = self.db.clone();
+                tasks.spawn(async move { insert_unspent_outputs(&db, &batch).await });
+                Result::<_>::Ok(tasks)
+            })
+            .await?;
 
-            info!("Inserted {} unspent outputs.", count);
+        while let Some(res) = tasks.join_next().await {
+            res??;
+        }
 
-            let starting_index = starting_index.unwrap_or_default();
+        info!("Inserted {} unspent outputs.", count);
 
-            // Get the timestamp for the starting index
-            let milestone_timestamp = inx
-                .read_milestone(starting_index.into())
-                .await?
-                .milestone_info
-                .milestone_timestamp
-                .into();
+        let starting_index = starting_index.unwrap_or_default();
 
-            info!(
-                "Setting starting index to {} with timestamp {}",
-                starting_index,
-                time::OffsetDateTime::try_from(milestone_timestamp)?
-                    .format(&time::format_description::well_known::Rfc3339)?
-            );
+        // Get the timestamp for the starting index
+        let milestone_timestamp = inx
+            .read_milestone(starting_index.into())
+            .await?
+            .milestone_info
+            .milestone_timestamp
+            .into();
+
+        info!(
+            "Setting starting index to {} with timestamp {}",
+            starting_index,
+            time::OffsetDateTime::try_from(milestone_timestamp)?
+                .format(&time::format_description::well_known::Rfc3339)?
+        );
 
-            let starting_index = starting_index.with_timestamp(milestone_timestamp);
+        Ok(starting_index.with_timestamp(milestone_timestamp))
+    }
 
-            self.db
-                .collection::<ApplicationStateCollection>()
-                .set_starting_index(starting_index)
-                .await?;
+    /// Inserts the unspent outputs, the treasury and the ledger milestone of a full snapshot and returns the starting
+    /// index.
+    async fn bootstrap_from_snapshot(
+        &self,
+        inx: &mut Inx,
+        mut snapshot: FullSnapshotReader<BufReader<File>>,
+        protocol_parameters: &iota_types::block::protocol::ProtocolParameters,
+        node_configuration: &NodeConfigurationMessage,
+    ) -> Result<MilestoneIndexTimestamp> {
+        let header = snapshot.header().clone();
+
+        self.sync_progress.set_phase(SyncPhase::ReadingSnapshot);
+        info!(
+            "Reading {} unspent outputs from snapshot at ledger index {}.",
+            snapshot.output_count(),
+            header.ledger_milestone_index
+        );
 
-            info!(
-                "Linking database `{}` to network `{}`.",
-                self.db.name(),
-                protocol_parameters.network_name()
-            );
+        let mut count = 0;
+        let mut tasks = JoinSet::new();
+        loop {
+            // Reading the file is blocking, so every batch is read on a blocking thread.
+            let (reader, batch) = tokio::task::spawn_blocking(move || {
+                let batch = snapshot.by_ref().take(INSERT_BATCH_SIZE).collect::<Result<Vec<_>, _>>();
+                (snapshot, batch)
+            })
+            .await?;
+            snapshot = reader;
+            let batch = batch?;
+            if batch.is_empty() {
+                break;
+            }
+            count += batch.len();
+            let db = self.db.clone();
+            tasks.spawn(async move { insert_unspent_outputs(&db, &batch).await });
+        }
 
+        while let Some(res) = tasks.join_next().await {
+            res??;
+        }
+
+        info!("Inserted {} unspent outputs.", count);
+
+        if header.treasury_milestone_id.0 != [0; MilestoneId::LENGTH] {
             self.db
-                .collection::<ProtocolUpdateCollection>()
-                .upsert_protocol_parameters(start_index, protocol_parameters.into())
+                .insert_treasury_payloads(vec![(
+                    header.ledger_milestone_index,
+                    header.treasury_milestone_id,
+                    header.treasury_amount,
+                )])
                 .await?;
         }
 
-        Ok((start_index, inx))
+        // The snapshot does not contain the ledger milestone, which is needed as the anchor of the milestone queries
+        // until the next milestone is synced. Its cone is not read, so it has no statistics.
+        let index = header.ledger_milestone_index;
+        let milestone = inx.read_milestone(index.0.into()).await?;
+        let milestone_id = milestone
+            .milestone_info
+            .milestone_id
+            .ok_or(InxWorkerError::MissingMilestone(index))?;
+        let milestone_timestamp = milestone.milestone_info.milestone_timestamp.into();
+        let payload = match milestone.milestone.inner_unverified()? {
+            iota_types::block::payload::Payload::Milestone(payload) => MilestonePayload::from(payload),
+            _ => bail!(InxWorkerError::MissingMilestone(index)),
+        };
+        let signature_verified = self.verify_milestone_signatures(
+            index,
+            &payload,
+            &protocol_parameters.clone().into(),
+            &node_configuration.clone().into(),
+        )?;
+        self.db
+            .insert_milestone(
+                milestone_id,
+                index,
+                milestone_timestamp,
+                payload,
+                signature_verified,
+                None,
+            )
+            .await?;
+
+        info!(
+            "Setting starting index to {} with timestamp {}",
+            header.ledger_milestone_index,
+            time::OffsetDateTime::try_from(milestone_timestamp)?
+                .format(&time::format_description::well_known::Rfc3339)?
+        );
+
+        Ok(header.ledger_milestone_index.with_timestamp(milestone_timestamp))
     }
 
     #[instrument(skip_all, fields(milestone_index, created, consumed), err, level = "debug")]
@@ -283,6 +486,13 @@ impl InxWorker {
         #[cfg(feature = "metrics")]
         let start_time = std::time::Instant::now();
 
+        let signature_verified = self.verify_milestone_signatures(
+            milestone.at.milestone_index,
+            &milestone.payload,
+            &milestone.protocol_params,
+            &milestone.node_config,
+        )?;
+
         let mut tasks = JoinSet::new();
 
         for batch in milestone.ledger_updates().created_outputs().chunks(INSERT_BATCH_SIZE) {
@@ -306,13 +516,12 @@ impl InxWorker {
         tracing::Span::current().record("created", milestone.ledger_updates().created_outputs().len());
         tracing::Span::current().record("consumed", milestone.ledger_updates().consumed_outputs().len());
 
-        self.handle_cone_stream(&milestone).await?;
+        let mut stats = self.handle_cone_stream(&milestone).await?;
+        stats.handle_ledger_updates(milestone.ledger_updates());
         self.db
-            .collection::<ProtocolUpdateCollection>()
             .upsert_protocol_parameters(milestone.at.milestone_index, milestone.protocol_params.clone())
             .await?;
         self.db
-            .collection::<ConfigurationUpdateCollection>()
             .upsert_node_configuration(milestone.at.milestone_index, milestone.node_config.clone())
             .await?;
 
@@ -328,51 +537,99 @@ impl InxWorker {
 
         // This acts as a checkpoint for the syncing and has to be done last, after everything else completed.
         self.db
-            .collection::<MilestoneCollection>()
             .insert_milestone(
                 milestone.milestone_id,
                 milestone.at.milestone_index,
                 milestone.at.milestone_timestamp,
                 milestone.payload.clone(),
+                signature_verified,
+                Some(stats),
             )
             .await?;
 
+        // The spent metadata of the consumed outputs is only visible to the API once the milestone is stored.
+        #[cfg(feature = "api")]
+        if let Some(read_cache) = &self.read_cache {
+            let consumed = milestone
+                .ledger_updates()
+                .consumed_outputs()
+                .iter()
+                .map(|output| output.output_id())
+                .collect::<Vec<_>>();
+            read_cache.invalidate_outputs(&consumed);
+        }
+
         Ok(())
     }
 
+    /// Verifies the signatures of a milestone according to the configured [`MilestoneVerification`].
+    ///
+    /// Returns `None` if verification is disabled.
+    fn verify_milestone_signatures(
+        &self,
+        index: MilestoneIndex,
+        payload: &MilestonePayload,
+        protocol_params: &ProtocolParameters,
+        node_config: &NodeConfiguration,
+    ) -> Result<Option<bool>> {
+        if self.config.milestone_verification == MilestoneVerification::Disabled {
+            return Ok(None);
+        }
+        let ctx = protocol_params.clone().try_into()?;
+        match payload.verify_signatures(&ctx, node_config) {
+            Ok(()) => Ok(Some(true)),
+            Err(MilestoneSignatureError::Invalid(e)) => match self.config.milestone_verification {
+                MilestoneVerification::Halt => bail!(InxWorkerError::InvalidMilestoneSignature {
+                    index,
+                    reason: format!("{e:?}"),
+                }),
+                _ => {
+                    warn!("Storing milestone {} with invalid signatures: {:?}", index, e);
+                    Ok(Some(false))
+                }
+            },
+            Err(e) => Err(e.into()),
+        }
+    }
+
     #[instrument(skip_all, err, level = "trace")]
-    async fn handle_cone_stream<'a>(&mut self, milestone: &Milestone<'a, Inx>) -> Result<()> {
+    async fn handle_cone_stream<'a>(&mut self, milestone: &Milestone<'a, Inx>) -> Result<MilestoneStats> {
         let cone_stream = milestone.cone_stream().await?;
 
+        // The statistics cover the whole cone, regardless of the ingestion filter.
+        let mut stats = MilestoneStats::default();
         let mut tasks = cone_stream
+            .inspect_ok(|data| stats.handle_block(data))
             .try_chunks(INSERT_BATCH_SIZE)
             .map_err(|e| e.1)
             .try_fold(JoinSet::new(), |mut tasks, batch| async {
                 let db = self.db.clone();
-                tasks.spawn(async move {
-                    let payloads = batch
-                        .iter()
-                        .filter_map(|data| {
-                            if data.metadata.inclusion_state == LedgerInclusionState::Included {
-                                if let Some(Payload::TreasuryTransaction(payload)) = &data.block.payload {
-                                    return Some((
-                                        data.metadata.referenced_by_milestone_index,
-                                        payload.input_milestone_id,
-                                        payload.output_amount,
-                                    ));
-                                }
+                let payloads = batch
+                    .iter()
+                    .filter_map(|data| {
+                        if data.metadata.inclusion_state == LedgerInclusionState::Included {
+                            if let Some(Payload::TreasuryTransaction(payload)) = &data.block.payload {
+                                return Some((
+                                    data.metadata.referenced_by_milestone_index,
+                                    payload.input_milestone_id,
+                                    payload.output_amount,
+                                ));
                             }
-                            None
-                        })
-                        .collect::<Vec<_>>();
+                        }
+                        None
+                    })
+                    .collect::<Vec<_>>();
+                let blocks = batch
+                    .into_iter()
+                    .filter(|data| self.config.ingestion_filter.matches(data, milestone.ledger_updates()))
+                    .collect::<Vec<_>>();
+                tasks.spawn(async move {
                     if !payloads.is_empty() {
-                        db.collection::<TreasuryCollection>()
-                            .insert_treasury_payloads(payloads)
-                            .await?;
+                        db.insert_treasury_payloads(payloads).await?;
+                    }
+                    if !blocks.is_empty() {
+                        db.insert_blocks_with_metadata(blocks).await?;
                     }
-                    db.collection::<BlockCollection>()
-                        .insert_blocks_with_metadata(batch)
-                        .await?;
                     Result::<_>::Ok(())
                 });
                 Ok(tasks)
@@ -383,21 +640,19 @@ impl InxWorker {
             res??;
         }
 
-        Ok(())
+        Ok(stats)
     }
 }
 
 #[instrument(skip_all, err, fields(num = outputs.len()), level = "trace")]
-async fn insert_unspent_outputs(db: &MongoDb, outputs: &[LedgerOutput]) -> Result<()> {
-    let output_collection = db.collection::<OutputCollection>();
-    let ledger_collection = db.collection::<LedgerUpdateCollection>();
+async fn insert_unspent_outputs(db: &impl Storage, outputs: &[LedgerOutput]) -> Result<()> {
     try_join! {
         async {
-            output_collection.insert_unspent_outputs(outputs).await?;
+            db.insert_unspent_outputs(outputs).await?;
             Result::<_>::Ok(())
         },
         async {
-            ledger_collection.insert_unspent_ledger_updates(outputs).await?;
+            db.insert_unspent_ledger_updates(outputs).await?;
             Ok(())
         }
     }?;
@@ -405,16 +660,14 @@ async fn insert_unspent_outputs(db: &MongoDb, outputs: &[LedgerOutput]) -> Resul
 }
 
 #[instrument(skip_all, err, fields(num = outputs.len()), level = "trace")]
-async fn update_spent_outputs(db: &MongoDb, outputs: &[LedgerSpent]) -> Result<()> {
-    let output_collection = db.collection::<OutputCollection>();
-    let ledger_collection = db.collection::<LedgerUpdateCollection>();
+async fn update_spent_outputs(db: &impl Storage, outputs: &[LedgerSpent]) -> Result<()> {
     try_join! {
         async {
-            output_collection.update_spent_outputs(outputs).await?;
+            db.update_spent_outputs(outputs).await?;
             Ok(())
         },
         async {
-            ledger_collection.insert_spent_ledger_updates(outputs).await?;
+            db.insert_spent_ledger_updates(outputs).await?;
             Ok(())
         }
     }
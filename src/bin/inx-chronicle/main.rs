@@ -12,16 +12,24 @@ mod config;
 mod inx;
 mod migrations;
 mod process;
+mod progress;
 
 use bytesize::ByteSize;
-use chronicle::db::MongoDb;
+#[cfg(feature = "kv")]
+use chronicle::db::KvDb;
+#[cfg(not(feature = "api"))]
+use chronicle::db::Storage as AppStorage;
+use chronicle::db::{mongodb::IndexProfile, MongoDb};
 use clap::Parser;
 use tokio::task::JoinSet;
 use tracing::{debug, error, info};
 use tracing_subscriber::{fmt::format::FmtSpan, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
+#[cfg(feature = "api")]
+use self::api::ApiStorage as AppStorage;
 use self::{
     cli::{ClArgs, PostCommand},
+    config::{ChronicleConfig, StorageKind},
     migrations::check_migration_version,
 };
 
@@ -38,24 +46,50 @@ async fn main() -> eyre::Result<()> {
         return Ok(());
     }
 
-    info!("Connecting to database using hosts: `{}`.", config.mongodb.hosts_str()?);
-    let db = MongoDb::connect(&config.mongodb).await?;
-    debug!("Available databases: `{:?}`", db.get_databases().await?);
-    info!(
-        "Connected to database `{}` ({})",
-        db.name(),
-        ByteSize::b(db.size().await?)
-    );
+    match config.storage {
+        StorageKind::MongoDb => {
+            info!("Connecting to database using hosts: `{}`.", config.mongodb.hosts_str()?);
+            let db = MongoDb::connect(&config.mongodb).await?;
+            debug!("Available databases: `{:?}`", db.get_databases().await?);
+            info!(
+                "Connected to database `{}` ({})",
+                db.name(),
+                ByteSize::b(db.size().await?)
+            );
 
-    check_migration_version(&db).await?;
+            check_migration_version(&db).await?;
 
-    #[cfg(feature = "inx")]
-    build_indexes(&db).await?;
+            #[cfg(feature = "inx")]
+            build_indexes(&db, &config.index_profile()).await?;
+
+            run(db, config).await
+        }
+        #[cfg(feature = "kv")]
+        StorageKind::Kv => {
+            info!("Opening key-value store at `{}`.", config.kv.path.display());
+            let db = KvDb::open(&config.kv)?;
+            info!("Opened key-value store ({})", ByteSize::b(db.size()?));
+
+            run(db, config).await
+        }
+    }
+}
 
+/// Runs the workers on top of the given storage backend until shutdown.
+async fn run<S: AppStorage>(#[allow(unused)] db: S, #[allow(unused)] config: ChronicleConfig) -> eyre::Result<()> {
     let mut tasks: JoinSet<eyre::Result<()>> = JoinSet::new();
 
     let (shutdown_signal, _) = tokio::sync::broadcast::channel::<()>(1);
 
+    #[allow(unused)]
+    let sync_progress = progress::SyncProgress::default();
+
+    // Outputs are only cached if the INX worker invalidates them when they are spent.
+    #[cfg(all(feature = "api", feature = "inx"))]
+    let read_cache = api::ReadCache::new(config.api.cache_capacity, config.inx.enabled);
+    #[cfg(all(feature = "api", not(feature = "inx")))]
+    let read_cache = api::ReadCache::new(config.api.cache_capacity, false);
+
     #[cfg(feature = "inx")]
     if config.inx.enabled {
         #[cfg(feature = "influx")]
@@ -87,6 +121,11 @@ async fn main() -> eyre::Result<()> {
         };
 
         let mut worker = inx::InxWorker::new(db.clone(), config.inx.clone());
+        worker.set_sync_progress(&sync_progress);
+        #[cfg(feature = "api")]
+        if config.api.enabled {
+            worker.set_read_cache(&read_cache);
+        }
         #[cfg(feature = "influx")]
         if let Some(influx_db) = &influx_db {
             worker.set_influx_db(influx_db);
@@ -105,7 +144,16 @@ async fn main() -> eyre::Result<()> {
     #[cfg(feature = "api")]
     if config.api.enabled {
         use futures::FutureExt;
-        let worker = api::ApiWorker::new(db.clone(), config.api.clone())?;
+        let mut worker = api::ApiWorker::new(db.clone(), config.api.clone())?;
+        worker.set_sync_progress(&sync_progress);
+        worker.set_read_cache(&read_cache);
+        #[cfg(feature = "inx")]
+        if config.inx.enabled && config.api.node_forwarding {
+            worker.set_node(&api::NodeClient::new(
+                chronicle::inx::Inx::connect_lazy(config.inx.url.clone())?,
+                config.api.node_forwarding_rate_limit,
+            ));
+        }
         let mut handle = shutdown_signal.subscribe();
         tasks.spawn(async move {
             worker.run(handle.recv().then(|_| async {})).await?;
@@ -172,13 +220,16 @@ fn set_up_logging() -> eyre::Result<()> {
     Ok(())
 }
 
-async fn build_indexes(db: &MongoDb) -> eyre::Result<()> {
+async fn build_indexes(db: &MongoDb, profile: &IndexProfile) -> eyre::Result<()> {
     use chronicle::db::mongodb::collections;
     let start_indexes = db.get_index_names().await?;
-    db.create_indexes::<collections::OutputCollection>().await?;
-    db.create_indexes::<collections::BlockCollection>().await?;
-    db.create_indexes::<collections::LedgerUpdateCollection>().await?;
-    db.create_indexes::<collections::MilestoneCollection>().await?;
+    db.create_index_profile::<collections::OutputCollection>(profile)
+        .await?;
+    db.create_index_profile::<collections::BlockCollection>(profile).await?;
+    db.create_index_profile::<collections::LedgerUpdateCollection>(profile)
+        .await?;
+    db.create_index_profile::<collections::MilestoneCollection>(profile)
+        .await?;
     let end_indexes = db.get_index_names().await?;
     for (collection, indexes) in end_indexes {
         if let Some(old_indexes) = start_indexes.get(&collection) {
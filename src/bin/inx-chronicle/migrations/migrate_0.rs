@@ -3,24 +3,122 @@
 
 use async_trait::async_trait;
 use chronicle::{
-    db::{mongodb::collections::OutputCollection, MongoDb, MongoDbCollectionExt},
+    db::{mongodb::collections::OutputCollection, MongoDb, MongoDbCollection, MongoDbCollectionExt},
     model::utxo::{AliasId, NftId, OutputId},
 };
 use futures::TryStreamExt;
-use mongodb::{bson::doc, options::IndexOptions, IndexModel};
+use mongodb::{
+    bson::{doc, Document},
+    options::IndexOptions,
+    IndexModel,
+};
 use serde::Deserialize;
 
-use super::Migration;
+use super::{
+    steps::{CreateIndex, DropIndex, UpdateMany},
+    Migration, MigrationStep, MigrationSteps,
+};
 
 pub struct Migrate;
 
-#[async_trait]
 impl Migration for Migrate {
     const ID: usize = 0;
     const APP_VERSION: &'static str = "1.0.0-beta.32";
     const DATE: time::Date = time::macros::date!(2023 - 02 - 03);
 
-    async fn migrate(db: &MongoDb) -> eyre::Result<()> {
+    fn steps() -> MigrationSteps {
+        vec![
+            Box::new(ConvertImplicitIds),
+            // Get the outputs that don't have implicit IDs
+            Box::new(UpdateMany::<OutputCollection>::new(
+                "set the indexed id of alias outputs",
+                doc! {
+                    "output.kind": "alias",
+                    "output.alias_id": { "$ne": AliasId::implicit() },
+                },
+                [doc! { "$set": {
+                    "details.indexed_id": "$output.alias_id",
+                } }],
+            )),
+            Box::new(UpdateMany::<OutputCollection>::new(
+                "set the indexed id of nft outputs",
+                doc! {
+                    "output.kind": "nft",
+                    "output.nft_id": { "$ne": NftId::implicit() },
+                },
+                [doc! { "$set": {
+                    "details.indexed_id": "$output.nft_id",
+                } }],
+            )),
+            Box::new(UpdateMany::<OutputCollection>::new(
+                "set the indexed id of foundry outputs",
+                doc! { "output.kind": "foundry" },
+                [doc! { "$set": {
+                    "details.indexed_id": "$output.foundry_id",
+                } }],
+            )),
+            Box::new(DropIndex::<OutputCollection>::new("output_alias_id_index")),
+            Box::new(DropIndex::<OutputCollection>::new("output_foundry_id_index")),
+            Box::new(DropIndex::<OutputCollection>::new("output_nft_id_index")),
+            Box::new(CreateIndex::<OutputCollection>::new(
+                IndexModel::builder()
+                    .keys(doc! { "details.indexed_id": 1 })
+                    .options(
+                        IndexOptions::builder()
+                            .name("output_indexed_id_index".to_string())
+                            .partial_filter_expression(doc! {
+                                "details.indexed_id": { "$exists": true },
+                            })
+                            .build(),
+                    )
+                    .build(),
+            )),
+        ]
+    }
+
+    fn down_steps() -> Option<MigrationSteps> {
+        // The dropped id indexes are recreated by previous versions on startup.
+        Some(vec![
+            Box::new(DropIndex::<OutputCollection>::new("output_indexed_id_index")),
+            Box::new(UpdateMany::<OutputCollection>::new(
+                "remove the indexed id of outputs",
+                doc! { "details.indexed_id": { "$exists": true } },
+                [doc! { "$unset": "details.indexed_id" }],
+            )),
+        ])
+    }
+}
+
+/// Sets the indexed id of the alias and nft outputs with implicit IDs, which is derived from their output id.
+struct ConvertImplicitIds;
+
+impl ConvertImplicitIds {
+    fn filter() -> Document {
+        doc! { "$or": [
+            { "output.alias_id": AliasId::implicit() },
+            { "output.nft_id": NftId::implicit() }
+        ] }
+    }
+}
+
+#[async_trait]
+impl MigrationStep for ConvertImplicitIds {
+    fn description(&self) -> String {
+        format!(
+            "set the indexed id of outputs with implicit ids in `{}`",
+            OutputCollection::NAME
+        )
+    }
+
+    async fn affected_documents(&self, db: &MongoDb) -> eyre::Result<u64> {
+        Ok(db
+            .collection::<OutputCollection>()
+            .collection()
+            .count_documents(Self::filter(), None)
+            .await?)
+    }
+
+    async fn apply(&self, db: &MongoDb) -> eyre::Result<()> {
         let collection = db.collection::<OutputCollection>();
 
         #[derive(Deserialize)]
@@ -32,10 +130,7 @@ impl Migration for Migrate {
         let outputs = collection
             .aggregate::<Res>(
                 [
-                    doc! { "$match": { "$or": [
-                        { "output.alias_id": AliasId::implicit() },
-                        { "output.nft_id": NftId::implicit() }
-                    ] } },
+                    doc! { "$match": Self::filter() },
                     doc! { "$project": {
                         "output_id": "$_id"
                     } },
@@ -59,66 +154,6 @@ impl Migration for Migrate {
                 .await?;
         }
 
-        // Get the outputs that don't have implicit IDs
-        collection
-            .update_many(
-                doc! {
-                    "output.kind": "alias",
-                    "output.alias_id": { "$ne": AliasId::implicit() },
-                },
-                vec![doc! { "$set": {
-                    "details.indexed_id": "$output.alias_id",
-                } }],
-                None,
-            )
-            .await?;
-
-        collection
-            .update_many(
-                doc! {
-                    "output.kind": "nft",
-                    "output.nft_id": { "$ne": NftId::implicit() },
-                },
-                vec![doc! { "$set": {
-                    "details.indexed_id": "$output.nft_id",
-                } }],
-                None,
-            )
-            .await?;
-
-        collection
-            .update_many(
-                doc! { "output.kind": "foundry" },
-                vec![doc! { "$set": {
-                    "details.indexed_id": "$output.foundry_id",
-                } }],
-                None,
-            )
-            .await?;
-
-        collection.drop_index("output_alias_id_index", None).await?;
-
-        collection.drop_index("output_foundry_id_index", None).await?;
-
-        collection.drop_index("output_nft_id_index", None).await?;
-
-        collection
-            .create_index(
-                IndexModel::builder()
-                    .keys(doc! { "details.indexed_id": 1 })
-                    .options(
-                        IndexOptions::builder()
-                            .name("output_indexed_id_index".to_string())
-                            .partial_filter_expression(doc! {
-                                "details.indexed_id": { "$exists": true },
-                            })
-                            .build(),
-                    )
-                    .build(),
-                None,
-            )
-            .await?;
-
         Ok(())
     }
 }
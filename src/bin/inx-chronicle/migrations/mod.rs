@@ -5,13 +5,16 @@ use std::collections::HashMap;
 
 use async_trait::async_trait;
 use chronicle::db::{
-    mongodb::collections::{ApplicationStateCollection, MigrationVersion},
+    mongodb::collections::{
+        ApplicationStateCollection, BlockCollection, MigrationDirection, MigrationProgress, MigrationVersion,
+    },
     MongoDb,
 };
-use eyre::bail;
+use eyre::{bail, eyre};
 
 pub mod migrate_0;
 pub mod migrate_1;
+mod steps;
 
 pub type LatestMigration = migrate_1::Migrate;
 
@@ -23,7 +26,7 @@ const MIGRATIONS: &[&'static dyn DynMigration] = &[
     &migrate_1::Migrate,
 ];
 
-fn build_migrations(migrations: &[&'static dyn DynMigration]) -> HashMap<Option<usize>, &'static dyn DynMigration> {
+fn build_migrations<'a>(migrations: &[&'a dyn DynMigration]) -> HashMap<Option<usize>, &'a dyn DynMigration> {
     let mut map = HashMap::default();
     let mut prev_version = None;
     for &migration in migrations {
@@ -34,7 +37,22 @@ fn build_migrations(migrations: &[&'static dyn DynMigration]) -> HashMap<Option<
     map
 }
 
+/// A single step of a migration. The progress of a migration is recorded after every step, so an interrupted
+/// migration resumes with the step that did not complete. Steps must therefore be safe to apply more than once.
 #[async_trait]
+pub trait MigrationStep: Send + Sync {
+    /// Describes what the step does.
+    fn description(&self) -> String;
+
+    /// Counts the documents that are modified by the step.
+    async fn affected_documents(&self, db: &MongoDb) -> eyre::Result<u64>;
+
+    /// Applies the step.
+    async fn apply(&self, db: &MongoDb) -> eyre::Result<()>;
+}
+
+pub type MigrationSteps = Vec<Box<dyn MigrationStep>>;
+
 pub trait Migration {
     const ID: usize;
     const APP_VERSION: &'static str;
@@ -48,30 +66,68 @@ pub trait Migration {
         }
     }
 
-    async fn migrate(db: &MongoDb) -> eyre::Result<()>;
+    /// The steps that apply the migration, in order.
+    fn steps() -> MigrationSteps;
+
+    /// The steps that revert the migration, in order, or `None` if the migration cannot be reverted.
+    fn down_steps() -> Option<MigrationSteps> {
+        None
+    }
 }
 
-#[async_trait]
 trait DynMigration: Send + Sync {
     fn version(&self) -> MigrationVersion;
 
-    async fn migrate(&self, db: &MongoDb) -> eyre::Result<()>;
+    fn steps(&self, direction: MigrationDirection) -> Option<MigrationSteps>;
 }
 
-#[async_trait]
 impl<T: Migration + Send + Sync> DynMigration for T {
     fn version(&self) -> MigrationVersion {
         T::version()
     }
 
-    async fn migrate(&self, db: &MongoDb) -> eyre::Result<()> {
-        let version = self.version();
-        tracing::info!("Migrating to version {}", version);
-        T::migrate(db).await?;
-        db.collection::<ApplicationStateCollection>()
-            .set_last_migration(version)
-            .await?;
-        Ok(())
+    fn steps(&self, direction: MigrationDirection) -> Option<MigrationSteps> {
+        match direction {
+            MigrationDirection::Up => Some(T::steps()),
+            MigrationDirection::Down => T::down_steps(),
+        }
+    }
+}
+
+/// Records which migrations were applied to the database and how far an interrupted migration got.
+#[async_trait]
+trait MigrationState: Send + Sync {
+    async fn get_last_migration(&self) -> eyre::Result<Option<MigrationVersion>>;
+
+    async fn set_last_migration(&self, version: MigrationVersion) -> eyre::Result<()>;
+
+    async fn unset_last_migration(&self) -> eyre::Result<()>;
+
+    async fn get_migration_progress(&self) -> eyre::Result<Option<MigrationProgress>>;
+
+    async fn set_migration_progress(&self, progress: Option<&MigrationProgress>) -> eyre::Result<()>;
+}
+
+#[async_trait]
+impl MigrationState for ApplicationStateCollection {
+    async fn get_last_migration(&self) -> eyre::Result<Option<MigrationVersion>> {
+        Ok(self.get_last_migration().await?)
+    }
+
+    async fn set_last_migration(&self, version: MigrationVersion) -> eyre::Result<()> {
+        Ok(self.set_last_migration(version).await?)
+    }
+
+    async fn unset_last_migration(&self) -> eyre::Result<()> {
+        Ok(self.unset_last_migration().await?)
+    }
+
+    async fn get_migration_progress(&self) -> eyre::Result<Option<MigrationProgress>> {
+        Ok(self.get_migration_progress().await?)
+    }
+
+    async fn set_migration_progress(&self, progress: Option<&MigrationProgress>) -> eyre::Result<()> {
+        Ok(self.set_migration_progress(progress).await?)
     }
 }
 
@@ -91,7 +147,7 @@ pub async fn check_migration_version(db: &MongoDb) -> eyre::Result<()> {
                 .is_some()
             {
                 #[cfg(feature = "inx")]
-                migrate(db).await?;
+                migrate(db, false).await?;
                 #[cfg(not(feature = "inx"))]
                 bail!("expected migration {}, found none", latest_version);
             }
@@ -99,30 +155,55 @@ pub async fn check_migration_version(db: &MongoDb) -> eyre::Result<()> {
         Some(v) => {
             if v != latest_version {
                 #[cfg(feature = "inx")]
-                migrate(db).await?;
+                migrate(db, false).await?;
                 #[cfg(not(feature = "inx"))]
                 bail!("expected migration {}, found {}", latest_version, v);
             }
         }
     }
+    #[cfg(feature = "inx")]
+    convert_block_storage(db, false).await?;
     Ok(())
 }
 
-pub async fn migrate(db: &MongoDb) -> eyre::Result<()> {
-    let migrations = build_migrations(MIGRATIONS);
+/// Applies all pending migrations. A dry run only reports the steps that would be applied.
+pub async fn migrate(db: &MongoDb, dry_run: bool) -> eyre::Result<()> {
+    apply_migrations(db, &db.collection::<ApplicationStateCollection>(), MIGRATIONS, dry_run).await
+}
 
-    loop {
-        let last_migration = db
-            .collection::<ApplicationStateCollection>()
-            .get_last_migration()
-            .await?
-            .map(|mig| mig.id);
-        if matches!(last_migration, Some(v) if v == LatestMigration::ID) {
-            break;
+async fn apply_migrations(
+    db: &MongoDb,
+    application_state: &impl MigrationState,
+    migrations: &[&dyn DynMigration],
+    dry_run: bool,
+) -> eyre::Result<()> {
+    let latest_migration = migrations.last().map(|migration| migration.version().id);
+    let migrations = build_migrations(migrations);
+
+    if let Some(progress) = application_state.get_migration_progress().await? {
+        if progress.direction == MigrationDirection::Down {
+            bail!(
+                "reverting migration `{}` was interrupted; it must be completed with `migrate --down`",
+                progress.id
+            );
         }
+    }
+
+    let mut last_migration = application_state.get_last_migration().await?.map(|mig| mig.id);
+    while last_migration != latest_migration {
         match migrations.get(&last_migration) {
-            Some(migration) => {
-                migration.migrate(db).await?;
+            Some(&migration) => {
+                let version = migration.version();
+                tracing::info!("Migrating to version {}", version);
+                if dry_run && last_migration.is_some() {
+                    tracing::info!("Document counts do not reflect the changes of the preceding migrations.");
+                }
+                run_steps(db, application_state, migration, MigrationDirection::Up, dry_run).await?;
+                if !dry_run {
+                    application_state.set_last_migration(version.clone()).await?;
+                    application_state.set_migration_progress(None).await?;
+                }
+                last_migration = Some(version.id);
             }
             None => {
                 if let Some(id) = last_migration {
@@ -135,3 +216,324 @@ pub async fn migrate(db: &MongoDb) -> eyre::Result<()> {
     }
     Ok(())
 }
+
+/// Reverts the migration that was interrupted, or the latest migration if there is none. A dry run only reports the
+/// steps that would be applied.
+pub async fn revert(db: &MongoDb, dry_run: bool) -> eyre::Result<()> {
+    revert_migration(db, &db.collection::<ApplicationStateCollection>(), MIGRATIONS, dry_run).await
+}
+
+async fn revert_migration(
+    db: &MongoDb,
+    application_state: &impl MigrationState,
+    migrations: &[&dyn DynMigration],
+    dry_run: bool,
+) -> eyre::Result<()> {
+    let id = match application_state.get_migration_progress().await? {
+        Some(progress) => progress.id,
+        None => match application_state.get_last_migration().await? {
+            Some(version) => version.id,
+            None => bail!("there is no migration to revert"),
+        },
+    };
+    let position = migrations
+        .iter()
+        .position(|migration| migration.version().id == id)
+        .ok_or_else(|| eyre!("cannot revert unknown migration `{id}`"))?;
+    let migration = migrations[position];
+
+    tracing::info!("Reverting migration {}", migration.version());
+    run_steps(db, application_state, migration, MigrationDirection::Down, dry_run).await?;
+    if !dry_run {
+        match position.checked_sub(1) {
+            Some(previous) => {
+                application_state
+                    .set_last_migration(migrations[previous].version())
+                    .await?
+            }
+            None => application_state.unset_last_migration().await?,
+        }
+        application_state.set_migration_progress(None).await?;
+    }
+    Ok(())
+}
+
+/// Converts the stored blocks to the configured storage mode if the database records a different one. A dry run only
+/// reports the number of blocks that would be converted.
+pub async fn convert_block_storage(db: &MongoDb, dry_run: bool) -> eyre::Result<()> {
+    let application_state = db.collection::<ApplicationStateCollection>();
+    let mode = db.block_storage_mode();
+    if application_state.get_block_storage_mode().await? == mode {
+        return Ok(());
+    }
+
+    let blocks = db.collection::<BlockCollection>();
+    if dry_run {
+        tracing::info!(
+            "Converting blocks to {:?} storage ({} documents affected)",
+            mode,
+            blocks.count_blocks_to_convert(mode).await?
+        );
+    } else {
+        tracing::info!("Converting blocks to {:?} storage", mode);
+        blocks.convert_blocks(mode).await?;
+        application_state.set_block_storage_mode(mode).await?;
+    }
+    Ok(())
+}
+
+/// Applies the steps of a migration in the given direction, starting after the last step that was recorded as
+/// completed.
+async fn run_steps(
+    db: &MongoDb,
+    application_state: &impl MigrationState,
+    migration: &dyn DynMigration,
+    direction: MigrationDirection,
+    dry_run: bool,
+) -> eyre::Result<()> {
+    let version = migration.version();
+    let steps = migration
+        .steps(direction)
+        .ok_or_else(|| eyre!("migration {} cannot be reverted", version))?;
+
+    let mut progress = match application_state.get_migration_progress().await? {
+        Some(progress) if progress.id == version.id && progress.direction == direction => {
+            tracing::info!(
+                "Resuming migration {} ({}) after step {}/{}",
+                version,
+                direction,
+                progress.completed_steps,
+                steps.len()
+            );
+            progress
+        }
+        // A failed migration is reverted from the start, as its down steps cannot know how far it got.
+        Some(progress) if progress.id == version.id => MigrationProgress {
+            direction,
+            completed_steps: 0,
+            ..progress
+        },
+        Some(progress) => bail!(
+            "migration `{}` ({}) is in progress; database is in invalid state",
+            progress.id,
+            progress.direction
+        ),
+        None => MigrationProgress {
+            id: version.id,
+            direction,
+            completed_steps: 0,
+        },
+    };
+
+    for (i, step) in steps.iter().enumerate().skip(progress.completed_steps) {
+        if dry_run {
+            tracing::info!(
+                "Step {}/{}: {} ({} documents affected)",
+                i + 1,
+                steps.len(),
+                step.description(),
+                step.affected_documents(db).await?
+            );
+        } else {
+            tracing::info!("Step {}/{}: {}", i + 1, steps.len(), step.description());
+            step.apply(db).await?;
+            progress.completed_steps = i + 1;
+            application_state.set_migration_progress(Some(&progress)).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use chronicle::db::MongoDbConfig;
+
+    use super::*;
+
+    /// Keeps the migration state in memory.
+    #[derive(Default)]
+    struct TestState {
+        last_migration: Mutex<Option<MigrationVersion>>,
+        progress: Mutex<Option<MigrationProgress>>,
+    }
+
+    #[async_trait]
+    impl MigrationState for TestState {
+        async fn get_last_migration(&self) -> eyre::Result<Option<MigrationVersion>> {
+            Ok(self.last_migration.lock().unwrap().clone())
+        }
+
+        async fn set_last_migration(&self, version: MigrationVersion) -> eyre::Result<()> {
+            *self.last_migration.lock().unwrap() = Some(version);
+            Ok(())
+        }
+
+        async fn unset_last_migration(&self) -> eyre::Result<()> {
+            *self.last_migration.lock().unwrap() = None;
+            Ok(())
+        }
+
+        async fn get_migration_progress(&self) -> eyre::Result<Option<MigrationProgress>> {
+            Ok(self.progress.lock().unwrap().clone())
+        }
+
+        async fn set_migration_progress(&self, progress: Option<&MigrationProgress>) -> eyre::Result<()> {
+            *self.progress.lock().unwrap() = progress.cloned();
+            Ok(())
+        }
+    }
+
+    /// Records the steps that were applied and fails the step with the given name once.
+    #[derive(Clone, Default)]
+    struct StepLog {
+        applied: Arc<Mutex<Vec<String>>>,
+        fail_at: Arc<Mutex<Option<String>>>,
+    }
+
+    impl StepLog {
+        fn fail_at(&self, name: &str) {
+            *self.fail_at.lock().unwrap() = Some(name.to_string());
+        }
+
+        fn take(&self) -> Vec<String> {
+            std::mem::take(&mut self.applied.lock().unwrap())
+        }
+    }
+
+    struct TestStep {
+        name: String,
+        log: StepLog,
+    }
+
+    #[async_trait]
+    impl MigrationStep for TestStep {
+        fn description(&self) -> String {
+            self.name.clone()
+        }
+
+        async fn affected_documents(&self, _: &MongoDb) -> eyre::Result<u64> {
+            Ok(0)
+        }
+
+        async fn apply(&self, _: &MongoDb) -> eyre::Result<()> {
+            let mut fail_at = self.log.fail_at.lock().unwrap();
+            if fail_at.as_ref() == Some(&self.name) {
+                *fail_at = None;
+                bail!("step `{}` failed", self.name);
+            }
+            self.log.applied.lock().unwrap().push(self.name.clone());
+            Ok(())
+        }
+    }
+
+    /// A migration with three steps in each direction.
+    struct TestMigration {
+        id: usize,
+        log: StepLog,
+    }
+
+    impl DynMigration for TestMigration {
+        fn version(&self) -> MigrationVersion {
+            MigrationVersion {
+                id: self.id,
+                app_version: "test".to_string(),
+                date: time::macros::date!(2023 - 01 - 01),
+            }
+        }
+
+        fn steps(&self, direction: MigrationDirection) -> Option<MigrationSteps> {
+            Some(
+                (0..3)
+                    .map(|i| {
+                        Box::new(TestStep {
+                            name: format!("{}-{direction}-{i}", self.id),
+                            log: self.log.clone(),
+                        }) as _
+                    })
+                    .collect(),
+            )
+        }
+    }
+
+    /// Sets up two migrations of which the first one was applied.
+    async fn setup() -> (MongoDb, TestState, StepLog, [TestMigration; 2]) {
+        // The handle does not connect to a server until it is used, which the test steps do not do.
+        let db = MongoDb::connect(&MongoDbConfig::default()).await.unwrap();
+        let log = StepLog::default();
+        let migrations = [0, 1].map(|id| TestMigration { id, log: log.clone() });
+        let state = TestState::default();
+        state.set_last_migration(migrations[0].version()).await.unwrap();
+        (db, state, log, migrations)
+    }
+
+    #[tokio::test]
+    async fn interrupted_migration_resumes() {
+        let (db, state, log, [first, second]) = setup().await;
+        let migrations: [&dyn DynMigration; 2] = [&first, &second];
+
+        log.fail_at("1-up-2");
+        assert!(apply_migrations(&db, &state, &migrations, false).await.is_err());
+        assert_eq!(log.take(), ["1-up-0", "1-up-1"]);
+        assert_eq!(state.get_last_migration().await.unwrap(), Some(first.version()));
+        assert_eq!(
+            state.get_migration_progress().await.unwrap(),
+            Some(MigrationProgress {
+                id: 1,
+                direction: MigrationDirection::Up,
+                completed_steps: 2,
+            })
+        );
+
+        apply_migrations(&db, &state, &migrations, false).await.unwrap();
+        assert_eq!(log.take(), ["1-up-2"]);
+        assert_eq!(state.get_last_migration().await.unwrap(), Some(second.version()));
+        assert_eq!(state.get_migration_progress().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn interrupted_revert_blocks_migration() {
+        let (db, state, log, [first, second]) = setup().await;
+        let migrations: [&dyn DynMigration; 2] = [&first, &second];
+        state.set_last_migration(second.version()).await.unwrap();
+
+        log.fail_at("1-down-1");
+        assert!(revert_migration(&db, &state, &migrations, false).await.is_err());
+        assert_eq!(log.take(), ["1-down-0"]);
+
+        assert!(apply_migrations(&db, &state, &migrations, false).await.is_err());
+        assert!(log.take().is_empty());
+
+        revert_migration(&db, &state, &migrations, false).await.unwrap();
+        assert_eq!(log.take(), ["1-down-1", "1-down-2"]);
+        assert_eq!(state.get_last_migration().await.unwrap(), Some(first.version()));
+        assert_eq!(state.get_migration_progress().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn interrupted_migration_is_reverted_from_the_start() {
+        let (db, state, log, [first, second]) = setup().await;
+        let migrations: [&dyn DynMigration; 2] = [&first, &second];
+
+        log.fail_at("1-up-2");
+        assert!(apply_migrations(&db, &state, &migrations, false).await.is_err());
+        log.take();
+
+        revert_migration(&db, &state, &migrations, false).await.unwrap();
+        assert_eq!(log.take(), ["1-down-0", "1-down-1", "1-down-2"]);
+        assert_eq!(state.get_last_migration().await.unwrap(), Some(first.version()));
+        assert_eq!(state.get_migration_progress().await.unwrap(), None);
+    }
+
+    #[test]
+    fn migrations_are_ordered() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version().id, i);
+        }
+        assert_eq!(
+            MIGRATIONS.last().map(|migration| migration.version()),
+            Some(<LatestMigration as Migration>::version())
+        );
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::marker::PhantomData;
+
+use async_trait::async_trait;
+use chronicle::db::{MongoDb, MongoDbCollection, MongoDbCollectionExt};
+use mongodb::{bson::Document, IndexModel};
+
+use super::MigrationStep;
+
+/// Updates all documents of a collection that match a filter.
+pub struct UpdateMany<C> {
+    description: &'static str,
+    filter: Document,
+    update: Vec<Document>,
+    _collection: PhantomData<C>,
+}
+
+impl<C> UpdateMany<C> {
+    pub fn new(description: &'static str, filter: Document, update: impl Into<Vec<Document>>) -> Self {
+        Self {
+            description,
+            filter,
+            update: update.into(),
+            _collection: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<C: MongoDbCollection + Send + Sync> MigrationStep for UpdateMany<C> {
+    fn description(&self) -> String {
+        format!("{} in `{}`", self.description, C::NAME)
+    }
+
+    async fn affected_documents(&self, db: &MongoDb) -> eyre::Result<u64> {
+        Ok(db
+            .collection::<C>()
+            .collection()
+            .count_documents(self.filter.clone(), None)
+            .await?)
+    }
+
+    async fn apply(&self, db: &MongoDb) -> eyre::Result<()> {
+        db.collection::<C>()
+            .update_many(self.filter.clone(), self.update.clone(), None)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Creates an index on a collection. Existing indexes with the same definition are left untouched.
+pub struct CreateIndex<C> {
+    model: IndexModel,
+    _collection: PhantomData<C>,
+}
+
+impl<C> CreateIndex<C> {
+    pub fn new(model: IndexModel) -> Self {
+        Self {
+            model,
+            _collection: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<C: MongoDbCollection + Send + Sync> MigrationStep for CreateIndex<C> {
+    fn description(&self) -> String {
+        format!(
+            "create index `{}` in `{}`",
+            self.model
+                .options
+                .as_ref()
+                .and_then(|o| o.name.as_deref())
+                .unwrap_or_default(),
+            C::NAME
+        )
+    }
+
+    async fn affected_documents(&self, _db: &MongoDb) -> eyre::Result<u64> {
+        Ok(0)
+    }
+
+    async fn apply(&self, db: &MongoDb) -> eyre::Result<()> {
+        db.collection::<C>().create_index(self.model.clone(), None).await?;
+        Ok(())
+    }
+}
+
+/// Drops an index of a collection. Missing indexes are ignored.
+pub struct DropIndex<C> {
+    name: &'static str,
+    _collection: PhantomData<C>,
+}
+
+impl<C> DropIndex<C> {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _collection: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<C: MongoDbCollection + Send + Sync> MigrationStep for DropIndex<C> {
+    fn description(&self) -> String {
+        format!("drop index `{}` in `{}`", self.name, C::NAME)
+    }
+
+    async fn affected_documents(&self, _db: &MongoDb) -> eyre::Result<u64> {
+        Ok(0)
+    }
+
+    async fn apply(&self, db: &MongoDb) -> eyre::Result<()> {
+        db.collection::<C>().drop_index(self.name, None).await?;
+        Ok(())
+    }
+}
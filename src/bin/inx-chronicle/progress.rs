@@ -0,0 +1,259 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tracks the synchronization progress of the INX worker so that it can be logged and reported by the API.
+
+// The progress is only updated by the INX worker.
+#![cfg_attr(not(feature = "inx"), allow(dead_code))]
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use chronicle::model::tangle::MilestoneIndex;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+/// The sliding windows over which the sync rate is measured.
+pub const RATE_WINDOWS: [Duration; 3] = [
+    Duration::from_secs(60),
+    Duration::from_secs(5 * 60),
+    Duration::from_secs(15 * 60),
+];
+
+/// The index of the window in [`RATE_WINDOWS`] that is used to estimate the remaining time.
+const ETA_WINDOW: usize = 1;
+
+/// The phase that the INX worker is in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncPhase {
+    /// The INX worker is not running.
+    Idle,
+    /// The INX worker is connecting to the node.
+    Connecting,
+    /// The ledger is bootstrapped from the unspent outputs of the node.
+    ReadingUnspentOutputs,
+    /// The ledger is bootstrapped from a snapshot file.
+    ReadingSnapshot,
+    /// The INX worker is catching up with the node.
+    Syncing,
+    /// The INX worker has synced the confirmed milestone of the node.
+    Synced,
+}
+
+impl Default for SyncPhase {
+    fn default() -> Self {
+        Self::Idle
+    }
+}
+
+impl fmt::Display for SyncPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Idle => "idle",
+            Self::Connecting => "connecting",
+            Self::ReadingUnspentOutputs => "reading unspent outputs",
+            Self::ReadingSnapshot => "reading snapshot",
+            Self::Syncing => "syncing",
+            Self::Synced => "synced",
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct SyncState {
+    phase: SyncPhase,
+    node_confirmed_index: Option<MilestoneIndex>,
+    synced_index: Option<MilestoneIndex>,
+    /// The synced milestones within the largest window, oldest first.
+    history: VecDeque<(Instant, MilestoneIndex)>,
+}
+
+impl SyncState {
+    fn set_phase(&mut self, phase: SyncPhase) {
+        if self.phase != phase {
+            // The worker switches between syncing and synced whenever the node confirms a new milestone.
+            if matches!(self.phase, SyncPhase::Syncing | SyncPhase::Synced) {
+                debug!("Entering sync phase: {phase}.");
+            } else {
+                info!("Entering sync phase: {phase}.");
+            }
+            self.phase = phase;
+        }
+    }
+}
+
+/// A snapshot of the synchronization progress.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyncReport {
+    pub phase: SyncPhase,
+    pub node_confirmed_index: Option<MilestoneIndex>,
+    pub synced_index: Option<MilestoneIndex>,
+    /// The number of milestones that the node confirmed but that have not been synced yet.
+    pub behind_by: Option<u32>,
+    /// The synced milestones per second for each of the [`RATE_WINDOWS`].
+    pub milestones_per_second: [Option<f64>; RATE_WINDOWS.len()],
+    /// The estimated time until the confirmed milestone of the node is synced.
+    pub eta: Option<Duration>,
+}
+
+impl fmt::Display for SyncReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Sync phase: {}", self.phase)?;
+        if let (Some(synced), Some(confirmed)) = (self.synced_index, self.node_confirmed_index) {
+            write!(f, ", synced milestone {synced} of {confirmed}")?;
+        }
+        if let Some(behind_by) = self.behind_by {
+            write!(f, ", {behind_by} behind")?;
+        }
+        if let Some(rate) = self.milestones_per_second[ETA_WINDOW] {
+            write!(f, ", {rate:.2} milestones/s")?;
+        }
+        if let Some(eta) = self.eta {
+            write!(f, ", ETA {}", humantime::format_duration(eta))?;
+        }
+        write!(f, ".")
+    }
+}
+
+/// A shared handle to the synchronization progress.
+#[derive(Clone, Debug, Default)]
+pub struct SyncProgress(Arc<RwLock<SyncState>>);
+
+impl SyncProgress {
+    /// Sets the current phase and logs it if it changed.
+    pub fn set_phase(&self, phase: SyncPhase) {
+        self.0.write().unwrap().set_phase(phase);
+    }
+
+    /// Updates the confirmed milestone of the node. The index never decreases.
+    pub fn set_node_confirmed_index(&self, index: MilestoneIndex) {
+        let mut state = self.0.write().unwrap();
+        state.node_confirmed_index = state.node_confirmed_index.max(Some(index));
+    }
+
+    /// Records that a milestone has been synced.
+    pub fn record_milestone(&self, index: MilestoneIndex) {
+        self.record_milestone_at(index, Instant::now())
+    }
+
+    /// Returns the current progress.
+    pub fn report(&self) -> SyncReport {
+        self.report_at(Instant::now())
+    }
+
+    fn record_milestone_at(&self, index: MilestoneIndex, now: Instant) {
+        let mut state = self.0.write().unwrap();
+        state.synced_index = Some(index);
+        state.node_confirmed_index = state.node_confirmed_index.max(Some(index));
+        let phase = if state.node_confirmed_index == Some(index) {
+            SyncPhase::Synced
+        } else {
+            SyncPhase::Syncing
+        };
+        state.set_phase(phase);
+        state.history.push_back((now, index));
+        let max_window = RATE_WINDOWS[RATE_WINDOWS.len() - 1];
+        while matches!(state.history.front(), Some((time, _)) if now.duration_since(*time) > max_window) {
+            state.history.pop_front();
+        }
+    }
+
+    fn report_at(&self, now: Instant) -> SyncReport {
+        let state = self.0.read().unwrap();
+        let behind_by = state
+            .node_confirmed_index
+            .zip(state.synced_index)
+            .map(|(confirmed, synced)| confirmed.0.saturating_sub(synced.0));
+        let mut milestones_per_second = [None; RATE_WINDOWS.len()];
+        if let Some(&(_, last_index)) = state.history.back() {
+            for (rate, window) in milestones_per_second.iter_mut().zip(RATE_WINDOWS) {
+                // The rate is measured up to now, so that it decreases while the sync is stalled.
+                *rate = match state
+                    .history
+                    .iter()
+                    .find(|(time, _)| now.saturating_duration_since(*time) <= window)
+                {
+                    Some(&(first_time, first_index)) => {
+                        let elapsed = now.saturating_duration_since(first_time).as_secs_f64();
+                        (elapsed > 0.0).then(|| (last_index.0 - first_index.0) as f64 / elapsed)
+                    }
+                    // No milestone was synced within the window.
+                    None => Some(0.0),
+                };
+            }
+        }
+        let eta = behind_by
+            .zip(milestones_per_second[ETA_WINDOW])
+            .and_then(|(behind_by, rate)| {
+                if rate > 0.0 {
+                    Some(Duration::from_secs((behind_by as f64 / rate).ceil() as u64))
+                } else {
+                    None
+                }
+            });
+        SyncReport {
+            phase: state.phase,
+            node_confirmed_index: state.node_confirmed_index,
+            synced_index: state.synced_index,
+            behind_by,
+            milestones_per_second,
+            eta,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sync_progress() {
+        let progress = SyncProgress::default();
+        assert_eq!(progress.report().phase, SyncPhase::Idle);
+
+        let start = Instant::now();
+        progress.set_phase(SyncPhase::Connecting);
+        progress.set_node_confirmed_index(1000.into());
+        progress.record_milestone_at(100.into(), start);
+        let report = progress.report_at(start);
+        assert_eq!(report.phase, SyncPhase::Syncing);
+        assert_eq!(report.behind_by, Some(900));
+        assert_eq!(report.milestones_per_second, [None; 3]);
+        assert_eq!(report.eta, None);
+
+        // Two milestones per second for two minutes.
+        for i in 1..=240 {
+            progress.record_milestone_at((100 + i).into(), start + Duration::from_millis(500 * i as u64));
+        }
+        let now = start + Duration::from_secs(120);
+        let report = progress.report_at(now);
+        assert_eq!(report.synced_index, Some(340.into()));
+        assert_eq!(report.behind_by, Some(660));
+        for rate in report.milestones_per_second {
+            assert!((rate.unwrap() - 2.0).abs() < 1e-9);
+        }
+        assert_eq!(report.eta, Some(Duration::from_secs(330)));
+
+        // The rate decreases while no milestones are synced.
+        let report = progress.report_at(now + Duration::from_secs(30));
+        assert!((report.milestones_per_second[0].unwrap() - 1.0).abs() < 1e-9);
+        assert!((report.milestones_per_second[2].unwrap() - 240.0 / 150.0).abs() < 1e-9);
+        let report = progress.report_at(now + Duration::from_secs(15 * 60));
+        assert_eq!(report.milestones_per_second, [Some(0.0); 3]);
+        assert_eq!(report.eta, None);
+
+        // The confirmed index of the node never decreases.
+        progress.set_node_confirmed_index(341.into());
+        assert_eq!(progress.report_at(now).node_confirmed_index, Some(1000.into()));
+        progress.record_milestone_at(1000.into(), now);
+        let report = progress.report_at(now);
+        assert_eq!(report.phase, SyncPhase::Synced);
+        assert_eq!(report.behind_by, Some(0));
+        assert_eq!(report.eta, Some(Duration::ZERO));
+    }
+}
@@ -0,0 +1,25 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Holds the `KvDb` config and its defaults.
+
+use std::path::PathBuf;
+
+/// The default path of the database directory.
+pub const DEFAULT_PATH: &str = "./chronicle-db";
+
+/// The [`super::KvDb`] config.
+#[must_use]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct KvDbConfig {
+    /// The path of the database directory.
+    pub path: PathBuf,
+}
+
+impl Default for KvDbConfig {
+    fn default() -> Self {
+        Self {
+            path: DEFAULT_PATH.into(),
+        }
+    }
+}
@@ -0,0 +1,207 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Holds the `KvDb` type, an embedded on-disk storage backend.
+//!
+//! Every kind of record is kept in its own tree. Keys are encoded big-endian so that the lexicographic order of the
+//! keys matches the order of the indexes that are used by the MongoDB backend. Values are encoded as BSON.
+
+pub mod config;
+mod storage;
+
+use packable::PackableExt;
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+pub use self::config::KvDbConfig;
+use crate::{
+    db::storage::StorageError,
+    model::{
+        payload::TransactionId,
+        tangle::MilestoneIndex,
+        utxo::{Address, OutputId},
+        BlockId,
+    },
+};
+
+/// An error that is returned by the [`KvDb`].
+#[derive(Debug, Error)]
+#[allow(missing_docs)]
+pub enum KvDbError {
+    #[error(transparent)]
+    Sled(#[from] sled::Error),
+    #[error("failed to serialize record: {0}")]
+    Serialize(#[from] mongodb::bson::ser::Error),
+    #[error("failed to deserialize record: {0}")]
+    Deserialize(#[from] mongodb::bson::de::Error),
+}
+
+impl From<sled::Error> for StorageError {
+    fn from(value: sled::Error) -> Self {
+        Self::Kv(value.into())
+    }
+}
+
+/// A handle to the embedded key-value database.
+#[derive(Clone, Debug)]
+pub struct KvDb {
+    db: sled::Db,
+}
+
+impl KvDb {
+    /// Opens the database at the configured path, creating it if it does not exist.
+    pub fn open(config: &KvDbConfig) -> Result<Self, KvDbError> {
+        Ok(Self {
+            db: sled::Config::new().path(&config.path).open()?,
+        })
+    }
+
+    /// Opens a temporary database that is removed once the last handle is dropped.
+    pub fn open_temporary() -> Result<Self, KvDbError> {
+        Ok(Self {
+            db: sled::Config::new().temporary(true).open()?,
+        })
+    }
+
+    /// Returns the size of the database on disk.
+    pub fn size(&self) -> Result<u64, KvDbError> {
+        Ok(self.db.size_on_disk()?)
+    }
+
+    /// Flushes all dirty data to disk.
+    pub async fn flush(&self) -> Result<(), KvDbError> {
+        self.db.flush_async().await?;
+        Ok(())
+    }
+
+    fn tree(&self, name: &str) -> Result<sled::Tree, KvDbError> {
+        Ok(self.db.open_tree(name)?)
+    }
+}
+
+/// The names of the trees.
+mod trees {
+    pub(super) const APPLICATION_STATE: &str = "application_state";
+    pub(super) const BLOCKS: &str = "blocks";
+    pub(super) const BLOCKS_BY_TRANSACTION: &str = "blocks_by_transaction";
+    pub(super) const BLOCKS_BY_MILESTONE: &str = "blocks_by_milestone";
+    pub(super) const BLOCK_CHILDREN: &str = "block_children";
+    pub(super) const SPENT_BY: &str = "spent_by";
+    pub(super) const OUTPUTS: &str = "outputs";
+    pub(super) const OUTPUTS_BY_BOOKED: &str = "outputs_by_booked";
+    pub(super) const OUTPUTS_BY_SPENT: &str = "outputs_by_spent";
+    pub(super) const LEDGER_UPDATES: &str = "ledger_updates";
+    pub(super) const LEDGER_UPDATES_BY_MILESTONE: &str = "ledger_updates_by_milestone";
+    pub(super) const MILESTONES: &str = "milestones";
+    pub(super) const MILESTONES_BY_ID: &str = "milestones_by_id";
+    pub(super) const RECEIPTS_BY_MIGRATED_AT: &str = "receipts_by_migrated_at";
+    pub(super) const TREASURY: &str = "treasury";
+    pub(super) const PROTOCOL_UPDATES: &str = "protocol_updates";
+    pub(super) const PROTOCOL_UPDATES_BY_VERSION: &str = "protocol_updates_by_version";
+    pub(super) const CONFIGURATION_UPDATES: &str = "configuration_updates";
+    pub(super) const API_KEYS: &str = "api_keys";
+}
+
+fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>, KvDbError> {
+    Ok(mongodb::bson::to_vec(value)?)
+}
+
+fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, KvDbError> {
+    Ok(mongodb::bson::from_slice(bytes)?)
+}
+
+/// Encodes the parts of a composite key.
+#[derive(Default)]
+struct Key(Vec<u8>);
+
+impl Key {
+    fn bytes(mut self, bytes: &[u8]) -> Self {
+        self.0.extend_from_slice(bytes);
+        self
+    }
+
+    fn index(self, index: MilestoneIndex) -> Self {
+        self.bytes(&index.0.to_be_bytes())
+    }
+
+    fn u32(self, value: u32) -> Self {
+        self.bytes(&value.to_be_bytes())
+    }
+
+    fn block_id(self, block_id: &BlockId) -> Self {
+        self.bytes(&block_id.0)
+    }
+
+    fn transaction_id(self, transaction_id: &TransactionId) -> Self {
+        self.bytes(&transaction_id.0)
+    }
+
+    fn output_id(self, output_id: &OutputId) -> Self {
+        self.transaction_id(&output_id.transaction_id)
+            .bytes(&output_id.index.to_be_bytes())
+    }
+
+    fn address(self, address: &Address) -> Self {
+        self.bytes(&iota_types::block::address::Address::from(*address).pack_to_vec())
+    }
+
+    fn spent(self, is_spent: bool) -> Self {
+        self.bytes(&[is_spent as u8])
+    }
+}
+
+impl AsRef<[u8]> for Key {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Key> for sled::IVec {
+    fn from(key: Key) -> Self {
+        key.0.into()
+    }
+}
+
+const MILESTONE_INDEX_LENGTH: usize = std::mem::size_of::<u32>();
+const OUTPUT_ID_LENGTH: usize = TransactionId::LENGTH + std::mem::size_of::<u16>();
+
+/// Decodes the parts of a composite key.
+struct KeyReader<'a>(&'a [u8]);
+
+impl<'a> KeyReader<'a> {
+    fn skip(mut self, len: usize) -> Self {
+        self.0 = &self.0[len..];
+        self
+    }
+
+    fn take(&mut self, len: usize) -> &'a [u8] {
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        head
+    }
+
+    fn index(&mut self) -> MilestoneIndex {
+        // Unwrap: The length is correct.
+        MilestoneIndex(u32::from_be_bytes(
+            self.take(MILESTONE_INDEX_LENGTH).try_into().unwrap(),
+        ))
+    }
+
+    fn block_id(&mut self) -> BlockId {
+        // Unwrap: The length is correct.
+        BlockId(self.take(BlockId::LENGTH).try_into().unwrap())
+    }
+
+    fn output_id(&mut self) -> OutputId {
+        let bytes = self.take(OUTPUT_ID_LENGTH);
+        // Unwrap: The lengths are correct.
+        OutputId {
+            transaction_id: TransactionId(bytes[..TransactionId::LENGTH].try_into().unwrap()),
+            index: u16::from_be_bytes(bytes[TransactionId::LENGTH..].try_into().unwrap()),
+        }
+    }
+
+    fn is_spent(&mut self) -> bool {
+        self.take(1)[0] != 0
+    }
+}
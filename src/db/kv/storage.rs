@@ -0,0 +1,986 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implements the storage traits for [`KvDb`].
+
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use packable::PackableExt;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::{decode, encode, trees, Key, KeyReader, KvDb, KvDbError, MILESTONE_INDEX_LENGTH};
+use crate::{
+    db::{
+        mongodb::collections::{
+            ApiKeyDocument, ApplicationStateDocument, BlockMetadataResult, BlockRawResult, ConfigurationUpdateDocument,
+            IncludedBlockMetadataResult, IncludedBlockResult, LedgerUpdateByAddressRecord,
+            LedgerUpdateByMilestoneRecord, MigrationVersion, OutputMetadataResult, OutputWithMetadataResult,
+            ProtocolUpdateDocument, SortOrder, TreasuryResult, UtxoChangesResult,
+        },
+        storage::{
+            ApiKeyStorage, ApplicationStateStorage, BlockStorage, ConfigurationUpdateStorage, LedgerUpdateStorage,
+            MilestoneStorage, OutputStorage, ProtocolUpdateStorage, StorageBackend, StorageError, TreasuryStorage,
+        },
+    },
+    model::{
+        ledger::{LedgerOutput, LedgerSpent, RentStructureBytes},
+        metadata::{BlockMetadata, LedgerInclusionState, SpentMetadata},
+        node::NodeConfiguration,
+        payload::{MilestoneId, MilestoneOption, MilestonePayload, Payload, TransactionEssence, TransactionId},
+        tangle::{MilestoneIndex, MilestoneIndexTimestamp, MilestoneTimestamp},
+        utxo::{Address, Input, Output, OutputId},
+        Block, BlockId, ProtocolParameters,
+    },
+    tangle::{BlockData, IngestionFilter, MilestoneStats},
+};
+
+const APPLICATION_STATE_KEY: &[u8] = b"state";
+
+/// The number of items that a stream over a tree buffers ahead of its consumer.
+const STREAM_BUFFER_SIZE: usize = 1000;
+
+#[derive(Serialize, Deserialize)]
+struct BlockRecord {
+    #[serde(with = "serde_bytes")]
+    raw: Vec<u8>,
+    metadata: BlockMetadata,
+}
+
+#[derive(Serialize, Deserialize)]
+struct OutputRecord {
+    output: Output,
+    block_id: BlockId,
+    booked: MilestoneIndexTimestamp,
+    spent_metadata: Option<SpentMetadata>,
+    rent_structure: RentStructureBytes,
+}
+
+impl OutputRecord {
+    fn into_metadata(self, output_id: OutputId) -> OutputMetadataResult {
+        OutputMetadataResult {
+            output_id,
+            block_id: self.block_id,
+            booked: self.booked,
+            spent_metadata: self.spent_metadata,
+        }
+    }
+}
+
+impl From<&LedgerOutput> for OutputRecord {
+    fn from(value: &LedgerOutput) -> Self {
+        Self {
+            output: value.output.clone(),
+            block_id: value.block_id,
+            booked: value.booked,
+            spent_metadata: None,
+            rent_structure: value.rent_structure,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct MilestoneRecord {
+    milestone_id: MilestoneId,
+    at: MilestoneIndexTimestamp,
+    payload: MilestonePayload,
+    signature_verified: Option<bool>,
+    #[serde(default)]
+    stats: Option<MilestoneStats>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AddressRecord {
+    address: Address,
+}
+
+/// Streams the items of an iterator over the database. The iterator runs on a blocking thread, so that disk reads and
+/// decoding do not stall the async runtime, and pauses while the buffer of the stream is full.
+fn spawn_stream<T: Send + 'static>(iter: impl Iterator<Item = T> + Send + 'static) -> BoxStream<'static, T> {
+    let (sender, receiver) = tokio::sync::mpsc::channel(STREAM_BUFFER_SIZE);
+    tokio::task::spawn_blocking(move || {
+        for item in iter {
+            // The stream was dropped, so nobody is interested in the remaining items.
+            if sender.blocking_send(item).is_err() {
+                break;
+            }
+        }
+    });
+    Box::pin(tokio_stream::wrappers::ReceiverStream::new(receiver))
+}
+
+fn unpack_block(raw: Vec<u8>) -> Block {
+    // Unwrap: The raw bytes were validated by the node.
+    iota_types::block::Block::unpack_unverified(raw).unwrap().into()
+}
+
+impl KvDb {
+    fn get<T: DeserializeOwned>(&self, tree: &str, key: impl AsRef<[u8]>) -> Result<Option<T>, KvDbError> {
+        self.tree(tree)?.get(key)?.map(|value| decode(&value)).transpose()
+    }
+
+    fn insert<T: Serialize>(&self, tree: &str, key: impl AsRef<[u8]>, value: &T) -> Result<(), KvDbError> {
+        self.tree(tree)?.insert(key, encode(value)?)?;
+        Ok(())
+    }
+
+    /// Gets the last record with a key that is less than or equal to the given index.
+    fn get_latest<T: DeserializeOwned>(
+        &self,
+        tree: &str,
+        index: Option<MilestoneIndex>,
+    ) -> Result<Option<T>, KvDbError> {
+        let tree = self.tree(tree)?;
+        let entry = match index {
+            Some(index) => tree.range(..=Key::default().index(index)).next_back().transpose()?,
+            None => tree.last()?,
+        };
+        entry.map(|(_, value)| decode(&value)).transpose()
+    }
+
+    fn get_application_state(&self) -> Result<ApplicationStateDocument, KvDbError> {
+        Ok(self
+            .get(trees::APPLICATION_STATE, APPLICATION_STATE_KEY)?
+            .unwrap_or(ApplicationStateDocument {
+                starting_index: None,
+                last_migration: None,
+                ingestion_filter: None,
+                migration_progress: None,
+                block_storage_mode: None,
+            }))
+    }
+
+    fn update_application_state(&self, f: impl FnOnce(&mut ApplicationStateDocument)) -> Result<(), KvDbError> {
+        let mut state = self.get_application_state()?;
+        f(&mut state);
+        self.insert(trees::APPLICATION_STATE, APPLICATION_STATE_KEY, &state)
+    }
+
+    fn get_block_record(&self, block_id: &BlockId) -> Result<Option<BlockRecord>, KvDbError> {
+        self.get(trees::BLOCKS, block_id.0)
+    }
+
+    fn get_block_id_for_transaction(&self, transaction_id: &TransactionId) -> Result<Option<BlockId>, KvDbError> {
+        Ok(self
+            .tree(trees::BLOCKS_BY_TRANSACTION)?
+            .get(transaction_id.0)?
+            .map(|block_id| KeyReader(&block_id).block_id()))
+    }
+
+    fn get_output_record(&self, output_id: &OutputId) -> Result<Option<OutputRecord>, KvDbError> {
+        self.get(trees::OUTPUTS, Key::default().output_id(output_id))
+    }
+
+    fn get_output_ids(&self, tree: &str, index: MilestoneIndex) -> Result<Vec<OutputId>, KvDbError> {
+        self.tree(tree)?
+            .scan_prefix(Key::default().index(index))
+            .keys()
+            .map(|key| Ok(KeyReader(&key?).skip(MILESTONE_INDEX_LENGTH).output_id()))
+            .collect()
+    }
+
+    /// Gets the receipts ordered by the index of the milestone that contains them. Receipts are keyed by their
+    /// `migrated_at` index, so only the receipts that are requested are read.
+    fn get_receipts(
+        &self,
+        migrated_at: Option<MilestoneIndex>,
+    ) -> Result<Vec<(MilestoneOption, MilestoneIndex)>, KvDbError> {
+        let tree = self.tree(trees::RECEIPTS_BY_MIGRATED_AT)?;
+        let entries = match migrated_at {
+            Some(migrated_at) => tree.scan_prefix(Key::default().index(migrated_at)),
+            None => tree.iter(),
+        };
+        let mut receipts = entries
+            .map(|entry| {
+                let (key, value) = entry?;
+                Ok((decode(&value)?, KeyReader(&key).skip(MILESTONE_INDEX_LENGTH).index()))
+            })
+            .collect::<Result<Vec<_>, KvDbError>>()?;
+        receipts.sort_by_key(|(_, milestone_index)| *milestone_index);
+        Ok(receipts)
+    }
+}
+
+#[async_trait]
+impl StorageBackend for KvDb {
+    fn backend_name(&self) -> &'static str {
+        "key-value store"
+    }
+
+    async fn clear(&self) -> Result<(), StorageError> {
+        for name in self.db.tree_names() {
+            self.db.open_tree(name).and_then(|tree| tree.clear())?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ApplicationStateStorage for KvDb {
+    async fn get_starting_index(&self) -> Result<Option<MilestoneIndexTimestamp>, StorageError> {
+        Ok(self.get_application_state()?.starting_index)
+    }
+
+    async fn set_starting_index(&self, starting_index: MilestoneIndexTimestamp) -> Result<(), StorageError> {
+        Ok(self.update_application_state(|state| state.starting_index = Some(starting_index))?)
+    }
+
+    async fn get_last_migration(&self) -> Result<Option<MigrationVersion>, StorageError> {
+        Ok(self.get_application_state()?.last_migration)
+    }
+
+    async fn set_last_migration(&self, last_migration: MigrationVersion) -> Result<(), StorageError> {
+        Ok(self.update_application_state(|state| state.last_migration = Some(last_migration))?)
+    }
+
+    async fn get_ingestion_filter(&self) -> Result<Option<IngestionFilter>, StorageError> {
+        Ok(self.get_application_state()?.ingestion_filter)
+    }
+
+    async fn set_ingestion_filter(&self, ingestion_filter: Option<&IngestionFilter>) -> Result<(), StorageError> {
+        Ok(self.update_application_state(|state| state.ingestion_filter = ingestion_filter.cloned())?)
+    }
+}
+
+#[async_trait]
+impl BlockStorage for KvDb {
+    async fn insert_blocks_with_metadata(&self, blocks: Vec<BlockData>) -> Result<(), StorageError> {
+        let tree = self.tree(trees::BLOCKS)?;
+        let mut by_transaction = sled::Batch::default();
+        let mut by_milestone = sled::Batch::default();
+        let mut children = sled::Batch::default();
+        let mut spent_by = sled::Batch::default();
+        for BlockData {
+            block_id,
+            block,
+            raw,
+            metadata,
+        } in blocks
+        {
+            if tree.contains_key(block_id.0)? {
+                continue;
+            }
+            by_milestone.insert(
+                Key::default()
+                    .index(metadata.referenced_by_milestone_index)
+                    .u32(metadata.white_flag_index),
+                &block_id.0,
+            );
+            for parent in block.parents.iter() {
+                children.insert(
+                    Key::default()
+                        .block_id(parent)
+                        .index(metadata.referenced_by_milestone_index)
+                        .block_id(&block_id),
+                    sled::IVec::default(),
+                );
+            }
+            if metadata.inclusion_state == LedgerInclusionState::Included {
+                if let Some(Payload::Transaction(payload)) = &block.payload {
+                    by_transaction.insert(&payload.transaction_id.0[..], &block_id.0[..]);
+                    let TransactionEssence::Regular { inputs, .. } = &payload.essence;
+                    for input in inputs.iter() {
+                        if let Input::Utxo(output_id) = input {
+                            spent_by.insert(Key::default().output_id(output_id), &block_id.0[..]);
+                        }
+                    }
+                }
+            }
+            tree.insert(block_id.0, encode(&BlockRecord { raw, metadata })?)?;
+        }
+        self.tree(trees::BLOCKS_BY_TRANSACTION)?.apply_batch(by_transaction)?;
+        self.tree(trees::BLOCKS_BY_MILESTONE)?.apply_batch(by_milestone)?;
+        self.tree(trees::BLOCK_CHILDREN)?.apply_batch(children)?;
+        self.tree(trees::SPENT_BY)?.apply_batch(spent_by)?;
+        Ok(())
+    }
+
+    async fn get_block(&self, block_id: &BlockId) -> Result<Option<Block>, StorageError> {
+        Ok(self.get_block_record(block_id)?.map(|record| unpack_block(record.raw)))
+    }
+
+    async fn get_block_raw(&self, block_id: &BlockId) -> Result<Option<Vec<u8>>, StorageError> {
+        Ok(self.get_block_record(block_id)?.map(|record| record.raw))
+    }
+
+    async fn get_block_metadata(&self, block_id: &BlockId) -> Result<Option<BlockMetadata>, StorageError> {
+        Ok(self.get_block_record(block_id)?.map(|record| record.metadata))
+    }
+
+    async fn get_blocks_raw(&self, block_ids: &[BlockId]) -> Result<Vec<BlockRawResult>, StorageError> {
+        let mut results = Vec::with_capacity(block_ids.len());
+        for block_id in block_ids {
+            if let Some(record) = self.get_block_record(block_id)? {
+                results.push(BlockRawResult {
+                    block_id: *block_id,
+                    raw: record.raw,
+                });
+            }
+        }
+        Ok(results)
+    }
+
+    async fn get_blocks_metadata(&self, block_ids: &[BlockId]) -> Result<Vec<BlockMetadataResult>, StorageError> {
+        let mut results = Vec::with_capacity(block_ids.len());
+        for block_id in block_ids {
+            if let Some(record) = self.get_block_record(block_id)? {
+                results.push(BlockMetadataResult {
+                    block_id: *block_id,
+                    metadata: record.metadata,
+                });
+            }
+        }
+        Ok(results)
+    }
+
+    async fn get_block_children(
+        &self,
+        block_id: &BlockId,
+        block_referenced_index: MilestoneIndex,
+        below_max_depth: u8,
+        page_size: usize,
+        page: usize,
+    ) -> Result<Vec<BlockId>, StorageError> {
+        let start = Key::default().block_id(block_id).index(block_referenced_index);
+        let end = Key::default()
+            .block_id(block_id)
+            .index(block_referenced_index + below_max_depth as u32)
+            .bytes(&[u8::MAX; BlockId::LENGTH]);
+        Ok(self
+            .tree(trees::BLOCK_CHILDREN)?
+            .range(start..=end)
+            .keys()
+            .rev()
+            .skip(page_size * page)
+            .take(page_size)
+            .map(|key| {
+                Ok(KeyReader(&key?)
+                    .skip(BlockId::LENGTH + MILESTONE_INDEX_LENGTH)
+                    .block_id())
+            })
+            .collect::<Result<_, KvDbError>>()?)
+    }
+
+    async fn get_referenced_blocks_in_white_flag_order(
+        &self,
+        index: MilestoneIndex,
+    ) -> Result<Vec<BlockId>, StorageError> {
+        Ok(self
+            .tree(trees::BLOCKS_BY_MILESTONE)?
+            .scan_prefix(Key::default().index(index))
+            .values()
+            .map(|block_id| Ok(KeyReader(&block_id?).block_id()))
+            .collect::<Result<_, KvDbError>>()?)
+    }
+
+    async fn get_block_for_transaction(
+        &self,
+        transaction_id: &TransactionId,
+    ) -> Result<Option<IncludedBlockResult>, StorageError> {
+        Ok(match self.get_block_id_for_transaction(transaction_id)? {
+            Some(block_id) => self.get_block_record(&block_id)?.map(|record| IncludedBlockResult {
+                block_id,
+                block: unpack_block(record.raw),
+            }),
+            None => None,
+        })
+    }
+
+    async fn get_block_raw_for_transaction(
+        &self,
+        transaction_id: &TransactionId,
+    ) -> Result<Option<Vec<u8>>, StorageError> {
+        Ok(match self.get_block_id_for_transaction(transaction_id)? {
+            Some(block_id) => self.get_block_record(&block_id)?.map(|record| record.raw),
+            None => None,
+        })
+    }
+
+    async fn get_block_metadata_for_transaction(
+        &self,
+        transaction_id: &TransactionId,
+    ) -> Result<Option<IncludedBlockMetadataResult>, StorageError> {
+        Ok(match self.get_block_id_for_transaction(transaction_id)? {
+            Some(block_id) => self
+                .get_block_record(&block_id)?
+                .map(|record| IncludedBlockMetadataResult {
+                    block_id,
+                    metadata: record.metadata,
+                }),
+            None => None,
+        })
+    }
+
+    async fn get_spending_transaction(&self, output_id: &OutputId) -> Result<Option<Block>, StorageError> {
+        let block_id = self
+            .tree(trees::SPENT_BY)?
+            .get(Key::default().output_id(output_id))?
+            .map(|block_id| KeyReader(&block_id).block_id());
+        Ok(match block_id {
+            Some(block_id) => self.get_block_record(&block_id)?.map(|record| unpack_block(record.raw)),
+            None => None,
+        })
+    }
+}
+
+#[async_trait]
+impl OutputStorage for KvDb {
+    async fn insert_unspent_outputs(&self, outputs: &[LedgerOutput]) -> Result<(), StorageError> {
+        let tree = self.tree(trees::OUTPUTS)?;
+        let mut by_booked = sled::Batch::default();
+        for output in outputs {
+            let key = Key::default().output_id(&output.output_id);
+            if !tree.contains_key(&key)? {
+                tree.insert(key, encode(&OutputRecord::from(output))?)?;
+                by_booked.insert(
+                    Key::default()
+                        .index(output.booked.milestone_index)
+                        .output_id(&output.output_id),
+                    sled::IVec::default(),
+                );
+            }
+        }
+        self.tree(trees::OUTPUTS_BY_BOOKED)?.apply_batch(by_booked)?;
+        Ok(())
+    }
+
+    async fn update_spent_outputs(&self, outputs: &[LedgerSpent]) -> Result<(), StorageError> {
+        let tree = self.tree(trees::OUTPUTS)?;
+        let mut by_booked = sled::Batch::default();
+        let mut by_spent = sled::Batch::default();
+        for output in outputs {
+            let mut record = OutputRecord::from(&output.output);
+            record.spent_metadata = Some(output.spent_metadata);
+            tree.insert(Key::default().output_id(&output.output_id()), encode(&record)?)?;
+            by_booked.insert(
+                Key::default()
+                    .index(output.output.booked.milestone_index)
+                    .output_id(&output.output_id()),
+                &[],
+            );
+            by_spent.insert(
+                Key::default()
+                    .index(output.spent_metadata.spent.milestone_index)
+                    .output_id(&output.output_id()),
+                &[],
+            );
+        }
+        self.tree(trees::OUTPUTS_BY_BOOKED)?.apply_batch(by_booked)?;
+        self.tree(trees::OUTPUTS_BY_SPENT)?.apply_batch(by_spent)?;
+        Ok(())
+    }
+
+    async fn get_output(&self, output_id: &OutputId) -> Result<Option<Output>, StorageError> {
+        Ok(self.get_output_record(output_id)?.map(|record| record.output))
+    }
+
+    async fn get_output_with_metadata(
+        &self,
+        output_id: &OutputId,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<OutputWithMetadataResult>, StorageError> {
+        Ok(self
+            .get_output_record(output_id)?
+            .filter(|record| record.booked.milestone_index <= ledger_index)
+            .map(|record| OutputWithMetadataResult {
+                output: record.output.clone(),
+                metadata: record.into_metadata(*output_id),
+            }))
+    }
+
+    async fn get_output_metadata(
+        &self,
+        output_id: &OutputId,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<OutputMetadataResult>, StorageError> {
+        Ok(self
+            .get_output_record(output_id)?
+            .filter(|record| record.booked.milestone_index <= ledger_index)
+            .map(|record| record.into_metadata(*output_id)))
+    }
+
+    async fn get_outputs_with_metadata(
+        &self,
+        output_ids: &[OutputId],
+        ledger_index: MilestoneIndex,
+    ) -> Result<Vec<OutputWithMetadataResult>, StorageError> {
+        let mut results = Vec::with_capacity(output_ids.len());
+        for output_id in output_ids {
+            if let Some(result) = self.get_output_with_metadata(output_id, ledger_index).await? {
+                results.push(result);
+            }
+        }
+        Ok(results)
+    }
+
+    async fn get_outputs_metadata(
+        &self,
+        output_ids: &[OutputId],
+        ledger_index: MilestoneIndex,
+    ) -> Result<Vec<OutputMetadataResult>, StorageError> {
+        let mut results = Vec::with_capacity(output_ids.len());
+        for output_id in output_ids {
+            if let Some(result) = self.get_output_metadata(output_id, ledger_index).await? {
+                results.push(result);
+            }
+        }
+        Ok(results)
+    }
+
+    async fn get_spending_transaction_metadata(
+        &self,
+        output_id: &OutputId,
+    ) -> Result<Option<SpentMetadata>, StorageError> {
+        Ok(self
+            .get_output_record(output_id)?
+            .and_then(|record| record.spent_metadata))
+    }
+
+    async fn get_unspent_outputs(
+        &self,
+        ledger_index: MilestoneIndex,
+    ) -> Result<BoxStream<'static, Result<LedgerOutput, StorageError>>, StorageError> {
+        let outputs = self.tree(trees::OUTPUTS)?.iter().filter_map(move |entry| {
+            let unspent = || -> Result<_, StorageError> {
+                let (key, value) = entry?;
+                let record: OutputRecord = decode(&value)?;
+                Ok((record.booked.milestone_index <= ledger_index
+                    && !matches!(record.spent_metadata, Some(spent) if spent.spent.milestone_index <= ledger_index))
+                .then(|| LedgerOutput {
+                    output_id: KeyReader(&key).output_id(),
+                    block_id: record.block_id,
+                    booked: record.booked,
+                    output: record.output,
+                    rent_structure: record.rent_structure,
+                }))
+            };
+            unspent().transpose()
+        });
+        Ok(spawn_stream(outputs))
+    }
+
+    async fn get_utxo_changes(
+        &self,
+        index: MilestoneIndex,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<UtxoChangesResult>, StorageError> {
+        if index > ledger_index {
+            Ok(None)
+        } else {
+            Ok(Some(UtxoChangesResult {
+                created_outputs: self.get_output_ids(trees::OUTPUTS_BY_BOOKED, index)?,
+                consumed_outputs: self.get_output_ids(trees::OUTPUTS_BY_SPENT, index)?,
+            }))
+        }
+    }
+}
+
+impl KvDb {
+    fn insert_ledger_updates<'a>(
+        &self,
+        updates: impl IntoIterator<Item = (&'a OutputId, &'a Output, MilestoneIndexTimestamp, bool)>,
+    ) -> Result<(), KvDbError> {
+        let mut by_address = sled::Batch::default();
+        let mut by_milestone = sled::Batch::default();
+        for (output_id, output, at, is_spent) in updates {
+            if let Some(address) = output.owning_address() {
+                by_address.insert(
+                    Key::default()
+                        .address(address)
+                        .index(at.milestone_index)
+                        .output_id(output_id)
+                        .spent(is_spent),
+                    &at.milestone_timestamp.0.to_be_bytes(),
+                );
+                by_milestone.insert(
+                    Key::default()
+                        .index(at.milestone_index)
+                        .output_id(output_id)
+                        .spent(is_spent),
+                    encode(&AddressRecord { address: *address })?,
+                );
+            }
+        }
+        self.tree(trees::LEDGER_UPDATES)?.apply_batch(by_address)?;
+        self.tree(trees::LEDGER_UPDATES_BY_MILESTONE)?
+            .apply_batch(by_milestone)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LedgerUpdateStorage for KvDb {
+    async fn insert_unspent_ledger_updates(&self, outputs: &[LedgerOutput]) -> Result<(), StorageError> {
+        Ok(self.insert_ledger_updates(
+            outputs
+                .iter()
+                .map(|output| (&output.output_id, &output.output, output.booked, false)),
+        )?)
+    }
+
+    async fn insert_spent_ledger_updates(&self, outputs: &[LedgerSpent]) -> Result<(), StorageError> {
+        Ok(self.insert_ledger_updates(outputs.iter().map(|spent| {
+            (
+                &spent.output.output_id,
+                &spent.output.output,
+                spent.spent_metadata.spent,
+                true,
+            )
+        }))?)
+    }
+
+    async fn get_ledger_updates_by_address(
+        &self,
+        address: &Address,
+        page_size: usize,
+        cursor: Option<(MilestoneIndex, Option<(OutputId, bool)>)>,
+        order: SortOrder,
+    ) -> Result<Vec<LedgerUpdateByAddressRecord>, StorageError> {
+        let tree = self.tree(trees::LEDGER_UPDATES)?;
+        let prefix = Key::default().address(address);
+        let prefix_len = prefix.as_ref().len();
+        let entries: Box<dyn Iterator<Item = sled::Result<(sled::IVec, sled::IVec)>>> = match (order, cursor) {
+            (SortOrder::Newest, None) => Box::new(tree.scan_prefix(prefix).rev()),
+            (SortOrder::Newest, Some((index, None))) => {
+                Box::new(tree.range(prefix..Key::default().address(address).index(index)).rev())
+            }
+            (SortOrder::Newest, Some((index, Some((output_id, is_spent))))) => {
+                let end = Key::default()
+                    .address(address)
+                    .index(index)
+                    .output_id(&output_id)
+                    .spent(is_spent);
+                Box::new(tree.range(prefix..=end).rev())
+            }
+            (SortOrder::Oldest, None) => Box::new(tree.scan_prefix(prefix)),
+            (SortOrder::Oldest, Some((index, rest))) => {
+                let start = match rest {
+                    Some((output_id, is_spent)) => Key::default()
+                        .address(address)
+                        .index(index)
+                        .output_id(&output_id)
+                        .spent(is_spent),
+                    None => Key::default().address(address).index(index + 1),
+                };
+                Box::new(
+                    tree.range(start..)
+                        .take_while(move |entry| !matches!(entry, Ok((key, _)) if !key.starts_with(prefix.as_ref()))),
+                )
+            }
+        };
+        Ok(entries
+            .take(page_size)
+            .map(|entry| {
+                let (key, value) = entry?;
+                let mut key = KeyReader(&key).skip(prefix_len);
+                let milestone_index = key.index();
+                Ok(LedgerUpdateByAddressRecord {
+                    // Unwrap: The length is correct.
+                    at: milestone_index.with_timestamp(MilestoneTimestamp(u32::from_be_bytes(
+                        value.as_ref().try_into().unwrap(),
+                    ))),
+                    output_id: key.output_id(),
+                    is_spent: key.is_spent(),
+                })
+            })
+            .collect::<Result<_, KvDbError>>()?)
+    }
+
+    async fn get_ledger_updates_by_milestone(
+        &self,
+        milestone_index: MilestoneIndex,
+        page_size: usize,
+        cursor: Option<(OutputId, bool)>,
+    ) -> Result<Vec<LedgerUpdateByMilestoneRecord>, StorageError> {
+        let tree = self.tree(trees::LEDGER_UPDATES_BY_MILESTONE)?;
+        let prefix = Key::default().index(milestone_index);
+        let start = match cursor {
+            Some((output_id, is_spent)) => Key::default()
+                .index(milestone_index)
+                .output_id(&output_id)
+                .spent(is_spent),
+            None => Key::default().index(milestone_index),
+        };
+        Ok(tree
+            .range(start..)
+            .take_while(|entry| !matches!(entry, Ok((key, _)) if !key.starts_with(prefix.as_ref())))
+            .take(page_size)
+            .map(|entry| {
+                let (key, value) = entry?;
+                let mut key = KeyReader(&key).skip(MILESTONE_INDEX_LENGTH);
+                let AddressRecord { address } = decode(&value)?;
+                Ok(LedgerUpdateByMilestoneRecord {
+                    address,
+                    output_id: key.output_id(),
+                    is_spent: key.is_spent(),
+                })
+            })
+            .collect::<Result<_, KvDbError>>()?)
+    }
+}
+
+#[async_trait]
+impl MilestoneStorage for KvDb {
+    async fn insert_milestone(
+        &self,
+        milestone_id: MilestoneId,
+        milestone_index: MilestoneIndex,
+        milestone_timestamp: MilestoneTimestamp,
+        payload: MilestonePayload,
+        signature_verified: Option<bool>,
+        stats: Option<MilestoneStats>,
+    ) -> Result<(), StorageError> {
+        for option in payload.essence.options.iter() {
+            if let MilestoneOption::Receipt { migrated_at, .. } = option {
+                self.insert(
+                    trees::RECEIPTS_BY_MIGRATED_AT,
+                    Key::default().index(*migrated_at).index(milestone_index),
+                    option,
+                )?;
+            }
+        }
+        self.insert(
+            trees::MILESTONES,
+            Key::default().index(milestone_index),
+            &MilestoneRecord {
+                milestone_id,
+                at: milestone_index.with_timestamp(milestone_timestamp),
+                payload,
+                signature_verified,
+                stats,
+            },
+        )?;
+        self.tree(trees::MILESTONES_BY_ID)?
+            .insert(milestone_id.0, Key::default().index(milestone_index))?;
+        Ok(())
+    }
+
+    async fn get_milestone_payload_by_id(
+        &self,
+        milestone_id: &MilestoneId,
+    ) -> Result<Option<MilestonePayload>, StorageError> {
+        let index = self
+            .tree(trees::MILESTONES_BY_ID)?
+            .get(milestone_id.0)?
+            .map(|index| KeyReader(&index).index());
+        Ok(match index {
+            Some(index) => self.get_milestone_payload(index).await?,
+            None => None,
+        })
+    }
+
+    async fn get_milestone_payload(&self, index: MilestoneIndex) -> Result<Option<MilestonePayload>, StorageError> {
+        Ok(self
+            .get::<MilestoneRecord>(trees::MILESTONES, Key::default().index(index))?
+            .map(|record| record.payload))
+    }
+
+    async fn get_milestone(
+        &self,
+        index: MilestoneIndex,
+    ) -> Result<Option<(MilestoneId, MilestoneIndexTimestamp, MilestonePayload)>, StorageError> {
+        Ok(self
+            .get::<MilestoneRecord>(trees::MILESTONES, Key::default().index(index))?
+            .map(|record| (record.milestone_id, record.at, record.payload)))
+    }
+
+    async fn get_milestone_id(&self, index: MilestoneIndex) -> Result<Option<MilestoneId>, StorageError> {
+        Ok(self
+            .get::<MilestoneRecord>(trees::MILESTONES, Key::default().index(index))?
+            .map(|record| record.milestone_id))
+    }
+
+    async fn get_milestone_signature_verified(&self, index: MilestoneIndex) -> Result<Option<bool>, StorageError> {
+        Ok(self
+            .get::<MilestoneRecord>(trees::MILESTONES, Key::default().index(index))?
+            .and_then(|record| record.signature_verified))
+    }
+
+    async fn get_milestone_stats(&self, index: MilestoneIndex) -> Result<Option<MilestoneStats>, StorageError> {
+        Ok(self
+            .get::<MilestoneRecord>(trees::MILESTONES, Key::default().index(index))?
+            .and_then(|record| record.stats))
+    }
+
+    async fn set_milestone_stats(&self, index: MilestoneIndex, stats: MilestoneStats) -> Result<(), StorageError> {
+        let key = Key::default().index(index);
+        if let Some(mut record) = self.get::<MilestoneRecord>(trees::MILESTONES, &key)? {
+            record.stats = Some(stats);
+            self.insert(trees::MILESTONES, key, &record)?;
+        }
+        Ok(())
+    }
+
+    async fn get_newest_milestone(&self) -> Result<Option<MilestoneIndexTimestamp>, StorageError> {
+        Ok(self
+            .get_latest::<MilestoneRecord>(trees::MILESTONES, None)?
+            .map(|record| record.at))
+    }
+
+    async fn get_oldest_milestone(&self) -> Result<Option<MilestoneIndexTimestamp>, StorageError> {
+        Ok(self
+            .tree(trees::MILESTONES)?
+            .first()?
+            .map(|(_, value)| decode::<MilestoneRecord>(&value))
+            .transpose()?
+            .map(|record| record.at))
+    }
+
+    async fn get_all_receipts(&self) -> Result<Vec<(MilestoneOption, MilestoneIndex)>, StorageError> {
+        Ok(self.get_receipts(None)?)
+    }
+
+    async fn get_receipts_migrated_at(
+        &self,
+        migrated_at: MilestoneIndex,
+    ) -> Result<Vec<(MilestoneOption, MilestoneIndex)>, StorageError> {
+        Ok(self.get_receipts(Some(migrated_at))?)
+    }
+}
+
+#[async_trait]
+impl TreasuryStorage for KvDb {
+    async fn insert_treasury_payloads(
+        &self,
+        payloads: Vec<(MilestoneIndex, MilestoneId, u64)>,
+    ) -> Result<(), StorageError> {
+        let tree = self.tree(trees::TREASURY)?;
+        for (milestone_index, milestone_id, amount) in payloads {
+            let key = Key::default().index(milestone_index);
+            if !tree.contains_key(&key)? {
+                tree.insert(key, encode(&TreasuryResult { milestone_id, amount })?)?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn get_latest_treasury(&self) -> Result<Option<TreasuryResult>, StorageError> {
+        Ok(self.get_latest(trees::TREASURY, None)?)
+    }
+
+    async fn get_treasury_for_ledger_index(
+        &self,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<TreasuryResult>, StorageError> {
+        Ok(self.get_latest(trees::TREASURY, Some(ledger_index))?)
+    }
+}
+
+#[async_trait]
+impl ProtocolUpdateStorage for KvDb {
+    async fn get_latest_protocol_parameters(&self) -> Result<Option<ProtocolUpdateDocument>, StorageError> {
+        Ok(self.get_latest(trees::PROTOCOL_UPDATES, None)?)
+    }
+
+    async fn get_protocol_parameters_for_ledger_index(
+        &self,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<ProtocolUpdateDocument>, StorageError> {
+        Ok(self.get_latest(trees::PROTOCOL_UPDATES, Some(ledger_index))?)
+    }
+
+    async fn get_protocol_parameters_for_version(
+        &self,
+        version: u8,
+    ) -> Result<Option<ProtocolUpdateDocument>, StorageError> {
+        let index = self
+            .tree(trees::PROTOCOL_UPDATES_BY_VERSION)?
+            .get([version])?
+            .map(|index| KeyReader(&index).index());
+        Ok(match index {
+            Some(index) => self
+                .get::<ProtocolUpdateDocument>(trees::PROTOCOL_UPDATES, Key::default().index(index))?
+                // The update may have been replaced by one of another version.
+                .filter(|document| document.parameters.version == version),
+            None => None,
+        })
+    }
+
+    async fn upsert_protocol_parameters(
+        &self,
+        ledger_index: MilestoneIndex,
+        parameters: ProtocolParameters,
+    ) -> Result<(), StorageError> {
+        let params = self.get_protocol_parameters_for_ledger_index(ledger_index).await?;
+        if !matches!(params, Some(params) if params.parameters == parameters) {
+            // A version is found by its earliest update.
+            let by_version = self.tree(trees::PROTOCOL_UPDATES_BY_VERSION)?;
+            let version = [parameters.version];
+            if !matches!(by_version.get(version)?, Some(index) if KeyReader(&index).index() <= ledger_index) {
+                by_version.insert(version, Key::default().index(ledger_index))?;
+            }
+            self.insert(
+                trees::PROTOCOL_UPDATES,
+                Key::default().index(ledger_index),
+                &ProtocolUpdateDocument {
+                    tangle_index: ledger_index,
+                    parameters,
+                },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ConfigurationUpdateStorage for KvDb {
+    async fn get_latest_node_configuration(&self) -> Result<Option<ConfigurationUpdateDocument>, StorageError> {
+        Ok(self.get_latest(trees::CONFIGURATION_UPDATES, None)?)
+    }
+
+    async fn get_node_configuration_for_ledger_index(
+        &self,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<ConfigurationUpdateDocument>, StorageError> {
+        Ok(self.get_latest(trees::CONFIGURATION_UPDATES, Some(ledger_index))?)
+    }
+
+    async fn upsert_node_configuration(
+        &self,
+        ledger_index: MilestoneIndex,
+        config: NodeConfiguration,
+    ) -> Result<(), StorageError> {
+        let node_config = self.get_node_configuration_for_ledger_index(ledger_index).await?;
+        if !matches!(node_config, Some(node_config) if node_config.config == config) {
+            self.insert(
+                trees::CONFIGURATION_UPDATES,
+                Key::default().index(ledger_index),
+                &ConfigurationUpdateDocument { ledger_index, config },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ApiKeyStorage for KvDb {
+    async fn insert_api_key(&self, key: &ApiKeyDocument) -> Result<bool, StorageError> {
+        Ok(self
+            .tree(trees::API_KEYS)?
+            .compare_and_swap(&key.name, None as Option<&[u8]>, Some(encode(key)?))?
+            .is_ok())
+    }
+
+    async fn get_api_key(&self, name: &str) -> Result<Option<ApiKeyDocument>, StorageError> {
+        Ok(self.get(trees::API_KEYS, name)?)
+    }
+
+    async fn get_api_keys(&self) -> Result<Vec<ApiKeyDocument>, StorageError> {
+        Ok(self
+            .tree(trees::API_KEYS)?
+            .iter()
+            .values()
+            .map(|value| decode(&value?))
+            .collect::<Result<_, KvDbError>>()?)
+    }
+
+    async fn revoke_api_key(&self, name: &str, revoked_at: u64) -> Result<bool, StorageError> {
+        match self.get::<ApiKeyDocument>(trees::API_KEYS, name)? {
+            Some(mut key) if key.revoked_at.is_none() => {
+                key.revoked_at = Some(revoked_at);
+                self.insert(trees::API_KEYS, name, &key)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
@@ -6,7 +6,20 @@
 /// Module containing InfluxDb types and traits.
 #[cfg(feature = "influx")]
 pub mod influxdb;
+/// Module containing the embedded key-value storage backend.
+#[cfg(feature = "kv")]
+pub mod kv;
 /// Module containing MongoDb types and traits.
 pub mod mongodb;
+/// Module containing the storage traits that abstract over the backends.
+pub mod storage;
 
-pub use self::mongodb::{config::MongoDbConfig, MongoDb, MongoDbCollection, MongoDbCollectionExt};
+#[cfg(feature = "kv")]
+pub use self::kv::{KvDb, KvDbConfig, KvDbError};
+pub use self::{
+    mongodb::{config::MongoDbConfig, MongoDb, MongoDbCollection, MongoDbCollectionExt},
+    storage::{
+        ApiKeyStorage, ApplicationStateStorage, BlockStorage, ConfigurationUpdateStorage, LedgerUpdateStorage,
+        MilestoneStorage, OutputStorage, ProtocolUpdateStorage, Storage, StorageBackend, StorageError, TreasuryStorage,
+    },
+};
@@ -17,7 +17,7 @@ use mongodb::{
 };
 use serde::{de::DeserializeOwned, Serialize};
 
-use super::MongoDb;
+use super::{indexes::IndexDefinition, MongoDb};
 
 const DUPLICATE_KEY_CODE: i32 = 11000;
 const INDEX_NOT_FOUND_CODE: i32 = 27;
@@ -48,8 +48,16 @@ pub trait MongoDbCollection {
         Ok(())
     }
 
+    /// Gets the definitions of the collection indexes.
+    fn indexes(&self) -> Vec<IndexDefinition> {
+        Vec::new()
+    }
+
     /// Creates the collection indexes.
     async fn create_indexes(&self) -> Result<(), Error> {
+        for index in self.indexes() {
+            self.collection().create_index(index.model().clone(), None).await?;
+        }
         Ok(())
     }
 }
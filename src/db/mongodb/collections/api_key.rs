@@ -0,0 +1,91 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use futures::TryStreamExt;
+use mongodb::{
+    bson::doc,
+    error::Error,
+    options::{FindOptions, UpdateOptions},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::db::{
+    mongodb::{MongoDbCollection, MongoDbCollectionExt},
+    MongoDb,
+};
+
+/// The stored representation of a named API key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiKeyDocument {
+    /// The unique name of the key.
+    #[serde(rename = "_id")]
+    pub name: String,
+    /// The Argon2 hash of the secret.
+    #[serde(with = "serde_bytes")]
+    pub key_hash: Vec<u8>,
+    /// The salt that was used to hash the secret.
+    pub salt: String,
+    /// The scopes that are granted to the tokens of this key.
+    pub scopes: Vec<String>,
+    /// The unix timestamp at which the key was created.
+    pub created_at: u64,
+    /// The unix timestamp at which the key was revoked, if it was.
+    pub revoked_at: Option<u64>,
+}
+
+/// A collection to store named API keys.
+pub struct ApiKeyCollection {
+    collection: mongodb::Collection<ApiKeyDocument>,
+}
+
+impl MongoDbCollection for ApiKeyCollection {
+    const NAME: &'static str = "api_keys";
+    type Document = ApiKeyDocument;
+
+    fn instantiate(_db: &MongoDb, collection: mongodb::Collection<Self::Document>) -> Self {
+        Self { collection }
+    }
+
+    fn collection(&self) -> &mongodb::Collection<Self::Document> {
+        &self.collection
+    }
+}
+
+impl ApiKeyCollection {
+    /// Inserts an API key. Returns `false` if a key with the same name already exists.
+    pub async fn insert_api_key(&self, key: &ApiKeyDocument) -> Result<bool, Error> {
+        let res = self
+            .update_one(
+                doc! { "_id": &key.name },
+                doc! { "$setOnInsert": mongodb::bson::to_document(key)? },
+                UpdateOptions::builder().upsert(true).build(),
+            )
+            .await?;
+        Ok(res.upserted_id.is_some())
+    }
+
+    /// Gets an API key by its name.
+    pub async fn get_api_key(&self, name: &str) -> Result<Option<ApiKeyDocument>, Error> {
+        self.find_one(doc! { "_id": name }, None).await
+    }
+
+    /// Gets all API keys ordered by their name.
+    pub async fn get_api_keys(&self) -> Result<Vec<ApiKeyDocument>, Error> {
+        self.find(doc! {}, FindOptions::builder().sort(doc! { "_id": 1 }).build())
+            .await?
+            .try_collect()
+            .await
+    }
+
+    /// Revokes an API key at the given time. Returns `false` if there is no such key that is not yet revoked.
+    pub async fn revoke_api_key(&self, name: &str, revoked_at: u64) -> Result<bool, Error> {
+        let res = self
+            .update_one(
+                doc! { "_id": name, "revoked_at": null },
+                doc! { "$set": { "revoked_at": revoked_at as i64 } },
+                None,
+            )
+            .await?;
+        Ok(res.modified_count > 0)
+    }
+}
@@ -6,17 +6,25 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     db::{
-        mongodb::{MongoDbCollection, MongoDbCollectionExt},
+        mongodb::{config::BlockStorageMode, MongoDbCollection, MongoDbCollectionExt},
         MongoDb,
     },
     model::tangle::MilestoneIndexTimestamp,
+    tangle::IngestionFilter,
 };
 
 /// The MongoDb document representation of singleton Application State.
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[allow(missing_docs)]
 pub struct ApplicationStateDocument {
     pub starting_index: Option<MilestoneIndexTimestamp>,
     pub last_migration: Option<MigrationVersion>,
+    #[serde(default)]
+    pub ingestion_filter: Option<IngestionFilter>,
+    #[serde(default)]
+    pub migration_progress: Option<MigrationProgress>,
+    #[serde(default)]
+    pub block_storage_mode: Option<BlockStorageMode>,
 }
 
 /// The migration version and associated metadata.
@@ -34,6 +42,34 @@ impl std::fmt::Display for MigrationVersion {
     }
 }
 
+/// The direction in which a migration is applied.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationDirection {
+    /// The migration is applied.
+    Up,
+    /// The migration is reverted.
+    Down,
+}
+
+impl std::fmt::Display for MigrationDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Up => write!(f, "up"),
+            Self::Down => write!(f, "down"),
+        }
+    }
+}
+
+/// The progress of a migration that was started but has not been completed yet.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct MigrationProgress {
+    pub id: usize,
+    pub direction: MigrationDirection,
+    pub completed_steps: usize,
+}
+
 /// A collection to store singleton Application State.
 pub struct ApplicationStateCollection {
     collection: mongodb::Collection<ApplicationStateDocument>,
@@ -94,4 +130,83 @@ impl ApplicationStateCollection {
         .await?;
         Ok(())
     }
+
+    /// Removes the last migration version, which happens when the first migration is reverted.
+    pub async fn unset_last_migration(&self) -> Result<(), Error> {
+        self.update_one(
+            doc! {},
+            doc! {
+                "$unset": { "last_migration": "" }
+            },
+            None,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the progress of the migration that is currently applied or reverted, if any.
+    pub async fn get_migration_progress(&self) -> Result<Option<MigrationProgress>, Error> {
+        Ok(self
+            .find_one::<ApplicationStateDocument>(doc! {}, None)
+            .await?
+            .and_then(|doc| doc.migration_progress))
+    }
+
+    /// Set the progress of the current migration in the singleton application state. Passing `None` marks the
+    /// migration as finished.
+    pub async fn set_migration_progress(&self, migration_progress: Option<&MigrationProgress>) -> Result<(), Error> {
+        self.update_one(
+            doc! {},
+            doc! {
+                "$set": { "migration_progress": mongodb::bson::to_bson(&migration_progress)? }
+            },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the mode in which the blocks of the database are stored. Databases that do not record a mode store the
+    /// blocks in [`BlockStorageMode::Full`] mode.
+    pub async fn get_block_storage_mode(&self) -> Result<BlockStorageMode, Error> {
+        Ok(self
+            .find_one::<ApplicationStateDocument>(doc! {}, None)
+            .await?
+            .and_then(|doc| doc.block_storage_mode)
+            .unwrap_or_default())
+    }
+
+    /// Set the mode in which the blocks are stored in the singleton application state.
+    pub async fn set_block_storage_mode(&self, block_storage_mode: BlockStorageMode) -> Result<(), Error> {
+        self.update_one(
+            doc! {},
+            doc! {
+                "$set": { "block_storage_mode": mongodb::bson::to_bson(&block_storage_mode)? }
+            },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the filter that is applied to the blocks that are stored, if any.
+    pub async fn get_ingestion_filter(&self) -> Result<Option<IngestionFilter>, Error> {
+        Ok(self
+            .find_one::<ApplicationStateDocument>(doc! {}, None)
+            .await?
+            .and_then(|doc| doc.ingestion_filter))
+    }
+
+    /// Set the filter that is applied to the blocks that are stored in the singleton application state.
+    pub async fn set_ingestion_filter(&self, ingestion_filter: Option<&IngestionFilter>) -> Result<(), Error> {
+        self.update_one(
+            doc! {},
+            doc! {
+                "$set": { "ingestion_filter": mongodb::bson::to_bson(&ingestion_filter)? }
+            },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await?;
+        Ok(())
+    }
 }
@@ -1,28 +1,37 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use std::ops::RangeInclusive;
+
 use futures::{Stream, TryStreamExt};
 use mongodb::{
-    bson::doc,
+    bson::{doc, spec::BinarySubtype, Binary, Bson, Document},
     error::Error,
     options::{IndexOptions, InsertManyOptions},
     IndexModel,
 };
 use packable::PackableExt;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use tracing::instrument;
 
 use super::SortOrder;
 use crate::{
     db::{
-        mongodb::{InsertIgnoreDuplicatesExt, MongoDbCollection, MongoDbCollectionExt},
+        mongodb::{
+            config::BlockStorageMode, IndexDefinition, IndexGroup, InsertIgnoreDuplicatesExt, MongoDbCollection,
+            MongoDbCollectionExt,
+        },
         MongoDb,
     },
     model::{
         metadata::{BlockMetadata, LedgerInclusionState},
-        payload::TransactionId,
+        payload::{
+            MilestonePayload, Payload, TaggedDataPayload, TransactionEssence, TransactionId, TransactionPayload,
+            TreasuryTransactionPayload,
+        },
         tangle::MilestoneIndex,
-        utxo::OutputId,
+        utxo::{Input, OutputId},
         Block, BlockId,
     },
     tangle::BlockData,
@@ -34,7 +43,7 @@ pub struct BlockDocument {
     #[serde(rename = "_id")]
     block_id: BlockId,
     /// The block.
-    block: Block,
+    block: StoredBlock,
     /// The raw bytes of the block.
     #[serde(with = "serde_bytes")]
     raw: Vec<u8>,
@@ -42,6 +51,15 @@ pub struct BlockDocument {
     metadata: BlockMetadata,
 }
 
+impl BlockDocument {
+    fn with_storage_mode(mut self, mode: BlockStorageMode) -> Self {
+        if let (BlockStorageMode::RawOnly, StoredBlock::Full(block)) = (mode, &self.block) {
+            self.block = StoredBlock::Indexed(block.into());
+        }
+        self
+    }
+}
+
 impl From<BlockData> for BlockDocument {
     fn from(
         BlockData {
@@ -53,7 +71,7 @@ impl From<BlockData> for BlockDocument {
     ) -> Self {
         Self {
             block_id,
-            block,
+            block: StoredBlock::Full(block),
             raw,
             metadata,
         }
@@ -64,16 +82,93 @@ impl From<(BlockId, Block, Vec<u8>, BlockMetadata)> for BlockDocument {
     fn from((block_id, block, raw, metadata): (BlockId, Block, Vec<u8>, BlockMetadata)) -> Self {
         Self {
             block_id,
-            block,
+            block: StoredBlock::Full(block),
             raw,
             metadata,
         }
     }
 }
 
+/// The representation of a block in a [`BlockDocument`], depending on the [`BlockStorageMode`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum StoredBlock {
+    Full(Block),
+    Indexed(IndexedBlock),
+}
+
+/// The fields of a [`Block`] that are needed by the indexes and queries. They are stored under the same paths as the
+/// fields of the full block, so that both representations can be queried alike. The parents are omitted, as they are
+/// part of the [`BlockMetadata`] as well.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct IndexedBlock {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payload: Option<IndexedPayload>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct IndexedPayload {
+    kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    transaction_id: Option<TransactionId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    essence: Option<IndexedEssence>,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "serde_bytes")]
+    tag: Option<Box<[u8]>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct IndexedEssence {
+    inputs: Box<[Input]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payload: Option<Box<IndexedPayload>>,
+}
+
+impl From<&Block> for IndexedBlock {
+    fn from(block: &Block) -> Self {
+        Self {
+            payload: block.payload.as_ref().map(Into::into),
+        }
+    }
+}
+
+impl From<&Payload> for IndexedPayload {
+    fn from(payload: &Payload) -> Self {
+        let mut indexed = Self {
+            kind: String::new(),
+            transaction_id: None,
+            essence: None,
+            tag: None,
+        };
+        match payload {
+            Payload::Transaction(payload) => {
+                let TransactionEssence::Regular {
+                    inputs,
+                    payload: essence_payload,
+                    ..
+                } = &payload.essence;
+                indexed.kind = TransactionPayload::KIND.to_string();
+                indexed.transaction_id = Some(payload.transaction_id);
+                indexed.essence = Some(IndexedEssence {
+                    inputs: inputs.clone(),
+                    payload: essence_payload.as_ref().map(|payload| Box::new(payload.into())),
+                });
+            }
+            Payload::Milestone(_) => indexed.kind = MilestonePayload::KIND.to_string(),
+            Payload::TreasuryTransaction(_) => indexed.kind = TreasuryTransactionPayload::KIND.to_string(),
+            Payload::TaggedData(payload) => {
+                indexed.kind = TaggedDataPayload::KIND.to_string();
+                indexed.tag = Some(payload.tag().into());
+            }
+        }
+        indexed
+    }
+}
+
 /// The stardust blocks collection.
 pub struct BlockCollection {
     collection: mongodb::Collection<BlockDocument>,
+    storage_mode: BlockStorageMode,
 }
 
 #[async_trait::async_trait]
@@ -81,51 +176,85 @@ impl MongoDbCollection for BlockCollection {
     const NAME: &'static str = "stardust_blocks";
     type Document = BlockDocument;
 
-    fn instantiate(_db: &MongoDb, collection: mongodb::Collection<Self::Document>) -> Self {
-        Self { collection }
+    fn instantiate(db: &MongoDb, collection: mongodb::Collection<Self::Document>) -> Self {
+        Self {
+            collection,
+            storage_mode: db.block_storage_mode(),
+        }
     }
 
     fn collection(&self) -> &mongodb::Collection<Self::Document> {
         &self.collection
     }
 
-    async fn create_indexes(&self) -> Result<(), Error> {
-        self.create_index(
-            IndexModel::builder()
-                .keys(doc! { "block.payload.transaction_id": 1 })
-                .options(
-                    IndexOptions::builder()
-                        .unique(true)
-                        .name("transaction_id_index".to_string())
-                        .partial_filter_expression(doc! {
-                            "block.payload.transaction_id": { "$exists": true },
-                            "metadata.inclusion_state": { "$eq": LedgerInclusionState::Included },
-                        })
-                        .build(),
-                )
-                .build(),
-            None,
-        )
-        .await?;
-
-        self.create_index(
-            IndexModel::builder()
-                .keys(doc! { "metadata.referenced_by_milestone_index": -1, "metadata.white_flag_index": 1, "metadata.inclusion_state": 1 })
-                .options(
-                    IndexOptions::builder()
-                        .name("block_referenced_index_comp".to_string())
-                        .build(),
-                )
-                .build(),
-            None,
-        )
-        .await?;
-
-        Ok(())
+    fn indexes(&self) -> Vec<IndexDefinition> {
+        vec![
+            IndexDefinition::new(
+                &[IndexGroup::Core],
+                IndexModel::builder()
+                    .keys(doc! { "block.payload.transaction_id": 1 })
+                    .options(
+                        IndexOptions::builder()
+                            .unique(true)
+                            .name("transaction_id_index".to_string())
+                            .partial_filter_expression(doc! {
+                                "block.payload.transaction_id": { "$exists": true },
+                                "metadata.inclusion_state": { "$eq": LedgerInclusionState::Included },
+                            })
+                            .build(),
+                    )
+                    .build(),
+            ),
+            IndexDefinition::new(
+                &[IndexGroup::Core],
+                IndexModel::builder()
+                    .keys(doc! {
+                        "metadata.referenced_by_milestone_index": -1,
+                        "metadata.white_flag_index": 1,
+                        "metadata.inclusion_state": 1
+                    })
+                    .options(
+                        IndexOptions::builder()
+                            .name("block_referenced_index_comp".to_string())
+                            .build(),
+                    )
+                    .build(),
+            ),
+            IndexDefinition::new(
+                &[IndexGroup::Explorer],
+                IndexModel::builder()
+                    .keys(doc! { "block.payload.tag": 1, "metadata.referenced_by_milestone_index": -1 })
+                    .options(
+                        IndexOptions::builder()
+                            .name("block_tag_index".to_string())
+                            .partial_filter_expression(doc! { "block.payload.tag": { "$exists": true } })
+                            .build(),
+                    )
+                    .build(),
+            ),
+            IndexDefinition::new(
+                &[IndexGroup::Explorer],
+                IndexModel::builder()
+                    .keys(doc! {
+                        "block.payload.essence.payload.tag": 1,
+                        "metadata.referenced_by_milestone_index": -1
+                    })
+                    .options(
+                        IndexOptions::builder()
+                            .name("block_transaction_tag_index".to_string())
+                            .partial_filter_expression(doc! {
+                                "block.payload.essence.payload.tag": { "$exists": true }
+                            })
+                            .build(),
+                    )
+                    .build(),
+            ),
+        ]
     }
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[allow(missing_docs)]
 pub struct IncludedBlockResult {
     #[serde(rename = "_id")]
     pub block_id: BlockId,
@@ -133,12 +262,39 @@ pub struct IncludedBlockResult {
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[allow(missing_docs)]
 pub struct IncludedBlockMetadataResult {
     #[serde(rename = "_id")]
     pub block_id: BlockId,
     pub metadata: BlockMetadata,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+#[allow(missing_docs)]
+pub struct BlockRawResult {
+    #[serde(rename = "_id")]
+    pub block_id: BlockId,
+    #[serde(with = "serde_bytes")]
+    pub raw: Vec<u8>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[allow(missing_docs)]
+pub struct BlockMetadataResult {
+    #[serde(rename = "_id")]
+    pub block_id: BlockId,
+    pub metadata: BlockMetadata,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[allow(missing_docs)]
+pub struct BlockGraphNodeResult {
+    #[serde(rename = "_id")]
+    pub block_id: BlockId,
+    pub payload_kind: Option<String>,
+    pub metadata: BlockMetadata,
+}
+
 #[derive(Deserialize)]
 struct RawResult {
     #[serde(with = "serde_bytes")]
@@ -191,6 +347,37 @@ impl BlockCollection {
         .await
     }
 
+    /// Get the raw bytes of multiple [`Block`]s by their [`BlockId`]s. Unknown ids are omitted from the result.
+    pub async fn get_blocks_raw(&self, block_ids: &[BlockId]) -> Result<Vec<BlockRawResult>, Error> {
+        self.aggregate(
+            [
+                doc! { "$match": { "_id": { "$in": block_ids.to_vec() } } },
+                doc! { "$project": { "raw": 1 } },
+            ],
+            None,
+        )
+        .await?
+        .try_collect()
+        .await
+    }
+
+    /// Get the metadata of multiple [`Block`]s by their [`BlockId`]s. Unknown ids are omitted from the result.
+    pub async fn get_blocks_metadata(&self, block_ids: &[BlockId]) -> Result<Vec<BlockMetadataResult>, Error> {
+        self.aggregate(
+            [
+                doc! { "$match": { "_id": { "$in": block_ids.to_vec() } } },
+                doc! { "$project": {
+                    "_id": 1,
+                    "metadata": 1,
+                } },
+            ],
+            None,
+        )
+        .await?
+        .try_collect()
+        .await
+    }
+
     /// Get the children of a [`Block`] as a stream of [`BlockId`]s.
     pub async fn get_block_children(
         &self,
@@ -210,7 +397,7 @@ impl BlockCollection {
                             "$gte": block_referenced_index,
                             "$lte": max_referenced_index
                         },
-                        "block.parents": block_id,
+                        "metadata.parents": block_id,
                     } },
                     doc! { "$sort": {"metadata.referenced_by_milestone_index": -1} },
                     doc! { "$skip": (page_size * page) as i64 },
@@ -223,6 +410,84 @@ impl BlockCollection {
             .map_ok(|BlockIdResult { block_id }| block_id))
     }
 
+    /// Get the graph nodes of multiple [`Block`]s by their [`BlockId`]s. Unknown ids are omitted from the result.
+    pub async fn get_graph_nodes(&self, block_ids: &[BlockId]) -> Result<Vec<BlockGraphNodeResult>, Error> {
+        self.aggregate(
+            [
+                doc! { "$match": { "_id": { "$in": block_ids.to_vec() } } },
+                doc! { "$project": {
+                    "_id": 1,
+                    "payload_kind": "$block.payload.kind",
+                    "metadata": 1,
+                } },
+            ],
+            None,
+        )
+        .await?
+        .try_collect()
+        .await
+    }
+
+    /// Get up to `limit` graph nodes of the children of multiple [`Block`]s. Children are only searched among the
+    /// blocks that are not referenced yet and those that were referenced within the given range of milestones.
+    pub async fn get_graph_children(
+        &self,
+        block_ids: &[BlockId],
+        referenced_by: Option<RangeInclusive<MilestoneIndex>>,
+        limit: usize,
+    ) -> Result<Vec<BlockGraphNodeResult>, Error> {
+        let mut windows = vec![doc! { "metadata.referenced_by_milestone_index": 0 }];
+        if let Some(referenced_by) = referenced_by {
+            windows.push(doc! { "metadata.referenced_by_milestone_index": {
+                "$gte": *referenced_by.start(),
+                "$lte": *referenced_by.end(),
+            } });
+        }
+        self.aggregate(
+            [
+                doc! { "$match": {
+                    "$or": windows,
+                    "metadata.parents": { "$in": block_ids.to_vec() },
+                } },
+                doc! { "$limit": limit as i64 },
+                doc! { "$project": {
+                    "_id": 1,
+                    "payload_kind": "$block.payload.kind",
+                    "metadata": 1,
+                } },
+            ],
+            None,
+        )
+        .await?
+        .try_collect()
+        .await
+    }
+
+    /// Get up to `limit` graph nodes of the blocks that were referenced by the specified milestone (in White-Flag
+    /// order).
+    pub async fn get_milestone_cone_graph_nodes(
+        &self,
+        index: MilestoneIndex,
+        limit: usize,
+    ) -> Result<Vec<BlockGraphNodeResult>, Error> {
+        self.aggregate(
+            [
+                doc! { "$match": { "metadata.referenced_by_milestone_index": index } },
+                doc! { "$sort": { "metadata.white_flag_index": 1 } },
+                doc! { "$limit": limit as i64 },
+                doc! { "$project": {
+                    "_id": 1,
+                    "payload_kind": "$block.payload.kind",
+                    "metadata": 1,
+                } },
+            ],
+            None,
+        )
+        .await?
+        .try_collect()
+        .await
+    }
+
     /// Get the blocks that were referenced by the specified milestone (in White-Flag order).
     pub async fn get_referenced_blocks_in_white_flag_order(
         &self,
@@ -310,7 +575,10 @@ impl BlockCollection {
         I::IntoIter: Send + Sync,
         BlockDocument: From<B>,
     {
-        let blocks_with_metadata = blocks_with_metadata.into_iter().map(BlockDocument::from);
+        let storage_mode = self.storage_mode;
+        let blocks_with_metadata = blocks_with_metadata
+            .into_iter()
+            .map(|block| BlockDocument::from(block).with_storage_mode(storage_mode));
 
         self.insert_many_ignore_duplicates(
             blocks_with_metadata,
@@ -400,6 +668,119 @@ impl BlockCollection {
     }
 }
 
+/// An error that is returned when converting blocks between the [`BlockStorageMode`]s.
+#[derive(Debug, Error)]
+#[allow(missing_docs)]
+pub enum ConvertBlocksError {
+    #[error(transparent)]
+    MongoDb(#[from] Error),
+    #[error("failed to serialize block: {0}")]
+    Serialize(#[from] mongodb::bson::ser::Error),
+    #[error("failed to unpack the raw bytes of block {}: {reason}", .block_id.to_hex())]
+    InvalidRaw { block_id: BlockId, reason: String },
+}
+
+/// Implements the conversion between the [`BlockStorageMode`]s.
+impl BlockCollection {
+    fn stored_in_mode(mode: BlockStorageMode) -> Document {
+        // Only full blocks store the nonce.
+        doc! { "block.nonce": { "$exists": mode == BlockStorageMode::Full } }
+    }
+
+    /// Counts the blocks that are not stored in the given mode.
+    pub async fn count_blocks_to_convert(&self, mode: BlockStorageMode) -> Result<u64, Error> {
+        let other = match mode {
+            BlockStorageMode::Full => BlockStorageMode::RawOnly,
+            BlockStorageMode::RawOnly => BlockStorageMode::Full,
+        };
+        self.collection()
+            .count_documents(Self::stored_in_mode(other), None)
+            .await
+    }
+
+    /// Converts all blocks to the given mode. Blocks that are already stored in that mode are not modified, so an
+    /// interrupted conversion can simply be repeated.
+    pub async fn convert_blocks(&self, mode: BlockStorageMode) -> Result<(), ConvertBlocksError> {
+        match mode {
+            BlockStorageMode::Full => {
+                #[derive(Deserialize)]
+                struct RawBlockResult {
+                    #[serde(rename = "_id")]
+                    block_id: BlockId,
+                    #[serde(with = "serde_bytes")]
+                    raw: Vec<u8>,
+                }
+
+                let mut raw_blocks = self
+                    .aggregate::<RawBlockResult>(
+                        [
+                            doc! { "$match": Self::stored_in_mode(BlockStorageMode::RawOnly) },
+                            doc! { "$project": { "raw": 1 } },
+                        ],
+                        None,
+                    )
+                    .await?;
+                while let Some(RawBlockResult { block_id, raw }) = raw_blocks.try_next().await? {
+                    let block = iota_types::block::Block::unpack_unverified(raw).map_err(|e| {
+                        ConvertBlocksError::InvalidRaw {
+                            block_id,
+                            reason: e.to_string(),
+                        }
+                    })?;
+                    let block = Block::from(block);
+                    self.update_one(
+                        doc! { "_id": block_id },
+                        doc! { "$set": { "block": mongodb::bson::to_bson(&block)? } },
+                        None,
+                    )
+                    .await?;
+                }
+            }
+            BlockStorageMode::RawOnly => {
+                let essence_payload = doc! { "$cond": [
+                    { "$eq": [ { "$type": "$block.payload.essence.payload" }, "missing" ] },
+                    "$$REMOVE",
+                    {
+                        "kind": "$block.payload.essence.payload.kind",
+                        "tag": "$block.payload.essence.payload.tag",
+                    },
+                ] };
+                let essence = doc! { "$cond": [
+                    { "$eq": [ { "$type": "$block.payload.essence" }, "missing" ] },
+                    "$$REMOVE",
+                    {
+                        "inputs": "$block.payload.essence.inputs",
+                        "payload": essence_payload,
+                    },
+                ] };
+                let payload = doc! { "$cond": [
+                    { "$eq": [ { "$type": "$block.payload" }, "missing" ] },
+                    "$$REMOVE",
+                    {
+                        "kind": "$block.payload.kind",
+                        "transaction_id": "$block.payload.transaction_id",
+                        "essence": essence,
+                        "tag": "$block.payload.tag",
+                    },
+                ] };
+                // Setting an embedded document merges it with the existing one, so the indexed fields are collected
+                // in a temporary field first.
+                self.update_many(
+                    Self::stored_in_mode(BlockStorageMode::Full),
+                    vec![
+                        doc! { "$set": { "indexed_block": { "payload": payload } } },
+                        doc! { "$set": { "block": "$indexed_block" } },
+                        doc! { "$unset": "indexed_block" },
+                    ],
+                    None,
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[allow(missing_docs)]
 pub struct BlocksByMilestoneResult {
@@ -443,4 +824,91 @@ impl BlockCollection {
         )
         .await
     }
+
+    /// Gets the id of the most recently referenced block with a tagged data payload that carries the tag, either
+    /// directly or within a transaction.
+    pub async fn get_block_id_by_tag(&self, tag: &[u8]) -> Result<Option<BlockId>, Error> {
+        let tag = Bson::Binary(Binary {
+            subtype: BinarySubtype::Generic,
+            bytes: tag.to_vec(),
+        });
+        self.aggregate(
+            [
+                doc! { "$match": { "$or": [
+                    { "block.payload.tag": tag.clone() },
+                    { "block.payload.essence.payload.tag": tag },
+                ] } },
+                doc! { "$sort": { "metadata.referenced_by_milestone_index": -1 } },
+                doc! { "$limit": 1 },
+                doc! { "$project": { "_id": 1 } },
+            ],
+            None,
+        )
+        .await?
+        .map_ok(|BlockIdResult { block_id }| block_id)
+        .try_next()
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs::File, io::BufReader};
+
+    use packable::PackableExt;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct BlockTestData {
+        #[serde(rename = "_id")]
+        block_id: BlockId,
+        #[serde(with = "serde_bytes")]
+        raw: Vec<u8>,
+        metadata: BlockMetadata,
+    }
+
+    fn get_path<'a>(document: &'a Document, path: &str) -> Option<&'a Bson> {
+        let (head, tail) = path
+            .split_once('.')
+            .map_or((path, None), |(head, tail)| (head, Some(tail)));
+        match tail {
+            Some(tail) => get_path(document.get_document(head).ok()?, tail),
+            None => document.get(head),
+        }
+    }
+
+    #[test]
+    fn raw_only_documents_keep_query_paths() {
+        let file = File::open("tests/data/blocks_ms_2418807.json").unwrap();
+        let test_data: Bson = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let blocks: Vec<BlockTestData> = mongodb::bson::from_bson(test_data).unwrap();
+
+        for BlockTestData {
+            block_id,
+            raw,
+            metadata,
+        } in blocks
+        {
+            let block = iota_types::block::Block::unpack_unverified(raw.clone()).unwrap().into();
+            let document = BlockDocument::from((block_id, block, raw, metadata));
+            let full = mongodb::bson::to_document(&document).unwrap();
+            let raw_only = mongodb::bson::to_document(&document.with_storage_mode(BlockStorageMode::RawOnly)).unwrap();
+
+            for path in [
+                "block.payload.kind",
+                "block.payload.transaction_id",
+                "block.payload.tag",
+                "block.payload.essence.inputs",
+                "block.payload.essence.payload.kind",
+                "block.payload.essence.payload.tag",
+                "raw",
+                "metadata",
+            ] {
+                assert_eq!(get_path(&full, path), get_path(&raw_only, path), "{path}");
+            }
+            assert!(get_path(&full, "block.nonce").is_some());
+            assert!(get_path(&raw_only, "block.nonce").is_none());
+        }
+    }
 }
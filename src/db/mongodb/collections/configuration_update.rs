@@ -1,10 +1,11 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use futures::Stream;
 use mongodb::{
     bson::doc,
     error::Error,
-    options::{FindOneOptions, UpdateOptions},
+    options::{FindOneOptions, FindOptions, UpdateOptions},
 };
 use serde::{Deserialize, Serialize};
 
@@ -18,6 +19,7 @@ use crate::{
 
 /// The corresponding MongoDb document representation to store [`NodeConfiguration`]s.
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[allow(missing_docs)]
 pub struct ConfigurationUpdateDocument {
     #[serde(rename = "_id")]
     pub ledger_index: MilestoneIndex,
@@ -62,6 +64,26 @@ impl ConfigurationUpdateCollection {
         .await
     }
 
+    /// Gets the first node configuration update that applies after the given ledger index.
+    pub async fn get_next_node_configuration(
+        &self,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<ConfigurationUpdateDocument>, Error> {
+        self.find_one(
+            doc! { "_id": { "$gt": ledger_index } },
+            FindOneOptions::builder().sort(doc! { "_id": 1 }).build(),
+        )
+        .await
+    }
+
+    /// Gets all versions of the node configuration, ordered by the ledger index from which they apply.
+    pub async fn get_node_configuration_history(
+        &self,
+    ) -> Result<impl Stream<Item = Result<ConfigurationUpdateDocument, Error>>, Error> {
+        self.find(doc! {}, FindOptions::builder().sort(doc! { "_id": 1 }).build())
+            .await
+    }
+
     /// Inserts or updates a node configuration for a given ledger index.
     pub async fn upsert_node_configuration(
         &self,
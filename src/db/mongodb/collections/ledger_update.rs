@@ -14,7 +14,9 @@ use tracing::instrument;
 use super::SortOrder;
 use crate::{
     db::{
-        mongodb::{InsertIgnoreDuplicatesExt, MongoDbCollection, MongoDbCollectionExt},
+        mongodb::{
+            IndexDefinition, IndexGroup, InsertIgnoreDuplicatesExt, MongoDbCollection, MongoDbCollectionExt,
+        },
         MongoDb,
     },
     model::{
@@ -58,8 +60,9 @@ impl MongoDbCollection for LedgerUpdateCollection {
         &self.collection
     }
 
-    async fn create_indexes(&self) -> Result<(), Error> {
-        self.create_index(
+    fn indexes(&self) -> Vec<IndexDefinition> {
+        vec![IndexDefinition::new(
+            &[IndexGroup::Explorer],
             IndexModel::builder()
                 .keys(newest())
                 .options(
@@ -69,11 +72,7 @@ impl MongoDbCollection for LedgerUpdateCollection {
                         .build(),
                 )
                 .build(),
-            None,
-        )
-        .await?;
-
-        Ok(())
+        )]
     }
 }
 
@@ -5,7 +5,7 @@ use std::ops::RangeInclusive;
 
 use futures::{Stream, TryStreamExt};
 use mongodb::{
-    bson::doc,
+    bson::{doc, spec::BinarySubtype, Binary, Bson, Document},
     error::Error,
     options::{FindOneOptions, FindOptions, IndexOptions},
     IndexModel,
@@ -16,13 +16,15 @@ use tracing::instrument;
 use super::SortOrder;
 use crate::{
     db::{
-        mongodb::{MongoDbCollection, MongoDbCollectionExt},
+        mongodb::{IndexDefinition, IndexGroup, MongoDbCollection, MongoDbCollectionExt},
         MongoDb,
     },
     model::{
-        payload::{MilestoneId, MilestoneOption, MilestonePayload},
+        payload::{milestone::MigratedFundsEntry, MilestoneId, MilestoneOption, MilestonePayload},
         tangle::{MilestoneIndex, MilestoneIndexTimestamp, MilestoneTimestamp},
+        utxo::Address,
     },
+    tangle::MilestoneStats,
 };
 
 const BY_OLDEST: i32 = 1;
@@ -38,6 +40,12 @@ pub struct MilestoneDocument {
     at: MilestoneIndexTimestamp,
     /// The milestone's payload.
     payload: MilestonePayload,
+    /// Whether the milestone's signatures were verified, if verification was enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signature_verified: Option<bool>,
+    /// The statistics of the milestone, if they were computed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stats: Option<MilestoneStats>,
 }
 
 /// The stardust milestones collection.
@@ -58,36 +66,61 @@ impl MongoDbCollection for MilestoneCollection {
         &self.collection
     }
 
-    async fn create_indexes(&self) -> Result<(), Error> {
-        self.create_index(
-            IndexModel::builder()
-                .keys(doc! { "at.milestone_index": BY_OLDEST })
-                .options(
-                    IndexOptions::builder()
-                        .unique(true)
-                        .name("milestone_idx_index".to_string())
-                        .build(),
-                )
-                .build(),
-            None,
-        )
-        .await?;
-
-        self.create_index(
-            IndexModel::builder()
-                .keys(doc! { "at.milestone_timestamp": BY_OLDEST })
-                .options(
-                    IndexOptions::builder()
-                        .unique(true)
-                        .name("milestone_timestamp_index".to_string())
-                        .build(),
-                )
-                .build(),
-            None,
-        )
-        .await?;
-
-        Ok(())
+    fn indexes(&self) -> Vec<IndexDefinition> {
+        vec![
+            IndexDefinition::new(
+                &[IndexGroup::Core],
+                IndexModel::builder()
+                    .keys(doc! { "at.milestone_index": BY_OLDEST })
+                    .options(
+                        IndexOptions::builder()
+                            .unique(true)
+                            .name("milestone_idx_index".to_string())
+                            .build(),
+                    )
+                    .build(),
+            ),
+            IndexDefinition::new(
+                &[IndexGroup::Core],
+                IndexModel::builder()
+                    .keys(doc! { "at.milestone_timestamp": BY_OLDEST })
+                    .options(
+                        IndexOptions::builder()
+                            .unique(true)
+                            .name("milestone_timestamp_index".to_string())
+                            .build(),
+                    )
+                    .build(),
+            ),
+            IndexDefinition::new(
+                &[IndexGroup::Explorer],
+                IndexModel::builder()
+                    .keys(doc! { "payload.essence.options.funds.tail_transaction_hash": 1 })
+                    .options(
+                        IndexOptions::builder()
+                            .name("migrated_funds_tail_transaction_hash_index".to_string())
+                            .partial_filter_expression(doc! {
+                                "payload.essence.options.funds.tail_transaction_hash": { "$exists": true },
+                            })
+                            .build(),
+                    )
+                    .build(),
+            ),
+            IndexDefinition::new(
+                &[IndexGroup::Explorer],
+                IndexModel::builder()
+                    .keys(doc! { "payload.essence.options.funds.address": 1 })
+                    .options(
+                        IndexOptions::builder()
+                            .name("migrated_funds_address_index".to_string())
+                            .partial_filter_expression(doc! {
+                                "payload.essence.options.funds.address": { "$exists": true },
+                            })
+                            .build(),
+                    )
+                    .build(),
+            ),
+        ]
     }
 }
 
@@ -144,6 +177,7 @@ impl MilestoneCollection {
                      milestone_id,
                      at,
                      payload,
+                     ..
                  }| (milestone_id, at, payload),
             )
             .try_next()
@@ -192,14 +226,69 @@ impl MilestoneCollection {
             .map(|ts| ts.milestone_id))
     }
 
+    /// Gets whether the signatures of a milestone were verified by the [`MilestoneIndex`].
+    ///
+    /// Returns `None` if the milestone does not exist or was stored without verification.
+    pub async fn get_milestone_signature_verified(&self, index: MilestoneIndex) -> Result<Option<bool>, Error> {
+        #[derive(Deserialize)]
+        struct SignatureVerifiedResult {
+            signature_verified: Option<bool>,
+        }
+        Ok(self
+            .find_one::<SignatureVerifiedResult>(
+                doc! { "at.milestone_index": index },
+                FindOneOptions::builder()
+                    .projection(doc! {
+                        "signature_verified": 1,
+                    })
+                    .build(),
+            )
+            .await?
+            .and_then(|res| res.signature_verified))
+    }
+
+    /// Gets the [`MilestoneStats`] of a milestone by the [`MilestoneIndex`].
+    ///
+    /// Returns `None` if the milestone does not exist or its statistics were not computed yet.
+    pub async fn get_milestone_stats(&self, index: MilestoneIndex) -> Result<Option<MilestoneStats>, Error> {
+        #[derive(Deserialize)]
+        struct StatsResult {
+            stats: Option<MilestoneStats>,
+        }
+        Ok(self
+            .find_one::<StatsResult>(
+                doc! { "at.milestone_index": index },
+                FindOneOptions::builder()
+                    .projection(doc! {
+                        "stats": 1,
+                    })
+                    .build(),
+            )
+            .await?
+            .and_then(|res| res.stats))
+    }
+
+    /// Sets the [`MilestoneStats`] of an existing milestone.
+    pub async fn set_milestone_stats(&self, index: MilestoneIndex, stats: MilestoneStats) -> Result<(), Error> {
+        self.update_one(
+            doc! { "at.milestone_index": index },
+            doc! { "$set": { "stats": mongodb::bson::to_bson(&stats)? } },
+            None,
+        )
+        .await?;
+        Ok(())
+    }
+
     /// Inserts the information of a milestone into the database.
-    #[instrument(skip(self, milestone_id, milestone_timestamp, payload), err, level = "trace")]
+    #[instrument(skip(self, milestone_id, milestone_timestamp, payload, stats), err, level = "trace")]
     pub async fn insert_milestone(
         &self,
         milestone_id: MilestoneId,
         milestone_index: MilestoneIndex,
         milestone_timestamp: MilestoneTimestamp,
         payload: MilestonePayload,
+        signature_verified: Option<bool>,
+        stats: Option<MilestoneStats>,
     ) -> Result<(), Error> {
         let milestone_document = MilestoneDocument {
             at: MilestoneIndexTimestamp {
@@ -208,6 +297,8 @@ impl MilestoneCollection {
             },
             milestone_id,
             payload,
+            signature_verified,
+            stats,
         };
 
         self.insert_one(milestone_document, None).await?;
@@ -308,12 +399,12 @@ impl MilestoneCollection {
                 [
                     doc! { "$unwind": "$payload.essence.options"},
                     doc! { "$match": {
-                        "payload.essence.options.receipt.migrated_at": { "$exists": true },
+                        "payload.essence.options.kind": "receipt",
                     } },
                     doc! { "$sort": { "at.milestone_index": 1 } },
                     doc! { "$replaceWith": {
-                        "receipt": "options.receipt" ,
-                        "index": "$at.milestone_index" ,
+                        "receipt": "$payload.essence.options",
+                        "index": "$at.milestone_index",
                     } },
                 ],
                 None,
@@ -339,12 +430,13 @@ impl MilestoneCollection {
                 [
                     doc! { "$unwind": "$payload.essence.options"},
                     doc! { "$match": {
-                        "payload.essence.options.receipt.migrated_at": { "$and": [ { "$exists": true }, { "$eq": migrated_at } ] },
+                        "payload.essence.options.kind": "receipt",
+                        "payload.essence.options.migrated_at": migrated_at,
                     } },
                     doc! { "$sort": { "at.milestone_index": 1 } },
                     doc! { "$replaceWith": {
-                        "receipt": "options.receipt" ,
-                        "index": "$at.milestone_index" ,
+                        "receipt": "$payload.essence.options",
+                        "index": "$at.milestone_index",
                     } },
                 ],
                 None,
@@ -354,14 +446,145 @@ impl MilestoneCollection {
     }
 }
 
+/// Selects the migrated funds entries of receipts.
+#[derive(Clone, Debug)]
+pub enum MigratedFundsQuery {
+    /// The entry of the legacy bundle with the given tail transaction hash.
+    TailTransactionHash([u8; MigratedFundsEntry::TAIL_TRANSACTION_HASH_LENGTH]),
+    /// The entries that migrated funds to the given address.
+    Address(Address),
+}
+
+/// A migrated funds entry together with the receipt and the milestone that contain it.
+#[derive(Clone, Debug, Deserialize)]
+#[allow(missing_docs)]
+pub struct MigratedFundsResult {
+    pub entry: MigratedFundsEntry,
+    pub migrated_at: MilestoneIndex,
+    pub last: bool,
+    pub milestone_id: MilestoneId,
+    pub at: MilestoneIndexTimestamp,
+}
+
+impl MilestoneCollection {
+    /// Gets the migrated funds entries of all receipts that match the query, ordered by the index of their milestone
+    /// and their tail transaction hash. The cursor is the milestone index and tail transaction hash of the first entry.
+    pub async fn get_migrated_funds(
+        &self,
+        query: MigratedFundsQuery,
+        page_size: usize,
+        cursor: Option<(MilestoneIndex, [u8; MigratedFundsEntry::TAIL_TRANSACTION_HASH_LENGTH])>,
+    ) -> Result<impl Stream<Item = Result<MigratedFundsResult, Error>>, Error> {
+        let tail_transaction_hash_bson = |hash: [u8; MigratedFundsEntry::TAIL_TRANSACTION_HASH_LENGTH]| {
+            Bson::Binary(Binary {
+                subtype: BinarySubtype::Generic,
+                bytes: hash.to_vec(),
+            })
+        };
+        let (path, value) = match query {
+            MigratedFundsQuery::TailTransactionHash(hash) => (
+                "payload.essence.options.funds.tail_transaction_hash",
+                tail_transaction_hash_bson(hash),
+            ),
+            MigratedFundsQuery::Address(address) => ("payload.essence.options.funds.address", address.into()),
+        };
+
+        let mut milestone_query = doc! { path: value.clone() };
+        let mut entry_query = doc! { path: value };
+        if let Some((milestone_index, tail_transaction_hash)) = cursor {
+            milestone_query.insert("at.milestone_index", doc! { "$gte": milestone_index });
+            entry_query.insert(
+                "$or",
+                vec![
+                    doc! { "at.milestone_index": { "$gt": milestone_index } },
+                    doc! {
+                        "at.milestone_index": milestone_index,
+                        "payload.essence.options.funds.tail_transaction_hash": {
+                            "$gte": tail_transaction_hash_bson(tail_transaction_hash)
+                        },
+                    },
+                ],
+            );
+        }
+
+        self.aggregate(
+            [
+                doc! { "$match": milestone_query },
+                doc! { "$unwind": "$payload.essence.options" },
+                doc! { "$match": { "payload.essence.options.kind": "receipt" } },
+                doc! { "$unwind": "$payload.essence.options.funds" },
+                doc! { "$match": entry_query },
+                doc! { "$sort": { "at.milestone_index": 1, "payload.essence.options.funds.tail_transaction_hash": 1 } },
+                doc! { "$limit": page_size as i64 },
+                doc! { "$project": {
+                    "_id": 0,
+                    "entry": "$payload.essence.options.funds",
+                    "migrated_at": "$payload.essence.options.migrated_at",
+                    "last": "$payload.essence.options.last",
+                    "milestone_id": "$_id",
+                    "at": 1,
+                } },
+            ],
+            None,
+        )
+        .await
+    }
+}
+
 #[derive(Copy, Clone, Debug, Deserialize)]
 #[allow(missing_docs)]
 pub struct MilestoneResult {
     pub milestone_id: MilestoneId,
     pub index: MilestoneIndex,
+    pub timestamp: MilestoneTimestamp,
+    #[serde(default)]
+    pub signature_verified: Option<bool>,
+    #[serde(default)]
+    pub stats: Option<MilestoneStats>,
+}
+
+fn milestone_result_projection() -> Document {
+    doc! {
+        "milestone_id": "$_id",
+        "index": "$at.milestone_index",
+        "timestamp": "$at.milestone_timestamp",
+        "signature_verified": 1,
+        "stats": 1,
+    }
 }
 
 impl MilestoneCollection {
+    /// Gets a [`MilestoneResult`] by the [`MilestoneIndex`].
+    pub async fn get_milestone_result(&self, index: MilestoneIndex) -> Result<Option<MilestoneResult>, Error> {
+        self.aggregate(
+            [
+                doc! { "$match": { "at.milestone_index": index } },
+                doc! { "$project": milestone_result_projection() },
+            ],
+            None,
+        )
+        .await?
+        .try_next()
+        .await
+    }
+
+    /// Gets a [`MilestoneResult`] by the [`MilestoneId`].
+    pub async fn get_milestone_result_by_id(
+        &self,
+        milestone_id: &MilestoneId,
+    ) -> Result<Option<MilestoneResult>, Error> {
+        self.aggregate(
+            [
+                doc! { "$match": { "_id": milestone_id } },
+                doc! { "$project": milestone_result_projection() },
+            ],
+            None,
+        )
+        .await?
+        .try_next()
+        .await
+    }
+
     /// Get milestones matching given conditions.
     pub async fn get_milestones(
         &self,
@@ -387,10 +610,7 @@ impl MilestoneCollection {
                 } },
                 doc! { "$sort": sort },
                 doc! { "$limit": page_size as i64 },
-                doc! { "$project": {
-                    "milestone_id": "$_id",
-                    "index": "$at.milestone_index"
-                } },
+                doc! { "$project": milestone_result_projection() },
             ],
             None,
         )
@@ -1,6 +1,8 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+/// Module containing the API key collection.
+mod api_key;
 mod application_state;
 /// Module containing the Block document model.
 mod block;
@@ -22,18 +24,24 @@ use std::str::FromStr;
 use thiserror::Error;
 
 pub use self::{
-    application_state::{ApplicationStateCollection, MigrationVersion},
-    block::BlockCollection,
-    configuration_update::ConfigurationUpdateCollection,
+    api_key::{ApiKeyCollection, ApiKeyDocument},
+    application_state::{
+        ApplicationStateCollection, ApplicationStateDocument, MigrationDirection, MigrationProgress, MigrationVersion,
+    },
+    block::{
+        BlockCollection, BlockGraphNodeResult, BlockMetadataResult, BlockRawResult, ConvertBlocksError,
+        IncludedBlockMetadataResult, IncludedBlockResult,
+    },
+    configuration_update::{ConfigurationUpdateCollection, ConfigurationUpdateDocument},
     ledger_update::{LedgerUpdateByAddressRecord, LedgerUpdateByMilestoneRecord, LedgerUpdateCollection},
-    milestone::{MilestoneCollection, MilestoneResult, SyncData},
+    milestone::{MigratedFundsQuery, MigratedFundsResult, MilestoneCollection, MilestoneResult, SyncData},
     outputs::{
         AddressStat, AliasOutputsQuery, BasicOutputsQuery, DistributionStat, FoundryOutputsQuery, IndexedId,
         NftOutputsQuery, OutputCollection, OutputMetadataResult, OutputWithMetadataResult, OutputsResult,
-        UtxoChangesResult,
+        StorageDepositResult, TimedBalanceResult, UnlockEventKind, UnlockEventResult, UtxoChangesResult,
     },
-    protocol_update::ProtocolUpdateCollection,
-    treasury::{TreasuryCollection, TreasuryResult},
+    protocol_update::{ProtocolUpdateCollection, ProtocolUpdateDocument},
+    treasury::{TreasuryCollection, TreasuryHistoryResult, TreasuryResult},
 };
 use crate::model::utxo::{AliasOutput, BasicOutput, FoundryOutput, NftOutput, Output};
 
@@ -22,7 +22,7 @@ pub use self::{
 };
 use super::{OutputCollection, OutputDocument};
 use crate::{
-    db::mongodb::{collections::SortOrder, MongoDbCollectionExt},
+    db::mongodb::{collections::SortOrder, IndexDefinition, IndexGroup, MongoDbCollectionExt},
     model::{
         metadata::OutputMetadata,
         tangle::MilestoneIndex,
@@ -177,19 +177,20 @@ impl OutputCollection {
             .await?;
         Ok(OutputsResult { outputs })
     }
+}
 
-    /// Creates indexer output indexes.
-    pub async fn create_indexer_indexes(&self) -> Result<(), Error> {
-        self.create_index(
+/// Gets the definitions of the indexes that are used by the indexer queries.
+pub(super) fn indexes() -> Vec<IndexDefinition> {
+    vec![
+        IndexDefinition::new(
+            &[IndexGroup::Indexer],
             IndexModel::builder()
                 .keys(doc! { "output.kind": 1 })
                 .options(IndexOptions::builder().name("output_kind_index".to_string()).build())
                 .build(),
-            None,
-        )
-        .await?;
-
-        self.create_index(
+        ),
+        IndexDefinition::new(
+            &[IndexGroup::Indexer],
             IndexModel::builder()
                 .keys(doc! { "details.indexed_id": 1 })
                 .options(
@@ -201,11 +202,9 @@ impl OutputCollection {
                         .build(),
                 )
                 .build(),
-            None,
-        )
-        .await?;
-
-        self.create_index(
+        ),
+        IndexDefinition::new(
+            &[IndexGroup::Explorer, IndexGroup::Indexer],
             IndexModel::builder()
                 .keys(doc! { "details.address": 1 })
                 .options(
@@ -217,11 +216,9 @@ impl OutputCollection {
                         .build(),
                 )
                 .build(),
-            None,
-        )
-        .await?;
-
-        self.create_index(
+        ),
+        IndexDefinition::new(
+            &[IndexGroup::Indexer],
             IndexModel::builder()
                 .keys(doc! { "output.storage_deposit_return_unlock_condition.return_address": 1 })
                 .options(
@@ -233,11 +230,9 @@ impl OutputCollection {
                         .build(),
                 )
                 .build(),
-            None,
-        )
-        .await?;
-
-        self.create_index(
+        ),
+        IndexDefinition::new(
+            &[IndexGroup::Explorer, IndexGroup::Indexer],
             IndexModel::builder()
                 .keys(doc! { "output.timelock_unlock_condition.timestamp": 1 })
                 .options(
@@ -249,11 +244,9 @@ impl OutputCollection {
                         .build(),
                 )
                 .build(),
-            None,
-        )
-        .await?;
-
-        self.create_index(
+        ),
+        IndexDefinition::new(
+            &[IndexGroup::Explorer, IndexGroup::Indexer],
             IndexModel::builder()
                 .keys(doc! { "output.expiration_unlock_condition.return_address": 1 })
                 .options(
@@ -265,11 +258,9 @@ impl OutputCollection {
                         .build(),
                 )
                 .build(),
-            None,
-        )
-        .await?;
-
-        self.create_index(
+        ),
+        IndexDefinition::new(
+            &[IndexGroup::Explorer, IndexGroup::Indexer],
             IndexModel::builder()
                 .keys(doc! { "output.expiration_unlock_condition.timestamp": 1 })
                 .options(
@@ -281,11 +272,9 @@ impl OutputCollection {
                         .build(),
                 )
                 .build(),
-            None,
-        )
-        .await?;
-
-        self.create_index(
+        ),
+        IndexDefinition::new(
+            &[IndexGroup::Indexer],
             IndexModel::builder()
                 .keys(doc! { "output.governor_address_unlock_condition.address": 1 })
                 .options(
@@ -297,20 +286,16 @@ impl OutputCollection {
                         .build(),
                 )
                 .build(),
-            None,
-        )
-        .await?;
-
-        self.create_index(
+        ),
+        IndexDefinition::new(
+            &[IndexGroup::Indexer],
             IndexModel::builder()
                 .keys(doc! { "output.features": 1 })
                 .options(IndexOptions::builder().name("output_feature_index".to_string()).build())
                 .build(),
-            None,
-        )
-        .await?;
-
-        self.create_index(
+        ),
+        IndexDefinition::new(
+            &[IndexGroup::Indexer],
             IndexModel::builder()
                 .keys(doc! { "output.native_tokens": 1 })
                 .options(
@@ -319,11 +304,9 @@ impl OutputCollection {
                         .build(),
                 )
                 .build(),
-            None,
-        )
-        .await?;
-
-        self.create_index(
+        ),
+        IndexDefinition::new(
+            &[IndexGroup::Core],
             IndexModel::builder()
                 .keys(doc! { "metadata.booked.milestone_index": -1 })
                 .options(
@@ -332,11 +315,9 @@ impl OutputCollection {
                         .build(),
                 )
                 .build(),
-            None,
-        )
-        .await?;
-
-        self.create_index(
+        ),
+        IndexDefinition::new(
+            &[IndexGroup::Core],
             IndexModel::builder()
                 .keys(
                     doc! { "metadata.spent_metadata.spent.milestone_index": -1, "metadata.booked.milestone_index": 1,  "details.address": 1 },
@@ -347,11 +328,9 @@ impl OutputCollection {
                         .build(),
                 )
                 .build(),
-            None,
-        )
-        .await?;
-
-        self.create_index(
+        ),
+        IndexDefinition::new(
+            &[IndexGroup::Indexer],
             IndexModel::builder()
                 .keys(doc! { "metadata.booked.milestone_timestamp": -1 })
                 .options(
@@ -360,11 +339,9 @@ impl OutputCollection {
                         .build(),
                 )
                 .build(),
-            None,
-        )
-        .await?;
-
-        self.create_index(
+        ),
+        IndexDefinition::new(
+            &[IndexGroup::Indexer],
             IndexModel::builder()
                 .keys(doc! { "metadata.spent_metadata.spent.milestone_timestamp": -1 })
                 .options(
@@ -373,10 +350,6 @@ impl OutputCollection {
                         .build(),
                 )
                 .build(),
-            None,
-        )
-        .await?;
-
-        Ok(())
-    }
+        ),
+    ]
 }
@@ -3,7 +3,7 @@
 
 mod indexer;
 
-use std::borrow::Borrow;
+use std::{borrow::Borrow, ops::RangeInclusive};
 
 use futures::{Stream, TryStreamExt};
 use mongodb::{
@@ -20,15 +20,17 @@ pub use self::indexer::{
 };
 use crate::{
     db::{
-        mongodb::{InsertIgnoreDuplicatesExt, MongoDbCollection, MongoDbCollectionExt},
+        mongodb::{
+            IndexDefinition, IndexGroup, InsertIgnoreDuplicatesExt, MongoDbCollection, MongoDbCollectionExt,
+        },
         MongoDb,
     },
     model::{
         ledger::{LedgerOutput, LedgerSpent, RentStructureBytes},
         metadata::{OutputMetadata, SpentMetadata},
         tangle::{MilestoneIndex, MilestoneIndexTimestamp, MilestoneTimestamp},
-        utxo::{Address, AliasId, NftId, Output, OutputId},
-        BlockId,
+        utxo::{Address, AliasId, NftId, Output, OutputId, TokenAmount},
+        BlockId, RentStructure,
     },
 };
 
@@ -64,8 +66,9 @@ impl MongoDbCollection for OutputCollection {
         &self.collection
     }
 
-    async fn create_indexes(&self) -> Result<(), Error> {
-        self.create_index(
+    fn indexes(&self) -> Vec<IndexDefinition> {
+        let mut indexes = vec![IndexDefinition::new(
+            &[IndexGroup::Core],
             IndexModel::builder()
                 .keys(doc! { "metadata.block_id": 1 })
                 .options(
@@ -75,13 +78,9 @@ impl MongoDbCollection for OutputCollection {
                         .build(),
                 )
                 .build(),
-            None,
-        )
-        .await?;
-
-        self.create_indexer_indexes().await?;
-
-        Ok(())
+        )];
+        indexes.extend(indexer::indexes());
+        indexes
     }
 }
 
@@ -169,6 +168,58 @@ pub struct BalanceResult {
     pub sig_locked_balance: String,
 }
 
+#[derive(Clone, Debug, Deserialize)]
+#[allow(missing_docs)]
+pub struct StorageDepositResult {
+    pub total_balance: String,
+    pub storage_deposit: String,
+    pub spendable_balance: String,
+    pub output_count: usize,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[allow(missing_docs)]
+pub struct TimedBalanceResult {
+    pub total_balance: String,
+    pub timelocked_balance: String,
+    pub spendable_balance: String,
+}
+
+/// How the ability of an address to spend an output changes at a point in time.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnlockEventKind {
+    /// The timelock of an output owned by the address ends.
+    BecomesSpendable,
+    /// An output expires and can then be spent by the address as its return address.
+    ExpiresToAddress,
+    /// An output owned by the address expires and can then be spent by its return address.
+    ExpiresFromAddress,
+}
+
+impl UnlockEventKind {
+    /// All unlock event kinds.
+    pub const ALL: &'static [Self] = &[Self::BecomesSpendable, Self::ExpiresToAddress, Self::ExpiresFromAddress];
+
+    /// The name of the kind, which is also how unlock events are ordered within an output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::BecomesSpendable => "becomes_spendable",
+            Self::ExpiresToAddress => "expires_to_address",
+            Self::ExpiresFromAddress => "expires_from_address",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[allow(missing_docs)]
+pub struct UnlockEventResult {
+    pub output_id: OutputId,
+    pub amount: TokenAmount,
+    pub kind: UnlockEventKind,
+    pub timestamp: MilestoneTimestamp,
+}
+
 #[derive(Clone, Debug, Default, Deserialize)]
 #[allow(missing_docs)]
 pub struct UtxoChangesResult {
@@ -296,6 +347,63 @@ impl OutputCollection {
         .await
     }
 
+    /// Get multiple [`Output`]s with their [`OutputMetadata`] by [`OutputId`]s. Unknown ids and outputs that were
+    /// booked after the ledger index are omitted from the result.
+    pub async fn get_outputs_with_metadata(
+        &self,
+        output_ids: &[OutputId],
+        ledger_index: MilestoneIndex,
+    ) -> Result<Vec<OutputWithMetadataResult>, Error> {
+        self.aggregate(
+            [
+                doc! { "$match": {
+                    "_id": { "$in": output_ids.to_vec() },
+                    "metadata.booked.milestone_index": { "$lte": ledger_index }
+                } },
+                doc! { "$project": {
+                    "output": "$output",
+                    "metadata": {
+                        "output_id": "$_id",
+                        "block_id": "$metadata.block_id",
+                        "booked": "$metadata.booked",
+                        "spent_metadata": "$metadata.spent_metadata",
+                    },
+                } },
+            ],
+            None,
+        )
+        .await?
+        .try_collect()
+        .await
+    }
+
+    /// Get multiple [`OutputMetadata`] by [`OutputId`]s. Unknown ids and outputs that were booked after the ledger
+    /// index are omitted from the result.
+    pub async fn get_outputs_metadata(
+        &self,
+        output_ids: &[OutputId],
+        ledger_index: MilestoneIndex,
+    ) -> Result<Vec<OutputMetadataResult>, Error> {
+        self.aggregate(
+            [
+                doc! { "$match": {
+                    "_id": { "$in": output_ids.to_vec() },
+                    "metadata.booked.milestone_index": { "$lte": ledger_index }
+                } },
+                doc! { "$project": {
+                    "output_id": "$_id",
+                    "block_id": "$metadata.block_id",
+                    "booked": "$metadata.booked",
+                    "spent_metadata": "$metadata.spent_metadata",
+                } },
+            ],
+            None,
+        )
+        .await?
+        .try_collect()
+        .await
+    }
+
     /// Stream all [`LedgerOutput`]s that were unspent at a given ledger index.
     pub async fn get_unspent_output_stream(
         &self,
@@ -450,6 +558,206 @@ impl OutputCollection {
             .await
     }
 
+    /// Sums the storage deposit that the outputs owned by the given [`Address`](crate::model::utxo::Address) require
+    /// under the given [`RentStructure`]. The deposit of an output is capped at its amount. The spendable balance
+    /// excludes the deposits, the amounts that storage deposit return unlock conditions owe to other addresses and
+    /// outputs that are still timelocked at the given timestamp.
+    pub async fn get_address_storage_deposit(
+        &self,
+        address: Address,
+        ledger_index: MilestoneIndex,
+        timestamp: MilestoneTimestamp,
+        rent_structure: RentStructure,
+    ) -> Result<StorageDepositResult, Error> {
+        Ok(self
+            .aggregate(
+                [
+                    doc! { "$match": {
+                        "details.address": &address,
+                        "metadata.booked.milestone_index": { "$lte": ledger_index },
+                        "metadata.spent_metadata.spent.milestone_index": { "$not": { "$lte": ledger_index } }
+                    } },
+                    doc! { "$project": {
+                        "amount": { "$toDecimal": "$output.amount" },
+                        "storage_deposit": { "$multiply": [
+                            { "$toDecimal": rent_structure.v_byte_cost as i64 },
+                            { "$add": [
+                                { "$multiply": [
+                                    "$details.rent_structure.num_data_bytes",
+                                    rent_structure.v_byte_factor_data as i64,
+                                ] },
+                                { "$multiply": [
+                                    "$details.rent_structure.num_key_bytes",
+                                    rent_structure.v_byte_factor_key as i64,
+                                ] },
+                            ] },
+                        ] },
+                        "return_amount": { "$toDecimal": {
+                            "$ifNull": [ "$output.storage_deposit_return_unlock_condition.amount", 0 ]
+                        } },
+                        "is_timelocked": { "$and": [
+                            { "$gt": [ "$output.timelock_unlock_condition", null ] },
+                            { "$gt": [ "$output.timelock_unlock_condition.timestamp", timestamp ] },
+                        ] },
+                    } },
+                    doc! { "$group": {
+                        "_id": null,
+                        "total_balance": { "$sum": "$amount" },
+                        "storage_deposit": { "$sum": { "$min": [ "$amount", "$storage_deposit" ] } },
+                        "locked_balance": { "$sum": { "$cond": [
+                            "$is_timelocked",
+                            "$amount",
+                            { "$min": [ "$amount", { "$add": [ "$storage_deposit", "$return_amount" ] } ] },
+                        ] } },
+                        "output_count": { "$sum": 1 },
+                    } },
+                    doc! { "$project": {
+                        "total_balance": { "$toString": "$total_balance" },
+                        "storage_deposit": { "$toString": "$storage_deposit" },
+                        "spendable_balance": { "$toString": { "$subtract": [ "$total_balance", "$locked_balance" ] } },
+                        "output_count": 1,
+                    } },
+                ],
+                None,
+            )
+            .await?
+            .try_next()
+            .await?
+            // An address without outputs has no balance.
+            .unwrap_or_else(|| StorageDepositResult {
+                total_balance: "0".to_string(),
+                storage_deposit: "0".to_string(),
+                spendable_balance: "0".to_string(),
+                output_count: 0,
+            }))
+    }
+
+    /// Sums the amounts of the outputs that the given [`Address`](crate::model::utxo::Address) controls at the given
+    /// timestamp. Unlike [`get_address_balance`](Self::get_address_balance), expired outputs count towards their
+    /// return address, and outputs that are still timelocked are reported separately.
+    pub async fn get_address_balance_at(
+        &self,
+        address: Address,
+        ledger_index: MilestoneIndex,
+        timestamp: MilestoneTimestamp,
+    ) -> Result<TimedBalanceResult, Error> {
+        Ok(self
+            .aggregate(
+                [
+                    doc! { "$match": {
+                        "$or": [
+                            { "details.address": &address },
+                            { "output.expiration_unlock_condition.return_address": &address },
+                        ],
+                        "metadata.booked.milestone_index": { "$lte": ledger_index },
+                        "metadata.spent_metadata.spent.milestone_index": { "$not": { "$lte": ledger_index } }
+                    } },
+                    doc! { "$project": {
+                        "amount": { "$toDecimal": "$output.amount" },
+                        "controller": { "$cond": [
+                            { "$and": [
+                                { "$gt": [ "$output.expiration_unlock_condition", null ] },
+                                { "$lte": [ "$output.expiration_unlock_condition.timestamp", timestamp ] },
+                            ] },
+                            "$output.expiration_unlock_condition.return_address",
+                            "$details.address",
+                        ] },
+                        "is_timelocked": { "$and": [
+                            { "$gt": [ "$output.timelock_unlock_condition", null ] },
+                            { "$gt": [ "$output.timelock_unlock_condition.timestamp", timestamp ] },
+                        ] },
+                    } },
+                    doc! { "$match": { "controller": &address } },
+                    doc! { "$group": {
+                        "_id": null,
+                        "total_balance": { "$sum": "$amount" },
+                        "timelocked_balance": { "$sum": { "$cond": [ "$is_timelocked", "$amount", 0 ] } },
+                    } },
+                    doc! { "$project": {
+                        "total_balance": { "$toString": "$total_balance" },
+                        "timelocked_balance": { "$toString": "$timelocked_balance" },
+                        "spendable_balance": { "$toString": {
+                            "$subtract": [ "$total_balance", "$timelocked_balance" ]
+                        } },
+                    } },
+                ],
+                None,
+            )
+            .await?
+            .try_next()
+            .await?
+            // An address without outputs has no balance.
+            .unwrap_or_else(|| TimedBalanceResult {
+                total_balance: "0".to_string(),
+                timelocked_balance: "0".to_string(),
+                spendable_balance: "0".to_string(),
+            }))
+    }
+
+    /// Gets the [`UnlockEventResult`]s of the unspent outputs of the given [`Address`](crate::model::utxo::Address)
+    /// that happen within the given time range, ordered by their timestamp, output id and kind. The cursor continues
+    /// at the given output id and kind of the first timestamp of the range.
+    pub async fn get_address_unlock_events(
+        &self,
+        address: Address,
+        ledger_index: MilestoneIndex,
+        time_range: RangeInclusive<MilestoneTimestamp>,
+        page_size: usize,
+        cursor: Option<(OutputId, UnlockEventKind)>,
+    ) -> Result<impl Stream<Item = Result<UnlockEventResult, Error>>, Error> {
+        let (start_timestamp, end_timestamp) = time_range.into_inner();
+        let events = |kind: UnlockEventKind, address_field: &str, timestamp_field: &str| {
+            vec![
+                doc! { "$match": {
+                    address_field: &address,
+                    timestamp_field: { "$gte": start_timestamp, "$lte": end_timestamp },
+                    "metadata.booked.milestone_index": { "$lte": ledger_index },
+                    "metadata.spent_metadata.spent.milestone_index": { "$not": { "$lte": ledger_index } }
+                } },
+                doc! { "$project": {
+                    "_id": 0,
+                    "output_id": "$_id",
+                    "amount": "$output.amount",
+                    "kind": { "$literal": kind.as_str() },
+                    "timestamp": format!("${}", timestamp_field),
+                } },
+            ]
+        };
+
+        let mut pipeline = events(
+            UnlockEventKind::BecomesSpendable,
+            "details.address",
+            "output.timelock_unlock_condition.timestamp",
+        );
+        pipeline.push(doc! { "$unionWith": {
+            "coll": Self::NAME,
+            "pipeline": events(
+                UnlockEventKind::ExpiresToAddress,
+                "output.expiration_unlock_condition.return_address",
+                "output.expiration_unlock_condition.timestamp",
+            ),
+        } });
+        pipeline.push(doc! { "$unionWith": {
+            "coll": Self::NAME,
+            "pipeline": events(
+                UnlockEventKind::ExpiresFromAddress,
+                "details.address",
+                "output.expiration_unlock_condition.timestamp",
+            ),
+        } });
+        if let Some((output_id, kind)) = cursor {
+            pipeline.push(doc! { "$match": { "$or": [
+                { "timestamp": { "$gt": start_timestamp } },
+                { "timestamp": start_timestamp, "output_id": { "$gt": output_id } },
+                { "timestamp": start_timestamp, "output_id": output_id, "kind": { "$gte": kind.as_str() } },
+            ] } });
+        }
+        pipeline.push(doc! { "$sort": { "timestamp": 1, "output_id": 1, "kind": 1 } });
+        pipeline.push(doc! { "$limit": page_size as i64 });
+
+        self.aggregate(pipeline, None).await
+    }
+
     /// Returns the changes to the UTXO ledger (as consumed and created output ids) that were applied at the given
     /// `index`. It returns `None` if the provided `index` is out of bounds (beyond Chronicle's ledger index). If
     /// the associated milestone did not perform any changes to the ledger, the returned `Vec`s will be empty.
@@ -1,10 +1,11 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use futures::Stream;
 use mongodb::{
     bson::doc,
     error::Error,
-    options::{FindOneOptions, UpdateOptions},
+    options::{FindOneOptions, FindOptions, UpdateOptions},
 };
 use serde::{Deserialize, Serialize};
 
@@ -18,6 +19,7 @@ use crate::{
 
 /// A milestone's metadata.
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[allow(missing_docs)]
 pub struct ProtocolUpdateDocument {
     #[serde(rename = "_id")]
     pub tangle_index: MilestoneIndex,
@@ -61,6 +63,26 @@ impl ProtocolUpdateCollection {
         .await
     }
 
+    /// Gets the first protocol parameters update that applies after the given ledger index.
+    pub async fn get_next_protocol_parameters(
+        &self,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<ProtocolUpdateDocument>, Error> {
+        self.find_one(
+            doc! { "_id": { "$gt": ledger_index } },
+            FindOneOptions::builder().sort(doc! { "_id": 1 }).build(),
+        )
+        .await
+    }
+
+    /// Gets all versions of the protocol parameters, ordered by the ledger index from which they apply.
+    pub async fn get_protocol_parameters_history(
+        &self,
+    ) -> Result<impl Stream<Item = Result<ProtocolUpdateDocument, Error>>, Error> {
+        self.find(doc! {}, FindOptions::builder().sort(doc! { "_id": 1 }).build())
+            .await
+    }
+
     /// Gets the protocol parameters for the given milestone index, if they were changed.
     pub async fn get_protocol_parameters_for_milestone_index(
         &self,
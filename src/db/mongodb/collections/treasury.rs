@@ -1,6 +1,7 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+use futures::Stream;
 use mongodb::{
     bson::doc,
     error::Error,
@@ -9,6 +10,7 @@ use mongodb::{
 use serde::{Deserialize, Serialize};
 use tracing::instrument;
 
+use super::SortOrder;
 use crate::{
     db::{
         mongodb::{InsertIgnoreDuplicatesExt, MongoDbCollection, MongoDbCollectionExt},
@@ -55,6 +57,15 @@ pub struct TreasuryResult {
     pub amount: u64,
 }
 
+/// The state of the treasury after a milestone.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct TreasuryHistoryResult {
+    pub milestone_index: MilestoneIndex,
+    pub milestone_id: MilestoneId,
+    pub amount: u64,
+}
+
 /// Queries that are related to the treasury.
 impl TreasuryCollection {
     /// Inserts treasury data.
@@ -98,4 +109,45 @@ impl TreasuryCollection {
         self.find_one(doc! {}, FindOneOptions::builder().sort(doc! { "_id": -1 }).build())
             .await
     }
+
+    /// Returns the state of the treasury at the given ledger index.
+    pub async fn get_treasury_for_ledger_index(
+        &self,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<TreasuryResult>, Error> {
+        self.find_one(
+            doc! { "_id": { "$lte": ledger_index } },
+            FindOneOptions::builder().sort(doc! { "_id": -1 }).build(),
+        )
+        .await
+    }
+
+    /// Returns the states of the treasury after each milestone that changed it.
+    pub async fn get_treasury_history(
+        &self,
+        order: SortOrder,
+        page_size: usize,
+        cursor: Option<MilestoneIndex>,
+    ) -> Result<impl Stream<Item = Result<TreasuryHistoryResult, Error>>, Error> {
+        let (sort, cmp) = match order {
+            SortOrder::Newest => (doc! { "_id": -1 }, "$gt"),
+            SortOrder::Oldest => (doc! { "_id": 1 }, "$lt"),
+        };
+
+        self.aggregate(
+            [
+                doc! { "$match": { "$nor": [ { "_id": { cmp: cursor } } ] } },
+                doc! { "$sort": sort },
+                doc! { "$limit": page_size as i64 },
+                doc! { "$project": {
+                    "_id": 0,
+                    "milestone_index": "$_id",
+                    "milestone_id": 1,
+                    "amount": 1,
+                } },
+            ],
+            None,
+        )
+        .await
+    }
 }
@@ -7,12 +7,30 @@ use mongodb::{
     error::Error,
     options::{ConnectionString, HostInfo},
 };
+use serde::{Deserialize, Serialize};
 
 /// The default connection string of the database.
 pub const DEFAULT_CONN_STR: &str = "mongodb://localhost:27017";
 /// The default name of the database to connect to.
 pub const DEFAULT_DATABASE_NAME: &str = "chronicle";
 
+/// How blocks are stored in the database.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockStorageMode {
+    /// The decoded block is stored together with its raw bytes.
+    Full,
+    /// Only the raw bytes are stored, together with the fields of the block that are needed by the indexes. The block
+    /// is decoded from the raw bytes when it is read.
+    RawOnly,
+}
+
+impl Default for BlockStorageMode {
+    fn default() -> Self {
+        Self::Full
+    }
+}
+
 /// The [`super::MongoDb`] config.
 #[must_use]
 #[derive(Clone, PartialEq, Eq, Debug)]
@@ -21,6 +39,8 @@ pub struct MongoDbConfig {
     pub conn_str: String,
     /// The name of the database to connect to.
     pub database_name: String,
+    /// How blocks are stored in the database.
+    pub block_storage_mode: BlockStorageMode,
 }
 
 impl MongoDbConfig {
@@ -40,6 +60,7 @@ impl Default for MongoDbConfig {
         Self {
             conn_str: DEFAULT_CONN_STR.to_string(),
             database_name: DEFAULT_DATABASE_NAME.to_string(),
+            block_storage_mode: Default::default(),
         }
     }
 }
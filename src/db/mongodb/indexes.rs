@@ -0,0 +1,164 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Definitions and status of the indexes that Chronicle maintains.
+
+use std::collections::HashSet;
+
+use mongodb::IndexModel;
+
+/// A group of indexes that serve the queries of one part of the application.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum IndexGroup {
+    /// Indexes that are needed for ingestion and the core API.
+    Core,
+    /// Indexes that are needed by the explorer API.
+    Explorer,
+    /// Indexes that are needed by the indexer API.
+    Indexer,
+}
+
+impl IndexGroup {
+    /// All index groups.
+    pub const ALL: &'static [Self] = &[Self::Core, Self::Explorer, Self::Indexer];
+}
+
+/// The definition of an index together with the groups that make use of it.
+#[derive(Clone, Debug)]
+pub struct IndexDefinition {
+    model: IndexModel,
+    groups: &'static [IndexGroup],
+}
+
+impl IndexDefinition {
+    /// Creates a new index definition. The model must be named.
+    pub fn new(groups: &'static [IndexGroup], model: IndexModel) -> Self {
+        debug_assert!(
+            model.options.as_ref().and_then(|o| o.name.as_ref()).is_some(),
+            "index definitions must be named"
+        );
+        Self { model, groups }
+    }
+
+    /// Gets the name of the index.
+    pub fn name(&self) -> &str {
+        self.model
+            .options
+            .as_ref()
+            .and_then(|o| o.name.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// Gets the groups that make use of the index.
+    pub fn groups(&self) -> &'static [IndexGroup] {
+        self.groups
+    }
+
+    /// Gets the underlying index model.
+    pub fn model(&self) -> &IndexModel {
+        &self.model
+    }
+}
+
+/// The set of index groups that should exist in the database.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexProfile {
+    groups: HashSet<IndexGroup>,
+}
+
+impl IndexProfile {
+    /// Creates a profile from the given groups. The [`IndexGroup::Core`] group is always included.
+    pub fn new(groups: impl IntoIterator<Item = IndexGroup>) -> Self {
+        let mut groups = groups.into_iter().collect::<HashSet<_>>();
+        groups.insert(IndexGroup::Core);
+        Self { groups }
+    }
+
+    /// Gets the groups of the profile in a stable order.
+    pub fn groups(&self) -> Vec<IndexGroup> {
+        let mut groups = self.groups.iter().copied().collect::<Vec<_>>();
+        groups.sort();
+        groups
+    }
+
+    /// Whether the profile requires the given index.
+    pub fn requires(&self, index: &IndexDefinition) -> bool {
+        index.groups.iter().any(|group| self.groups.contains(group))
+    }
+}
+
+impl Default for IndexProfile {
+    fn default() -> Self {
+        Self::new(IndexGroup::ALL.iter().copied())
+    }
+}
+
+/// The name and size of an index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub struct IndexInfo {
+    pub name: String,
+    pub size: u64,
+}
+
+/// The status of the indexes of a collection with regard to an [`IndexProfile`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IndexStatus {
+    /// The name of the collection.
+    pub collection: String,
+    /// Indexes that are required by the profile and exist.
+    pub present: Vec<IndexInfo>,
+    /// Indexes that are required by the profile but do not exist.
+    pub missing: Vec<String>,
+    /// Indexes that are defined by Chronicle and exist, but are not required by the profile.
+    pub unused: Vec<IndexInfo>,
+    /// Indexes that exist but are not defined by Chronicle.
+    pub unknown: Vec<IndexInfo>,
+}
+
+impl IndexStatus {
+    /// The total size of all existing indexes of the collection.
+    pub fn total_size(&self) -> u64 {
+        self.present
+            .iter()
+            .chain(&self.unused)
+            .chain(&self.unknown)
+            .map(|info| info.size)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use mongodb::{bson::doc, options::IndexOptions};
+
+    use super::*;
+
+    fn definition(name: &str, groups: &'static [IndexGroup]) -> IndexDefinition {
+        IndexDefinition::new(
+            groups,
+            IndexModel::builder()
+                .keys(doc! { name: 1 })
+                .options(IndexOptions::builder().name(name.to_string()).build())
+                .build(),
+        )
+    }
+
+    #[test]
+    fn profile_always_requires_core() {
+        let profile = IndexProfile::new([]);
+        assert_eq!(profile.groups(), vec![IndexGroup::Core]);
+        assert!(profile.requires(&definition("core", &[IndexGroup::Core])));
+        assert!(!profile.requires(&definition("indexer", &[IndexGroup::Indexer])));
+    }
+
+    #[test]
+    fn profile_requires_shared_indexes() {
+        let profile = IndexProfile::new([IndexGroup::Explorer]);
+        let shared = definition("shared", &[IndexGroup::Explorer, IndexGroup::Indexer]);
+        assert_eq!(shared.name(), "shared");
+        assert!(profile.requires(&shared));
+        assert!(!profile.requires(&definition("indexer", &[IndexGroup::Indexer])));
+        assert!(IndexProfile::default().requires(&definition("indexer", &[IndexGroup::Indexer])));
+    }
+}
@@ -7,24 +7,30 @@ mod collection;
 /// Module containing the collections in the database.
 pub mod collections;
 pub mod config;
+mod indexes;
+mod storage;
 
 use std::collections::{HashMap, HashSet};
 
-use config::MongoDbConfig;
+use config::{BlockStorageMode, MongoDbConfig};
 use mongodb::{
-    bson::{doc, Document},
+    bson::{doc, Bson, Document},
     error::Error,
     options::ClientOptions,
     Client,
 };
 
-pub use self::collection::{InsertIgnoreDuplicatesExt, MongoDbCollection, MongoDbCollectionExt};
+pub use self::{
+    collection::{InsertIgnoreDuplicatesExt, MongoDbCollection, MongoDbCollectionExt},
+    indexes::{IndexDefinition, IndexGroup, IndexInfo, IndexProfile, IndexStatus},
+};
 
 /// A handle to the underlying `MongoDB` database.
 #[derive(Clone, Debug)]
 pub struct MongoDb {
     pub(crate) database_name: String,
     pub(crate) client: mongodb::Client,
+    pub(crate) block_storage_mode: BlockStorageMode,
 }
 
 impl MongoDb {
@@ -39,6 +45,7 @@ impl MongoDb {
         Ok(Self {
             database_name: config.database_name.clone(),
             client,
+            block_storage_mode: config.block_storage_mode,
         })
     }
 
@@ -55,6 +62,99 @@ impl MongoDb {
         Ok(())
     }
 
+    /// Creates a collection if it does not exist together with the indexes that are required by the profile.
+    pub async fn create_index_profile<T: MongoDbCollection + Send + Sync>(
+        &self,
+        profile: &IndexProfile,
+    ) -> Result<(), Error> {
+        let collection = self.collection::<T>();
+        collection.create_collection(self).await?;
+        for index in collection.indexes().into_iter().filter(|index| profile.requires(index)) {
+            collection
+                .collection()
+                .create_index(index.model().clone(), None)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Drops the indexes of a collection that are defined by Chronicle but not required by the profile. Indexes that
+    /// are unknown to Chronicle are left untouched. Returns the names of the dropped indexes.
+    pub async fn drop_unused_indexes<T: MongoDbCollection + Send + Sync>(
+        &self,
+        profile: &IndexProfile,
+    ) -> Result<Vec<String>, Error> {
+        let collection = self.collection::<T>();
+        let status = self.index_status::<T>(profile).await?;
+        let mut dropped = Vec::new();
+        for IndexInfo { name, .. } in status.unused {
+            collection.drop_index(&name, None).await?;
+            dropped.push(name);
+        }
+        Ok(dropped)
+    }
+
+    /// Compares the existing indexes of a collection with the indexes that are required by the profile.
+    pub async fn index_status<T: MongoDbCollection + Send + Sync>(
+        &self,
+        profile: &IndexProfile,
+    ) -> Result<IndexStatus, Error> {
+        let mut status = IndexStatus {
+            collection: T::NAME.to_string(),
+            ..Default::default()
+        };
+        let definitions = self.collection::<T>().indexes();
+        let sizes = self.get_index_sizes(T::NAME).await?;
+        for definition in &definitions {
+            if profile.requires(definition) {
+                match sizes.get(definition.name()) {
+                    Some(&size) => status.present.push(IndexInfo {
+                        name: definition.name().to_string(),
+                        size,
+                    }),
+                    None => status.missing.push(definition.name().to_string()),
+                }
+            } else if let Some(&size) = sizes.get(definition.name()) {
+                status.unused.push(IndexInfo {
+                    name: definition.name().to_string(),
+                    size,
+                });
+            }
+        }
+        let mut unknown = sizes
+            .into_iter()
+            .filter(|(name, _)| name != ID_INDEX_NAME && !definitions.iter().any(|d| d.name() == name))
+            .map(|(name, size)| IndexInfo { name, size })
+            .collect::<Vec<_>>();
+        unknown.sort_by(|a, b| a.name.cmp(&b.name));
+        status.unknown = unknown;
+        Ok(status)
+    }
+
+    /// Gets the sizes of the indexes of a collection by their name. Returns an empty map if the collection does not
+    /// exist.
+    async fn get_index_sizes(&self, collection: &str) -> Result<HashMap<String, u64>, Error> {
+        if !self
+            .db()
+            .list_collection_names(doc! { "name": collection })
+            .await?
+            .iter()
+            .any(|name| name == collection)
+        {
+            return Ok(HashMap::new());
+        }
+        let stats = self.db().run_command(doc! { "collStats": collection }, None).await?;
+        Ok(stats
+            .get_document("indexSizes")
+            .map(|sizes| {
+                sizes
+                    .iter()
+                    .map(|(name, size)| (name.clone(), bson_to_u64(size)))
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
     /// Gets a collection of the provided type.
     pub fn collection<T: MongoDbCollection>(&self) -> T {
         T::instantiate(self, self.db().collection(T::NAME))
@@ -118,8 +218,25 @@ impl MongoDb {
         self.client.list_database_names(None, None).await
     }
 
+    /// Returns how blocks are stored in the database.
+    pub fn block_storage_mode(&self) -> BlockStorageMode {
+        self.block_storage_mode
+    }
+
     /// Returns the name of the database.
     pub fn name(&self) -> &str {
         &self.database_name
     }
 }
+
+/// The name of the index that MongoDB creates for the `_id` field of every collection.
+const ID_INDEX_NAME: &str = "_id_";
+
+fn bson_to_u64(value: &Bson) -> u64 {
+    match value {
+        Bson::Int32(i) => *i as u64,
+        Bson::Int64(i) => *i as u64,
+        Bson::Double(f) => *f as u64,
+        _ => 0,
+    }
+}
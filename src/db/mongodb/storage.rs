@@ -0,0 +1,558 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implements the storage traits for [`MongoDb`] by delegating to the collections.
+
+use async_trait::async_trait;
+use futures::{stream::BoxStream, TryStreamExt};
+
+use super::{
+    collections::{
+        ApiKeyCollection, ApiKeyDocument, ApplicationStateCollection, BlockCollection, BlockMetadataResult,
+        BlockRawResult, ConfigurationUpdateCollection, ConfigurationUpdateDocument, IncludedBlockMetadataResult,
+        IncludedBlockResult, LedgerUpdateByAddressRecord, LedgerUpdateByMilestoneRecord, LedgerUpdateCollection,
+        MigrationVersion, MilestoneCollection, OutputCollection, OutputMetadataResult, OutputWithMetadataResult,
+        ProtocolUpdateCollection, ProtocolUpdateDocument, SortOrder, TreasuryCollection, TreasuryResult,
+        UtxoChangesResult,
+    },
+    MongoDb,
+};
+use crate::{
+    db::storage::{
+        ApiKeyStorage, ApplicationStateStorage, BlockStorage, ConfigurationUpdateStorage, LedgerUpdateStorage,
+        MilestoneStorage, OutputStorage, ProtocolUpdateStorage, StorageBackend, StorageError, TreasuryStorage,
+    },
+    model::{
+        ledger::{LedgerOutput, LedgerSpent},
+        metadata::{BlockMetadata, SpentMetadata},
+        node::NodeConfiguration,
+        payload::{MilestoneId, MilestoneOption, MilestonePayload, TransactionId},
+        tangle::{MilestoneIndex, MilestoneIndexTimestamp, MilestoneTimestamp},
+        utxo::{Address, Output, OutputId},
+        Block, BlockId, ProtocolParameters,
+    },
+    tangle::{BlockData, IngestionFilter, MilestoneStats},
+};
+
+#[async_trait]
+impl StorageBackend for MongoDb {
+    fn backend_name(&self) -> &'static str {
+        "MongoDB"
+    }
+
+    async fn clear(&self) -> Result<(), StorageError> {
+        Ok(MongoDb::clear(self).await?)
+    }
+}
+
+#[async_trait]
+impl ApplicationStateStorage for MongoDb {
+    async fn get_starting_index(&self) -> Result<Option<MilestoneIndexTimestamp>, StorageError> {
+        Ok(self
+            .collection::<ApplicationStateCollection>()
+            .get_starting_index()
+            .await?)
+    }
+
+    async fn set_starting_index(&self, starting_index: MilestoneIndexTimestamp) -> Result<(), StorageError> {
+        Ok(self
+            .collection::<ApplicationStateCollection>()
+            .set_starting_index(starting_index)
+            .await?)
+    }
+
+    async fn get_last_migration(&self) -> Result<Option<MigrationVersion>, StorageError> {
+        Ok(self
+            .collection::<ApplicationStateCollection>()
+            .get_last_migration()
+            .await?)
+    }
+
+    async fn set_last_migration(&self, last_migration: MigrationVersion) -> Result<(), StorageError> {
+        Ok(self
+            .collection::<ApplicationStateCollection>()
+            .set_last_migration(last_migration)
+            .await?)
+    }
+
+    async fn get_ingestion_filter(&self) -> Result<Option<IngestionFilter>, StorageError> {
+        Ok(self
+            .collection::<ApplicationStateCollection>()
+            .get_ingestion_filter()
+            .await?)
+    }
+
+    async fn set_ingestion_filter(&self, ingestion_filter: Option<&IngestionFilter>) -> Result<(), StorageError> {
+        Ok(self
+            .collection::<ApplicationStateCollection>()
+            .set_ingestion_filter(ingestion_filter)
+            .await?)
+    }
+}
+
+#[async_trait]
+impl BlockStorage for MongoDb {
+    async fn insert_blocks_with_metadata(&self, blocks: Vec<BlockData>) -> Result<(), StorageError> {
+        Ok(self
+            .collection::<BlockCollection>()
+            .insert_blocks_with_metadata(blocks)
+            .await?)
+    }
+
+    async fn get_block(&self, block_id: &BlockId) -> Result<Option<Block>, StorageError> {
+        Ok(self.collection::<BlockCollection>().get_block(block_id).await?)
+    }
+
+    async fn get_block_raw(&self, block_id: &BlockId) -> Result<Option<Vec<u8>>, StorageError> {
+        Ok(self.collection::<BlockCollection>().get_block_raw(block_id).await?)
+    }
+
+    async fn get_block_metadata(&self, block_id: &BlockId) -> Result<Option<BlockMetadata>, StorageError> {
+        Ok(self
+            .collection::<BlockCollection>()
+            .get_block_metadata(block_id)
+            .await?)
+    }
+
+    async fn get_blocks_raw(&self, block_ids: &[BlockId]) -> Result<Vec<BlockRawResult>, StorageError> {
+        Ok(self.collection::<BlockCollection>().get_blocks_raw(block_ids).await?)
+    }
+
+    async fn get_blocks_metadata(&self, block_ids: &[BlockId]) -> Result<Vec<BlockMetadataResult>, StorageError> {
+        Ok(self
+            .collection::<BlockCollection>()
+            .get_blocks_metadata(block_ids)
+            .await?)
+    }
+
+    async fn get_block_children(
+        &self,
+        block_id: &BlockId,
+        block_referenced_index: MilestoneIndex,
+        below_max_depth: u8,
+        page_size: usize,
+        page: usize,
+    ) -> Result<Vec<BlockId>, StorageError> {
+        Ok(self
+            .collection::<BlockCollection>()
+            .get_block_children(block_id, block_referenced_index, below_max_depth, page_size, page)
+            .await?
+            .try_collect()
+            .await?)
+    }
+
+    async fn get_referenced_blocks_in_white_flag_order(
+        &self,
+        index: MilestoneIndex,
+    ) -> Result<Vec<BlockId>, StorageError> {
+        Ok(self
+            .collection::<BlockCollection>()
+            .get_referenced_blocks_in_white_flag_order(index)
+            .await?)
+    }
+
+    async fn get_block_for_transaction(
+        &self,
+        transaction_id: &TransactionId,
+    ) -> Result<Option<IncludedBlockResult>, StorageError> {
+        Ok(self
+            .collection::<BlockCollection>()
+            .get_block_for_transaction(transaction_id)
+            .await?)
+    }
+
+    async fn get_block_raw_for_transaction(
+        &self,
+        transaction_id: &TransactionId,
+    ) -> Result<Option<Vec<u8>>, StorageError> {
+        Ok(self
+            .collection::<BlockCollection>()
+            .get_block_raw_for_transaction(transaction_id)
+            .await?)
+    }
+
+    async fn get_block_metadata_for_transaction(
+        &self,
+        transaction_id: &TransactionId,
+    ) -> Result<Option<IncludedBlockMetadataResult>, StorageError> {
+        Ok(self
+            .collection::<BlockCollection>()
+            .get_block_metadata_for_transaction(transaction_id)
+            .await?)
+    }
+
+    async fn get_spending_transaction(&self, output_id: &OutputId) -> Result<Option<Block>, StorageError> {
+        Ok(self
+            .collection::<BlockCollection>()
+            .get_spending_transaction(output_id)
+            .await?)
+    }
+}
+
+#[async_trait]
+impl OutputStorage for MongoDb {
+    async fn insert_unspent_outputs(&self, outputs: &[LedgerOutput]) -> Result<(), StorageError> {
+        Ok(self
+            .collection::<OutputCollection>()
+            .insert_unspent_outputs(outputs)
+            .await?)
+    }
+
+    async fn update_spent_outputs(&self, outputs: &[LedgerSpent]) -> Result<(), StorageError> {
+        Ok(self
+            .collection::<OutputCollection>()
+            .update_spent_outputs(outputs)
+            .await?)
+    }
+
+    async fn get_output(&self, output_id: &OutputId) -> Result<Option<Output>, StorageError> {
+        Ok(self.collection::<OutputCollection>().get_output(output_id).await?)
+    }
+
+    async fn get_output_with_metadata(
+        &self,
+        output_id: &OutputId,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<OutputWithMetadataResult>, StorageError> {
+        Ok(self
+            .collection::<OutputCollection>()
+            .get_output_with_metadata(output_id, ledger_index)
+            .await?)
+    }
+
+    async fn get_output_metadata(
+        &self,
+        output_id: &OutputId,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<OutputMetadataResult>, StorageError> {
+        Ok(self
+            .collection::<OutputCollection>()
+            .get_output_metadata(output_id, ledger_index)
+            .await?)
+    }
+
+    async fn get_outputs_with_metadata(
+        &self,
+        output_ids: &[OutputId],
+        ledger_index: MilestoneIndex,
+    ) -> Result<Vec<OutputWithMetadataResult>, StorageError> {
+        Ok(self
+            .collection::<OutputCollection>()
+            .get_outputs_with_metadata(output_ids, ledger_index)
+            .await?)
+    }
+
+    async fn get_outputs_metadata(
+        &self,
+        output_ids: &[OutputId],
+        ledger_index: MilestoneIndex,
+    ) -> Result<Vec<OutputMetadataResult>, StorageError> {
+        Ok(self
+            .collection::<OutputCollection>()
+            .get_outputs_metadata(output_ids, ledger_index)
+            .await?)
+    }
+
+    async fn get_spending_transaction_metadata(
+        &self,
+        output_id: &OutputId,
+    ) -> Result<Option<SpentMetadata>, StorageError> {
+        Ok(self
+            .collection::<OutputCollection>()
+            .get_spending_transaction_metadata(output_id)
+            .await?)
+    }
+
+    async fn get_unspent_outputs(
+        &self,
+        ledger_index: MilestoneIndex,
+    ) -> Result<BoxStream<'static, Result<LedgerOutput, StorageError>>, StorageError> {
+        Ok(Box::pin(
+            self.collection::<OutputCollection>()
+                .get_unspent_output_stream(ledger_index)
+                .await?
+                .map_err(Into::into),
+        ))
+    }
+
+    async fn get_utxo_changes(
+        &self,
+        index: MilestoneIndex,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<UtxoChangesResult>, StorageError> {
+        Ok(self
+            .collection::<OutputCollection>()
+            .get_utxo_changes(index, ledger_index)
+            .await?)
+    }
+}
+
+#[async_trait]
+impl LedgerUpdateStorage for MongoDb {
+    async fn insert_unspent_ledger_updates(&self, outputs: &[LedgerOutput]) -> Result<(), StorageError> {
+        Ok(self
+            .collection::<LedgerUpdateCollection>()
+            .insert_unspent_ledger_updates(outputs)
+            .await?)
+    }
+
+    async fn insert_spent_ledger_updates(&self, outputs: &[LedgerSpent]) -> Result<(), StorageError> {
+        Ok(self
+            .collection::<LedgerUpdateCollection>()
+            .insert_spent_ledger_updates(outputs)
+            .await?)
+    }
+
+    async fn get_ledger_updates_by_address(
+        &self,
+        address: &Address,
+        page_size: usize,
+        cursor: Option<(MilestoneIndex, Option<(OutputId, bool)>)>,
+        order: SortOrder,
+    ) -> Result<Vec<LedgerUpdateByAddressRecord>, StorageError> {
+        Ok(self
+            .collection::<LedgerUpdateCollection>()
+            .get_ledger_updates_by_address(address, page_size, cursor, order)
+            .await?
+            .try_collect()
+            .await?)
+    }
+
+    async fn get_ledger_updates_by_milestone(
+        &self,
+        milestone_index: MilestoneIndex,
+        page_size: usize,
+        cursor: Option<(OutputId, bool)>,
+    ) -> Result<Vec<LedgerUpdateByMilestoneRecord>, StorageError> {
+        Ok(self
+            .collection::<LedgerUpdateCollection>()
+            .get_ledger_updates_by_milestone(milestone_index, page_size, cursor)
+            .await?
+            .try_collect()
+            .await?)
+    }
+}
+
+#[async_trait]
+impl MilestoneStorage for MongoDb {
+    async fn insert_milestone(
+        &self,
+        milestone_id: MilestoneId,
+        milestone_index: MilestoneIndex,
+        milestone_timestamp: MilestoneTimestamp,
+        payload: MilestonePayload,
+        signature_verified: Option<bool>,
+        stats: Option<MilestoneStats>,
+    ) -> Result<(), StorageError> {
+        Ok(self
+            .collection::<MilestoneCollection>()
+            .insert_milestone(
+                milestone_id,
+                milestone_index,
+                milestone_timestamp,
+                payload,
+                signature_verified,
+                stats,
+            )
+            .await?)
+    }
+
+    async fn get_milestone_payload_by_id(
+        &self,
+        milestone_id: &MilestoneId,
+    ) -> Result<Option<MilestonePayload>, StorageError> {
+        Ok(self
+            .collection::<MilestoneCollection>()
+            .get_milestone_payload_by_id(milestone_id)
+            .await?)
+    }
+
+    async fn get_milestone_payload(&self, index: MilestoneIndex) -> Result<Option<MilestonePayload>, StorageError> {
+        Ok(self
+            .collection::<MilestoneCollection>()
+            .get_milestone_payload(index)
+            .await?)
+    }
+
+    async fn get_milestone(
+        &self,
+        index: MilestoneIndex,
+    ) -> Result<Option<(MilestoneId, MilestoneIndexTimestamp, MilestonePayload)>, StorageError> {
+        Ok(self.collection::<MilestoneCollection>().get_milestone(index).await?)
+    }
+
+    async fn get_milestone_id(&self, index: MilestoneIndex) -> Result<Option<MilestoneId>, StorageError> {
+        Ok(self.collection::<MilestoneCollection>().get_milestone_id(index).await?)
+    }
+
+    async fn get_milestone_signature_verified(&self, index: MilestoneIndex) -> Result<Option<bool>, StorageError> {
+        Ok(self
+            .collection::<MilestoneCollection>()
+            .get_milestone_signature_verified(index)
+            .await?)
+    }
+
+    async fn get_milestone_stats(&self, index: MilestoneIndex) -> Result<Option<MilestoneStats>, StorageError> {
+        Ok(self
+            .collection::<MilestoneCollection>()
+            .get_milestone_stats(index)
+            .await?)
+    }
+
+    async fn set_milestone_stats(&self, index: MilestoneIndex, stats: MilestoneStats) -> Result<(), StorageError> {
+        Ok(self
+            .collection::<MilestoneCollection>()
+            .set_milestone_stats(index, stats)
+            .await?)
+    }
+
+    async fn get_newest_milestone(&self) -> Result<Option<MilestoneIndexTimestamp>, StorageError> {
+        Ok(self.collection::<MilestoneCollection>().get_newest_milestone().await?)
+    }
+
+    async fn get_oldest_milestone(&self) -> Result<Option<MilestoneIndexTimestamp>, StorageError> {
+        Ok(self.collection::<MilestoneCollection>().get_oldest_milestone().await?)
+    }
+
+    async fn get_all_receipts(&self) -> Result<Vec<(MilestoneOption, MilestoneIndex)>, StorageError> {
+        Ok(self
+            .collection::<MilestoneCollection>()
+            .get_all_receipts()
+            .await?
+            .try_collect()
+            .await?)
+    }
+
+    async fn get_receipts_migrated_at(
+        &self,
+        migrated_at: MilestoneIndex,
+    ) -> Result<Vec<(MilestoneOption, MilestoneIndex)>, StorageError> {
+        Ok(self
+            .collection::<MilestoneCollection>()
+            .get_receipts_migrated_at(migrated_at)
+            .await?
+            .try_collect()
+            .await?)
+    }
+}
+
+#[async_trait]
+impl TreasuryStorage for MongoDb {
+    async fn insert_treasury_payloads(
+        &self,
+        payloads: Vec<(MilestoneIndex, MilestoneId, u64)>,
+    ) -> Result<(), StorageError> {
+        Ok(self
+            .collection::<TreasuryCollection>()
+            .insert_treasury_payloads(payloads)
+            .await?)
+    }
+
+    async fn get_latest_treasury(&self) -> Result<Option<TreasuryResult>, StorageError> {
+        Ok(self.collection::<TreasuryCollection>().get_latest_treasury().await?)
+    }
+
+    async fn get_treasury_for_ledger_index(
+        &self,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<TreasuryResult>, StorageError> {
+        Ok(self
+            .collection::<TreasuryCollection>()
+            .get_treasury_for_ledger_index(ledger_index)
+            .await?)
+    }
+}
+
+#[async_trait]
+impl ProtocolUpdateStorage for MongoDb {
+    async fn get_latest_protocol_parameters(&self) -> Result<Option<ProtocolUpdateDocument>, StorageError> {
+        Ok(self
+            .collection::<ProtocolUpdateCollection>()
+            .get_latest_protocol_parameters()
+            .await?)
+    }
+
+    async fn get_protocol_parameters_for_ledger_index(
+        &self,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<ProtocolUpdateDocument>, StorageError> {
+        Ok(self
+            .collection::<ProtocolUpdateCollection>()
+            .get_protocol_parameters_for_ledger_index(ledger_index)
+            .await?)
+    }
+
+    async fn get_protocol_parameters_for_version(
+        &self,
+        version: u8,
+    ) -> Result<Option<ProtocolUpdateDocument>, StorageError> {
+        Ok(self
+            .collection::<ProtocolUpdateCollection>()
+            .get_protocol_parameters_for_version(version)
+            .await?)
+    }
+
+    async fn upsert_protocol_parameters(
+        &self,
+        ledger_index: MilestoneIndex,
+        parameters: ProtocolParameters,
+    ) -> Result<(), StorageError> {
+        Ok(self
+            .collection::<ProtocolUpdateCollection>()
+            .upsert_protocol_parameters(ledger_index, parameters)
+            .await?)
+    }
+}
+
+#[async_trait]
+impl ConfigurationUpdateStorage for MongoDb {
+    async fn get_latest_node_configuration(&self) -> Result<Option<ConfigurationUpdateDocument>, StorageError> {
+        Ok(self
+            .collection::<ConfigurationUpdateCollection>()
+            .get_latest_node_configuration()
+            .await?)
+    }
+
+    async fn get_node_configuration_for_ledger_index(
+        &self,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<ConfigurationUpdateDocument>, StorageError> {
+        Ok(self
+            .collection::<ConfigurationUpdateCollection>()
+            .get_node_configuration_for_ledger_index(ledger_index)
+            .await?)
+    }
+
+    async fn upsert_node_configuration(
+        &self,
+        ledger_index: MilestoneIndex,
+        config: NodeConfiguration,
+    ) -> Result<(), StorageError> {
+        Ok(self
+            .collection::<ConfigurationUpdateCollection>()
+            .upsert_node_configuration(ledger_index, config)
+            .await?)
+    }
+}
+
+#[async_trait]
+impl ApiKeyStorage for MongoDb {
+    async fn insert_api_key(&self, key: &ApiKeyDocument) -> Result<bool, StorageError> {
+        Ok(self.collection::<ApiKeyCollection>().insert_api_key(key).await?)
+    }
+
+    async fn get_api_key(&self, name: &str) -> Result<Option<ApiKeyDocument>, StorageError> {
+        Ok(self.collection::<ApiKeyCollection>().get_api_key(name).await?)
+    }
+
+    async fn get_api_keys(&self) -> Result<Vec<ApiKeyDocument>, StorageError> {
+        Ok(self.collection::<ApiKeyCollection>().get_api_keys().await?)
+    }
+
+    async fn revoke_api_key(&self, name: &str, revoked_at: u64) -> Result<bool, StorageError> {
+        Ok(self
+            .collection::<ApiKeyCollection>()
+            .revoke_api_key(name, revoked_at)
+            .await?)
+    }
+}
@@ -0,0 +1,398 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Storage traits that abstract over the database backends.
+//!
+//! The traits cover the queries of the INX worker and the core API. Queries that rely on the aggregation framework of
+//! MongoDB (e.g. the explorer and indexer APIs, or analytics over arbitrary time ranges) are only available on
+//! [`MongoDb`](super::MongoDb) directly.
+
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use thiserror::Error;
+
+use super::mongodb::collections::{
+    ApiKeyDocument, BlockMetadataResult, BlockRawResult, ConfigurationUpdateDocument, IncludedBlockMetadataResult,
+    IncludedBlockResult, LedgerUpdateByAddressRecord, LedgerUpdateByMilestoneRecord, MigrationVersion,
+    OutputMetadataResult, OutputWithMetadataResult, ProtocolUpdateDocument, SortOrder, TreasuryResult,
+    UtxoChangesResult,
+};
+use crate::{
+    model::{
+        ledger::{LedgerOutput, LedgerSpent},
+        metadata::{BlockMetadata, SpentMetadata},
+        node::NodeConfiguration,
+        payload::{MilestoneId, MilestoneOption, MilestonePayload, TransactionId},
+        tangle::{MilestoneIndex, MilestoneIndexTimestamp, MilestoneTimestamp},
+        utxo::{Address, Output, OutputId},
+        Block, BlockId, ProtocolParameters,
+    },
+    tangle::{BlockData, IngestionFilter, MilestoneStats},
+};
+
+/// An error that is returned by a storage backend.
+#[derive(Debug, Error)]
+#[allow(missing_docs)]
+pub enum StorageError {
+    #[error(transparent)]
+    MongoDb(#[from] mongodb::error::Error),
+    #[cfg(feature = "kv")]
+    #[error(transparent)]
+    Kv(#[from] super::kv::KvDbError),
+}
+
+/// The base of all storage traits.
+#[async_trait]
+pub trait StorageBackend: Clone + Send + Sync + 'static {
+    /// Returns the name of the storage backend.
+    fn backend_name(&self) -> &'static str;
+
+    /// Removes all data from the storage.
+    async fn clear(&self) -> Result<(), StorageError>;
+}
+
+/// Stores the singleton application state.
+#[async_trait]
+pub trait ApplicationStateStorage: StorageBackend {
+    /// Gets the application starting milestone index.
+    async fn get_starting_index(&self) -> Result<Option<MilestoneIndexTimestamp>, StorageError>;
+
+    /// Sets the application starting milestone index.
+    async fn set_starting_index(&self, starting_index: MilestoneIndexTimestamp) -> Result<(), StorageError>;
+
+    /// Gets the last migration version of the storage.
+    async fn get_last_migration(&self) -> Result<Option<MigrationVersion>, StorageError>;
+
+    /// Sets the last migration version of the storage.
+    async fn set_last_migration(&self, last_migration: MigrationVersion) -> Result<(), StorageError>;
+
+    /// Gets the filter that is applied to the blocks that are stored, if any.
+    async fn get_ingestion_filter(&self) -> Result<Option<IngestionFilter>, StorageError>;
+
+    /// Sets the filter that is applied to the blocks that are stored.
+    async fn set_ingestion_filter(&self, ingestion_filter: Option<&IngestionFilter>) -> Result<(), StorageError>;
+}
+
+/// Stores [`Block`]s together with their [`BlockMetadata`].
+#[async_trait]
+pub trait BlockStorage: StorageBackend {
+    /// Inserts [`Block`]s together with their associated [`BlockMetadata`]. Blocks that already exist are ignored.
+    async fn insert_blocks_with_metadata(&self, blocks: Vec<BlockData>) -> Result<(), StorageError>;
+
+    /// Gets a [`Block`] by its [`BlockId`].
+    async fn get_block(&self, block_id: &BlockId) -> Result<Option<Block>, StorageError>;
+
+    /// Gets the raw bytes of a [`Block`] by its [`BlockId`].
+    async fn get_block_raw(&self, block_id: &BlockId) -> Result<Option<Vec<u8>>, StorageError>;
+
+    /// Gets the [`BlockMetadata`] of a [`Block`] by its [`BlockId`].
+    async fn get_block_metadata(&self, block_id: &BlockId) -> Result<Option<BlockMetadata>, StorageError>;
+
+    /// Gets the raw bytes of multiple [`Block`]s by their [`BlockId`]s. Unknown ids are omitted from the result.
+    async fn get_blocks_raw(&self, block_ids: &[BlockId]) -> Result<Vec<BlockRawResult>, StorageError>;
+
+    /// Gets the [`BlockMetadata`] of multiple [`Block`]s by their [`BlockId`]s. Unknown ids are omitted from the
+    /// result.
+    async fn get_blocks_metadata(&self, block_ids: &[BlockId]) -> Result<Vec<BlockMetadataResult>, StorageError>;
+
+    /// Gets a page of the children of a [`Block`] that were referenced within `below_max_depth` milestones of the
+    /// block, newest first.
+    async fn get_block_children(
+        &self,
+        block_id: &BlockId,
+        block_referenced_index: MilestoneIndex,
+        below_max_depth: u8,
+        page_size: usize,
+        page: usize,
+    ) -> Result<Vec<BlockId>, StorageError>;
+
+    /// Gets the blocks that were referenced by the specified milestone in White-Flag order.
+    async fn get_referenced_blocks_in_white_flag_order(
+        &self,
+        index: MilestoneIndex,
+    ) -> Result<Vec<BlockId>, StorageError>;
+
+    /// Finds the [`Block`] that included a transaction by [`TransactionId`].
+    async fn get_block_for_transaction(
+        &self,
+        transaction_id: &TransactionId,
+    ) -> Result<Option<IncludedBlockResult>, StorageError>;
+
+    /// Finds the raw bytes of the [`Block`] that included a transaction by [`TransactionId`].
+    async fn get_block_raw_for_transaction(
+        &self,
+        transaction_id: &TransactionId,
+    ) -> Result<Option<Vec<u8>>, StorageError>;
+
+    /// Finds the [`BlockMetadata`] of the [`Block`] that included a transaction by [`TransactionId`].
+    async fn get_block_metadata_for_transaction(
+        &self,
+        transaction_id: &TransactionId,
+    ) -> Result<Option<IncludedBlockMetadataResult>, StorageError>;
+
+    /// Gets the [`Block`] of the transaction that spent an [`Output`] by [`OutputId`].
+    async fn get_spending_transaction(&self, output_id: &OutputId) -> Result<Option<Block>, StorageError>;
+}
+
+/// Stores [`Output`]s together with their metadata.
+#[async_trait]
+pub trait OutputStorage: StorageBackend {
+    /// Inserts unspent [`Output`]s. Outputs that already exist are ignored.
+    async fn insert_unspent_outputs(&self, outputs: &[LedgerOutput]) -> Result<(), StorageError>;
+
+    /// Inserts or updates spent [`Output`]s.
+    async fn update_spent_outputs(&self, outputs: &[LedgerSpent]) -> Result<(), StorageError>;
+
+    /// Gets an [`Output`] by [`OutputId`].
+    async fn get_output(&self, output_id: &OutputId) -> Result<Option<Output>, StorageError>;
+
+    /// Gets an [`Output`] with its metadata by [`OutputId`] if it was booked at or before the ledger index.
+    async fn get_output_with_metadata(
+        &self,
+        output_id: &OutputId,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<OutputWithMetadataResult>, StorageError>;
+
+    /// Gets the metadata of an [`Output`] by [`OutputId`] if it was booked at or before the ledger index.
+    async fn get_output_metadata(
+        &self,
+        output_id: &OutputId,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<OutputMetadataResult>, StorageError>;
+
+    /// Gets multiple [`Output`]s with their metadata by [`OutputId`]s. Unknown ids and outputs that were booked after
+    /// the ledger index are omitted from the result.
+    async fn get_outputs_with_metadata(
+        &self,
+        output_ids: &[OutputId],
+        ledger_index: MilestoneIndex,
+    ) -> Result<Vec<OutputWithMetadataResult>, StorageError>;
+
+    /// Gets the metadata of multiple [`Output`]s by [`OutputId`]s. Unknown ids and outputs that were booked after the
+    /// ledger index are omitted from the result.
+    async fn get_outputs_metadata(
+        &self,
+        output_ids: &[OutputId],
+        ledger_index: MilestoneIndex,
+    ) -> Result<Vec<OutputMetadataResult>, StorageError>;
+
+    /// Gets the [`SpentMetadata`] of an [`Output`] by [`OutputId`], if it was spent.
+    async fn get_spending_transaction_metadata(
+        &self,
+        output_id: &OutputId,
+    ) -> Result<Option<SpentMetadata>, StorageError>;
+
+    /// Gets a stream of all [`LedgerOutput`]s that were unspent at the given ledger index.
+    async fn get_unspent_outputs(
+        &self,
+        ledger_index: MilestoneIndex,
+    ) -> Result<BoxStream<'static, Result<LedgerOutput, StorageError>>, StorageError>;
+
+    /// Gets the changes to the UTXO ledger that were applied at the given `index`. Returns `None` if `index` is beyond
+    /// the ledger index.
+    async fn get_utxo_changes(
+        &self,
+        index: MilestoneIndex,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<UtxoChangesResult>, StorageError>;
+}
+
+/// Stores the updates to the ledger per address.
+#[async_trait]
+pub trait LedgerUpdateStorage: StorageBackend {
+    /// Inserts the ledger updates of unspent [`LedgerOutput`]s.
+    async fn insert_unspent_ledger_updates(&self, outputs: &[LedgerOutput]) -> Result<(), StorageError>;
+
+    /// Inserts the ledger updates of [`LedgerSpent`]s.
+    async fn insert_spent_ledger_updates(&self, outputs: &[LedgerSpent]) -> Result<(), StorageError>;
+
+    /// Gets a page of the ledger updates of an [`Address`], starting at the cursor.
+    async fn get_ledger_updates_by_address(
+        &self,
+        address: &Address,
+        page_size: usize,
+        cursor: Option<(MilestoneIndex, Option<(OutputId, bool)>)>,
+        order: SortOrder,
+    ) -> Result<Vec<LedgerUpdateByAddressRecord>, StorageError>;
+
+    /// Gets a page of the ledger updates of a milestone sorted by [`OutputId`], starting at the cursor.
+    async fn get_ledger_updates_by_milestone(
+        &self,
+        milestone_index: MilestoneIndex,
+        page_size: usize,
+        cursor: Option<(OutputId, bool)>,
+    ) -> Result<Vec<LedgerUpdateByMilestoneRecord>, StorageError>;
+}
+
+/// Stores milestones.
+#[async_trait]
+pub trait MilestoneStorage: StorageBackend {
+    /// Inserts a milestone. The statistics are missing if the cone of the milestone was not read.
+    async fn insert_milestone(
+        &self,
+        milestone_id: MilestoneId,
+        milestone_index: MilestoneIndex,
+        milestone_timestamp: MilestoneTimestamp,
+        payload: MilestonePayload,
+        signature_verified: Option<bool>,
+        stats: Option<MilestoneStats>,
+    ) -> Result<(), StorageError>;
+
+    /// Gets the [`MilestonePayload`] of a milestone by [`MilestoneId`].
+    async fn get_milestone_payload_by_id(
+        &self,
+        milestone_id: &MilestoneId,
+    ) -> Result<Option<MilestonePayload>, StorageError>;
+
+    /// Gets the [`MilestonePayload`] of a milestone by [`MilestoneIndex`].
+    async fn get_milestone_payload(&self, index: MilestoneIndex) -> Result<Option<MilestonePayload>, StorageError>;
+
+    /// Gets the [`MilestoneId`], the [`MilestoneIndexTimestamp`] and the [`MilestonePayload`] of a milestone by
+    /// [`MilestoneIndex`].
+    async fn get_milestone(
+        &self,
+        index: MilestoneIndex,
+    ) -> Result<Option<(MilestoneId, MilestoneIndexTimestamp, MilestonePayload)>, StorageError>;
+
+    /// Gets the [`MilestoneId`] of a milestone by [`MilestoneIndex`].
+    async fn get_milestone_id(&self, index: MilestoneIndex) -> Result<Option<MilestoneId>, StorageError>;
+
+    /// Gets whether the signatures of a milestone were verified by [`MilestoneIndex`].
+    async fn get_milestone_signature_verified(&self, index: MilestoneIndex) -> Result<Option<bool>, StorageError>;
+
+    /// Gets the statistics of a milestone by [`MilestoneIndex`].
+    async fn get_milestone_stats(&self, index: MilestoneIndex) -> Result<Option<MilestoneStats>, StorageError>;
+
+    /// Sets the statistics of an existing milestone.
+    async fn set_milestone_stats(&self, index: MilestoneIndex, stats: MilestoneStats) -> Result<(), StorageError>;
+
+    /// Gets the newest milestone.
+    async fn get_newest_milestone(&self) -> Result<Option<MilestoneIndexTimestamp>, StorageError>;
+
+    /// Gets the oldest milestone.
+    async fn get_oldest_milestone(&self) -> Result<Option<MilestoneIndexTimestamp>, StorageError>;
+
+    /// Gets the current ledger index.
+    async fn get_ledger_index(&self) -> Result<Option<MilestoneIndex>, StorageError> {
+        Ok(self.get_newest_milestone().await?.map(|at| at.milestone_index))
+    }
+
+    /// Gets all receipt milestone options together with the index of their milestone.
+    async fn get_all_receipts(&self) -> Result<Vec<(MilestoneOption, MilestoneIndex)>, StorageError>;
+
+    /// Gets the receipt milestone options that were migrated at the given index together with the index of their
+    /// milestone.
+    async fn get_receipts_migrated_at(
+        &self,
+        migrated_at: MilestoneIndex,
+    ) -> Result<Vec<(MilestoneOption, MilestoneIndex)>, StorageError>;
+}
+
+/// Stores the state of the treasury.
+#[async_trait]
+pub trait TreasuryStorage: StorageBackend {
+    /// Inserts the state of the treasury at the given milestones. Existing states are ignored.
+    async fn insert_treasury_payloads(
+        &self,
+        payloads: Vec<(MilestoneIndex, MilestoneId, u64)>,
+    ) -> Result<(), StorageError>;
+
+    /// Gets the current state of the treasury.
+    async fn get_latest_treasury(&self) -> Result<Option<TreasuryResult>, StorageError>;
+
+    /// Gets the state of the treasury at the given ledger index.
+    async fn get_treasury_for_ledger_index(
+        &self,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<TreasuryResult>, StorageError>;
+}
+
+/// Stores the history of the [`ProtocolParameters`].
+#[async_trait]
+pub trait ProtocolUpdateStorage: StorageBackend {
+    /// Gets the latest protocol parameters.
+    async fn get_latest_protocol_parameters(&self) -> Result<Option<ProtocolUpdateDocument>, StorageError>;
+
+    /// Gets the protocol parameters that are valid for the given ledger index.
+    async fn get_protocol_parameters_for_ledger_index(
+        &self,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<ProtocolUpdateDocument>, StorageError>;
+
+    /// Gets the protocol parameters for a given protocol version.
+    async fn get_protocol_parameters_for_version(
+        &self,
+        version: u8,
+    ) -> Result<Option<ProtocolUpdateDocument>, StorageError>;
+
+    /// Adds the protocol parameters at the given ledger index if they have changed.
+    async fn upsert_protocol_parameters(
+        &self,
+        ledger_index: MilestoneIndex,
+        parameters: ProtocolParameters,
+    ) -> Result<(), StorageError>;
+}
+
+/// Stores the history of the [`NodeConfiguration`].
+#[async_trait]
+pub trait ConfigurationUpdateStorage: StorageBackend {
+    /// Gets the latest node configuration.
+    async fn get_latest_node_configuration(&self) -> Result<Option<ConfigurationUpdateDocument>, StorageError>;
+
+    /// Gets the node configuration that was valid for the given ledger index.
+    async fn get_node_configuration_for_ledger_index(
+        &self,
+        ledger_index: MilestoneIndex,
+    ) -> Result<Option<ConfigurationUpdateDocument>, StorageError>;
+
+    /// Adds the node configuration at the given ledger index if it has changed.
+    async fn upsert_node_configuration(
+        &self,
+        ledger_index: MilestoneIndex,
+        config: NodeConfiguration,
+    ) -> Result<(), StorageError>;
+}
+
+/// Stores the named keys that are used to authenticate against the API.
+#[async_trait]
+pub trait ApiKeyStorage: StorageBackend {
+    /// Inserts an API key. Returns `false` if a key with the same name already exists.
+    async fn insert_api_key(&self, key: &ApiKeyDocument) -> Result<bool, StorageError>;
+
+    /// Gets an API key by its name.
+    async fn get_api_key(&self, name: &str) -> Result<Option<ApiKeyDocument>, StorageError>;
+
+    /// Gets all API keys ordered by their name.
+    async fn get_api_keys(&self) -> Result<Vec<ApiKeyDocument>, StorageError>;
+
+    /// Revokes an API key at the given unix timestamp. Returns `false` if there is no such key that is not yet
+    /// revoked.
+    async fn revoke_api_key(&self, name: &str, revoked_at: u64) -> Result<bool, StorageError>;
+}
+
+/// A storage backend that implements all storage traits.
+pub trait Storage:
+    ApplicationStateStorage
+    + BlockStorage
+    + OutputStorage
+    + LedgerUpdateStorage
+    + MilestoneStorage
+    + TreasuryStorage
+    + ProtocolUpdateStorage
+    + ConfigurationUpdateStorage
+    + ApiKeyStorage
+{
+}
+
+impl<T> Storage for T where
+    T: ApplicationStateStorage
+        + BlockStorage
+        + OutputStorage
+        + LedgerUpdateStorage
+        + MilestoneStorage
+        + TreasuryStorage
+        + ProtocolUpdateStorage
+        + ConfigurationUpdateStorage
+        + ApiKeyStorage
+{
+}
@@ -12,6 +12,7 @@ use super::{
     request::MilestoneRequest,
     InxError, LedgerUpdateMessage, MilestoneRangeRequest, NodeStatusMessage, RawProtocolParametersMessage,
 };
+use crate::model::BlockId;
 
 /// An INX client connection.
 #[derive(Clone, Debug)]
@@ -35,6 +36,14 @@ impl Inx {
         })
     }
 
+    /// Creates a client for the INX interface of a node that only connects once it is used.
+    #[allow(clippy::result_large_err)]
+    pub fn connect_lazy(address: String) -> Result<Self, InxError> {
+        Ok(Self {
+            inx: InxClient::new(inx::tonic::transport::Endpoint::from_shared(address)?.connect_lazy()),
+        })
+    }
+
     /// Convenience wrapper that listen to ledger updates as a stream of
     /// [`MilestoneAndProtocolParametersMessages`](MilestoneAndProtocolParametersMessage).
     pub async fn listen_to_confirmed_milestones(
@@ -121,4 +130,26 @@ impl Inx {
                 .into_inner(),
         )
     }
+
+    /// Convenience wrapper that submits a serialized block to the node and returns its [`BlockId`].
+    pub async fn submit_block(&mut self, raw_block: Vec<u8>) -> Result<BlockId, InxError> {
+        BlockId::try_from(
+            self.inx
+                .submit_block(proto::RawBlock { data: raw_block })
+                .await?
+                .into_inner(),
+        )
+    }
+
+    /// Convenience wrapper that requests up to `count` tips from the node.
+    pub async fn request_tips(&mut self, count: u32, allow_semi_lazy: bool) -> Result<Vec<BlockId>, InxError> {
+        self.inx
+            .request_tips(proto::TipsRequest { count, allow_semi_lazy })
+            .await?
+            .into_inner()
+            .tips
+            .into_iter()
+            .map(BlockId::try_from)
+            .collect()
+    }
 }
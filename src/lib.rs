@@ -15,6 +15,8 @@ pub mod inx;
 #[cfg(feature = "metrics")]
 pub mod metrics;
 pub mod model;
+pub mod poi;
+pub mod snapshot;
 pub mod tangle;
 
 #[allow(missing_docs)]
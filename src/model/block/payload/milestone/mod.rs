@@ -11,11 +11,12 @@ use std::borrow::Borrow;
 
 use iota_types::block::payload::milestone as iota;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 pub use self::{milestone_id::MilestoneId, milestone_index::MilestoneIndex, milestone_timestamp::MilestoneTimestamp};
 use crate::model::{
     block::BlockId, bytify, payload::TreasuryTransactionPayload, signature::Signature, stringify, utxo::Address,
-    TryFromWithContext, TryIntoWithContext,
+    NodeConfiguration, TryFromWithContext, TryIntoWithContext,
 };
 
 /// [`MilestoneIndex`] and [`MilestoneTimestamp`] pair.
@@ -45,6 +46,30 @@ pub struct MilestonePayload {
 impl MilestonePayload {
     /// A `&str` representation of the type.
     pub const KIND: &'static str = "milestone";
+
+    /// Verifies the signatures of the milestone against the public keys that are applicable at its index.
+    pub fn verify_signatures(
+        &self,
+        ctx: &iota_types::block::protocol::ProtocolParameters,
+        node_config: &NodeConfiguration,
+    ) -> Result<(), MilestoneSignatureError> {
+        let public_keys = node_config.applicable_public_keys(self.essence.index)?;
+        iota::MilestonePayload::try_from_with_context(ctx, self.clone())?
+            .validate(&public_keys, node_config.milestone_public_key_count as usize)
+            .map_err(MilestoneSignatureError::Invalid)
+    }
+}
+
+/// The different errors that can happen when verifying the signatures of a milestone.
+#[derive(Debug, Error)]
+#[allow(missing_docs)]
+pub enum MilestoneSignatureError {
+    #[error("invalid milestone public key: {0}")]
+    InvalidPublicKey(#[from] prefix_hex::Error),
+    #[error(transparent)]
+    Block(#[from] iota_types::block::Error),
+    #[error("invalid milestone signatures: {0:?}")]
+    Invalid(iota::MilestoneValidationError),
 }
 
 impl<T: Borrow<iota::MilestonePayload>> From<T> for MilestonePayload {
@@ -302,7 +327,23 @@ pub struct MigratedFundsEntry {
 }
 
 impl MigratedFundsEntry {
-    const TAIL_TRANSACTION_HASH_LENGTH: usize = iota::option::TailTransactionHash::LENGTH;
+    /// The length of the tail transaction hash of a legacy bundle.
+    pub const TAIL_TRANSACTION_HASH_LENGTH: usize = iota::option::TailTransactionHash::LENGTH;
+
+    /// Returns the tail transaction hash of the bundle in which these funds were migrated.
+    pub fn tail_transaction_hash(&self) -> &[u8; Self::TAIL_TRANSACTION_HASH_LENGTH] {
+        &self.tail_transaction_hash
+    }
+
+    /// Returns the target address.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Returns the amount of tokens that have been migrated.
+    pub fn amount(&self) -> u64 {
+        self.amount
+    }
 }
 
 impl<T: Borrow<iota::option::MigratedFundsEntry>> From<T> for MigratedFundsEntry {
@@ -402,9 +443,11 @@ mod rand {
 
 #[cfg(all(test, feature = "rand"))]
 mod test {
+    use crypto::signatures::ed25519::SecretKey;
     use mongodb::bson::{from_bson, to_bson, Bson};
 
     use super::*;
+    use crate::model::{BaseToken, MilestoneKeyRange};
 
     #[test]
     fn test_milestone_id_bson() {
@@ -422,4 +465,68 @@ mod test {
         let bson = to_bson(&payload).unwrap();
         assert_eq!(payload, from_bson::<MilestonePayload>(bson).unwrap());
     }
+
+    #[test]
+    fn test_milestone_signatures() {
+        let ctx = iota_types::block::protocol::protocol_parameters();
+        let keys = [SecretKey::from_bytes([1; 32]), SecretKey::from_bytes([2; 32])];
+        let mut payload = MilestonePayload::rand(&ctx);
+        payload.essence.index = 10.into();
+        let essence_hash = iota::MilestonePayload::try_from_with_context(&ctx, payload.clone())
+            .unwrap()
+            .essence()
+            .hash();
+        payload.signatures = keys
+            .iter()
+            .map(|key| Signature::Ed25519 {
+                public_key: key.public_key().to_bytes(),
+                signature: key.sign(&essence_hash).to_bytes(),
+            })
+            .collect();
+        let node_config = NodeConfiguration {
+            milestone_public_key_count: 2,
+            milestone_key_ranges: keys
+                .iter()
+                .map(|key| MilestoneKeyRange {
+                    public_key: prefix_hex::encode(key.public_key().to_bytes()),
+                    start: 0.into(),
+                    end: 0.into(),
+                })
+                .collect(),
+            base_token: BaseToken {
+                name: "Shimmer".to_string(),
+                ticker_symbol: "SMR".to_string(),
+                unit: "SMR".to_string(),
+                subunit: "glow".to_string(),
+                decimals: 6,
+                use_metric_prefix: false,
+            },
+        };
+        assert!(payload.verify_signatures(&ctx, &node_config).is_ok());
+
+        let mut tampered = payload.clone();
+        let Signature::Ed25519 { signature, .. } = &mut tampered.signatures[1];
+        signature[0] ^= 1;
+        assert!(matches!(
+            tampered.verify_signatures(&ctx, &node_config),
+            Err(MilestoneSignatureError::Invalid(_))
+        ));
+
+        let mut tampered = payload.clone();
+        tampered.essence.timestamp = (tampered.essence.timestamp.0 ^ 1).into();
+        assert!(matches!(
+            tampered.verify_signatures(&ctx, &node_config),
+            Err(MilestoneSignatureError::Invalid(_))
+        ));
+
+        // The keys are not applicable before the start of their range.
+        let mut node_config = node_config;
+        for key_range in node_config.milestone_key_ranges.iter_mut() {
+            key_range.start = 11.into();
+        }
+        assert!(matches!(
+            payload.verify_signatures(&ctx, &node_config),
+            Err(MilestoneSignatureError::Invalid(_))
+        ));
+    }
 }
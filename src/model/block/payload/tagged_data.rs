@@ -20,6 +20,11 @@ pub struct TaggedDataPayload {
 impl TaggedDataPayload {
     /// A `&str` representation of the type.
     pub const KIND: &'static str = "tagged_data";
+
+    /// Returns the tag of the payload.
+    pub fn tag(&self) -> &[u8] {
+        &self.tag
+    }
 }
 
 impl<T: Borrow<iota::TaggedDataPayload>> From<T> for TaggedDataPayload {
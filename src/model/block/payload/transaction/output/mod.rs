@@ -280,6 +280,12 @@ impl Tag {
     }
 }
 
+impl AsRef<[u8]> for Tag {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
 // Note: assumes an ASCII string as input.
 impl<T: ToString> From<T> for Tag {
     fn from(value: T) -> Self {
@@ -4,6 +4,7 @@
 //! Module containing the node models.
 
 use core::cmp::Ordering;
+use std::collections::HashSet;
 
 use serde::{Deserialize, Serialize};
 
@@ -18,6 +19,15 @@ pub struct NodeConfiguration {
     pub base_token: BaseToken,
 }
 
+impl NodeConfiguration {
+    /// Returns the milestone public keys that are applicable at the given index.
+    ///
+    /// The keys are hex-encoded without the `0x` prefix, which is the format expected for milestone validation.
+    pub fn applicable_public_keys(&self, index: MilestoneIndex) -> Result<Vec<String>, prefix_hex::Error> {
+        MilestoneKeyRange::applicable_public_keys(&self.milestone_key_ranges, index)
+    }
+}
+
 /// The [`BaseToken`] type.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[allow(missing_docs)]
@@ -39,6 +49,31 @@ pub struct MilestoneKeyRange {
     pub end: MilestoneIndex,
 }
 
+impl MilestoneKeyRange {
+    /// Returns the public keys of the given key ranges that are applicable at the given index.
+    ///
+    /// The keys are hex-encoded without the `0x` prefix, which is the format expected for milestone validation.
+    pub fn applicable_public_keys(
+        key_ranges: &[Self],
+        index: MilestoneIndex,
+    ) -> Result<Vec<String>, prefix_hex::Error> {
+        let mut key_ranges = key_ranges.to_vec();
+        key_ranges.sort();
+        let mut public_keys = HashSet::with_capacity(key_ranges.len());
+        for key_range in key_ranges.iter() {
+            match (key_range.start, key_range.end) {
+                (start, _) if start > index => break,
+                (start, end) if index <= end || start == end => {
+                    let public_key_raw = prefix_hex::decode::<Vec<u8>>(&key_range.public_key)?;
+                    public_keys.insert(prefix_hex::encode(public_key_raw)[2..].to_string());
+                }
+                (_, _) => continue,
+            }
+        }
+        Ok(public_keys.into_iter().collect())
+    }
+}
+
 impl Ord for MilestoneKeyRange {
     fn cmp(&self, other: &Self) -> Ordering {
         self.start.cmp(&other.start)
@@ -50,3 +85,42 @@ impl PartialOrd for MilestoneKeyRange {
         Some(self.cmp(other))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_applicable_public_keys() {
+        let key_range = |public_key: &str, start: u32, end: u32| MilestoneKeyRange {
+            public_key: public_key.to_string(),
+            start: start.into(),
+            end: end.into(),
+        };
+        let config = NodeConfiguration {
+            milestone_public_key_count: 1,
+            milestone_key_ranges: vec![
+                key_range("0xaa", 10, 20),
+                key_range("0xbb", 0, 0),
+                key_range("0xcc", 0, 15),
+            ]
+            .into_boxed_slice(),
+            base_token: BaseToken {
+                name: "Shimmer".to_string(),
+                ticker_symbol: "SMR".to_string(),
+                unit: "SMR".to_string(),
+                subunit: "glow".to_string(),
+                decimals: 6,
+                use_metric_prefix: false,
+            },
+        };
+
+        let mut keys = config.applicable_public_keys(12.into()).unwrap();
+        keys.sort();
+        assert_eq!(keys, vec!["aa", "bb", "cc"]);
+        let mut keys = config.applicable_public_keys(18.into()).unwrap();
+        keys.sort();
+        assert_eq!(keys, vec!["aa", "bb"]);
+        assert_eq!(config.applicable_public_keys(5.into()).unwrap().len(), 2);
+    }
+}
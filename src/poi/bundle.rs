@@ -0,0 +1,246 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use iota_types::block::{
+    payload::{dto::MilestonePayloadDto, milestone::MilestonePayload},
+    Block, BlockDto,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    error::VerifyProofError,
+    merkle_proof::{MerkleAuditPath, MerkleAuditPathDto},
+};
+use crate::model::{node::MilestoneKeyRange, tangle::MilestoneIndex, BlockId};
+
+/// The kind of inclusion that is proven by a [`ProofBundle`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofKind {
+    /// The block was referenced by the milestone.
+    Referenced,
+    /// The block was referenced by the milestone and applied to the ledger.
+    Applied,
+}
+
+/// A milestone public key and the range of milestone indexes in which it is applicable.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct MilestoneKeyRangeDto {
+    pub public_key: String,
+    pub start: MilestoneIndex,
+    pub end: MilestoneIndex,
+}
+
+impl From<MilestoneKeyRange> for MilestoneKeyRangeDto {
+    fn from(range: MilestoneKeyRange) -> Self {
+        Self {
+            public_key: range.public_key,
+            start: range.start,
+            end: range.end,
+        }
+    }
+}
+
+impl From<MilestoneKeyRangeDto> for MilestoneKeyRange {
+    fn from(range: MilestoneKeyRangeDto) -> Self {
+        Self {
+            public_key: range.public_key,
+            start: range.start,
+            end: range.end,
+        }
+    }
+}
+
+/// A self-contained proof that a block was included by a milestone.
+///
+/// Besides the block, the milestone and the Merkle audit path between them, the bundle contains the milestone public
+/// keys that were applicable at the time, so that the signatures of the milestone can be checked offline.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct ProofBundle {
+    pub kind: ProofKind,
+    pub milestone: MilestonePayloadDto,
+    pub block: BlockDto,
+    #[serde(rename = "proof")]
+    pub audit_path: MerkleAuditPathDto,
+    pub milestone_public_key_count: u32,
+    pub milestone_key_ranges: Vec<MilestoneKeyRangeDto>,
+}
+
+impl ProofBundle {
+    /// Verifies the bundle against the milestone public keys that it contains, and returns the id of the proven block.
+    ///
+    /// The keys are part of the bundle, so this only proves the inclusion if they are known to be the keys of the
+    /// network. Use [`verify_with_key_ranges`](Self::verify_with_key_ranges) to check against trusted keys instead.
+    pub fn verify(&self) -> Result<BlockId, VerifyProofError> {
+        let key_ranges = self
+            .milestone_key_ranges
+            .iter()
+            .cloned()
+            .map(Into::into)
+            .collect::<Vec<_>>();
+        self.verify_with_key_ranges(&key_ranges, self.milestone_public_key_count)
+    }
+
+    /// Verifies the bundle against the given milestone public keys, and returns the id of the proven block.
+    ///
+    /// The milestone must be signed by at least `public_key_count` of the keys that are applicable at its index, and
+    /// the audit path must lead from the block to the Merkle root of the milestone that matches the [`ProofKind`].
+    pub fn verify_with_key_ranges(
+        &self,
+        key_ranges: &[MilestoneKeyRange],
+        public_key_count: u32,
+    ) -> Result<BlockId, VerifyProofError> {
+        let block = Block::try_from_dto_unverified(&self.block).map_err(VerifyProofError::MalformedBlock)?;
+        let milestone =
+            MilestonePayload::try_from_dto_unverified(&self.milestone).map_err(VerifyProofError::MalformedMilestone)?;
+        let audit_path = MerkleAuditPath::try_from(self.audit_path.clone())?;
+
+        let public_keys = MilestoneKeyRange::applicable_public_keys(key_ranges, milestone.essence().index().into())
+            .map_err(VerifyProofError::DecodePublicKey)?;
+        milestone
+            .validate(&public_keys, public_key_count as usize)
+            .map_err(VerifyProofError::InvalidMilestone)?;
+
+        let block_id = BlockId::from(block.id());
+        if !audit_path.contains_block_id(&block_id) {
+            return Err(VerifyProofError::BlockNotContained(block_id.to_hex()));
+        }
+
+        let expected_merkle_root = match self.kind {
+            ProofKind::Referenced => milestone.essence().inclusion_merkle_root(),
+            ProofKind::Applied => milestone.essence().applied_merkle_root(),
+        };
+        let calculated_merkle_root = audit_path.hash();
+        if *calculated_merkle_root != **expected_merkle_root {
+            return Err(VerifyProofError::MerkleRootMismatch {
+                calculated_merkle_root: prefix_hex::encode(calculated_merkle_root.as_slice()),
+                expected_merkle_root: prefix_hex::encode(expected_merkle_root.as_ref()),
+            });
+        }
+
+        Ok(block_id)
+    }
+}
+
+#[cfg(all(test, feature = "rand"))]
+mod tests {
+    use crypto::signatures::ed25519::SecretKey;
+    use iota_types::block::{rand::block::rand_block, signature::dto::SignatureDto};
+
+    use super::*;
+    use crate::{
+        model::{payload, signature::Signature, TryFromWithContext},
+        poi::MerkleProof,
+    };
+
+    fn key_ranges(keys: &[SecretKey], start: u32, end: u32) -> Vec<MilestoneKeyRange> {
+        keys.iter()
+            .map(|key| MilestoneKeyRange {
+                public_key: prefix_hex::encode(key.public_key().to_bytes()),
+                start: start.into(),
+                end: end.into(),
+            })
+            .collect()
+    }
+
+    /// Creates a bundle for a block that was referenced by a milestone at index 10, which is signed by both keys.
+    fn referenced_bundle(keys: &[SecretKey]) -> (ProofBundle, BlockId) {
+        let ctx = iota_types::block::protocol::protocol_parameters();
+        let block = rand_block();
+        let block_id = BlockId::from(block.id());
+        let audit_path =
+            MerkleProof::create_audit_path(&[BlockId::rand(), block_id, BlockId::rand()], &block_id).unwrap();
+
+        let mut milestone = payload::MilestonePayload::rand(&ctx);
+        milestone.essence.index = 10.into();
+        milestone.essence.inclusion_merkle_root = audit_path.hash().as_slice().try_into().unwrap();
+        let essence_hash = MilestonePayload::try_from_with_context(&ctx, milestone.clone())
+            .unwrap()
+            .essence()
+            .hash();
+        milestone.signatures = keys
+            .iter()
+            .map(|key| Signature::Ed25519 {
+                public_key: key.public_key().to_bytes(),
+                signature: key.sign(&essence_hash).to_bytes(),
+            })
+            .collect();
+        let milestone = MilestonePayload::try_from_with_context(&ctx, milestone).unwrap();
+
+        let bundle = ProofBundle {
+            kind: ProofKind::Referenced,
+            milestone: MilestonePayloadDto::from(&milestone),
+            block: BlockDto::from(&block),
+            audit_path: audit_path.into(),
+            milestone_public_key_count: keys.len() as u32,
+            milestone_key_ranges: key_ranges(keys, 0, 0).into_iter().map(Into::into).collect(),
+        };
+        (bundle, block_id)
+    }
+
+    fn keys() -> [SecretKey; 2] {
+        [SecretKey::from_bytes([1; 32]), SecretKey::from_bytes([2; 32])]
+    }
+
+    #[test]
+    fn test_verify_bundle() {
+        let keys = keys();
+        let (bundle, block_id) = referenced_bundle(&keys);
+
+        assert_eq!(bundle.verify().unwrap(), block_id);
+        assert_eq!(
+            bundle.verify_with_key_ranges(&key_ranges(&keys, 0, 0), 2).unwrap(),
+            block_id
+        );
+    }
+
+    #[test]
+    fn test_verify_bundle_with_tampered_signature() {
+        let (mut bundle, _) = referenced_bundle(&keys());
+        let SignatureDto::Ed25519(signature) = &mut bundle.milestone.signatures[1];
+        let mut signature_bytes = prefix_hex::decode::<[u8; 64]>(&signature.signature).unwrap();
+        signature_bytes[0] ^= 1;
+        signature.signature = prefix_hex::encode(signature_bytes);
+
+        assert!(matches!(bundle.verify(), Err(VerifyProofError::InvalidMilestone(_))));
+    }
+
+    #[test]
+    fn test_verify_bundle_with_key_outside_of_range() {
+        let keys = keys();
+        let (mut bundle, _) = referenced_bundle(&keys);
+
+        // The keys are no longer applicable at the index of the milestone.
+        assert!(matches!(
+            bundle.verify_with_key_ranges(&key_ranges(&keys, 0, 5), 2),
+            Err(VerifyProofError::InvalidMilestone(_))
+        ));
+
+        bundle.milestone_key_ranges[1].start = 11.into();
+        assert!(matches!(bundle.verify(), Err(VerifyProofError::InvalidMilestone(_))));
+    }
+
+    #[test]
+    fn test_verify_bundle_with_wrong_kind() {
+        let (mut bundle, _) = referenced_bundle(&keys());
+        // The audit path leads to the inclusion Merkle root, not to the applied one.
+        bundle.kind = ProofKind::Applied;
+
+        assert!(matches!(
+            bundle.verify(),
+            Err(VerifyProofError::MerkleRootMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify_bundle_with_block_missing_from_path() {
+        let (mut bundle, _) = referenced_bundle(&keys());
+        bundle.block = BlockDto::from(&rand_block());
+
+        assert!(matches!(bundle.verify(), Err(VerifyProofError::BlockNotContained(_))));
+    }
+}
@@ -0,0 +1,45 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use iota_types::block::{payload::milestone::MilestoneValidationError, DtoError};
+use thiserror::Error;
+
+/// An error that occurred while creating a Merkle audit path.
+#[derive(Error, Debug)]
+#[allow(missing_docs)]
+pub enum CreateProofError {
+    #[error("Block '{0}' is not included in the given ordered list of blocks")]
+    BlockNotIncluded(String),
+    #[error(
+        "The calculated merkle root '{calculated_merkle_root}' does not match the expected: '{expected_merkle_root}'"
+    )]
+    MerkleRootMismatch {
+        calculated_merkle_root: String,
+        expected_merkle_root: String,
+    },
+}
+
+/// An error that occurred while verifying a [`ProofBundle`](super::ProofBundle).
+#[derive(Error, Debug)]
+#[allow(missing_docs)]
+pub enum VerifyProofError {
+    #[error("Invalid block: {0}")]
+    MalformedBlock(DtoError),
+    #[error("Invalid milestone: {0}")]
+    MalformedMilestone(DtoError),
+    #[error("Invalid audit path: {0}")]
+    MalformedAuditPath(#[from] prefix_hex::Error),
+    #[error("Invalid milestone public key: {0}")]
+    DecodePublicKey(prefix_hex::Error),
+    #[error("Invalid milestone signatures: {0:?}")]
+    InvalidMilestone(MilestoneValidationError),
+    #[error("Block '{0}' is not contained in the audit path")]
+    BlockNotContained(String),
+    #[error(
+        "The calculated merkle root '{calculated_merkle_root}' does not match the expected: '{expected_merkle_root}'"
+    )]
+    MerkleRootMismatch {
+        calculated_merkle_root: String,
+        expected_merkle_root: String,
+    },
+}
@@ -6,12 +6,14 @@ use crypto::hashes::{blake2b::Blake2b256, Digest, Output};
 const LEAF_HASH_PREFIX: u8 = 0;
 const NODE_HASH_PREFIX: u8 = 1;
 
+/// The hash of a node of a Merkle tree.
 pub type MerkleHash = Output<Blake2b256>;
 
 /// A Merkle tree hasher that uses the `Blake2b256` hash function.
 pub struct MerkleHasher;
 
 impl MerkleHasher {
+    /// Computes the Merkle root of the given leaves.
     pub fn hash(data: &[impl AsRef<[u8]>]) -> MerkleHash {
         match data {
             [] => Self::hash_empty(),
@@ -25,10 +27,12 @@ impl MerkleHasher {
         }
     }
 
+    /// Computes the Merkle root of an empty tree.
     pub fn hash_empty() -> MerkleHash {
         Blake2b256::digest([])
     }
 
+    /// Computes the hash of a leaf.
     pub fn hash_leaf(l: impl AsRef<[u8]>) -> MerkleHash {
         let mut hasher = Blake2b256::default();
         hasher.update([LEAF_HASH_PREFIX]);
@@ -36,6 +40,7 @@ impl MerkleHasher {
         hasher.finalize()
     }
 
+    /// Computes the hash of an inner node from the hashes of its children.
     pub fn hash_node(l: impl AsRef<[u8]>, r: impl AsRef<[u8]>) -> MerkleHash {
         let mut hasher = Blake2b256::default();
         hasher.update([NODE_HASH_PREFIX]);
@@ -59,12 +64,11 @@ const fn bit_length(n: u32) -> u32 {
 mod tests {
     use std::str::FromStr;
 
-    use chronicle::model::BlockId;
-
     use super::*;
+    use crate::model::BlockId;
 
     impl MerkleHasher {
-        pub fn hash_block_ids(data: &[BlockId]) -> MerkleHash {
+        pub(crate) fn hash_block_ids(data: &[BlockId]) -> MerkleHash {
             let data = data.iter().map(|id| &id.0[..]).collect::<Vec<_>>();
             Self::hash(&data[..])
         }
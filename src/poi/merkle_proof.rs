@@ -1,14 +1,15 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use chronicle::model::BlockId;
 use serde::{Deserialize, Serialize};
 
 use super::{
     error::CreateProofError,
-    merkle_hasher::{MerkleHash, MerkleHasher},
+    merkle_hasher::{largest_power_of_two, MerkleHash, MerkleHasher},
 };
+use crate::model::BlockId;
 
+/// The path from a block id to the root of a Merkle tree, which proves that the block id is a leaf of the tree.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct MerkleAuditPath {
     left: Hashable,
@@ -16,25 +17,33 @@ pub struct MerkleAuditPath {
 }
 
 impl MerkleAuditPath {
+    /// Computes the Merkle root that is proven by the path.
     pub fn hash(&self) -> MerkleHash {
-        // Handle edge case where the Merkle Tree consists solely of the "value".
-        if self.left.is_value() && self.right.is_none() {
-            self.left.hash()
-        } else {
-            // We make sure that unwrapping is safe.
-            MerkleHasher::hash_node(self.left.hash(), self.right.as_ref().unwrap().hash())
+        match &self.right {
+            Some(right) => MerkleHasher::hash_node(self.left.hash(), right.hash()),
+            // Handle edge case where the Merkle Tree consists solely of the "value".
+            None => self.left.hash(),
         }
     }
 
+    /// Returns whether the given block id is a leaf of the path.
     pub fn contains_block_id(&self, block_id: &BlockId) -> bool {
-        self.left.contains_block_id(block_id) || self.right.as_ref().unwrap().contains_block_id(block_id)
+        self.left.contains_block_id(block_id)
+            || self
+                .right
+                .as_ref()
+                .map_or(false, |right| right.contains_block_id(block_id))
     }
 }
 
+/// A child of a [`MerkleAuditPath`].
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Hashable {
+    /// A subtree that contains the proven value.
     Path(Box<MerkleAuditPath>),
+    /// The hash of a subtree that does not contain the proven value.
     Node(MerkleHash),
+    /// The proven value.
     Value([u8; BlockId::LENGTH]),
 }
 
@@ -54,12 +63,9 @@ impl Hashable {
             Hashable::Value(v) => v == &block_id.0,
         }
     }
-
-    fn is_value(&self) -> bool {
-        matches!(self, Hashable::Value(_))
-    }
 }
 
+/// Creates Merkle audit paths.
 pub struct MerkleProof;
 
 impl MerkleProof {
@@ -96,7 +102,7 @@ impl MerkleProof {
         }
 
         // Select a `pivot` element to split `data` into two slices `left` and `right`.
-        let pivot = super::merkle_hasher::largest_power_of_two(n);
+        let pivot = largest_power_of_two(n);
         let (left, right) = block_ids.split_at(pivot);
 
         // Produces the Merkle hash of a sub tree not containing the `value`.
@@ -127,6 +133,7 @@ impl MerkleProof {
     }
 }
 
+/// The JSON representation of a [`MerkleAuditPath`].
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MerkleAuditPathDto {
     #[serde(rename = "l")]
@@ -155,8 +162,10 @@ impl TryFrom<MerkleAuditPathDto> for MerkleAuditPath {
     }
 }
 
+/// The JSON representation of a [`Hashable`].
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(untagged)]
+#[allow(missing_docs)]
 pub enum HashableDto {
     Node {
         #[serde(rename = "h")]
@@ -263,4 +272,19 @@ mod tests {
             "audit path does not contain that block id"
         );
     }
+
+    #[test]
+    fn test_audit_path_without_value() {
+        let block_id = BlockId::from_str("0x52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649").unwrap();
+        let audit_path = MerkleAuditPath {
+            left: Hashable::Node(MerkleHasher::hash_empty()),
+            right: None,
+        };
+
+        assert_eq!(audit_path.hash(), MerkleHasher::hash_empty());
+        assert!(
+            !audit_path.contains_block_id(&block_id),
+            "audit path without a value contains a block id"
+        );
+    }
 }
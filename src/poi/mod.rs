@@ -0,0 +1,19 @@
+// Copyright 2022 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Proofs of inclusion, which show that a block was referenced or applied by a milestone.
+//!
+//! A [`ProofBundle`] contains everything that is needed to verify such a proof, so that it can be checked without
+//! access to a database or a node.
+
+mod bundle;
+mod error;
+mod merkle_hasher;
+mod merkle_proof;
+
+pub use self::{
+    bundle::{MilestoneKeyRangeDto, ProofBundle, ProofKind},
+    error::{CreateProofError, VerifyProofError},
+    merkle_hasher::{MerkleHash, MerkleHasher},
+    merkle_proof::{Hashable, HashableDto, MerkleAuditPath, MerkleAuditPathDto, MerkleProof},
+};
@@ -0,0 +1,30 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use thiserror::Error;
+
+use crate::model::tangle::MilestoneIndex;
+
+/// The different errors that can happen when working with snapshots.
+#[derive(Debug, Error)]
+#[allow(missing_docs)]
+pub enum SnapshotError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Storage(#[from] crate::db::StorageError),
+    #[error(transparent)]
+    Block(#[from] iota_types::block::Error),
+    #[error("snapshot task failed: {0}")]
+    Task(#[from] tokio::task::JoinError),
+    #[error("missing milestone {0}")]
+    MissingMilestone(MilestoneIndex),
+    #[error("missing protocol parameters for ledger index {0}")]
+    MissingProtocolParameters(MilestoneIndex),
+    #[error("unexpected milestone option kind {0}, expected protocol parameters")]
+    UnexpectedMilestoneOptionKind(u8),
+    #[error("unexpected snapshot kind {0}, expected a full snapshot")]
+    UnexpectedKind(u8),
+    #[error("unsupported snapshot version {0}")]
+    UnsupportedVersion(u8),
+}
@@ -0,0 +1,494 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    fs::File,
+    io::{BufWriter, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use futures::StreamExt;
+use iota_types::block as iota;
+use packable::{error::UnpackError, PackableExt};
+
+use super::{SnapshotError, SnapshotKind, SNAPSHOT_VERSION};
+use crate::{
+    db::Storage,
+    model::{
+        ledger::{LedgerOutput, RentStructureBytes},
+        payload::{MilestoneId, TransactionId},
+        tangle::{MilestoneIndex, MilestoneTimestamp},
+        utxo::{Output, OutputId},
+        BlockId, ProtocolParameters, TryFromWithContext,
+    },
+};
+
+/// The header of a full snapshot.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FullSnapshotHeader {
+    /// The index of the first milestone of the network.
+    pub genesis_milestone_index: MilestoneIndex,
+    /// The index of the milestone that the snapshot was taken at.
+    pub target_milestone_index: MilestoneIndex,
+    /// The timestamp of the milestone that the snapshot was taken at.
+    pub target_milestone_timestamp: MilestoneTimestamp,
+    /// The id of the milestone that the snapshot was taken at.
+    pub target_milestone_id: MilestoneId,
+    /// The index of the ledger state contained in the snapshot.
+    pub ledger_milestone_index: MilestoneIndex,
+    /// The id of the milestone that last changed the treasury.
+    pub treasury_milestone_id: MilestoneId,
+    /// The amount of tokens in the treasury.
+    pub treasury_amount: u64,
+    /// The milestone index at which the protocol parameters became active.
+    pub protocol_parameters_milestone_index: MilestoneIndex,
+    /// The protocol parameters that are valid for the ledger state.
+    pub protocol_parameters: ProtocolParameters,
+}
+
+/// Writes a full snapshot to an underlying writer.
+///
+/// The number of outputs is only known after all outputs were written, so it is patched into the header by
+/// [`finish`](FullSnapshotWriter::finish).
+pub struct FullSnapshotWriter<W: Write + Seek> {
+    writer: W,
+    ctx: iota::protocol::ProtocolParameters,
+    solid_entry_points: Vec<BlockId>,
+    output_count_pos: u64,
+    output_count: u64,
+}
+
+impl<W: Write + Seek> FullSnapshotWriter<W> {
+    /// Creates a new writer and writes the snapshot header.
+    pub fn new(
+        mut writer: W,
+        header: FullSnapshotHeader,
+        solid_entry_points: Vec<BlockId>,
+    ) -> Result<Self, SnapshotError> {
+        let ctx = iota::protocol::ProtocolParameters::try_from(header.protocol_parameters)?;
+        let parameters_option =
+            iota::payload::milestone::MilestoneOption::from(iota::payload::milestone::ParametersMilestoneOption::new(
+                header.protocol_parameters_milestone_index.0.into(),
+                ctx.protocol_version(),
+                ctx.pack_to_vec(),
+            )?)
+            .pack_to_vec();
+
+        writer.write_all(&[SNAPSHOT_VERSION, SnapshotKind::Full as u8])?;
+        writer.write_all(&header.genesis_milestone_index.0.to_le_bytes())?;
+        writer.write_all(&header.target_milestone_index.0.to_le_bytes())?;
+        writer.write_all(&header.target_milestone_timestamp.0.to_le_bytes())?;
+        writer.write_all(&header.target_milestone_id.0)?;
+        writer.write_all(&header.ledger_milestone_index.0.to_le_bytes())?;
+        writer.write_all(&header.treasury_milestone_id.0)?;
+        writer.write_all(&header.treasury_amount.to_le_bytes())?;
+        writer.write_all(&(parameters_option.len() as u16).to_le_bytes())?;
+        writer.write_all(&parameters_option)?;
+        let output_count_pos = writer.stream_position()?;
+        writer.write_all(&0u64.to_le_bytes())?;
+        // Full snapshots created by Chronicle never contain milestone diffs.
+        writer.write_all(&0u32.to_le_bytes())?;
+        writer.write_all(&(solid_entry_points.len() as u16).to_le_bytes())?;
+
+        Ok(Self {
+            writer,
+            ctx,
+            solid_entry_points,
+            output_count_pos,
+            output_count: 0,
+        })
+    }
+
+    /// Writes an unspent output to the snapshot.
+    pub fn write_output(&mut self, output: LedgerOutput) -> Result<(), SnapshotError> {
+        let bytes = iota::output::Output::try_from_with_context(&self.ctx, output.output)?.pack_to_vec();
+        self.writer.write_all(&output.output_id.transaction_id.0)?;
+        self.writer.write_all(&output.output_id.index.to_le_bytes())?;
+        self.writer.write_all(&output.block_id.0)?;
+        self.writer.write_all(&output.booked.milestone_index.0.to_le_bytes())?;
+        self.writer
+            .write_all(&output.booked.milestone_timestamp.0.to_le_bytes())?;
+        self.writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+        self.writer.write_all(&bytes)?;
+        self.output_count += 1;
+        Ok(())
+    }
+
+    /// Writes the solid entry points, patches the output count and returns the number of written outputs.
+    pub fn finish(mut self) -> Result<u64, SnapshotError> {
+        for block_id in self.solid_entry_points.iter() {
+            self.writer.write_all(&block_id.0)?;
+        }
+        let end = self.writer.stream_position()?;
+        self.writer.seek(SeekFrom::Start(self.output_count_pos))?;
+        self.writer.write_all(&self.output_count.to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(end))?;
+        self.writer.flush()?;
+        Ok(self.output_count)
+    }
+}
+
+/// Reads the ledger state from a full snapshot.
+///
+/// Only the header and the unspent outputs are read, milestone diffs and solid entry points are ignored. The unspent
+/// outputs are returned by iterating over the reader.
+pub struct FullSnapshotReader<R: Read> {
+    reader: R,
+    header: FullSnapshotHeader,
+    ctx: iota::protocol::ProtocolParameters,
+    output_count: u64,
+    outputs_read: u64,
+}
+
+impl<R: Read> FullSnapshotReader<R> {
+    /// Creates a new reader and reads the snapshot header.
+    pub fn new(mut reader: R) -> Result<Self, SnapshotError> {
+        let version = read_u8(&mut reader)?;
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+        let kind = read_u8(&mut reader)?;
+        if kind != SnapshotKind::Full as u8 {
+            return Err(SnapshotError::UnexpectedKind(kind));
+        }
+        let genesis_milestone_index = read_u32(&mut reader)?.into();
+        let target_milestone_index = read_u32(&mut reader)?.into();
+        let target_milestone_timestamp = read_u32(&mut reader)?.into();
+        let target_milestone_id = MilestoneId(read_array(&mut reader)?);
+        let ledger_milestone_index = read_u32(&mut reader)?.into();
+        let treasury_milestone_id = MilestoneId(read_array(&mut reader)?);
+        let treasury_amount = read_u64(&mut reader)?;
+
+        // The protocol parameters are stored as a packed `ParametersMilestoneOption`.
+        let _parameters_option_len = read_u16(&mut reader)?;
+        let option_kind = read_u8(&mut reader)?;
+        if option_kind != iota::payload::milestone::ParametersMilestoneOption::KIND {
+            return Err(SnapshotError::UnexpectedMilestoneOptionKind(option_kind));
+        }
+        let protocol_parameters_milestone_index = read_u32(&mut reader)?.into();
+        let _protocol_version = read_u8(&mut reader)?;
+        let mut binary_parameters = vec![0; read_u16(&mut reader)? as usize];
+        reader.read_exact(&mut binary_parameters)?;
+        let ctx = unpack::<iota::protocol::ProtocolParameters>(&binary_parameters, &())?;
+
+        let output_count = read_u64(&mut reader)?;
+        let _milestone_diff_count = read_u32(&mut reader)?;
+        let _solid_entry_point_count = read_u16(&mut reader)?;
+
+        Ok(Self {
+            reader,
+            header: FullSnapshotHeader {
+                genesis_milestone_index,
+                target_milestone_index,
+                target_milestone_timestamp,
+                target_milestone_id,
+                ledger_milestone_index,
+                treasury_milestone_id,
+                treasury_amount,
+                protocol_parameters_milestone_index,
+                protocol_parameters: ctx.clone().into(),
+            },
+            ctx,
+            output_count,
+            outputs_read: 0,
+        })
+    }
+
+    /// Returns the header of the snapshot.
+    pub fn header(&self) -> &FullSnapshotHeader {
+        &self.header
+    }
+
+    /// Returns the number of unspent outputs contained in the snapshot.
+    pub fn output_count(&self) -> u64 {
+        self.output_count
+    }
+
+    fn read_output(&mut self) -> Result<LedgerOutput, SnapshotError> {
+        let output_id = OutputId {
+            transaction_id: TransactionId(read_array(&mut self.reader)?),
+            index: read_u16(&mut self.reader)?,
+        };
+        let block_id = BlockId(read_array(&mut self.reader)?);
+        let booked = MilestoneIndex(read_u32(&mut self.reader)?).with_timestamp(read_u32(&mut self.reader)?.into());
+        let mut bytes = vec![0; read_u32(&mut self.reader)? as usize];
+        self.reader.read_exact(&mut bytes)?;
+        let output = unpack::<iota::output::Output>(&bytes, &self.ctx)?;
+
+        Ok(LedgerOutput {
+            output_id,
+            block_id,
+            booked,
+            rent_structure: RentStructureBytes::compute(&output),
+            output: Output::from(&output),
+        })
+    }
+}
+
+impl<R: Read> Iterator for FullSnapshotReader<R> {
+    type Item = Result<LedgerOutput, SnapshotError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.outputs_read == self.output_count {
+            return None;
+        }
+        self.outputs_read += 1;
+        Some(self.read_output())
+    }
+}
+
+fn read_array<const N: usize>(reader: &mut impl Read) -> Result<[u8; N], SnapshotError> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_u8(reader: &mut impl Read) -> Result<u8, SnapshotError> {
+    Ok(u8::from_le_bytes(read_array(reader)?))
+}
+
+fn read_u16(reader: &mut impl Read) -> Result<u16, SnapshotError> {
+    Ok(u16::from_le_bytes(read_array(reader)?))
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, SnapshotError> {
+    Ok(u32::from_le_bytes(read_array(reader)?))
+}
+
+fn read_u64(reader: &mut impl Read) -> Result<u64, SnapshotError> {
+    Ok(u64::from_le_bytes(read_array(reader)?))
+}
+
+fn unpack<T: PackableExt<UnpackError = iota::Error>>(
+    bytes: &[u8],
+    visitor: &T::UnpackVisitor,
+) -> Result<T, SnapshotError> {
+    T::unpack_verified(bytes, visitor).map_err(|e| match e {
+        UnpackError::Packable(e) => e.into(),
+        UnpackError::Unpacker(_) => std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into(),
+    })
+}
+
+/// The number of outputs that are written to a snapshot by a single blocking task.
+const OUTPUT_BATCH_SIZE: usize = 1000;
+
+/// Creates a full snapshot of the ledger state at the given index and returns the number of written outputs.
+///
+/// The writer is only used on blocking threads, so that writing to a file does not stall the async runtime.
+pub async fn create_full_snapshot<W: Write + Seek + Send + 'static>(
+    db: &impl Storage,
+    ledger_index: MilestoneIndex,
+    writer: W,
+) -> Result<u64, SnapshotError> {
+    let (milestone_id, at, payload) = db
+        .get_milestone(ledger_index)
+        .await?
+        .ok_or(SnapshotError::MissingMilestone(ledger_index))?;
+    let protocol_update = db
+        .get_protocol_parameters_for_ledger_index(ledger_index)
+        .await?
+        .ok_or(SnapshotError::MissingProtocolParameters(ledger_index))?;
+    let genesis_milestone_index = db
+        .get_starting_index()
+        .await?
+        .map_or(ledger_index, |starting_index| starting_index.milestone_index);
+    let (treasury_milestone_id, treasury_amount) = db
+        .get_treasury_for_ledger_index(ledger_index)
+        .await?
+        .map_or((MilestoneId([0; MilestoneId::LENGTH]), 0), |treasury| {
+            (treasury.milestone_id, treasury.amount)
+        });
+
+    let header = FullSnapshotHeader {
+        genesis_milestone_index,
+        target_milestone_index: at.milestone_index,
+        target_milestone_timestamp: at.milestone_timestamp,
+        target_milestone_id: milestone_id,
+        ledger_milestone_index: ledger_index,
+        treasury_milestone_id,
+        treasury_amount,
+        protocol_parameters_milestone_index: protocol_update.tangle_index,
+        protocol_parameters: protocol_update.parameters,
+    };
+    let solid_entry_points = payload.essence.parents.into_vec();
+    let mut writer =
+        tokio::task::spawn_blocking(move || FullSnapshotWriter::new(writer, header, solid_entry_points)).await??;
+
+    let mut batches = db.get_unspent_outputs(ledger_index).await?.chunks(OUTPUT_BATCH_SIZE);
+    while let Some(batch) = batches.next().await {
+        writer = write_outputs(writer, batch.into_iter().collect::<Result<_, _>>()?).await?;
+    }
+
+    tokio::task::spawn_blocking(move || writer.finish()).await?
+}
+
+/// Creates a full snapshot of the ledger state at the given index in the file at `path` and returns the number of
+/// written outputs.
+///
+/// The snapshot is written to a temporary file next to `path` that only replaces it once the snapshot is complete.
+/// A failed snapshot therefore neither leaves a partial file behind nor destroys an existing snapshot at `path`.
+pub async fn write_full_snapshot(
+    db: &impl Storage,
+    ledger_index: MilestoneIndex,
+    path: &Path,
+) -> Result<u64, SnapshotError> {
+    let mut tmp_file_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_file_name.push(format!(".{}.tmp", uuid::Uuid::new_v4()));
+    let tmp_path = path.with_file_name(tmp_file_name);
+
+    let res = async {
+        let file = {
+            let tmp_path = tmp_path.clone();
+            tokio::task::spawn_blocking(move || File::create(tmp_path)).await??
+        };
+        let output_count = create_full_snapshot(db, ledger_index, BufWriter::new(file)).await?;
+        let (tmp_path, path) = (tmp_path.clone(), path.to_owned());
+        tokio::task::spawn_blocking(move || std::fs::rename(tmp_path, path)).await??;
+        Ok(output_count)
+    }
+    .await;
+    if res.is_err() {
+        tokio::task::spawn_blocking(move || std::fs::remove_file(tmp_path).ok())
+            .await
+            .ok();
+    }
+    res
+}
+
+async fn write_outputs<W: Write + Seek + Send + 'static>(
+    mut writer: FullSnapshotWriter<W>,
+    outputs: Vec<LedgerOutput>,
+) -> Result<FullSnapshotWriter<W>, SnapshotError> {
+    tokio::task::spawn_blocking(move || {
+        for output in outputs {
+            writer.write_output(output)?;
+        }
+        Ok(writer)
+    })
+    .await?
+}
+
+#[cfg(all(test, feature = "rand"))]
+mod test {
+    use std::io::Cursor;
+
+    use iota_types::block::protocol::protocol_parameters;
+
+    use super::*;
+
+    fn rand_header() -> FullSnapshotHeader {
+        FullSnapshotHeader {
+            genesis_milestone_index: 1.into(),
+            target_milestone_index: 10.into(),
+            target_milestone_timestamp: 12345.into(),
+            target_milestone_id: MilestoneId::rand(),
+            ledger_milestone_index: 10.into(),
+            treasury_milestone_id: MilestoneId::rand(),
+            treasury_amount: 1000,
+            protocol_parameters_milestone_index: 1.into(),
+            protocol_parameters: protocol_parameters().into(),
+        }
+    }
+
+    fn rand_outputs(ctx: &iota::protocol::ProtocolParameters) -> Vec<LedgerOutput> {
+        (0..5)
+            .map(|_| {
+                let output = Output::rand_basic(ctx);
+                LedgerOutput {
+                    output_id: OutputId::rand(),
+                    block_id: BlockId::rand(),
+                    booked: MilestoneIndex(5).with_timestamp(1000.into()),
+                    rent_structure: RentStructureBytes::compute(
+                        &iota::output::Output::try_from_with_context(ctx, output.clone()).unwrap(),
+                    ),
+                    output,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_full_snapshot_writer() {
+        let ctx = protocol_parameters();
+        let header = rand_header();
+        let solid_entry_points = BlockId::rand_many(3).collect::<Vec<_>>();
+        let mut bytes = Cursor::new(Vec::new());
+        let mut writer = FullSnapshotWriter::new(&mut bytes, header.clone(), solid_entry_points.clone()).unwrap();
+        let output_count_pos = writer.output_count_pos as usize;
+        for output in rand_outputs(&ctx) {
+            writer.write_output(output).unwrap();
+        }
+        assert_eq!(writer.finish().unwrap(), 5);
+
+        let bytes = bytes.into_inner();
+        assert_eq!(bytes[0], SNAPSHOT_VERSION);
+        assert_eq!(bytes[1], SnapshotKind::Full as u8);
+        assert_eq!(&bytes[2..6], &header.genesis_milestone_index.0.to_le_bytes());
+        assert_eq!(&bytes[14..46], &header.target_milestone_id.0);
+        assert_eq!(&bytes[output_count_pos..output_count_pos + 8], &5u64.to_le_bytes());
+        assert_eq!(
+            &bytes[bytes.len() - 3 * BlockId::LENGTH..],
+            solid_entry_points
+                .iter()
+                .flat_map(|block_id| block_id.0)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_full_snapshot_round_trip() {
+        let ctx = protocol_parameters();
+        let header = rand_header();
+        let outputs = rand_outputs(&ctx);
+        let mut bytes = Cursor::new(Vec::new());
+        let mut writer = FullSnapshotWriter::new(&mut bytes, header.clone(), BlockId::rand_many(3).collect()).unwrap();
+        for output in outputs.iter().cloned() {
+            writer.write_output(output).unwrap();
+        }
+        writer.finish().unwrap();
+
+        bytes.set_position(0);
+        let reader = FullSnapshotReader::new(bytes).unwrap();
+        assert_eq!(reader.header(), &header);
+        assert_eq!(reader.output_count(), 5);
+        assert_eq!(reader.collect::<Result<Vec<_>, _>>().unwrap(), outputs);
+    }
+
+    #[test]
+    fn test_full_snapshot_reader_rejects_delta() {
+        let mut bytes = Cursor::new(Vec::new());
+        FullSnapshotWriter::new(&mut bytes, rand_header(), Vec::new())
+            .unwrap()
+            .finish()
+            .unwrap();
+        let mut bytes = bytes.into_inner();
+        bytes[1] = SnapshotKind::Delta as u8;
+
+        assert!(matches!(
+            FullSnapshotReader::new(bytes.as_slice()),
+            Err(SnapshotError::UnexpectedKind(1))
+        ));
+    }
+
+    #[cfg(feature = "kv")]
+    #[tokio::test]
+    async fn test_failed_snapshot_leaves_no_file() {
+        let db = crate::db::KvDb::open_temporary().unwrap();
+        let dir = std::env::temp_dir().join(format!("chronicle-snapshot-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("full_snapshot_10.bin");
+
+        assert!(matches!(
+            write_full_snapshot(&db, 10.into(), &path).await,
+            Err(SnapshotError::MissingMilestone(MilestoneIndex(10)))
+        ));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        std::fs::write(&path, b"snapshot").unwrap();
+        assert!(write_full_snapshot(&db, 10.into(), &path).await.is_err());
+        assert_eq!(std::fs::read(&path).unwrap(), b"snapshot");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
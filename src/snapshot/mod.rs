@@ -0,0 +1,27 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Ledger snapshots in the binary format that is used by the node.
+
+mod error;
+mod full;
+
+pub use self::{
+    error::SnapshotError,
+    full::{
+        create_full_snapshot, write_full_snapshot, FullSnapshotHeader, FullSnapshotReader, FullSnapshotWriter,
+    },
+};
+
+/// The version of the snapshot file format.
+pub const SNAPSHOT_VERSION: u8 = 2;
+
+/// The kind of a snapshot file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SnapshotKind {
+    /// A full snapshot, which contains the complete ledger state.
+    Full = 0,
+    /// A delta snapshot, which only contains milestone diffs.
+    Delta = 1,
+}
@@ -0,0 +1,247 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! Filters that select which blocks of a milestone cone are stored.
+
+use serde::{Deserialize, Serialize};
+
+use super::{BlockData, LedgerUpdateStore};
+use crate::model::{
+    payload::{Payload, TaggedDataPayload, TransactionEssence},
+    utxo::{Address, Input, Tag},
+};
+
+/// The kind of payload that a block carries.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadKind {
+    /// A block without a payload.
+    NoPayload,
+    /// A transaction payload.
+    Transaction,
+    /// A milestone payload.
+    Milestone,
+    /// A treasury transaction payload.
+    TreasuryTransaction,
+    /// A tagged data payload.
+    TaggedData,
+}
+
+impl From<Option<&Payload>> for PayloadKind {
+    fn from(value: Option<&Payload>) -> Self {
+        match value {
+            None => Self::NoPayload,
+            Some(Payload::Transaction(_)) => Self::Transaction,
+            Some(Payload::Milestone(_)) => Self::Milestone,
+            Some(Payload::TreasuryTransaction(_)) => Self::TreasuryTransaction,
+            Some(Payload::TaggedData(_)) => Self::TaggedData,
+        }
+    }
+}
+
+/// Selects the blocks that are stored during ingestion.
+///
+/// A block is stored if it matches any of the criteria, and an empty filter stores every block. Milestone blocks are
+/// always stored. The ledger and the milestones are not affected by the filter.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IngestionFilter {
+    /// Prefixes of the tags of tagged data payloads, which includes the payloads that are part of a transaction.
+    #[serde(default)]
+    pub tag_prefixes: Vec<Tag>,
+    /// Addresses that own an output that is consumed or created by a transaction.
+    #[serde(default)]
+    pub addresses: Vec<Address>,
+    /// Kinds of payloads.
+    #[serde(default)]
+    pub payload_kinds: Vec<PayloadKind>,
+}
+
+impl IngestionFilter {
+    /// Returns `true` if the filter stores every block.
+    pub fn is_empty(&self) -> bool {
+        self.tag_prefixes.is_empty() && self.addresses.is_empty() && self.payload_kinds.is_empty()
+    }
+
+    /// Returns `true` if the block should be stored. The ledger updates of the milestone are used to look up the
+    /// owners of consumed outputs.
+    pub fn matches(&self, block: &BlockData, ledger_updates: &LedgerUpdateStore) -> bool {
+        let payload = block.block.payload.as_ref();
+        if self.is_empty() || matches!(payload, Some(Payload::Milestone(_))) {
+            return true;
+        }
+        if self.payload_kinds.contains(&PayloadKind::from(payload)) {
+            return true;
+        }
+        match payload {
+            Some(Payload::TaggedData(tagged_data)) => self.matches_tag(tagged_data),
+            Some(Payload::Transaction(transaction)) => {
+                let TransactionEssence::Regular {
+                    inputs,
+                    outputs,
+                    payload,
+                    ..
+                } = &transaction.essence;
+                if let Some(Payload::TaggedData(tagged_data)) = payload {
+                    if self.matches_tag(tagged_data) {
+                        return true;
+                    }
+                }
+                !self.addresses.is_empty()
+                    && inputs
+                        .iter()
+                        .filter_map(|input| match input {
+                            Input::Utxo(output_id) => ledger_updates
+                                .get_consumed(output_id)
+                                .and_then(|spent| spent.owning_address()),
+                            Input::Treasury { .. } => None,
+                        })
+                        .chain(outputs.iter().filter_map(|output| output.owning_address()))
+                        .any(|address| self.addresses.contains(address))
+            }
+            _ => false,
+        }
+    }
+
+    fn matches_tag(&self, tagged_data: &TaggedDataPayload) -> bool {
+        self.tag_prefixes
+            .iter()
+            .any(|prefix| tagged_data.tag().starts_with(prefix.as_ref()))
+    }
+}
+
+#[cfg(all(test, feature = "rand"))]
+mod test {
+    use super::*;
+    use crate::model::{
+        ledger::{LedgerOutput, LedgerSpent, RentStructureBytes},
+        metadata::{BlockMetadata, ConflictReason, LedgerInclusionState, SpentMetadata},
+        payload::TransactionId,
+        tangle::MilestoneIndexTimestamp,
+        utxo::{Output, OutputId},
+        Block, BlockId,
+    };
+
+    fn block_data(block: Block) -> BlockData {
+        BlockData {
+            block_id: BlockId::rand(),
+            metadata: BlockMetadata {
+                parents: block.parents.clone(),
+                is_solid: true,
+                should_promote: false,
+                should_reattach: false,
+                referenced_by_milestone_index: 1.into(),
+                milestone_index: 0.into(),
+                inclusion_state: LedgerInclusionState::NoTransaction,
+                conflict_reason: ConflictReason::None,
+                white_flag_index: 0,
+            },
+            block,
+            raw: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_ingestion_filter() {
+        let ctx = iota_types::block::protocol::protocol_parameters();
+        let ledger_updates = LedgerUpdateStore::default();
+        let tagged_data = block_data(Block::rand_tagged_data());
+        let milestone = block_data(Block::rand_milestone(&ctx));
+        let no_payload = block_data(Block::rand_no_payload());
+
+        let filter = IngestionFilter::default();
+        assert!(filter.matches(&tagged_data, &ledger_updates));
+        assert!(filter.matches(&no_payload, &ledger_updates));
+
+        let tag = match &tagged_data.block.payload {
+            Some(Payload::TaggedData(payload)) => payload.tag().to_vec(),
+            _ => unreachable!(),
+        };
+        let filter = IngestionFilter {
+            tag_prefixes: vec![Tag::from_hex(prefix_hex::encode([tag.as_slice(), &[0]].concat())).unwrap()],
+            ..Default::default()
+        };
+        assert!(!filter.matches(&tagged_data, &ledger_updates));
+        assert!(!filter.matches(&no_payload, &ledger_updates));
+        assert!(filter.matches(&milestone, &ledger_updates));
+
+        let filter = IngestionFilter {
+            tag_prefixes: vec![Tag::from_hex(prefix_hex::encode(&tag[..tag.len() / 2])).unwrap()],
+            payload_kinds: vec![PayloadKind::NoPayload],
+            ..Default::default()
+        };
+        assert!(filter.matches(&tagged_data, &ledger_updates));
+        assert!(filter.matches(&no_payload, &ledger_updates));
+    }
+    #[test]
+    fn test_ingestion_filter_addresses() {
+        let ctx = iota_types::block::protocol::protocol_parameters();
+        let at = MilestoneIndexTimestamp {
+            milestone_index: 1.into(),
+            milestone_timestamp: 12345.into(),
+        };
+        let spent = LedgerSpent {
+            output: LedgerOutput {
+                output_id: OutputId::rand(),
+                block_id: BlockId::rand(),
+                booked: at,
+                output: Output::rand_basic(&ctx),
+                rent_structure: RentStructureBytes {
+                    num_key_bytes: 0,
+                    num_data_bytes: 0,
+                },
+            },
+            spent_metadata: SpentMetadata {
+                transaction_id: TransactionId::rand(),
+                spent: at,
+            },
+        };
+        let created = Output::rand_basic(&ctx);
+
+        let mut block = Block::rand_transaction(&ctx);
+        match &mut block.payload {
+            Some(Payload::Transaction(transaction)) => {
+                let TransactionEssence::Regular {
+                    inputs,
+                    outputs,
+                    payload,
+                    ..
+                } = &mut transaction.essence;
+                *inputs = vec![Input::Utxo(spent.output_id())].into_boxed_slice();
+                *outputs = vec![created.clone()].into_boxed_slice();
+                *payload = None;
+            }
+            _ => unreachable!(),
+        }
+        let transaction = block_data(block);
+        let tagged_data = block_data(Block::rand_tagged_data());
+
+        let filter = IngestionFilter {
+            addresses: vec![*created.owning_address().unwrap()],
+            ..Default::default()
+        };
+        assert!(filter.matches(&transaction, &LedgerUpdateStore::default()));
+        assert!(!filter.matches(&tagged_data, &LedgerUpdateStore::default()));
+
+        // The owner of a consumed output is looked up in the ledger updates of the milestone.
+        let filter = IngestionFilter {
+            addresses: vec![*spent.owning_address().unwrap()],
+            ..Default::default()
+        };
+        assert!(!filter.matches(&transaction, &LedgerUpdateStore::default()));
+        assert!(filter.matches(&transaction, &LedgerUpdateStore::init(vec![spent], Vec::new())));
+
+        let filter = IngestionFilter {
+            addresses: vec![Address::rand_ed25519()],
+            payload_kinds: vec![PayloadKind::TaggedData],
+            ..Default::default()
+        };
+        assert!(!filter.matches(&transaction, &LedgerUpdateStore::default()));
+        assert!(filter.matches(&tagged_data, &LedgerUpdateStore::default()));
+
+        let filter = IngestionFilter {
+            payload_kinds: vec![PayloadKind::Transaction],
+            ..Default::default()
+        };
+        assert!(filter.matches(&transaction, &LedgerUpdateStore::default()));
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+
+use super::{BlockData, LedgerUpdateStore};
+use crate::model::{metadata::LedgerInclusionState, payload::Payload, utxo::TokenAmount};
+
+/// Statistics about the blocks that were referenced by a milestone and the ledger updates that it applied.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct MilestoneStats {
+    pub block_count: u32,
+    pub no_payload_count: u32,
+    pub tagged_data_count: u32,
+    pub transaction_count: u32,
+    pub milestone_count: u32,
+    pub treasury_transaction_count: u32,
+    pub included_count: u32,
+    pub conflicting_count: u32,
+    pub no_transaction_count: u32,
+    pub created_output_count: u32,
+    pub consumed_output_count: u32,
+    /// The amount of tokens in the created outputs.
+    pub created_amount: TokenAmount,
+}
+
+impl MilestoneStats {
+    /// Counts a block of the milestone cone.
+    pub fn handle_block(&mut self, BlockData { block, metadata, .. }: &BlockData) {
+        self.block_count += 1;
+        match block.payload {
+            None => self.no_payload_count += 1,
+            Some(Payload::TaggedData(_)) => self.tagged_data_count += 1,
+            Some(Payload::Transaction(_)) => self.transaction_count += 1,
+            Some(Payload::Milestone(_)) => self.milestone_count += 1,
+            Some(Payload::TreasuryTransaction(_)) => self.treasury_transaction_count += 1,
+        }
+        match metadata.inclusion_state {
+            LedgerInclusionState::Included => self.included_count += 1,
+            LedgerInclusionState::Conflicting => self.conflicting_count += 1,
+            LedgerInclusionState::NoTransaction => self.no_transaction_count += 1,
+        }
+    }
+
+    /// Counts the outputs that were created and consumed by the milestone.
+    pub fn handle_ledger_updates(&mut self, ledger_updates: &LedgerUpdateStore) {
+        self.created_output_count += ledger_updates.created_outputs().len() as u32;
+        self.consumed_output_count += ledger_updates.consumed_outputs().len() as u32;
+        self.created_amount += ledger_updates
+            .created_outputs()
+            .iter()
+            .map(|output| output.amount())
+            .sum::<TokenAmount>();
+    }
+}
+
+#[cfg(all(test, feature = "rand"))]
+mod test {
+    use super::*;
+    use crate::model::{
+        ledger::{LedgerOutput, RentStructureBytes},
+        metadata::{BlockMetadata, ConflictReason},
+        tangle::MilestoneIndexTimestamp,
+        utxo::{Output, OutputId},
+        Block, BlockId,
+    };
+
+    #[test]
+    fn test_milestone_stats() {
+        let ctx = iota_types::block::protocol::protocol_parameters();
+
+        let mut stats = MilestoneStats::default();
+        for (block, inclusion_state) in [
+            (Block::rand_transaction(&ctx), LedgerInclusionState::Included),
+            (Block::rand_transaction(&ctx), LedgerInclusionState::Conflicting),
+            (Block::rand_milestone(&ctx), LedgerInclusionState::NoTransaction),
+            (Block::rand_tagged_data(), LedgerInclusionState::NoTransaction),
+            (Block::rand_no_payload(), LedgerInclusionState::NoTransaction),
+        ] {
+            stats.handle_block(&BlockData {
+                block_id: BlockId::rand(),
+                metadata: BlockMetadata {
+                    parents: block.parents.clone(),
+                    is_solid: true,
+                    should_promote: false,
+                    should_reattach: false,
+                    referenced_by_milestone_index: 1.into(),
+                    milestone_index: 0.into(),
+                    inclusion_state,
+                    conflict_reason: ConflictReason::None,
+                    white_flag_index: 0,
+                },
+                block,
+                raw: Vec::new(),
+            });
+        }
+
+        let created = std::iter::repeat_with(|| Output::rand(&ctx))
+            .take(3)
+            .map(|output| LedgerOutput {
+                output_id: OutputId::rand(),
+                block_id: BlockId::rand(),
+                booked: MilestoneIndexTimestamp {
+                    milestone_index: 1.into(),
+                    milestone_timestamp: 12345.into(),
+                },
+                rent_structure: RentStructureBytes {
+                    num_key_bytes: 0,
+                    num_data_bytes: 100,
+                },
+                output,
+            })
+            .collect::<Vec<_>>();
+        let created_amount = created.iter().map(|output| output.amount()).sum();
+        stats.handle_ledger_updates(&LedgerUpdateStore::init(Vec::new(), created));
+
+        assert_eq!(
+            stats,
+            MilestoneStats {
+                block_count: 5,
+                no_payload_count: 1,
+                tagged_data_count: 1,
+                transaction_count: 2,
+                milestone_count: 1,
+                treasury_transaction_count: 0,
+                included_count: 1,
+                conflicting_count: 1,
+                no_transaction_count: 3,
+                created_output_count: 3,
+                consumed_output_count: 0,
+                created_amount,
+            }
+        );
+    }
+}
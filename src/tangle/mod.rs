@@ -3,7 +3,9 @@
 
 //! Defines types that allow for unified data processing.
 
+mod filter;
 mod ledger_updates;
+mod milestone_stats;
 mod milestone_stream;
 pub(crate) mod sources;
 use std::ops::RangeBounds;
@@ -11,7 +13,9 @@ use std::ops::RangeBounds;
 use futures::{StreamExt, TryStreamExt};
 
 pub use self::{
+    filter::{IngestionFilter, PayloadKind},
     ledger_updates::LedgerUpdateStore,
+    milestone_stats::MilestoneStats,
     milestone_stream::{Milestone, MilestoneStream},
     sources::{BlockData, InputSource, MilestoneData},
 };
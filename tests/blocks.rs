@@ -293,4 +293,60 @@ mod test_rand {
 
         teardown(db).await;
     }
+
+    #[tokio::test]
+    async fn test_block_id_by_tag() {
+        let db = setup_database("test-block-id-by-tag").await.unwrap();
+        let block_collection = setup_collection::<BlockCollection>(&db).await.unwrap();
+
+        let metadata = |referenced_by_milestone_index: u32| BlockMetadata {
+            parents: BlockId::rand_parents(),
+            is_solid: true,
+            should_promote: false,
+            should_reattach: false,
+            referenced_by_milestone_index: referenced_by_milestone_index.into(),
+            milestone_index: referenced_by_milestone_index.into(),
+            inclusion_state: LedgerInclusionState::NoTransaction,
+            conflict_reason: ConflictReason::None,
+            white_flag_index: 0,
+        };
+        let block = Block::rand_tagged_data();
+        let tag = match &block.payload {
+            Some(Payload::TaggedData(payload)) => payload.tag().to_vec(),
+            _ => unreachable!(),
+        };
+        let (older_id, newer_id) = (BlockId::rand(), BlockId::rand());
+        let blocks = vec![
+            (
+                older_id,
+                block.clone(),
+                iota_types::block::rand::bytes::rand_bytes(100),
+                metadata(1),
+            ),
+            (
+                newer_id,
+                block,
+                iota_types::block::rand::bytes::rand_bytes(100),
+                metadata(2),
+            ),
+            (
+                BlockId::rand(),
+                Block::rand_no_payload(),
+                iota_types::block::rand::bytes::rand_bytes(100),
+                metadata(3),
+            ),
+        ];
+        block_collection.insert_blocks_with_metadata(blocks).await.unwrap();
+
+        // The most recently referenced block with the tag is found.
+        assert_eq!(
+            block_collection.get_block_id_by_tag(&tag).await.unwrap(),
+            Some(newer_id)
+        );
+        let mut other_tag = tag.clone();
+        other_tag.push(0);
+        assert_eq!(block_collection.get_block_id_by_tag(&other_tag).await.unwrap(), None);
+
+        teardown(db).await;
+    }
 }
@@ -0,0 +1,565 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! A mock INX server that serves the milestones in `tests/data/in_memory_data.json`.
+
+// The handlers mirror the gRPC interface, which reports errors as `tonic::Status`.
+#![allow(clippy::result_large_err)]
+
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    fmt::Debug,
+    fs::File,
+    io::BufReader,
+    sync::{Arc, Mutex},
+};
+
+use chronicle::{
+    inx::{BlockMetadataMessage, BlockWithMetadataMessage},
+    model::{
+        ledger::{LedgerOutput, LedgerSpent},
+        metadata::BlockMetadata,
+        node::NodeConfiguration,
+        payload::{MilestoneId, MilestonePayload},
+        protocol::ProtocolParameters,
+        tangle::{MilestoneIndex, MilestoneIndexTimestamp},
+        BlockId, TryFromWithContext, TryIntoWithContext,
+    },
+};
+use futures::stream::Iter;
+use inx::proto;
+use iota_types::block as iota;
+use packable::PackableExt;
+use serde::Deserialize;
+use tokio::{net::TcpListener, sync::oneshot, task::JoinHandle};
+use tonic::{
+    body::BoxBody,
+    codec::ProstCodec,
+    codegen::{empty_body, http, Body, BoxFuture, Context, Poll, Ready, Service, StdError},
+    server::{Grpc, NamedService, ServerStreamingService, UnaryService},
+    transport::Server,
+    Request, Response, Status,
+};
+
+const FIXTURE_PATH: &str = "tests/data/in_memory_data.json";
+
+#[derive(Deserialize)]
+struct MilestoneFixture {
+    milestone_data: MilestoneDataFixture,
+    cone: BTreeMap<String, BlockFixture>,
+    created: Vec<LedgerOutput>,
+    consumed: Vec<LedgerSpent>,
+}
+
+#[derive(Deserialize)]
+struct MilestoneDataFixture {
+    milestone_id: MilestoneId,
+    at: MilestoneIndexTimestamp,
+    payload: MilestonePayload,
+    protocol_params: ProtocolParameters,
+    node_config: NodeConfiguration,
+}
+
+#[derive(Deserialize)]
+struct BlockFixture {
+    block_id: BlockId,
+    #[serde(with = "serde_bytes")]
+    raw: Vec<u8>,
+    metadata: BlockMetadata,
+}
+
+/// The part of the node's state that tests can change between runs.
+#[derive(Clone, Debug)]
+pub struct MockInxState {
+    /// Milestones up to and including this index are pruned. The ledger state is served at this index.
+    pub pruning_index: MilestoneIndex,
+    /// The latest confirmed milestone. Milestone streams end after this index.
+    pub confirmed_index: MilestoneIndex,
+    /// Overrides the network name of the served protocol parameters.
+    pub network_name: Option<String>,
+}
+
+/// An INX service backed by the test fixtures.
+#[derive(Clone)]
+pub struct MockInx {
+    milestones: Arc<BTreeMap<MilestoneIndex, MilestoneFixture>>,
+    state: Arc<Mutex<MockInxState>>,
+}
+
+impl MockInx {
+    /// Loads the fixture data. The node initially holds the ledger state at the first fixture milestone and has
+    /// confirmed all remaining ones.
+    pub fn from_fixture() -> eyre::Result<Self> {
+        let file = File::open(FIXTURE_PATH)?;
+        let data: mongodb::bson::Bson = serde_json::from_reader(BufReader::new(file))?;
+        let milestones = mongodb::bson::from_bson::<BTreeMap<String, MilestoneFixture>>(data)?
+            .into_iter()
+            .map(|(index, milestone)| Ok((index.parse::<u32>()?.into(), milestone)))
+            .collect::<eyre::Result<BTreeMap<MilestoneIndex, _>>>()?;
+        let (first_index, last_index) = match (milestones.keys().next(), milestones.keys().next_back()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => eyre::bail!("no milestones in `{FIXTURE_PATH}`"),
+        };
+        Ok(Self {
+            milestones: Arc::new(milestones),
+            state: Arc::new(Mutex::new(MockInxState {
+                pruning_index: first_index,
+                confirmed_index: last_index,
+                network_name: None,
+            })),
+        })
+    }
+
+    /// The indexes of all milestones in the fixture.
+    pub fn milestone_indexes(&self) -> Vec<MilestoneIndex> {
+        self.milestones.keys().copied().collect()
+    }
+
+    /// The milestone id of a fixture milestone.
+    pub fn milestone_id(&self, index: MilestoneIndex) -> Option<MilestoneId> {
+        self.milestones.get(&index).map(|ms| ms.milestone_data.milestone_id)
+    }
+
+    /// The ids of all blocks in the cone of a fixture milestone.
+    pub fn cone_block_ids(&self, index: MilestoneIndex) -> Vec<BlockId> {
+        self.milestones
+            .get(&index)
+            .map(|ms| ms.cone.values().map(|block| block.block_id).collect())
+            .unwrap_or_default()
+    }
+
+    /// The outputs created in a fixture milestone.
+    pub fn created_outputs(&self, index: MilestoneIndex) -> Vec<LedgerOutput> {
+        self.milestones
+            .get(&index)
+            .map(|ms| ms.created.clone())
+            .unwrap_or_default()
+    }
+
+    /// The outputs consumed in a fixture milestone.
+    pub fn consumed_outputs(&self, index: MilestoneIndex) -> Vec<LedgerSpent> {
+        self.milestones
+            .get(&index)
+            .map(|ms| ms.consumed.clone())
+            .unwrap_or_default()
+    }
+
+    /// The ids and serialized blocks in the cone of a fixture milestone.
+    pub fn cone_blocks(&self, index: MilestoneIndex) -> Vec<(BlockId, Vec<u8>)> {
+        self.milestones
+            .get(&index)
+            .map(|ms| ms.cone.values().map(|block| (block.block_id, block.raw.clone())).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn state(&self) -> MockInxState {
+        self.state.lock().unwrap().clone()
+    }
+
+    pub fn update_state(&self, f: impl FnOnce(&mut MockInxState)) {
+        f(&mut self.state.lock().unwrap())
+    }
+
+    /// Serves the INX interface on a random local port.
+    pub async fn serve(&self) -> eyre::Result<MockInxServer> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let incoming = futures::stream::unfold(listener, |listener| async move {
+            Some((listener.accept().await.map(|(stream, _)| stream), listener))
+        });
+        let (shutdown, signal) = oneshot::channel::<()>();
+        let handle = tokio::spawn(
+            Server::builder()
+                .add_service(self.clone())
+                .serve_with_incoming_shutdown(incoming, async {
+                    signal.await.ok();
+                }),
+        );
+        Ok(MockInxServer { url, shutdown, handle })
+    }
+
+    /// The fixture milestone with the highest index that is less than or equal to `index`.
+    fn milestone_at(&self, index: MilestoneIndex) -> Result<&MilestoneFixture, Status> {
+        self.milestones
+            .range(..=index)
+            .next_back()
+            .or_else(|| self.milestones.iter().next())
+            .map(|(_, ms)| ms)
+            .ok_or_else(|| Status::not_found(format!("no milestone at index {index}")))
+    }
+
+    fn milestone_index(&self, request: proto::MilestoneRequest) -> Result<MilestoneIndex, Status> {
+        match request.milestone_id {
+            Some(id) => {
+                let milestone_id = MilestoneId::try_from(id).map_err(internal)?;
+                self.milestones
+                    .iter()
+                    .find(|(_, ms)| ms.milestone_data.milestone_id == milestone_id)
+                    .map(|(index, _)| *index)
+                    .ok_or_else(|| Status::not_found(format!("no milestone with id {}", milestone_id.to_hex())))
+            }
+            None => Ok(request.milestone_index.into()),
+        }
+    }
+
+    fn milestone(&self, index: MilestoneIndex) -> Result<proto::Milestone, Status> {
+        let ms = &self
+            .milestones
+            .get(&index)
+            .ok_or_else(|| Status::not_found(format!("no milestone at index {index}")))?
+            .milestone_data;
+        let ctx = iota::protocol::ProtocolParameters::try_from(ms.protocol_params.clone()).map_err(internal)?;
+        let payload = iota::payload::Payload::from(
+            iota::payload::MilestonePayload::try_from_with_context(&ctx, ms.payload.clone()).map_err(internal)?,
+        );
+        Ok(proto::Milestone {
+            milestone_info: Some(proto::MilestoneInfo {
+                milestone_id: Some(ms.milestone_id.into()),
+                milestone_index: index.0,
+                milestone_timestamp: ms.at.milestone_timestamp.0,
+            }),
+            milestone: Some(proto::RawMilestone {
+                data: payload.pack_to_vec(),
+            }),
+        })
+    }
+
+    fn protocol_parameters(&self, index: MilestoneIndex) -> Result<proto::RawProtocolParameters, Status> {
+        let mut params = self.milestone_at(index)?.milestone_data.protocol_params.clone();
+        if let Some(network_name) = self.state().network_name {
+            params.network_name = network_name;
+        }
+        Ok(proto::RawProtocolParameters {
+            protocol_version: params.version as u32,
+            params: iota::protocol::ProtocolParameters::try_from(params)
+                .map_err(internal)?
+                .pack_to_vec(),
+        })
+    }
+
+    fn read_node_status(&self, _: proto::NoParams) -> Result<proto::NodeStatus, Status> {
+        let state = self.state();
+        let confirmed_milestone = self.milestone(state.confirmed_index)?;
+        Ok(proto::NodeStatus {
+            is_healthy: true,
+            is_synced: true,
+            is_almost_synced: true,
+            latest_milestone: Some(confirmed_milestone.clone()),
+            confirmed_milestone: Some(confirmed_milestone),
+            current_protocol_parameters: Some(self.protocol_parameters(state.confirmed_index)?),
+            tangle_pruning_index: state.pruning_index.0,
+            milestones_pruning_index: state.pruning_index.0,
+            ledger_pruning_index: state.pruning_index.0,
+            ledger_index: state.confirmed_index.0,
+        })
+    }
+
+    fn read_node_configuration(&self, _: proto::NoParams) -> Result<proto::NodeConfiguration, Status> {
+        let ms = &self.milestone_at(self.state().confirmed_index)?.milestone_data;
+        let config = &ms.node_config;
+        Ok(proto::NodeConfiguration {
+            milestone_public_key_count: config.milestone_public_key_count,
+            milestone_key_ranges: config
+                .milestone_key_ranges
+                .iter()
+                .map(|range| {
+                    Ok(proto::MilestoneKeyRange {
+                        public_key: prefix_hex::decode(&range.public_key).map_err(internal)?,
+                        start_index: range.start.0,
+                        end_index: range.end.0,
+                    })
+                })
+                .collect::<Result<_, Status>>()?,
+            base_token: Some(proto::BaseToken {
+                name: config.base_token.name.clone(),
+                ticker_symbol: config.base_token.ticker_symbol.clone(),
+                unit: config.base_token.unit.clone(),
+                subunit: config.base_token.subunit.clone(),
+                decimals: config.base_token.decimals,
+                use_metric_prefix: config.base_token.use_metric_prefix,
+            }),
+            supported_protocol_versions: vec![ms.protocol_params.version as u32],
+        })
+    }
+
+    fn read_protocol_parameters(
+        &self,
+        request: proto::MilestoneRequest,
+    ) -> Result<proto::RawProtocolParameters, Status> {
+        self.protocol_parameters(self.milestone_index(request)?)
+    }
+
+    fn read_milestone(&self, request: proto::MilestoneRequest) -> Result<proto::Milestone, Status> {
+        self.milestone(self.milestone_index(request)?)
+    }
+
+    /// The confirmed milestones within the requested range. An end index of `0` means the latest confirmed milestone.
+    fn confirmed_range(
+        &self,
+        request: proto::MilestoneRangeRequest,
+    ) -> impl Iterator<Item = (&MilestoneIndex, &MilestoneFixture)> {
+        let state = self.state();
+        let start = MilestoneIndex(request.start_milestone_index.max(state.pruning_index.0 + 1));
+        let end = match request.end_milestone_index {
+            0 => state.confirmed_index,
+            end => MilestoneIndex(end).min(state.confirmed_index),
+        };
+        self.milestones
+            .iter()
+            .filter(move |(index, _)| (start..=end).contains(*index))
+    }
+
+    fn listen_to_confirmed_milestones(
+        &self,
+        request: proto::MilestoneRangeRequest,
+    ) -> Result<Vec<proto::MilestoneAndProtocolParameters>, Status> {
+        self.confirmed_range(request)
+            .map(|(index, _)| {
+                Ok(proto::MilestoneAndProtocolParameters {
+                    milestone: Some(self.milestone(*index)?),
+                    current_protocol_parameters: Some(self.protocol_parameters(*index)?),
+                })
+            })
+            .collect()
+    }
+
+    fn read_milestone_cone(&self, request: proto::MilestoneRequest) -> Result<Vec<proto::BlockWithMetadata>, Status> {
+        let index = self.milestone_index(request)?;
+        let ms = self
+            .milestones
+            .get(&index)
+            .ok_or_else(|| Status::not_found(format!("no milestone at index {index}")))?;
+        Ok(ms
+            .cone
+            .values()
+            .map(|block| {
+                BlockWithMetadataMessage {
+                    metadata: BlockMetadataMessage {
+                        block_id: block.block_id,
+                        parents: block.metadata.parents.clone(),
+                        is_solid: block.metadata.is_solid,
+                        should_promote: block.metadata.should_promote,
+                        should_reattach: block.metadata.should_reattach,
+                        referenced_by_milestone_index: block.metadata.referenced_by_milestone_index,
+                        milestone_index: block.metadata.milestone_index,
+                        inclusion_state: block.metadata.inclusion_state,
+                        conflict_reason: block.metadata.conflict_reason,
+                        white_flag_index: block.metadata.white_flag_index,
+                    },
+                    block: block.raw.clone().into(),
+                }
+                .into()
+            })
+            .collect())
+    }
+
+    /// Serves the ledger at the pruning index, which consists of the outputs that are created up to that index and
+    /// the outputs that are consumed by later milestones.
+    fn read_unspent_outputs(&self, _: proto::NoParams) -> Result<Vec<proto::UnspentOutput>, Status> {
+        let ledger_index = self.state().pruning_index;
+        let ctx = self.context(ledger_index)?;
+        let (pruned, confirmed): (Vec<_>, Vec<_>) =
+            self.milestones.iter().partition(|(index, _)| **index <= ledger_index);
+        let consumed = pruned
+            .iter()
+            .flat_map(|(_, ms)| ms.consumed.iter().map(|spent| spent.output.output_id))
+            .collect::<Vec<_>>();
+        pruned
+            .iter()
+            .flat_map(|(_, ms)| ms.created.iter())
+            .chain(
+                confirmed
+                    .iter()
+                    .flat_map(|(_, ms)| ms.consumed.iter().map(|spent| &spent.output))
+                    .filter(|output| output.booked.milestone_index <= ledger_index),
+            )
+            .filter(|output| !consumed.contains(&output.output_id))
+            .map(|output| {
+                Ok(proto::UnspentOutput {
+                    ledger_index: ledger_index.0,
+                    output: Some(output.clone().try_into_with_context(&ctx).map_err(internal)?),
+                })
+            })
+            .collect()
+    }
+
+    fn listen_to_ledger_updates(
+        &self,
+        request: proto::MilestoneRangeRequest,
+    ) -> Result<Vec<proto::LedgerUpdate>, Status> {
+        use proto::ledger_update::{marker::MarkerType, Marker, Op};
+
+        let mut updates = Vec::new();
+        for (index, ms) in self.confirmed_range(request) {
+            let ctx = self.context(*index)?;
+            let marker = |marker_type: MarkerType| proto::LedgerUpdate {
+                op: Some(Op::BatchMarker(Marker {
+                    milestone_index: index.0,
+                    marker_type: marker_type.into(),
+                    consumed_count: ms.consumed.len() as u32,
+                    created_count: ms.created.len() as u32,
+                })),
+            };
+            updates.push(marker(MarkerType::Begin));
+            for spent in &ms.consumed {
+                updates.push(proto::LedgerUpdate {
+                    op: Some(Op::Consumed(proto::LedgerSpent {
+                        output: Some(spent.output.clone().try_into_with_context(&ctx).map_err(internal)?),
+                        transaction_id_spent: Some(spent.spent_metadata.transaction_id.into()),
+                        milestone_index_spent: spent.spent_metadata.spent.milestone_index.0,
+                        milestone_timestamp_spent: spent.spent_metadata.spent.milestone_timestamp.0,
+                    })),
+                });
+            }
+            for output in &ms.created {
+                updates.push(proto::LedgerUpdate {
+                    op: Some(Op::Created(
+                        output.clone().try_into_with_context(&ctx).map_err(internal)?,
+                    )),
+                });
+            }
+            updates.push(marker(MarkerType::End));
+        }
+        Ok(updates)
+    }
+
+    /// Accepts every block that is valid under the current protocol parameters, like a node would.
+    fn submit_block(&self, request: proto::RawBlock) -> Result<proto::BlockId, Status> {
+        let block = iota::Block::unpack_strict(request.data.as_slice(), &self.context(self.state().confirmed_index)?)
+            .map_err(|e| Status::invalid_argument(format!("invalid block: {e:?}")))?;
+        Ok(proto::BlockId {
+            id: block.id().to_vec(),
+        })
+    }
+
+    /// Serves the blocks in the cone of the latest confirmed milestone as tips.
+    fn request_tips(&self, request: proto::TipsRequest) -> Result<proto::TipsResponse, Status> {
+        Ok(proto::TipsResponse {
+            tips: self
+                .cone_block_ids(self.state().confirmed_index)
+                .into_iter()
+                .take(request.count as usize)
+                .map(|block_id| proto::BlockId {
+                    id: block_id.0.to_vec(),
+                })
+                .collect(),
+        })
+    }
+
+    fn context(&self, index: MilestoneIndex) -> Result<iota::protocol::ProtocolParameters, Status> {
+        iota::protocol::ProtocolParameters::try_from(self.milestone_at(index)?.milestone_data.protocol_params.clone())
+            .map_err(internal)
+    }
+}
+
+fn internal(err: impl Debug) -> Status {
+    Status::internal(format!("{err:?}"))
+}
+
+/// A running [`MockInx`] server.
+pub struct MockInxServer {
+    url: String,
+    shutdown: oneshot::Sender<()>,
+    handle: JoinHandle<Result<(), tonic::transport::Error>>,
+}
+
+impl MockInxServer {
+    /// The address that Chronicle can use to connect to this server.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub async fn shutdown(self) -> eyre::Result<()> {
+        self.shutdown.send(()).ok();
+        Ok(self.handle.await??)
+    }
+}
+
+struct Unary<F>(F);
+
+impl<F, Req, Res> UnaryService<Req> for Unary<F>
+where
+    F: FnMut(Req) -> Result<Res, Status>,
+{
+    type Response = Res;
+    type Future = Ready<Result<Response<Res>, Status>>;
+
+    fn call(&mut self, request: Request<Req>) -> Self::Future {
+        futures::future::ready((self.0)(request.into_inner()).map(Response::new))
+    }
+}
+
+struct ServerStreaming<F>(F);
+
+impl<F, Req, Res> ServerStreamingService<Req> for ServerStreaming<F>
+where
+    F: FnMut(Req) -> Result<Vec<Res>, Status>,
+{
+    type Response = Res;
+    type ResponseStream = Iter<std::vec::IntoIter<Result<Res, Status>>>;
+    type Future = Ready<Result<Response<Self::ResponseStream>, Status>>;
+
+    fn call(&mut self, request: Request<Req>) -> Self::Future {
+        futures::future::ready(
+            (self.0)(request.into_inner())
+                .map(|items| Response::new(futures::stream::iter(items.into_iter().map(Ok).collect::<Vec<_>>()))),
+        )
+    }
+}
+
+impl NamedService for MockInx {
+    const NAME: &'static str = "inx.INX";
+}
+
+impl<B> Service<http::Request<B>> for MockInx
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let inx = self.clone();
+        Box::pin(async move {
+            macro_rules! unary {
+                ($method:ident) => {
+                    Grpc::new(ProstCodec::default())
+                        .unary(Unary(|request| inx.$method(request)), req)
+                        .await
+                };
+            }
+            macro_rules! server_streaming {
+                ($method:ident) => {
+                    Grpc::new(ProstCodec::default())
+                        .server_streaming(ServerStreaming(|request| inx.$method(request)), req)
+                        .await
+                };
+            }
+            Ok(match req.uri().path() {
+                "/inx.INX/ReadNodeStatus" => unary!(read_node_status),
+                "/inx.INX/ReadNodeConfiguration" => unary!(read_node_configuration),
+                "/inx.INX/ReadProtocolParameters" => unary!(read_protocol_parameters),
+                "/inx.INX/ReadMilestone" => unary!(read_milestone),
+                "/inx.INX/ListenToConfirmedMilestones" => server_streaming!(listen_to_confirmed_milestones),
+                "/inx.INX/ReadMilestoneCone" => server_streaming!(read_milestone_cone),
+                "/inx.INX/ReadUnspentOutputs" => server_streaming!(read_unspent_outputs),
+                "/inx.INX/ListenToLedgerUpdates" => server_streaming!(listen_to_ledger_updates),
+                "/inx.INX/SubmitBlock" => unary!(submit_block),
+                "/inx.INX/RequestTips" => unary!(request_tips),
+                _ => http::Response::builder()
+                    .status(200)
+                    .header("grpc-status", "12")
+                    .header("content-type", "application/grpc")
+                    .body(empty_body())
+                    .unwrap(),
+            })
+        })
+    }
+}
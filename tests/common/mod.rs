@@ -1,7 +1,17 @@
 // Copyright 2022 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use chronicle::db::{MongoDb, MongoDbCollection, MongoDbConfig};
+#[cfg(feature = "inx")]
+#[allow(unused)]
+pub mod inx;
+
+use chronicle::db::{
+    mongodb::collections::{
+        ApiKeyCollection, ApplicationStateCollection, BlockCollection, ConfigurationUpdateCollection,
+        LedgerUpdateCollection, MilestoneCollection, OutputCollection, ProtocolUpdateCollection, TreasuryCollection,
+    },
+    MongoDb, MongoDbCollection, MongoDbConfig,
+};
 
 #[allow(unused)]
 pub async fn setup_database(database_name: impl ToString) -> eyre::Result<MongoDb> {
@@ -27,6 +37,21 @@ pub async fn setup_collection<T: MongoDbCollection + Send + Sync>(db: &MongoDb)
     Ok(db.collection::<T>())
 }
 
+#[allow(unused)]
+pub async fn setup_storage(database_name: impl ToString) -> eyre::Result<MongoDb> {
+    let db = setup_database(database_name).await?;
+    db.create_indexes::<ApplicationStateCollection>().await?;
+    db.create_indexes::<BlockCollection>().await?;
+    db.create_indexes::<OutputCollection>().await?;
+    db.create_indexes::<LedgerUpdateCollection>().await?;
+    db.create_indexes::<MilestoneCollection>().await?;
+    db.create_indexes::<TreasuryCollection>().await?;
+    db.create_indexes::<ProtocolUpdateCollection>().await?;
+    db.create_indexes::<ConfigurationUpdateCollection>().await?;
+    db.create_indexes::<ApiKeyCollection>().await?;
+    Ok(db)
+}
+
 #[allow(unused)]
 pub async fn teardown(db: MongoDb) {
     db.drop().await.unwrap();
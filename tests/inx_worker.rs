@@ -0,0 +1,309 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+#[cfg(feature = "inx")]
+mod test_inx {
+    use std::process::Output;
+
+    use chronicle::{
+        db::{
+            mongodb::collections::{
+                ApplicationStateCollection, BlockCollection, MilestoneCollection, OutputCollection,
+                ProtocolUpdateCollection,
+            },
+            MongoDb,
+        },
+        inx::{Inx, InxError},
+        tangle::Tangle,
+    };
+    use futures::TryStreamExt;
+
+    use super::common::{inx::MockInx, setup_database, teardown};
+
+    /// Runs the Chronicle binary against the mock INX server until the milestone stream ends.
+    async fn run_chronicle(inx_url: &str, db: &MongoDb, args: &[&str]) -> Output {
+        let mut command = std::process::Command::new(env!("CARGO_BIN_EXE_inx-chronicle"));
+        command
+            .args(["--inx-url", inx_url, "--mongodb-database-name", db.name()])
+            .args(args);
+        #[cfg(feature = "api")]
+        command.arg("--disable-api");
+        #[cfg(feature = "analytics")]
+        command.arg("--disable-analytics");
+        #[cfg(feature = "metrics")]
+        command.arg("--disable-metrics");
+        tokio::task::spawn_blocking(move || command.output().unwrap())
+            .await
+            .unwrap()
+    }
+
+    fn stderr(output: &Output) -> String {
+        String::from_utf8_lossy(&output.stderr).into_owned()
+    }
+
+    #[tokio::test]
+    async fn test_mock_inx() {
+        let mock = MockInx::from_fixture().unwrap();
+        let indexes = mock.milestone_indexes();
+        let server = mock.serve().await.unwrap();
+
+        let mut inx = Inx::connect(server.url().to_owned()).await.unwrap();
+        let node_status = inx.read_node_status().await.unwrap();
+        assert_eq!(node_status.tangle_pruning_index, indexes[0]);
+        assert_eq!(
+            node_status.confirmed_milestone.milestone_info.milestone_index,
+            *indexes.last().unwrap()
+        );
+
+        let unspent_outputs = inx.read_unspent_outputs().await.unwrap().try_collect::<Vec<_>>().await.unwrap();
+        assert!(unspent_outputs.iter().all(|msg| msg.ledger_index == indexes[0]));
+        for spent in mock.consumed_outputs(indexes[1]) {
+            if spent.output.booked.milestone_index <= indexes[0] {
+                assert!(unspent_outputs.iter().any(|msg| msg.output == spent.output));
+            }
+        }
+
+        let tangle = Tangle::from(inx);
+        let milestones = tangle
+            .milestone_stream(indexes[0]..)
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(
+            milestones.iter().map(|ms| ms.at.milestone_index).collect::<Vec<_>>(),
+            indexes[1..]
+        );
+        for milestone in milestones {
+            let index = milestone.at.milestone_index;
+            assert_eq!(Some(milestone.milestone_id), mock.milestone_id(index));
+            let block_ids = milestone
+                .cone_stream()
+                .await
+                .unwrap()
+                .map_ok(|block| block.block_id)
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+            assert_eq!(block_ids, mock.cone_block_ids(index));
+            let ledger_updates = milestone.ledger_updates();
+            assert_eq!(ledger_updates.created_outputs(), mock.created_outputs(index));
+            assert_eq!(ledger_updates.consumed_outputs(), mock.consumed_outputs(index));
+        }
+
+        server.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_inx_block_submission() {
+        let mock = MockInx::from_fixture().unwrap();
+        let index = *mock.milestone_indexes().last().unwrap();
+        let server = mock.serve().await.unwrap();
+        let mut inx = Inx::connect(server.url().to_owned()).await.unwrap();
+
+        assert_eq!(inx.request_tips(2, false).await.unwrap(), mock.cone_block_ids(index)[..2]);
+
+        let (block_id, raw) = mock.cone_blocks(index).remove(0);
+        assert_eq!(inx.submit_block(raw.clone()).await.unwrap(), block_id);
+        match inx.submit_block(raw[..raw.len() - 1].to_vec()).await {
+            Err(InxError::StatusCode(status)) => assert_eq!(status.code(), tonic::Code::InvalidArgument),
+            res => panic!("expected an invalid argument status, got {res:?}"),
+        }
+
+        server.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_inx_worker_sync() {
+        let db = setup_database("test-inx-worker-sync").await.unwrap();
+        let mock = MockInx::from_fixture().unwrap();
+        let indexes = mock.milestone_indexes();
+        let server = mock.serve().await.unwrap();
+
+        let output = run_chronicle(server.url(), &db, &["--inx-milestone-verification", "halt"]).await;
+        assert!(output.status.success(), "{}", stderr(&output));
+
+        assert_eq!(
+            db.collection::<ApplicationStateCollection>()
+                .get_starting_index()
+                .await
+                .unwrap()
+                .map(|at| at.milestone_index),
+            Some(indexes[0])
+        );
+        let milestones = db.collection::<MilestoneCollection>();
+        assert_eq!(
+            milestones
+                .get_newest_milestone()
+                .await
+                .unwrap()
+                .map(|at| at.milestone_index),
+            indexes.last().copied()
+        );
+        for &index in &indexes[1..] {
+            assert_eq!(milestones.get_milestone_id(index).await.unwrap(), mock.milestone_id(index));
+            assert_eq!(
+                milestones.get_milestone_signature_verified(index).await.unwrap(),
+                Some(true)
+            );
+            for block_id in mock.cone_block_ids(index) {
+                assert!(db
+                    .collection::<BlockCollection>()
+                    .get_block_metadata(&block_id)
+                    .await
+                    .unwrap()
+                    .is_some());
+            }
+            for created in mock.created_outputs(index) {
+                assert!(db
+                    .collection::<OutputCollection>()
+                    .get_output_metadata(&created.output_id, index)
+                    .await
+                    .unwrap()
+                    .is_some());
+            }
+            for consumed in mock.consumed_outputs(index) {
+                let metadata = db
+                    .collection::<OutputCollection>()
+                    .get_output_metadata(&consumed.output_id(), index)
+                    .await
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(metadata.spent_metadata, Some(consumed.spent_metadata));
+            }
+        }
+
+        server.shutdown().await.unwrap();
+        teardown(db).await;
+    }
+
+    #[tokio::test]
+    async fn test_inx_worker_reconnect() {
+        let db = setup_database("test-inx-worker-reconnect").await.unwrap();
+        let mock = MockInx::from_fixture().unwrap();
+        let indexes = mock.milestone_indexes();
+        mock.update_state(|state| state.confirmed_index = indexes[1]);
+
+        let server = mock.serve().await.unwrap();
+        let output = run_chronicle(server.url(), &db, &[]).await;
+        assert!(output.status.success(), "{}", stderr(&output));
+        server.shutdown().await.unwrap();
+
+        let milestones = db.collection::<MilestoneCollection>();
+        assert_eq!(
+            milestones
+                .get_newest_milestone()
+                .await
+                .unwrap()
+                .map(|at| at.milestone_index),
+            Some(indexes[1])
+        );
+
+        // The node comes back with more confirmed milestones.
+        mock.update_state(|state| state.confirmed_index = *indexes.last().unwrap());
+        let server = mock.serve().await.unwrap();
+        let output = run_chronicle(server.url(), &db, &[]).await;
+        assert!(output.status.success(), "{}", stderr(&output));
+        server.shutdown().await.unwrap();
+
+        assert_eq!(
+            milestones
+                .get_newest_milestone()
+                .await
+                .unwrap()
+                .map(|at| at.milestone_index),
+            indexes.last().copied()
+        );
+        for &index in &indexes[1..] {
+            assert_eq!(milestones.get_milestone_id(index).await.unwrap(), mock.milestone_id(index));
+        }
+        // The database must not be bootstrapped again.
+        assert_eq!(
+            db.collection::<ApplicationStateCollection>()
+                .get_starting_index()
+                .await
+                .unwrap()
+                .map(|at| at.milestone_index),
+            Some(indexes[0])
+        );
+
+        teardown(db).await;
+    }
+
+    #[tokio::test]
+    async fn test_inx_worker_gap() {
+        let db = setup_database("test-inx-worker-gap").await.unwrap();
+        let mock = MockInx::from_fixture().unwrap();
+        let indexes = mock.milestone_indexes();
+        mock.update_state(|state| state.confirmed_index = indexes[1]);
+        let server = mock.serve().await.unwrap();
+
+        let output = run_chronicle(server.url(), &db, &[]).await;
+        assert!(output.status.success(), "{}", stderr(&output));
+
+        // The node pruned the milestones that Chronicle has not synced yet.
+        mock.update_state(|state| {
+            state.pruning_index = *indexes.last().unwrap();
+            state.confirmed_index = *indexes.last().unwrap();
+        });
+        let output = run_chronicle(server.url(), &db, &[]).await;
+        assert!(!output.status.success());
+        assert!(
+            stderr(&output).contains("node pruned required milestones"),
+            "{}",
+            stderr(&output)
+        );
+
+        assert_eq!(
+            db.collection::<MilestoneCollection>()
+                .get_newest_milestone()
+                .await
+                .unwrap()
+                .map(|at| at.milestone_index),
+            Some(indexes[1])
+        );
+
+        server.shutdown().await.unwrap();
+        teardown(db).await;
+    }
+
+    #[tokio::test]
+    async fn test_inx_worker_network_change() {
+        let db = setup_database("test-inx-worker-network-change").await.unwrap();
+        let mock = MockInx::from_fixture().unwrap();
+        let server = mock.serve().await.unwrap();
+
+        let output = run_chronicle(server.url(), &db, &[]).await;
+        assert!(output.status.success(), "{}", stderr(&output));
+        let network_name = db
+            .collection::<ProtocolUpdateCollection>()
+            .get_latest_protocol_parameters()
+            .await
+            .unwrap()
+            .unwrap()
+            .parameters
+            .network_name;
+
+        mock.update_state(|state| state.network_name = Some("other-network".to_owned()));
+        let output = run_chronicle(server.url(), &db, &[]).await;
+        assert!(!output.status.success());
+        assert!(stderr(&output).contains("network changed"), "{}", stderr(&output));
+
+        assert_eq!(
+            db.collection::<ProtocolUpdateCollection>()
+                .get_latest_protocol_parameters()
+                .await
+                .unwrap()
+                .unwrap()
+                .parameters
+                .network_name,
+            network_name
+        );
+
+        server.shutdown().await.unwrap();
+        teardown(db).await;
+    }
+}
@@ -6,8 +6,20 @@ mod common;
 #[cfg(feature = "rand")]
 mod test_rand {
     use chronicle::{
-        db::mongodb::collections::MilestoneCollection,
-        model::payload::{MilestoneId, MilestonePayload},
+        db::mongodb::collections::{MigratedFundsQuery, MilestoneCollection},
+        model::{
+            payload::{
+                milestone::{MigratedFundsEntry, MilestoneOption},
+                MilestoneId, MilestonePayload,
+            },
+            utxo::Address,
+        },
+        tangle::MilestoneStats,
+    };
+    use futures::TryStreamExt;
+    use iota_types::block::{
+        payload::milestone::option::MigratedFundsEntry as IotaMigratedFundsEntry,
+        rand::{address::rand_address, receipt::rand_migrated_funds_entry},
     };
 
     use super::common::{setup_collection, setup_database, teardown};
@@ -26,6 +38,8 @@ mod test_rand {
                 milestone.essence.index,
                 milestone.essence.timestamp,
                 milestone.clone(),
+                Some(true),
+                Some(MilestoneStats::default()),
             )
             .await
             .unwrap();
@@ -56,6 +70,177 @@ mod test_rand {
             Some(&milestone)
         );
 
+        assert_eq!(
+            milestone_collection
+                .get_milestone_signature_verified(milestone.essence.index)
+                .await
+                .unwrap(),
+            Some(true)
+        );
+
+        assert_eq!(
+            milestone_collection
+                .get_milestone_stats(milestone.essence.index)
+                .await
+                .unwrap(),
+            Some(MilestoneStats::default())
+        );
+
+        teardown(db).await;
+    }
+
+    #[tokio::test]
+    async fn test_receipts_and_migrated_funds() {
+        let db = setup_database("test-receipts-and-migrated-funds").await.unwrap();
+        let milestone_collection = setup_collection::<MilestoneCollection>(&db).await.unwrap();
+
+        let protocol_params = iota_types::block::protocol::protocol_parameters();
+        let token_supply = protocol_params.token_supply();
+
+        let address = rand_address();
+        let migrated_funds_entry = |to_address: bool| {
+            let entry = rand_migrated_funds_entry(token_supply);
+            if to_address {
+                IotaMigratedFundsEntry::new(
+                    entry.tail_transaction_hash().clone(),
+                    address,
+                    entry.amount(),
+                    token_supply,
+                )
+                .unwrap()
+                .into()
+            } else {
+                MigratedFundsEntry::from(entry)
+            }
+        };
+        let receipt = |migrated_at: u32, last, funds: Vec<MigratedFundsEntry>| match MilestoneOption::rand_receipt(
+            &protocol_params,
+        ) {
+            MilestoneOption::Receipt { transaction, .. } => MilestoneOption::Receipt {
+                migrated_at: migrated_at.into(),
+                last,
+                funds: funds.into_boxed_slice(),
+                transaction,
+            },
+            MilestoneOption::Parameters { .. } => unreachable!(),
+        };
+
+        let mut first_entries = vec![migrated_funds_entry(true), migrated_funds_entry(true)];
+        let other_entry = migrated_funds_entry(false);
+        let last_entry = migrated_funds_entry(true);
+
+        let options = vec![
+            vec![receipt(10, false, [first_entries.clone(), vec![other_entry]].concat())],
+            vec![receipt(11, true, vec![last_entry])],
+            vec![MilestoneOption::rand_parameters()],
+        ];
+        let mut milestones = Vec::new();
+        for (index, options) in (1..).zip(options) {
+            let mut milestone = MilestonePayload::rand(&protocol_params);
+            milestone.essence.index = index.into();
+            milestone.essence.options = options.into_boxed_slice();
+            let milestone_id = MilestoneId::rand();
+            milestone_collection
+                .insert_milestone(
+                    milestone_id,
+                    milestone.essence.index,
+                    milestone.essence.timestamp,
+                    milestone.clone(),
+                    Some(true),
+                    None,
+                )
+                .await
+                .unwrap();
+            milestones.push((milestone_id, milestone));
+        }
+
+        assert_eq!(
+            milestone_collection
+                .get_all_receipts()
+                .await
+                .unwrap()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap(),
+            vec![
+                (milestones[0].1.essence.options[0].clone(), 1.into()),
+                (milestones[1].1.essence.options[0].clone(), 2.into()),
+            ]
+        );
+
+        assert_eq!(
+            milestone_collection
+                .get_receipts_migrated_at(11.into())
+                .await
+                .unwrap()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap(),
+            vec![(milestones[1].1.essence.options[0].clone(), 2.into())]
+        );
+
+        let migrated_funds = |query, page_size, cursor| {
+            let milestone_collection = &milestone_collection;
+            async move {
+                milestone_collection
+                    .get_migrated_funds(query, page_size, cursor)
+                    .await
+                    .unwrap()
+                    .map_ok(|res| (res.at.milestone_index.0, res.migrated_at.0, res.entry))
+                    .try_collect::<Vec<_>>()
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let res = milestone_collection
+            .get_migrated_funds(
+                MigratedFundsQuery::TailTransactionHash(*other_entry.tail_transaction_hash()),
+                1,
+                None,
+            )
+            .await
+            .unwrap()
+            .try_next()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(res.entry, other_entry);
+        assert_eq!(res.migrated_at, 10);
+        assert!(!res.last);
+        assert_eq!(res.milestone_id, milestones[0].0);
+
+        // Entries are ordered by milestone index and tail transaction hash.
+        first_entries.sort_by_key(|entry| *entry.tail_transaction_hash());
+        assert_eq!(
+            migrated_funds(MigratedFundsQuery::Address(Address::from(&address)), 10, None).await,
+            vec![
+                (1, 10, first_entries[0]),
+                (1, 10, first_entries[1]),
+                (2, 11, last_entry),
+            ]
+        );
+
+        // The cursor includes the entry it points to.
+        assert_eq!(
+            migrated_funds(
+                MigratedFundsQuery::Address(Address::from(&address)),
+                1,
+                Some((1.into(), *first_entries[1].tail_transaction_hash())),
+            )
+            .await,
+            vec![(1, 10, first_entries[1])]
+        );
+        assert_eq!(
+            migrated_funds(
+                MigratedFundsQuery::Address(Address::from(&address)),
+                10,
+                Some((2.into(), *last_entry.tail_transaction_hash())),
+            )
+            .await,
+            vec![(2, 11, last_entry)]
+        );
+
         teardown(db).await;
     }
 }
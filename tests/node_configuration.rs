@@ -161,6 +161,18 @@ mod test_rand {
         assert_eq!(doc.config.base_token.decimals, 6);
         assert!(!doc.config.base_token.use_metric_prefix);
 
+        // get the updates that follow
+        for (ledger_index, next_index) in [(1, Some(2)), (3, Some(10)), (10, None)] {
+            assert_eq!(
+                node_configuration
+                    .get_next_node_configuration(ledger_index.into())
+                    .await
+                    .unwrap()
+                    .map(|doc| doc.ledger_index),
+                next_index.map(Into::into)
+            );
+        }
+
         teardown(db).await;
     }
 }
@@ -6,14 +6,27 @@ mod common;
 #[cfg(feature = "rand")]
 mod test_rand {
     use chronicle::{
-        db::mongodb::collections::{OutputCollection, OutputMetadataResult, OutputWithMetadataResult},
+        db::mongodb::collections::{
+            OutputCollection, OutputMetadataResult, OutputWithMetadataResult, UnlockEventKind,
+        },
         model::{
             ledger::{LedgerOutput, LedgerSpent, RentStructureBytes},
             metadata::SpentMetadata,
             payload::TransactionId,
             tangle::MilestoneIndexTimestamp,
-            utxo::{Output, OutputId},
-            BlockId,
+            utxo::{Address, Output, OutputId},
+            BlockId, RentStructure,
+        },
+    };
+    use futures::TryStreamExt;
+    use iota_types::block::{
+        address::{Address as IotaAddress, Ed25519Address},
+        output::{
+            unlock_condition::{
+                AddressUnlockCondition, ExpirationUnlockCondition, StorageDepositReturnUnlockCondition,
+                TimelockUnlockCondition,
+            },
+            BasicOutputBuilder,
         },
     };
 
@@ -169,4 +182,261 @@ mod test_rand {
 
         teardown(db).await;
     }
+
+    #[tokio::test]
+    async fn test_address_storage_deposit() {
+        let db = setup_database("test-address-storage-deposit").await.unwrap();
+        let output_collection = setup_collection::<OutputCollection>(&db).await.unwrap();
+
+        let protocol_params = iota_types::block::protocol::protocol_parameters();
+        let token_supply = protocol_params.token_supply();
+        let rent_structure = RentStructure {
+            v_byte_cost: 10,
+            v_byte_factor_data: 1,
+            v_byte_factor_key: 10,
+        };
+
+        let address = IotaAddress::from(Ed25519Address::new([1; 32]));
+        let other_address = IotaAddress::from(Ed25519Address::new([2; 32]));
+
+        let basic_output = |amount, owner, unlock_condition: Option<_>| {
+            let mut builder = BasicOutputBuilder::new_with_amount(amount)
+                .unwrap()
+                .add_unlock_condition(AddressUnlockCondition::new(owner).into());
+            if let Some(unlock_condition) = unlock_condition {
+                builder = builder.add_unlock_condition(unlock_condition);
+            }
+            let output = builder.finish_output(token_supply).unwrap();
+            LedgerOutput {
+                output_id: OutputId::rand(),
+                rent_structure: RentStructureBytes {
+                    num_key_bytes: 0,
+                    num_data_bytes: 100,
+                },
+                output: Output::from(&output),
+                block_id: BlockId::rand(),
+                booked: MilestoneIndexTimestamp {
+                    milestone_index: 1.into(),
+                    milestone_timestamp: 1000.into(),
+                },
+            }
+        };
+
+        // Every output needs a deposit of 10 * 100 = 1000 unless its amount is lower.
+        let outputs = vec![
+            basic_output(5000, address, None),
+            basic_output(
+                5000,
+                address,
+                Some(
+                    StorageDepositReturnUnlockCondition::new(other_address, 2000, token_supply)
+                        .unwrap()
+                        .into(),
+                ),
+            ),
+            basic_output(4000, address, Some(TimelockUnlockCondition::new(2000).unwrap().into())),
+            basic_output(500, address, None),
+            basic_output(7000, other_address, None),
+        ];
+
+        output_collection.insert_unspent_outputs(&outputs).await.unwrap();
+
+        let result = output_collection
+            .get_address_storage_deposit(Address::from(&address), 1.into(), 1000.into(), rent_structure)
+            .await
+            .unwrap();
+
+        assert_eq!(result.output_count, 4);
+        assert_eq!(result.total_balance, "14500");
+        assert_eq!(result.storage_deposit, "3500");
+        // The deposits, the returned amount and the timelocked output are not spendable.
+        assert_eq!(result.spendable_balance, "6000");
+
+        let result = output_collection
+            .get_address_storage_deposit(
+                Address::from(&IotaAddress::from(Ed25519Address::new([3; 32]))),
+                1.into(),
+                1000.into(),
+                rent_structure,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.output_count, 0);
+        assert_eq!(result.total_balance, "0");
+        assert_eq!(result.storage_deposit, "0");
+        assert_eq!(result.spendable_balance, "0");
+
+        teardown(db).await;
+    }
+
+    #[tokio::test]
+    async fn test_address_balance_at() {
+        let db = setup_database("test-address-balance-at").await.unwrap();
+        let output_collection = setup_collection::<OutputCollection>(&db).await.unwrap();
+
+        let protocol_params = iota_types::block::protocol::protocol_parameters();
+        let token_supply = protocol_params.token_supply();
+
+        let address = IotaAddress::from(Ed25519Address::new([1; 32]));
+        let other_address = IotaAddress::from(Ed25519Address::new([2; 32]));
+
+        let basic_output = |amount, owner, unlock_conditions: Vec<_>| {
+            let output = BasicOutputBuilder::new_with_amount(amount)
+                .unwrap()
+                .add_unlock_condition(AddressUnlockCondition::new(owner).into())
+                .with_unlock_conditions(unlock_conditions)
+                .finish_output(token_supply)
+                .unwrap();
+            LedgerOutput {
+                output_id: OutputId::rand(),
+                rent_structure: RentStructureBytes {
+                    num_key_bytes: 0,
+                    num_data_bytes: 100,
+                },
+                output: Output::from(&output),
+                block_id: BlockId::rand(),
+                booked: MilestoneIndexTimestamp {
+                    milestone_index: 1.into(),
+                    milestone_timestamp: 1000.into(),
+                },
+            }
+        };
+        let expiration = |return_address, timestamp| ExpirationUnlockCondition::new(return_address, timestamp).unwrap();
+
+        let outputs = vec![
+            basic_output(1000, address, vec![]),
+            basic_output(2000, address, vec![TimelockUnlockCondition::new(2000).unwrap().into()]),
+            basic_output(3000, other_address, vec![expiration(address, 1200).into()]),
+            basic_output(4000, address, vec![expiration(other_address, 1300).into()]),
+        ];
+
+        output_collection.insert_unspent_outputs(&outputs).await.unwrap();
+
+        let balance_at = |address, timestamp: u32| {
+            let output_collection = &output_collection;
+            async move {
+                let result = output_collection
+                    .get_address_balance_at(Address::from(&address), 1.into(), timestamp.into())
+                    .await
+                    .unwrap();
+                (result.total_balance, result.timelocked_balance, result.spendable_balance)
+            }
+        };
+        let balance = |total: &str, timelocked: &str, spendable: &str| {
+            (total.to_string(), timelocked.to_string(), spendable.to_string())
+        };
+
+        // Before the expirations, the outputs belong to their owners.
+        assert_eq!(balance_at(address, 1100).await, balance("7000", "2000", "5000"));
+        assert_eq!(balance_at(other_address, 1100).await, balance("3000", "0", "3000"));
+        // Expired outputs belong to their return address.
+        assert_eq!(balance_at(address, 1500).await, balance("6000", "2000", "4000"));
+        assert_eq!(balance_at(other_address, 1500).await, balance("4000", "0", "4000"));
+        // Timelocks end.
+        assert_eq!(balance_at(address, 2000).await, balance("6000", "0", "6000"));
+        // An unknown address has no balance.
+        assert_eq!(
+            balance_at(IotaAddress::from(Ed25519Address::new([3; 32])), 1500).await,
+            balance("0", "0", "0")
+        );
+
+        teardown(db).await;
+    }
+
+    #[tokio::test]
+    async fn test_address_unlock_events() {
+        let db = setup_database("test-address-unlock-events").await.unwrap();
+        let output_collection = setup_collection::<OutputCollection>(&db).await.unwrap();
+
+        let protocol_params = iota_types::block::protocol::protocol_parameters();
+        let token_supply = protocol_params.token_supply();
+
+        let address = IotaAddress::from(Ed25519Address::new([1; 32]));
+        let other_address = IotaAddress::from(Ed25519Address::new([2; 32]));
+
+        let basic_output = |owner, unlock_conditions: Vec<_>| {
+            let output = BasicOutputBuilder::new_with_amount(1000)
+                .unwrap()
+                .add_unlock_condition(AddressUnlockCondition::new(owner).into())
+                .with_unlock_conditions(unlock_conditions)
+                .finish_output(token_supply)
+                .unwrap();
+            LedgerOutput {
+                output_id: OutputId::rand(),
+                rent_structure: RentStructureBytes {
+                    num_key_bytes: 0,
+                    num_data_bytes: 100,
+                },
+                output: Output::from(&output),
+                block_id: BlockId::rand(),
+                booked: MilestoneIndexTimestamp {
+                    milestone_index: 1.into(),
+                    milestone_timestamp: 1000.into(),
+                },
+            }
+        };
+        let expiration = |return_address, timestamp| ExpirationUnlockCondition::new(return_address, timestamp).unwrap();
+        let timelock = |timestamp| TimelockUnlockCondition::new(timestamp).unwrap();
+
+        let outputs = vec![
+            basic_output(other_address, vec![expiration(address, 1200).into()]),
+            basic_output(address, vec![expiration(other_address, 1300).into()]),
+            basic_output(address, vec![timelock(1500).into()]),
+            basic_output(
+                address,
+                vec![timelock(1800).into(), expiration(other_address, 1800).into()],
+            ),
+            // Outside of the time range.
+            basic_output(address, vec![timelock(5000).into()]),
+            basic_output(other_address, vec![timelock(1500).into()]),
+        ];
+
+        output_collection.insert_unspent_outputs(&outputs).await.unwrap();
+
+        let unlock_events = |range, cursor| {
+            let output_collection = &output_collection;
+            async move {
+                output_collection
+                    .get_address_unlock_events(Address::from(&address), 1.into(), range, 10, cursor)
+                    .await
+                    .unwrap()
+                    .map_ok(|event| (event.timestamp.0, event.output_id, event.kind))
+                    .try_collect::<Vec<_>>()
+                    .await
+                    .unwrap()
+            }
+        };
+
+        assert_eq!(
+            unlock_events(1000.into()..=2000.into(), None).await,
+            vec![
+                (1200, outputs[0].output_id, UnlockEventKind::ExpiresToAddress),
+                (1300, outputs[1].output_id, UnlockEventKind::ExpiresFromAddress),
+                (1500, outputs[2].output_id, UnlockEventKind::BecomesSpendable),
+                (1800, outputs[3].output_id, UnlockEventKind::BecomesSpendable),
+                (1800, outputs[3].output_id, UnlockEventKind::ExpiresFromAddress),
+            ]
+        );
+
+        // The cursor continues within the events of the start timestamp.
+        assert_eq!(
+            unlock_events(
+                1800.into()..=2000.into(),
+                Some((outputs[3].output_id, UnlockEventKind::ExpiresFromAddress)),
+            )
+            .await,
+            vec![(1800, outputs[3].output_id, UnlockEventKind::ExpiresFromAddress)]
+        );
+        assert_eq!(
+            unlock_events(
+                1300.into()..=1500.into(),
+                Some((outputs[1].output_id, UnlockEventKind::ExpiresToAddress)),
+            )
+            .await,
+            vec![(1500, outputs[2].output_id, UnlockEventKind::BecomesSpendable)]
+        );
+
+        teardown(db).await;
+    }
 }
@@ -54,6 +54,14 @@ mod test_rand {
                     .version,
                 version as u8
             );
+            assert_eq!(
+                update_collection
+                    .get_next_protocol_parameters(index)
+                    .await
+                    .unwrap()
+                    .map(|doc| doc.parameters.version),
+                (version < 9).then(|| version as u8 + 1)
+            );
         }
 
         let mut parameters = ProtocolParameters::from(iota_types::block::protocol::protocol_parameters());
@@ -0,0 +1,543 @@
+// Copyright 2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+mod common;
+
+#[cfg(feature = "rand")]
+mod test_rand {
+    use std::{
+        collections::{HashMap, HashSet},
+        fs::File,
+        io::BufReader,
+    };
+
+    use chronicle::{
+        db::{
+            mongodb::collections::{ApiKeyDocument, OutputMetadataResult, UtxoChangesResult},
+            Storage,
+        },
+        model::{
+            ledger::{LedgerOutput, LedgerSpent, RentStructureBytes},
+            metadata::{BlockMetadata, ConflictReason, LedgerInclusionState, SpentMetadata},
+            payload::{milestone::MilestoneOption, MilestoneId, MilestonePayload, Payload, TransactionId},
+            tangle::{MilestoneIndex, MilestoneIndexTimestamp},
+            utxo::{Output, OutputId},
+            Block, BlockId, ProtocolParameters,
+        },
+        tangle::{BlockData, MilestoneStats},
+    };
+    use futures::TryStreamExt;
+    use packable::PackableExt;
+    use serde::Deserialize;
+
+    /// Runs a storage test against every storage backend.
+    macro_rules! storage_test {
+        ($name:ident) => {
+            mod $name {
+                #[tokio::test]
+                async fn mongodb() {
+                    let db = crate::common::setup_storage(concat!("test-storage-", stringify!($name)))
+                        .await
+                        .unwrap();
+                    super::$name(&db).await;
+                    crate::common::teardown(db).await;
+                }
+
+                #[cfg(feature = "kv")]
+                #[tokio::test]
+                async fn kv() {
+                    super::$name(&chronicle::db::KvDb::open_temporary().unwrap()).await;
+                }
+            }
+        };
+    }
+
+    storage_test!(blocks);
+    storage_test!(block_children);
+    storage_test!(milestones);
+    storage_test!(receipts);
+    storage_test!(outputs);
+    storage_test!(protocol_updates);
+    storage_test!(treasury_updates);
+    storage_test!(api_keys);
+
+    #[derive(Deserialize)]
+    struct BlockTestData {
+        #[serde(rename = "_id")]
+        block_id: BlockId,
+        #[serde(with = "serde_bytes")]
+        raw: Vec<u8>,
+        metadata: BlockMetadata,
+    }
+
+    fn rand_metadata(parents: Box<[BlockId]>, white_flag_index: u32) -> BlockMetadata {
+        BlockMetadata {
+            parents,
+            is_solid: true,
+            should_promote: false,
+            should_reattach: false,
+            referenced_by_milestone_index: 1.into(),
+            milestone_index: 0.into(),
+            inclusion_state: LedgerInclusionState::Included,
+            conflict_reason: ConflictReason::None,
+            white_flag_index,
+        }
+    }
+
+    async fn blocks(db: &impl Storage) {
+        let file = File::open("tests/data/blocks_ms_2418807.json").unwrap();
+        let test_data: mongodb::bson::Bson = serde_json::from_reader(BufReader::new(file)).unwrap();
+
+        let blocks: Vec<BlockTestData> = mongodb::bson::from_bson(test_data).unwrap();
+
+        let blocks = blocks
+            .into_iter()
+            .map(
+                |BlockTestData {
+                     block_id,
+                     raw,
+                     metadata,
+                 }| {
+                    let block = iota_types::block::Block::unpack_unverified(raw.clone()).unwrap().into();
+                    BlockData {
+                        block_id,
+                        block,
+                        raw,
+                        metadata,
+                    }
+                },
+            )
+            .collect::<Vec<_>>();
+
+        db.insert_blocks_with_metadata(blocks.clone()).await.unwrap();
+
+        for (transaction_id, block) in blocks.iter().filter_map(|data| {
+            data.block.payload.as_ref().and_then(|p| {
+                if let Payload::Transaction(payload) = p {
+                    Some((payload.transaction_id, &data.block))
+                } else {
+                    None
+                }
+            })
+        }) {
+            assert_eq!(
+                db.get_block_for_transaction(&transaction_id)
+                    .await
+                    .unwrap()
+                    .map(|res| res.block)
+                    .as_ref(),
+                Some(block),
+            );
+        }
+
+        for data in &blocks {
+            assert_eq!(db.get_block(&data.block_id).await.unwrap().as_ref(), Some(&data.block));
+            assert_eq!(
+                db.get_block_raw(&data.block_id).await.unwrap().as_ref(),
+                Some(&data.raw)
+            );
+            assert_eq!(
+                db.get_block_metadata(&data.block_id).await.unwrap().as_ref(),
+                Some(&data.metadata)
+            );
+        }
+
+        let block_ids = blocks
+            .iter()
+            .map(|data| data.block_id)
+            .chain(std::iter::once(BlockId::rand()))
+            .collect::<Vec<_>>();
+        // The results are not ordered by the given ids.
+        let blocks_by_id = blocks
+            .iter()
+            .map(|data| (data.block_id, data))
+            .collect::<HashMap<_, _>>();
+        let raw = db.get_blocks_raw(&block_ids).await.unwrap();
+        assert_eq!(raw.len(), blocks.len());
+        assert!(raw.iter().all(|res| res.raw == blocks_by_id[&res.block_id].raw));
+        let metadata = db.get_blocks_metadata(&block_ids).await.unwrap();
+        assert_eq!(metadata.len(), blocks.len());
+        assert!(
+            metadata
+                .iter()
+                .all(|res| res.metadata == blocks_by_id[&res.block_id].metadata)
+        );
+
+        let mut white_flag_order = blocks
+            .iter()
+            .filter(|data| data.metadata.referenced_by_milestone_index == MilestoneIndex(2418807))
+            .map(|data| (data.metadata.white_flag_index, data.block_id))
+            .collect::<Vec<_>>();
+        white_flag_order.sort();
+        assert_eq!(
+            db.get_referenced_blocks_in_white_flag_order(2418807.into())
+                .await
+                .unwrap(),
+            white_flag_order
+                .into_iter()
+                .map(|(_, block_id)| block_id)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    async fn block_children(db: &impl Storage) {
+        let parents = std::iter::repeat_with(BlockId::rand)
+            .take(2)
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+        let mut children = HashSet::new();
+
+        let blocks = std::iter::repeat_with(|| (BlockId::rand(), Block::rand_no_payload_with_parents(parents.clone())))
+            .take(5)
+            .inspect(|(block_id, _)| {
+                children.insert(*block_id);
+            })
+            .chain(std::iter::repeat_with(|| (BlockId::rand(), Block::rand_no_payload())).take(5))
+            .enumerate()
+            .map(|(i, (block_id, block))| BlockData {
+                block_id,
+                metadata: rand_metadata(block.parents.clone(), i as u32),
+                block,
+                raw: iota_types::block::rand::bytes::rand_bytes(100),
+            })
+            .collect::<Vec<_>>();
+
+        db.insert_blocks_with_metadata(blocks).await.unwrap();
+
+        for child_id in db.get_block_children(&parents[0], 1.into(), 15, 100, 0).await.unwrap() {
+            assert!(children.remove(&child_id))
+        }
+        assert!(children.is_empty());
+    }
+
+    async fn milestones(db: &impl Storage) {
+        let milestone = MilestonePayload::rand(&iota_types::block::protocol::protocol_parameters());
+        let milestone_id = MilestoneId::rand();
+
+        db.insert_milestone(
+            milestone_id,
+            milestone.essence.index,
+            milestone.essence.timestamp,
+            milestone.clone(),
+            Some(true),
+            Some(MilestoneStats {
+                block_count: 3,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            db.get_milestone_id(milestone.essence.index).await.unwrap(),
+            Some(milestone_id)
+        );
+        assert_eq!(
+            db.get_milestone_payload_by_id(&milestone_id).await.unwrap().as_ref(),
+            Some(&milestone)
+        );
+        assert_eq!(
+            db.get_milestone_payload(milestone.essence.index)
+                .await
+                .unwrap()
+                .as_ref(),
+            Some(&milestone)
+        );
+        assert_eq!(
+            db.get_milestone_signature_verified(milestone.essence.index)
+                .await
+                .unwrap(),
+            Some(true)
+        );
+        assert_eq!(
+            db.get_milestone_stats(milestone.essence.index)
+                .await
+                .unwrap()
+                .map(|stats| stats.block_count),
+            Some(3)
+        );
+        let stats = MilestoneStats {
+            block_count: 4,
+            included_count: 1,
+            ..Default::default()
+        };
+        db.set_milestone_stats(milestone.essence.index, stats).await.unwrap();
+        assert_eq!(
+            db.get_milestone_stats(milestone.essence.index).await.unwrap(),
+            Some(stats)
+        );
+        assert_eq!(
+            db.get_newest_milestone().await.unwrap(),
+            Some(MilestoneIndexTimestamp {
+                milestone_index: milestone.essence.index,
+                milestone_timestamp: milestone.essence.timestamp,
+            })
+        );
+        assert_eq!(
+            db.get_newest_milestone().await.unwrap(),
+            db.get_oldest_milestone().await.unwrap()
+        );
+    }
+
+    async fn receipts(db: &impl Storage) {
+        let protocol_params = iota_types::block::protocol::protocol_parameters();
+        let receipt = |migrated_at: u32| match MilestoneOption::rand_receipt(&protocol_params) {
+            MilestoneOption::Receipt {
+                last,
+                funds,
+                transaction,
+                ..
+            } => MilestoneOption::Receipt {
+                migrated_at: migrated_at.into(),
+                last,
+                funds,
+                transaction,
+            },
+            MilestoneOption::Parameters { .. } => unreachable!(),
+        };
+
+        // Receipts are not necessarily migrated in the order of the milestones that contain them.
+        let options = [vec![receipt(20)], vec![receipt(10)], Vec::new(), vec![receipt(20)]];
+        for (index, options) in (1u32..).zip(options.iter()) {
+            let mut milestone = MilestonePayload::rand(&protocol_params);
+            milestone.essence.index = index.into();
+            milestone.essence.options = options.clone().into_boxed_slice();
+            db.insert_milestone(
+                MilestoneId::rand(),
+                milestone.essence.index,
+                milestone.essence.timestamp,
+                milestone,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        }
+
+        assert_eq!(
+            db.get_all_receipts().await.unwrap(),
+            vec![
+                (options[0][0].clone(), 1.into()),
+                (options[1][0].clone(), 2.into()),
+                (options[3][0].clone(), 4.into()),
+            ]
+        );
+        assert_eq!(
+            db.get_receipts_migrated_at(20.into()).await.unwrap(),
+            vec![(options[0][0].clone(), 1.into()), (options[3][0].clone(), 4.into())]
+        );
+        assert!(db.get_receipts_migrated_at(30.into()).await.unwrap().is_empty());
+    }
+
+    async fn outputs(db: &impl Storage) {
+        let protocol_params = iota_types::block::protocol::protocol_parameters();
+
+        let outputs = std::iter::repeat_with(|| Output::rand(&protocol_params))
+            .take(100)
+            .map(|output| LedgerOutput {
+                output_id: OutputId::rand(),
+                rent_structure: RentStructureBytes {
+                    num_key_bytes: 0,
+                    num_data_bytes: 100,
+                },
+                output,
+                block_id: BlockId::rand(),
+                booked: MilestoneIndexTimestamp {
+                    milestone_index: 1.into(),
+                    milestone_timestamp: 12345.into(),
+                },
+            })
+            .collect::<Vec<_>>();
+
+        db.insert_unspent_outputs(&outputs).await.unwrap();
+
+        for output in &outputs {
+            assert_eq!(
+                db.get_output(&output.output_id).await.unwrap().as_ref(),
+                Some(&output.output)
+            );
+            assert_eq!(
+                db.get_output_metadata(&output.output_id, 1.into()).await.unwrap(),
+                Some(OutputMetadataResult {
+                    output_id: output.output_id,
+                    block_id: output.block_id,
+                    booked: output.booked,
+                    spent_metadata: None,
+                }),
+            );
+            assert_eq!(
+                db.get_spending_transaction_metadata(&output.output_id).await.unwrap(),
+                None
+            );
+        }
+        let output_ids = outputs
+            .iter()
+            .map(|output| output.output_id)
+            .chain(std::iter::once(OutputId::rand()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            db.get_outputs_with_metadata(&output_ids, 1.into()).await.unwrap().len(),
+            100
+        );
+        assert_eq!(db.get_outputs_metadata(&output_ids, 1.into()).await.unwrap().len(), 100);
+        assert!(db.get_outputs_metadata(&output_ids, 0.into()).await.unwrap().is_empty());
+        assert_eq!(
+            db.get_unspent_outputs(1.into())
+                .await
+                .unwrap()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap()
+                .len(),
+            100
+        );
+
+        let spent = outputs
+            .into_iter()
+            .take(40)
+            .map(|output| LedgerSpent {
+                output,
+                spent_metadata: SpentMetadata {
+                    transaction_id: TransactionId::rand(),
+                    spent: MilestoneIndexTimestamp {
+                        milestone_index: 2.into(),
+                        milestone_timestamp: 23456.into(),
+                    },
+                },
+            })
+            .collect::<Vec<_>>();
+
+        db.update_spent_outputs(&spent).await.unwrap();
+
+        for output in &spent {
+            assert_eq!(
+                db.get_output_metadata(&output.output.output_id, 2.into())
+                    .await
+                    .unwrap()
+                    .and_then(|metadata| metadata.spent_metadata),
+                Some(output.spent_metadata),
+            );
+        }
+
+        assert_eq!(
+            db.get_unspent_outputs(1.into())
+                .await
+                .unwrap()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap()
+                .len(),
+            100
+        );
+        assert_eq!(
+            db.get_unspent_outputs(2.into())
+                .await
+                .unwrap()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap()
+                .len(),
+            60
+        );
+
+        let UtxoChangesResult {
+            created_outputs,
+            consumed_outputs,
+        } = db.get_utxo_changes(2.into(), 2.into()).await.unwrap().unwrap();
+        assert!(created_outputs.is_empty());
+        assert_eq!(
+            consumed_outputs.into_iter().collect::<HashSet<_>>(),
+            spent
+                .iter()
+                .map(|output| output.output.output_id)
+                .collect::<HashSet<_>>()
+        );
+    }
+
+    async fn protocol_updates(db: &impl Storage) {
+        for (ledger_index, version) in [(0u32, 0u8), (10, 1), (20, 2)] {
+            let mut parameters = ProtocolParameters::from(iota_types::block::protocol::protocol_parameters());
+            parameters.version = version;
+            db.upsert_protocol_parameters(ledger_index.into(), parameters)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            db.get_latest_protocol_parameters().await.unwrap(),
+            db.get_protocol_parameters_for_version(2).await.unwrap()
+        );
+        assert_eq!(
+            db.get_protocol_parameters_for_version(1)
+                .await
+                .unwrap()
+                .map(|update| update.tangle_index),
+            Some(10.into())
+        );
+        assert!(db.get_protocol_parameters_for_version(3).await.unwrap().is_none());
+        for (ledger_index, version) in [(0u32, 0u8), (9, 0), (10, 1), (15, 1), (25, 2)] {
+            assert_eq!(
+                db.get_protocol_parameters_for_ledger_index(ledger_index.into())
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .parameters
+                    .version,
+                version
+            );
+        }
+    }
+
+    async fn treasury_updates(db: &impl Storage) {
+        let treasury_updates = (0..10u32)
+            .map(|milestone_index| (MilestoneIndex::from(milestone_index), MilestoneId::rand(), 1000))
+            .collect::<Vec<_>>();
+
+        db.insert_treasury_payloads(treasury_updates.clone()).await.unwrap();
+
+        assert_eq!(
+            db.get_latest_treasury().await.unwrap().unwrap().milestone_id,
+            treasury_updates[9].1
+        );
+        assert_eq!(
+            db.get_treasury_for_ledger_index(4.into())
+                .await
+                .unwrap()
+                .unwrap()
+                .milestone_id,
+            treasury_updates[4].1
+        );
+    }
+
+    async fn api_keys(db: &impl Storage) {
+        let key = |name: &str| ApiKeyDocument {
+            name: name.to_string(),
+            key_hash: vec![1, 2, 3],
+            salt: "salt".to_string(),
+            scopes: vec!["core:read".to_string()],
+            created_at: 1000,
+            revoked_at: None,
+        };
+
+        assert!(db.insert_api_key(&key("bob")).await.unwrap());
+        assert!(db.insert_api_key(&key("alice")).await.unwrap());
+        // Names are unique.
+        assert!(!db.insert_api_key(&key("alice")).await.unwrap());
+
+        assert_eq!(
+            db.get_api_keys()
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|key| key.name)
+                .collect::<Vec<_>>(),
+            vec!["alice", "bob"]
+        );
+
+        assert!(db.revoke_api_key("alice", 2000).await.unwrap());
+        assert!(!db.revoke_api_key("alice", 3000).await.unwrap());
+        assert!(!db.revoke_api_key("carol", 3000).await.unwrap());
+        assert_eq!(db.get_api_key("alice").await.unwrap().unwrap().revoked_at, Some(2000));
+        assert_eq!(db.get_api_key("bob").await.unwrap().unwrap().revoked_at, None);
+    }
+}